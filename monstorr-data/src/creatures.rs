@@ -6,14 +6,16 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
-pub struct CreatureSummary<PropertyType> {
+// ListType is separate so the stored creatures can use a static slice in a const.
+pub struct CreatureSummary<PropertyType, ListType = Vec<PropertyType>> {
     pub name: PropertyType,
     pub slug: PropertyType,
     pub type_: PropertyType,
     pub subtype: Option<PropertyType>,
     pub size: PropertyType,
     pub alignment: PropertyType,
-    pub challenge_rating: PropertyType
+    pub challenge_rating: PropertyType,
+    pub environments: ListType
 }
 
 include!("../data/creatures/creature_database.rs.inc");
\ No newline at end of file
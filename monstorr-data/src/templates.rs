@@ -32,19 +32,39 @@ macro_rules! latex_main_template {
     };
 }
 
+macro_rules! latex_document_template {
+    () => {
+        "latex-document-template.tex"
+    };
+}
+
 macro_rules! plain_main_template {
     () => {
         "plain-stat-block-template.txt"
     };
 }
 
+macro_rules! discord_main_template {
+    () => {
+        "discord-stat-block-template.md"
+    };
+}
+
 pub const FULL_HTML_TEMPLATE: &'static str = html_full_page_template!();
 pub const STAT_BLOCK_HTML_TEMPLATE: &'static str = html_stat_block_template!();
 pub const HTML_TWO_COLUMN_TEMPLATE: &'static str = "html-two-column";
+pub const HTML_FOOTER_TEMPLATE: &'static str = "html-footer";
+pub const HTML_CARD_SIZE_TEMPLATE: &'static str = "html-card-size";
+pub const HTML_CLASS_TEMPLATE: &'static str = "html-class";
+pub const HTML_NARROW_TEMPLATE: &'static str = "html-narrow";
 pub const LATEX_TEMPLATE: &'static str = latex_main_template!();
+pub const LATEX_DOCUMENT_TEMPLATE: &'static str = latex_document_template!();
+pub const LATEX_THEME_TEMPLATE: &'static str = "latex-theme";
+pub const LATEX_GEOMETRY_TEMPLATE: &'static str = "latex-geometry";
 pub const PLAIN_TEMPLATE: &'static str = plain_main_template!();
+pub const DISCORD_TEMPLATE: &'static str = discord_main_template!();
 
-pub const STORED_TEMPLATES: [(&'static str, (&'static str, &'static str)); 16] = [
+pub const STORED_TEMPLATES: [(&'static str, (&'static str, &'static str)); 23] = [
     template!("html",html_full_page_template!()),
     template!("html",html_stat_block_template!()),
     template!("html","html-styles-fragment.html"),
@@ -53,35 +73,114 @@ pub const STORED_TEMPLATES: [(&'static str, (&'static str, &'static str)); 16] =
     template!("html","spans-template.html"),
     template!("html","feature-template.html"),
     template!("html","tapered-rule.html"),
+    template!("html","html-card-styles-fragment.html"),
+    template!("html","html-narrow-styles-fragment.html"),
     template!("latex",latex_main_template!()),
+    template!("latex",latex_document_template!()),
     template!("latex","feature-template.tex"),
     template!("latex","blocks-template.tex"),
     template!("latex","spans-template.tex"),
     template!("plain",plain_main_template!()),
     template!("plain","feature-template.txt"),
     template!("plain","blocks-template.txt"),
-    template!("plain","spans-template.txt")
+    template!("plain","spans-template.txt"),
+    template!("discord",discord_main_template!()),
+    template!("discord","feature-template.md"),
+    template!("discord","blocks-template.md"),
+    template!("discord","spans-template.md")
+];
+
+// These are generated at run-time from the options, but are still needed as files if the stored templates are used elsewhere.
+pub const GENERATED_TEMPLATES: [(&'static str, &'static str); 7] = [
+    (HTML_TWO_COLUMN_TEMPLATE,"html"),
+    (HTML_FOOTER_TEMPLATE,"html"),
+    (HTML_CARD_SIZE_TEMPLATE,"html"),
+    (HTML_CLASS_TEMPLATE,"html"),
+    (HTML_NARROW_TEMPLATE,"html"),
+    (LATEX_THEME_TEMPLATE,"latex"),
+    (LATEX_GEOMETRY_TEMPLATE,"latex")
 ];
 
 // TODO: I'm repeating these next to string constants
 
 
+#[derive(Clone)]
+/// The size of index card to print an HTML stat-block on.
+pub enum CardSize {
+    ThreeByFive,
+    FourBySix
+}
+
+impl CardSize {
+
+    // a margin is kept so printers which can't print to the edge don't cut anything off.
+    const MARGIN: f32 = 0.15;
+
+    // width and height in inches of the card, in landscape orientation, and the font size in pixels that fits it.
+    fn dimensions(&self) -> (f32,f32,f32) {
+        match self {
+            CardSize::ThreeByFive => (5.0,3.0,7.5),
+            CardSize::FourBySix => (6.0,4.0,9.0)
+        }
+    }
+
+    /**
+    Estimates how many characters fit on a line in one of the card's two columns, and how many lines fit on the card in both columns together. The browser's fonts decide the real numbers, so this is only good enough to warn about a stat-block which is probably too long.
+    */
+    pub fn text_capacity(&self) -> (usize,usize) {
+        // 96 pixels to the inch, characters average about half the font size across, and lines are 1.3 times the font size apart, as in html-card-styles-fragment.html.
+        let (width,height,font_size) = self.dimensions();
+        let inner_width = (width - (Self::MARGIN * 2.0)) * 96.0 - (font_size * (0.8 + 1.5));
+        let inner_height = (height - (Self::MARGIN * 2.0)) * 96.0 - (font_size * 0.8);
+        let line_length = (inner_width / 2.0) / (font_size * 0.5);
+        let lines = inner_height / (font_size * 1.3);
+        (line_length as usize,(lines as usize) * 2)
+    }
+
+    fn to_style(&self) -> String {
+        let (width,height,font_size) = self.dimensions();
+        format!("<style>\n    @page {{\n        size: {}in {}in;\n        margin: {}in;\n    }}\n\n    body {{\n        --card-width: {}in;\n        --card-height: {}in;\n        --card-font-size: {}px;\n    }}\n</style>\n{{% include 'html-card-styles-fragment.html' %}}",
+                width,height,Self::MARGIN,width - (Self::MARGIN * 2.0),height - (Self::MARGIN * 2.0),font_size)
+    }
+}
+
+#[derive(Clone,Default)]
+/// The options for an HTML stat-block. The defaults give a full page with a one-column stat-block.
+pub struct HtmlOptions {
+    pub two_column_height: Option<usize>, // if set, the stat-block is supposed to be two-columns, and the value is the height of the div in pixels
+    pub fragment: bool, // if set, only the stat-block is output, instead of a full page
+    pub link: Option<String>, // if set, a link to place at the bottom of the stat-block, such as to an online version
+    pub qr: bool, // if set, a QR code for the link is placed with it
+    pub card_size: Option<CardSize>, // if set, the page is styled for printing on index cards of this size
+    pub css_class: Option<String>, // if set, extra classes to add to the stat-block's div
+    pub narrow: bool, // if set, the stat-block is styled to fit a narrow sidebar or a phone
+}
+
 #[derive(Default)]
 pub struct TemplateOptions {
-    html: Option<usize>, // if set, the html template is supposed to be two-columns, and the value is the height of the div in pixels
+    html: HtmlOptions, // the css class is already escaped for the attribute
+    html_footer: Option<String>, // if set, markup to place at the bottom of the html stat block, built from the link
+    latex_color: Option<String>, // if set, the color of the latex stat block's text, as six hexadecimal digits
+    latex_font: Option<String>, // if set, the name of the font family used for the latex stat block, such as 'ppl'
+    latex_margin: Option<String>, // if set, the page margin of a standalone latex document, such as '1in'
 }
 
 impl TemplateOptions {
 
-    pub fn html(html: Option<usize>) -> Option<Self> {
+    pub fn html(html: HtmlOptions, html_footer: Option<String>) -> Option<Self> {
         Some(Self {
-            html
+            html,
+            html_footer,
+            ..Default::default()
         })
     }
 
-    pub fn latex() -> Option<Self> {
+    pub fn latex(latex_color: Option<String>, latex_font: Option<String>, latex_margin: Option<String>) -> Option<Self> {
         Some(Self {
-            html: None
+            latex_color,
+            latex_font,
+            latex_margin,
+            ..Default::default()
         })
     }
 
@@ -107,17 +206,44 @@ impl StoredTemplates {
             Some((*template).to_owned())
         } else {
             match name {
-                HTML_TWO_COLUMN_TEMPLATE => Some(if let Some(stat_block_height) = self.options.html {
+                HTML_TWO_COLUMN_TEMPLATE => Some(if let Some(stat_block_height) = self.options.html.two_column_height {
                     format!("data-two-column=\"\" style=\"--data-content-height: {}px;\"",stat_block_height)
                 } else {
                     String::new()
                 }),
+                HTML_FOOTER_TEMPLATE => Some(self.options.html_footer.clone().unwrap_or_default()),
+                HTML_CARD_SIZE_TEMPLATE => Some(self.options.html.card_size.as_ref().map(|card| card.to_style()).unwrap_or_default()),
+                HTML_CLASS_TEMPLATE => Some(self.options.html.css_class.as_ref().map(|class| format!(" {}",class)).unwrap_or_default()),
+                HTML_NARROW_TEMPLATE => Some(if self.options.html.narrow {
+                    "{% include 'html-narrow-styles-fragment.html' %}".to_owned()
+                } else {
+                    String::new()
+                }),
+                LATEX_THEME_TEMPLATE => {
+                    let mut theme = String::new();
+                    if let Some(color) = &self.options.latex_color {
+                        theme.push_str(&format!("\\definecolor{{monstorrThemeColor}}{{HTML}}{{{}}}\\color{{monstorrThemeColor}}\n",color))
+                    }
+                    if let Some(font) = &self.options.latex_font {
+                        theme.push_str(&format!("\\fontfamily{{{}}}\\selectfont\n",font))
+                    }
+                    Some(theme)
+                },
+                LATEX_GEOMETRY_TEMPLATE => Some(format!("\\usepackage[margin={}]{{geometry}}",self.options.latex_margin.as_deref().unwrap_or("1in"))),
                 _ => None
             }
         }
 
     }
 
+    pub fn list_generated(&self, class: Option<&str>) -> Vec<String> {
+        GENERATED_TEMPLATES.iter().filter_map(|(i,c)| if class.map(|class| &class == c).unwrap_or(true) {
+            Some((*i).to_owned())
+        } else {
+            None
+        }).collect()
+    }
+
     pub fn list(&self, class: Option<&str>) -> Vec<String> {
         // TODO: Need to filter by class if specified
         if let Some(class) = &class {
@@ -25,9 +25,9 @@ pub enum UsageLimit {
     /**
     `Recharge(<integer>)`
 
-    Specifies that the action recharges on a random roll. This is phrased in the name of the action as "Recharge <number>-6".
+    Specifies that the action recharges on a random roll. This is phrased in the name of the action as "Recharge <number>–6", with an en dash as in the books.
     */
-    Recharge(u8), // the roll equal or above that the monster must roll to recharge a feature (i.e. Recharge 5-6 would be 5)
+    Recharge(u8), // the roll equal or above that the monster must roll to recharge a feature (i.e. Recharge 5–6 would be 5)
     /**
     `PerDay(<integer>)`
 
@@ -59,7 +59,7 @@ impl std::fmt::Display for UsageLimit {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(),std::fmt::Error> {
         match self {
             UsageLimit::Recharge(6) => write!(f,"Recharge 6"),
-            UsageLimit::Recharge(roll) => write!(f,"Recharge {}-6",roll),
+            UsageLimit::Recharge(roll) => write!(f,"Recharge {}–6",roll),
             UsageLimit::PerDay(count) => write!(f,"{}/Day",count),
             UsageLimit::PerTurn(count) => write!(f,"{}/Turn",count),
             UsageLimit::RechargeAfterRest => f.write_str("Recharges after a Short or Long Rest"),
@@ -390,10 +390,47 @@ pub enum LegendaryAction {
 
 }
 
+#[derive(PartialEq,Debug,Clone)]
+#[derive(Serialize,Deserialize)]
+/**
+Specifies the wording of the introduction to the legendary actions, for matching the style of a particular edition or book.
+*/
+pub enum LegendaryActionsStyle {
+    /**
+    `Classic`
+
+    The wording used in the 2014 rules, which is the default: "The dragon can take 3 legendary actions, choosing from the options below..."
+    */
+    Classic,
+    /**
+    `Capitalized`
+
+    The same as classic, but with "Legendary Actions" capitalized as a game term, as used by some books and homebrew: "The dragon can take 3 Legendary Actions, choosing from the options below..."
+    */
+    Capitalized,
+    /**
+    `Uses`
+
+    The wording used in the 2024 rules: "Legendary Action Uses: 3. Immediately after another creature's turn, the dragon can expend a use to take one of the following actions..."
+    */
+    Uses
+}
+
+impl Default for LegendaryActionsStyle {
+
+    fn default() -> Self {
+        Self::Classic
+    }
+}
+
 impl LegendaryAction {
 
-    pub fn get_main_description(amount: &u8) -> String {
-        format!("${{Subj}} can take {} legendary actions, choosing from the options below. Only one legendary action option can be used at a time and only at the end of another creature's turn. ${{Subj}} regains spent legendary actions at the start of ${{posspro}} turn.",amount)
+    pub fn get_main_description(amount: &u8, style: &LegendaryActionsStyle) -> String {
+        match style {
+            LegendaryActionsStyle::Classic => format!("${{Subj}} can take {} legendary actions, choosing from the options below. Only one legendary action option can be used at a time and only at the end of another creature's turn. ${{Subj}} regains spent legendary actions at the start of ${{posspro}} turn.",amount),
+            LegendaryActionsStyle::Capitalized => format!("${{Subj}} can take {} Legendary Actions, choosing from the options below. Only one Legendary Action option can be used at a time and only at the end of another creature's turn. ${{Subj}} regains spent Legendary Actions at the start of ${{posspro}} turn.",amount),
+            LegendaryActionsStyle::Uses => format!("${{italic(}}Legendary Action Uses: {}.${{)}} Immediately after another creature's turn, ${{subj}} can expend a use to take one of the following actions. ${{Subj}} regains all expended uses at the start of each of ${{posspro}} turns.",amount)
+        }
     }
 
 }
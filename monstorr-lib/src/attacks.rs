@@ -54,6 +54,17 @@ pub enum AttackEffect {
     */
     FixedDamage(u8,AttackBonus,Damage), // fixed amount, magic, bonus, damage type
     /**
+    `ExpressionDamage(<string>,<AttackBonus>,<Damage>)`
+
+    The same as `Damage`, except that the dice come from an interpolation expression, written as it would be inside `${...}` in a description. The expression is evaluated against the creature when the description is built, so the damage can scale with it. This is most useful in a compound effect, for riders such as a sneak attack which grows with a level passed in as a parameter:
+
+    ```ron
+    DefineProperty("rogue_level","$<level>"),
+    Weapon(Shortsword(0),Some(Plus(ExpressionDamage("(rogue_level /> 2) * 1d6",Zero,Piercing)))),
+    ```
+    */
+    ExpressionDamage(String,AttackBonus,Damage),
+    /**
     `Special(<string>)`
 
     The attack causes an effect that would not change damage per round. This might be a condition effect such as grappled or poisoned, or non-immediate damage that continues per turn until something happens. These would have to be artificially added to the damage per round later.
@@ -62,6 +73,12 @@ pub enum AttackEffect {
     */
     Special(String),
     /**
+    `Escape(<string>,<AttackBonus>)`
+
+    The target is held by a condition, such as "grappled" or "restrained", which it can escape from. The wording will be "the target is <string> (escape DC <dc>)". The DC is 8 plus the proficiency bonus plus the modifier for the ability given by the attack bonus, usually `Strength`, and is calculated when the description is built, so it changes with the creature's ability scores. A `Default` bonus uses the same ability as the attack's damage. Like `Special`, this doesn't change damage per round.
+    */
+    Escape(String,AttackBonus),
+    /**
     `Or(<dice-expression-string>,<AttackBonus>,<Damage>,<dice-expression-string>,<AttackBonus>,<Damage>,<String>)`
 
     If an attack can have two results depending on some condition, this can be used. The wording of the attack is will be "<damage> or <damage> <condition>.". You should specify an "if", "while" or other conditional in the last string.
@@ -115,38 +132,121 @@ pub enum AttackEffect {
 
 impl AttackEffect {
 
+    /**
+    Multiplies the damage of the effect by the scale, see [`DiceExpression::scale`]. The ability bonus added to the damage, and any save DC, is not changed. Expression damage can't be scaled, as the expression isn't evaluated until the description is built, so it is left as it is. Use `is_expression_damage` to find it first.
+    */
+    pub fn scale(&self, scale: f32) -> Self {
+        match self {
+            AttackEffect::FixedDamage(amount,bonus,damage) => 
+                AttackEffect::FixedDamage((*amount as f32 * scale).round() as u8,bonus.clone(),damage.clone()),
+            AttackEffect::Damage(dice,bonus,damage) => 
+                AttackEffect::Damage(dice.scale(scale),bonus.clone(),damage.clone()),
+            AttackEffect::ExpressionDamage(..) |
+            AttackEffect::Special(_) |
+            AttackEffect::Escape(..) => 
+                self.clone(),
+            AttackEffect::Or(dice,bonus,damage,alt_dice,alt_bonus,alt_damage,condition) => 
+                AttackEffect::Or(dice.scale(scale),bonus.clone(),damage.clone(),alt_dice.scale(scale),alt_bonus.clone(),alt_damage.clone(),condition.clone()),
+            AttackEffect::DoubleOr(dice,bonus,damage,condition,alt_dice,alt_bonus,alt_damage,alt_condition) => 
+                AttackEffect::DoubleOr(dice.scale(scale),bonus.clone(),damage.clone(),condition.clone(),alt_dice.scale(scale),alt_bonus.clone(),alt_damage.clone(),alt_condition.clone()),
+            AttackEffect::DjinnisChoice(dice,bonus,damage,alt_damage) =>
+                AttackEffect::DjinnisChoice(dice.scale(scale),bonus.clone(),damage.clone(),alt_damage.clone()),
+            AttackEffect::SaveAll(save_dc,save_ability,dice,bonus,damage) =>
+                AttackEffect::SaveAll(*save_dc,save_ability.clone(),dice.scale(scale),bonus.clone(),damage.clone()),
+            AttackEffect::SaveHalf(save_dc,save_ability,dice,bonus,damage) =>
+                AttackEffect::SaveHalf(*save_dc,save_ability.clone(),dice.scale(scale),bonus.clone(),damage.clone()),
+            AttackEffect::AreaDamage(dice,bonus,damage) =>
+                AttackEffect::AreaDamage(dice.scale(scale),bonus.clone(),damage.clone()),
+            AttackEffect::AreaSaveAll(save_dc,save_ability,dice,bonus,damage) =>
+                AttackEffect::AreaSaveAll(*save_dc,save_ability.clone(),dice.scale(scale),bonus.clone(),damage.clone()),
+            AttackEffect::AreaSaveHalf(save_dc,save_ability,dice,bonus,damage) =>
+                AttackEffect::AreaSaveHalf(*save_dc,save_ability.clone(),dice.scale(scale),bonus.clone(),damage.clone()),
+        }
+    }
+
+    /// Returns true if the damage comes from an interpolation expression, which `scale` can't change.
+    pub fn is_expression_damage(&self) -> bool {
+        matches!(self,AttackEffect::ExpressionDamage(..))
+    }
+
+    /**
+    Returns the interpolation expression for the damage caused by the effect, for estimating damage per round. Where the effect has a choice of damage, the first is used. Returns `None` if the effect doesn't cause damage.
+    */
+    pub fn get_damage_expr(&self, default_bonus: &str) -> Option<String> {
+        match self {
+            AttackEffect::FixedDamage(amount,bonus,_) => 
+                Some(format!("{} + {}",amount,bonus.get_expr(default_bonus,false))),
+            AttackEffect::ExpressionDamage(expression,bonus,_) => 
+                Some(format!("({}) + {}",expression,bonus.get_expr(default_bonus,false))),
+            AttackEffect::Special(_) |
+            AttackEffect::Escape(..) => 
+                None,
+            AttackEffect::Damage(dice,bonus,_) |
+            AttackEffect::Or(dice,bonus,..) |
+            AttackEffect::DoubleOr(dice,bonus,..) |
+            AttackEffect::DjinnisChoice(dice,bonus,..) |
+            AttackEffect::SaveAll(_,_,dice,bonus,_) |
+            AttackEffect::SaveHalf(_,_,dice,bonus,_) |
+            AttackEffect::AreaDamage(dice,bonus,_) |
+            AttackEffect::AreaSaveAll(_,_,dice,bonus,_) |
+            AttackEffect::AreaSaveHalf(_,_,dice,bonus,_) =>
+                Some(format!("{} + {}",dice.serialize_to_string(),bonus.get_expr(default_bonus,false)))
+        }
+    }
+
+    /// Returns true if the effect damages every creature in an area.
+    pub fn is_area(&self) -> bool {
+        matches!(self,AttackEffect::AreaDamage(..) | AttackEffect::AreaSaveAll(..) | AttackEffect::AreaSaveHalf(..))
+    }
+
+    /// Returns the DC of the saving throw against the effect, if there is one.
+    pub fn get_save_dc(&self) -> Option<u8> {
+        match self {
+            AttackEffect::SaveAll(save_dc,..) |
+            AttackEffect::SaveHalf(save_dc,..) |
+            AttackEffect::AreaSaveAll(save_dc,..) |
+            AttackEffect::AreaSaveHalf(save_dc,..) => Some(*save_dc),
+            _ => None
+        }
+    }
+
+    // the amount and type of damage, such as "${1d6 + str} slashing damage". All effects phrase their damage with this, so it's the same whether it comes from a hit or a saving throw.
+    fn damage_description(amount: &str, bonus: &AttackBonus, default_bonus: &str, damage: &Damage) -> String {
+        format!("${{{} + {}}} {} damage",amount,bonus.get_expr(default_bonus,false),damage)
+    }
+
+    // frames the damage for an effect with a saving throw, the rest of the effects are framed by the "Hit:" of the attack.
+    fn save_description(target: &str, save_dc: &u8, save_ability: &Ability, damage: &str, half_on_success: bool) -> String {
+        format!("{} must make a DC {} {} saving throw, taking {} on a failed save{}",
+                target,
+                save_dc,
+                save_ability,
+                damage,
+                if half_on_success { ", or half as much damage on a successful one" } else { "" })
+    }
+
     fn get_base_description(&self, default_bonus: &str) -> String {
         match self {
             AttackEffect::FixedDamage(amount,bonus,damage) => 
-                format!("${{{} + {}}} {} damage",
-                        amount,
-                        bonus.get_expr(default_bonus,false),
-                        damage),
+                Self::damage_description(&amount.to_string(),bonus,default_bonus,damage),
             AttackEffect::Damage(dice,bonus,damage) => 
-                format!("${{{} + {}}} {} damage",
-                        dice.serialize_to_string(),
-                        bonus.get_expr(default_bonus,false),
-                        damage),
+                Self::damage_description(&dice.serialize_to_string(),bonus,default_bonus,damage),
+            AttackEffect::ExpressionDamage(expression,bonus,damage) => 
+                Self::damage_description(&format!("({})",expression),bonus,default_bonus,damage),
             AttackEffect::Special(special) => 
                 special.clone(),
+            AttackEffect::Escape(condition,bonus) =>
+                format!("the target is {} (escape DC ${{8 + {}}})",condition,bonus.get_expr(default_bonus,true)),
             AttackEffect::Or(dice,bonus,damage,alt_dice,alt_bonus,alt_damage,condition) => 
-                format!("${{{} + {}}} {} damage, or ${{{} + {}}} {} damage {}",
-                        dice.serialize_to_string(),
-                        bonus.get_expr(default_bonus,false),
-                        damage,
-                        alt_dice.serialize_to_string(),
-                        alt_bonus.get_expr(default_bonus,false),
-                        alt_damage,
+                format!("{}, or {} {}",
+                        Self::damage_description(&dice.serialize_to_string(),bonus,default_bonus,damage),
+                        Self::damage_description(&alt_dice.serialize_to_string(),alt_bonus,default_bonus,alt_damage),
                         condition),
             AttackEffect::DoubleOr(dice,bonus,damage,condition,alt_dice,alt_bonus,alt_damage,alt_condition) => 
-                format!("${{{} + {}}} {} damage {} or ${{{} + {}}} {} damage {}",
-                        dice.serialize_to_string(),
-                        bonus.get_expr(default_bonus,false),
-                        damage,
+                format!("{} {} or {} {}",
+                        Self::damage_description(&dice.serialize_to_string(),bonus,default_bonus,damage),
                         condition,
-                        alt_dice.serialize_to_string(),
-                        alt_bonus.get_expr(default_bonus,false),
-                        alt_damage,
+                        Self::damage_description(&alt_dice.serialize_to_string(),alt_bonus,default_bonus,alt_damage),
                         alt_condition),
             // The Djinni, at least, has a "plus" effect for which it can choose between two types of damage
             AttackEffect::DjinnisChoice(dice,bonus,damage,alt_damage) =>
@@ -157,38 +257,16 @@ impl AttackEffect {
                        alt_damage),
             // Represents an attack result which only causes damage if the target fails to save on a certain ability.
             AttackEffect::SaveAll(save_dc,save_ability,dice,bonus,damage) =>
-               format!("the target must make a DC {} {} saving throw, taking ${{{} + {}}} {} damage on a failed save",
-                       save_dc,
-                       save_ability,
-                       dice.serialize_to_string(),
-                       bonus.get_expr(default_bonus,false),
-                       damage),
+                Self::save_description("the target",save_dc,save_ability,&Self::damage_description(&dice.serialize_to_string(),bonus,default_bonus,damage),false),
             AttackEffect::SaveHalf(save_dc,save_ability,dice,bonus,damage) =>
-               format!("the target must make a DC {} {} saving throw, taking ${{{} + {}}} {} damage on a failed save, or half as much damage on a successful one",
-                       save_dc,
-                       save_ability,
-                       dice.serialize_to_string(),
-                       bonus.get_expr(default_bonus,false),
-                       damage),
+                Self::save_description("the target",save_dc,save_ability,&Self::damage_description(&dice.serialize_to_string(),bonus,default_bonus,damage),true),
             AttackEffect::AreaDamage(dice,bonus,damage) => // FUTURE: I don't know which creature I got this from, so wording might be wrong.
-                format!("each target in the area takes ${{{} + {}}} {} damage",
-                       dice.serialize_to_string(),
-                       bonus.get_expr(default_bonus,false),
-                       damage),
+                format!("each target in the area takes {}",
+                        Self::damage_description(&dice.serialize_to_string(),bonus,default_bonus,damage)),
             AttackEffect::AreaSaveAll(save_dc,save_ability,dice,bonus,damage) =>
-                format!("each target in the area must make a DC {} {} saving throw, taking ${{{} + {}}} {} damage on a failed save",
-                        save_dc,
-                        save_ability,
-                        dice.serialize_to_string(),
-                        bonus.get_expr(default_bonus,false),
-                        damage),
+                Self::save_description("each target in the area",save_dc,save_ability,&Self::damage_description(&dice.serialize_to_string(),bonus,default_bonus,damage),false),
             AttackEffect::AreaSaveHalf(save_dc,save_ability,dice,bonus,damage) =>
-                format!("each target in the area must make a DC {} {} saving throw, taking ${{{} + {}}} {} damage on a failed save, or half as much damage on a successful one",
-                        save_dc,
-                        save_ability,
-                        dice.serialize_to_string(),
-                        bonus.get_expr(default_bonus,false),
-                        damage),
+                Self::save_description("each target in the area",save_dc,save_ability,&Self::damage_description(&dice.serialize_to_string(),bonus,default_bonus,damage),true),
         }
     }
 
@@ -289,6 +367,34 @@ pub enum CompoundAttackEffect {
 
 }
 
+impl CompoundAttackEffect {
+
+    pub fn scale(&self, scale: f32) -> Self {
+        match self {
+            CompoundAttackEffect::And(alt) => CompoundAttackEffect::And(alt.scale(scale)),
+            CompoundAttackEffect::AndAnd(alt,and) => CompoundAttackEffect::AndAnd(alt.scale(scale),and.clone()),
+            CompoundAttackEffect::Additional(alt) => CompoundAttackEffect::Additional(alt.scale(scale)),
+            CompoundAttackEffect::AndAdditional(first,second) => CompoundAttackEffect::AndAdditional(first.scale(scale),second.scale(scale)),
+            CompoundAttackEffect::Plus(alt) => CompoundAttackEffect::Plus(alt.scale(scale)),
+            CompoundAttackEffect::PlusAdditional(first,second) => CompoundAttackEffect::PlusAdditional(first.scale(scale),second.scale(scale)),
+            CompoundAttackEffect::PlusAnd(first,second) => CompoundAttackEffect::PlusAnd(first.scale(scale),second.scale(scale))
+        }
+    }
+
+    /// Returns the effects added to the original effect.
+    pub fn get_effects(&self) -> Vec<&AttackEffect> {
+        match self {
+            CompoundAttackEffect::And(alt) |
+            CompoundAttackEffect::AndAnd(alt,_) |
+            CompoundAttackEffect::Additional(alt) |
+            CompoundAttackEffect::Plus(alt) => vec![alt],
+            CompoundAttackEffect::AndAdditional(first,second) |
+            CompoundAttackEffect::PlusAdditional(first,second) |
+            CompoundAttackEffect::PlusAnd(first,second) => vec![first,second]
+        }
+    }
+}
+
 
 // FUTURE: I'd like to change how this (de)serializes:
 // -- anywhere there is a Vec, I want the enum to flatten out the vec into arguments. So, Or([...]) becomes Or(...), count(1,[...]) becomes count(1,...)
@@ -298,7 +404,7 @@ pub enum CompoundAttackEffect {
 #[derive(PartialEq,Debug,Clone)]
 #[derive(Serialize,Deserialize)]
 /**
-The description for a "Multiattack" action is written by hand, not generated from multiattack values. The Multiattack value's primary use is for calculating the Challenge Rating, as that calculation needs to know what attacks a creature can make in each round. While the challenge rating is not yet automatically calculated, it is good to have this information for when it will be.
+The description for a "Multiattack" action is usually written by hand, but it can be generated from the multiattack value with the `AutoMultiattack` creature command, see [`Multiattack::get_description`]. The Multiattack value's primary use is for calculating the Challenge Rating, as that calculation needs to know what attacks a creature can make in each round. It is used to find the damage per round when checking an expected challenge rating, see [`crate::creature::Creature::calculate_challenge_rating`].
 
 A Multiattack is a simple structure complicated by multiple combinations. While some creatures can simply make any attacks of their attacks a certain number of times, others are limited in unusual ways. For example, a creature might be able to make one bite and two claw attacks. Another might make three melee, but only one spell, and if they attack with their staff they can only attack twice. There are few very complicated multiattacks in the official books, but being able to model even a mixed multiattack like that first requires a few unusual structures. 
 
@@ -375,6 +481,71 @@ pub enum Multiattack {
     Dice(DiceExpression,Vec<Multiattack>) // roll the specified die to determine how many of any one of these specified attacks
 }
 
+impl Multiattack {
+
+    // the kind of attack, without a count, such as "claw attack", or None if this isn't a single attack.
+    fn get_noun(&self) -> Option<String> {
+        match self {
+            Multiattack::Any => Some("attack".to_owned()),
+            Multiattack::Ranged => Some("ranged attack".to_owned()),
+            Multiattack::Melee => Some("melee attack".to_owned()),
+            Multiattack::Spell => Some("spell attack".to_owned()),
+            Multiattack::Attack(name) => Some(format!("{} attack",name.to_lowercase())),
+            // the magic bonus is left out of the name, "scimitar attack" rather than "scimitar +1 attack".
+            Multiattack::Weapon(weapon) => Some(format!("{} attack",weapon.to_string().trim_end_matches(|c: char| c.is_ascii_digit()).trim_end_matches(&['+','-'][..]).trim_end().to_lowercase())),
+            _ => None
+        }
+    }
+
+    fn with_article(noun: &str) -> String {
+        if noun.starts_with(&['a','e','i','o','u'][..]) {
+            format!("an {}",noun)
+        } else {
+            format!("a {}",noun)
+        }
+    }
+
+    fn join(list: &[String], conjunction: &str) -> String {
+        match list.split_last() {
+            Some((last,rest)) if !rest.is_empty() => format!("{} {} {}",rest.join(", "),conjunction,last),
+            Some((last,_)) => last.clone(),
+            None => String::new()
+        }
+    }
+
+    // the attacks made a number of times, given in words, such as "two claw attacks".
+    fn describe_count(count: &str, list: &[Multiattack]) -> String {
+        let nouns: Option<Vec<String>> = list.iter().map(Multiattack::get_noun).collect();
+        match nouns.as_deref() {
+            Some([noun]) => format!("{} {}s",count,noun),
+            Some(nouns) => format!("{} attacks, each one {}",count,Self::join(&nouns.iter().map(|noun| Self::with_article(noun)).collect::<Vec<String>>(),"or")),
+            None => format!("{}, {} times",Self::join(&list.iter().map(Multiattack::describe).collect::<Vec<String>>(),"or"),count)
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Multiattack::Except(list) => match list.iter().map(Multiattack::get_noun).collect::<Option<Vec<String>>>() {
+                Some(nouns) => format!("one attack other than {}",Self::join(&nouns.iter().map(|noun| Self::with_article(noun)).collect::<Vec<String>>(),"or")),
+                None => "one attack".to_owned()
+            },
+            Multiattack::Or(list) => Self::join(&list.iter().map(Multiattack::describe).collect::<Vec<String>>(),"or"),
+            Multiattack::And(list) => Self::join(&list.iter().map(Multiattack::describe).collect::<Vec<String>>(),"and"),
+            Multiattack::Count(count,list) => Self::describe_count(&count_to_words(count),list),
+            Multiattack::Dice(dice,list) => Self::describe_count(&dice.serialize_to_string(),list),
+            single => format!("one {}",single.get_noun().unwrap_or_else(|| "attack".to_owned()))
+        }
+    }
+
+    /**
+    Generates a description for the "Multiattack" action, such as "${Subj} makes two claw attacks." or "${Subj} makes one bite attack and two claw attacks.". The names of attacks and weapons are used in lowercase. This is used by the `AutoMultiattack` creature command.
+    */
+    pub fn get_description(&self) -> String {
+        format!("${{Subj}} makes {}.",self.describe())
+    }
+
+}
+
 #[derive(PartialEq,Debug,Clone)]
 #[derive(Serialize,Deserialize)]
 /**
@@ -515,7 +686,84 @@ pub enum AttackType {
 #[derive(PartialEq,Debug,Clone)]
 #[derive(Serialize,Deserialize)]
 /**
-`(type: option(<AttackType>)?, bonus: <AttackBonus>?, magic: option(<integer>)?, reach: option(<integer>)?, range: option(<integer>)?, long_range: option(<integer>)?, target: <string>)`
+Used to specify attacks which target more than one creature, or all creatures in an area, instead of writing out the target phrasing by hand. Distances are in feet.
+*/
+pub enum AttackTargeting {
+    /**
+    `Targets(<integer>,option(<integer>))`
+
+    The attack hits the specified number of targets. If the second value is given, the targets must be within that distance of each other. The phrasing will be something like "two targets within 5 feet of each other".
+    */
+    Targets(u8,Option<u8>),
+    /**
+    `Cone(<integer>)`
+
+    The attack hits each creature in a cone of the specified length.
+    */
+    Cone(u16),
+    /**
+    `Line(<integer>,<integer>)`
+
+    The attack hits each creature in a line of the specified length and width.
+    */
+    Line(u16,u16),
+    /**
+    `Cube(<integer>)`
+
+    The attack hits each creature in a cube of the specified size.
+    */
+    Cube(u16),
+    /**
+    `Sphere(<integer>)`
+
+    The attack hits each creature in a sphere of the specified radius.
+    */
+    Sphere(u16),
+    /**
+    `Cylinder(<integer>,<integer>)`
+
+    The attack hits each creature in a cylinder of the specified radius and height.
+    */
+    Cylinder(u16,u16)
+}
+
+fn count_to_words(count: &u8) -> String {
+    match count {
+        1 => "one".to_owned(),
+        2 => "two".to_owned(),
+        3 => "three".to_owned(),
+        4 => "four".to_owned(),
+        5 => "five".to_owned(),
+        6 => "six".to_owned(),
+        7 => "seven".to_owned(),
+        8 => "eight".to_owned(),
+        9 => "nine".to_owned(),
+        10 => "ten".to_owned(),
+        count => format!("{}",count)
+    }
+}
+
+impl AttackTargeting {
+
+    pub fn get_description(&self) -> String {
+        match self {
+            AttackTargeting::Targets(1,_) => "one target".to_owned(),
+            AttackTargeting::Targets(count,None) => format!("{} targets",count_to_words(count)),
+            AttackTargeting::Targets(count,Some(distance)) => format!("{} targets within {} feet of each other",count_to_words(count),distance),
+            AttackTargeting::Cone(length) => format!("each creature in a {}-foot cone",length),
+            AttackTargeting::Line(length,width) => format!("each creature in a {}-foot line that is {} feet wide",length,width),
+            AttackTargeting::Cube(size) => format!("each creature in a {}-foot cube",size),
+            AttackTargeting::Sphere(radius) => format!("each creature in a {}-foot-radius sphere",radius),
+            AttackTargeting::Cylinder(radius,height) => format!("each creature in a {}-foot-radius, {}-foot-high cylinder",radius,height),
+        }
+    }
+}
+
+
+#[derive(PartialEq,Debug,Clone)]
+#[derive(Serialize,Deserialize)]
+/**
+`(type: option(<AttackType>)?, bonus: <AttackBonus>?, magic: option(<integer>)?, reach: option(<integer>)?, range: option(<integer>)?, long_range: option(<integer>)?, target: <string>?, targeting: option(<AttackTargeting>)?)`
 
 An Attack value is a mapped struct containing data needed to generating a description, of the attack attempt of an action. This is the part before the "hit:" phrasing. See below for what the various properties mean.
 
@@ -556,7 +804,7 @@ pub struct Attack {
     /**
     `reach: option(<integer>)?`
 
-    Specifies that this can be a melee attack, and that this value is the reach.
+    Specifies that this can be a melee attack, and that this value is the reach. Any reach in feet can be given, such as 20 for the tentacle of a gargantuan creature. The reach of the built-in weapons does not change with the size of the creature, so a larger reach has to be given here or with a `CustomWeapon`.
     */
     pub reach: Option<u8>, // indicates that it's a melee attack and this is the reach.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -575,31 +823,64 @@ pub struct Attack {
     Specifies the long range for a ranged attack. This is ignored unless `range` is also set.
     */
     pub long_range: Option<u16>, // indicates that it's a ranged attack and this is the long range (requires "range"),
+    #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(default)]
     /**
-    `target: <string>`
+    `target: <string>?`
 
-    Specifies the phrasing for the target of the attack. Usually "one creature", but occasionally its "one target", and even rarely something like "two Large or Smaller humanoids".
+    Specifies the phrasing for the target of the attack. Usually "one creature", but occasionally its "one target", and even rarely something like "two Large or Smaller humanoids". If this is not specified, and `targeting` isn't either, the phrasing will be "one target".
     */ 
     pub target: String, // indicates the type of targets (i.e. "one creature", "one target", "two Large or smaller humanoids", etc.)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    /**
+    `targeting: option(<AttackTargeting>)?`
+
+    Specifies that the attack hits multiple targets or an area, see [`AttackTargeting`]. If this is set, it replaces the phrasing from `target`.
+    */
+    pub targeting: Option<AttackTargeting>
 }
 
 impl Attack {
 
+    /// Returns the interpolation variable for the ability bonus used when the bonus is `Default`: strength for melee, dexterity for ranged, and the best of them for attacks which can be either.
+    pub fn get_default_bonus(&self) -> &'static str {
+        match (self.reach,self.range) {
+            (Some(_),None) => "str",
+            (None,Some(_)) => "dex",
+            _ => "atk"
+        }
+    }
+
+    /// Returns the interpolation expression for the bonus to hit.
+    pub fn get_attack_bonus_expr(&self) -> String {
+        format!("{} + {}",self.bonus.get_expr(self.get_default_bonus(),true),self.magic.unwrap_or(0))
+    }
+
+    fn get_target(&self) -> String {
+        match &self.targeting {
+            Some(targeting) => targeting.get_description(),
+            None if self.target.is_empty() => "one target".to_owned(),
+            None => self.target.clone()
+        }
+    }
+
     pub fn get_description(&self, effect: Option<&AttackEffect>, compound_effect: &Option<CompoundAttackEffect>) -> String {
     
+        let targets = self.get_target();
         let (target,melee,ranged) = match (self.reach,self.range,self.long_range) {
             (Some(reach),Some(short_range),Some(long_range)) => 
-                (format!("reach {} ft. or range {}/{} ft., {}",reach,short_range,long_range,self.target),true,true),
+                (format!("reach {} ft. or range {}/{} ft., {}",reach,short_range,long_range,targets),true,true),
             (Some(reach),Some(range),None) => 
-                (format!("reach {} ft. or range {} ft., {}",reach,range,self.target),true,true),
+                (format!("reach {} ft. or range {} ft., {}",reach,range,targets),true,true),
             (Some(reach),None,_) => 
-                (format!("reach {} ft., {}",reach,self.target),true,false),
+                (format!("reach {} ft., {}",reach,targets),true,false),
             (None,Some(short_range),Some(long_range)) => 
-                (format!("range {}/{} ft., {}",short_range,long_range,self.target),false,true),
+                (format!("range {}/{} ft., {}",short_range,long_range,targets),false,true),
             (None,Some(range),None) => 
-                (format!("range {} ft., {}",range,self.target),false,true),
+                (format!("range {} ft., {}",range,targets),false,true),
             (None,None,_) => 
-                (format!("{}",self.target),false,false)
+                (targets,false,false)
         };
         let attack_type = match self.type_ {
             Some(AttackType::Weapon) => "Weapon ",
@@ -719,6 +1000,9 @@ pub enum Weapon {
 
 impl Weapon {
 
+    // the condition on the larger damage of versatile weapons.
+    const TWO_HANDED_CONDITION: &'static str = "if used with two hands to make a melee attack";
+
     pub fn get_attack(&self) -> Attack {
         // Finnesse weapons use 'Best' attack bonus.
         // Reach weapons get an extra five feet of reach
@@ -775,6 +1059,7 @@ impl Weapon {
             range,
             long_range,
             target,
+            targeting: None
 
 
         }
@@ -825,18 +1110,18 @@ impl Weapon {
             Weapon::Mace(magic) =>          AttackEffect::Damage(die!(D6,magic), AttackBonus::Strength,    Damage::Bludgeoning),
             Weapon::Quarterstaff(magic) =>  AttackEffect::Or(die!(D6,magic), AttackBonus::Strength,    Damage::Bludgeoning,
                                                              die!(D8,magic), AttackBonus::Strength,    Damage::Bludgeoning,
-                                                             "if used with two hands to make a melee attack".to_owned()),
+                                                             Self::TWO_HANDED_CONDITION.to_owned()),
             Weapon::Sickle(magic) =>        AttackEffect::Damage(die!(D4,magic), AttackBonus::Strength,    Damage::Slashing),
             Weapon::Spear(magic) =>         AttackEffect::Or(die!(D6,magic), AttackBonus::Strength,    Damage::Piercing,
                                                              die!(D8,magic), AttackBonus::Strength,    Damage::Piercing,
-                                                             "if used with two hands to make a melee attack".to_owned()),
+                                                             Self::TWO_HANDED_CONDITION.to_owned()),
             Weapon::LightCrossbow(magic) => AttackEffect::Damage(die!(D8,magic), AttackBonus::Dexterity,   Damage::Piercing),
             Weapon::Dart(magic) =>          AttackEffect::Damage(die!(D4,magic), AttackBonus::Best,        Damage::Piercing),
             Weapon::Shortbow(magic) =>      AttackEffect::Damage(die!(D6,magic), AttackBonus::Dexterity,   Damage::Piercing),
             Weapon::Sling(magic) =>         AttackEffect::Damage(die!(D4,magic), AttackBonus::Dexterity,   Damage::Bludgeoning),
             Weapon::Battleaxe(magic) =>     AttackEffect::Or(die!(D8,magic), AttackBonus::Strength,    Damage::Slashing,
                                                              die!(D10,magic), AttackBonus::Strength,    Damage::Slashing,
-                                                             "if used with two hands to make a melee attack".to_owned()),
+                                                             Self::TWO_HANDED_CONDITION.to_owned()),
             Weapon::Flail(magic) =>         AttackEffect::Damage(die!(D8,magic), AttackBonus::Strength,    Damage::Bludgeoning),
             Weapon::Glaive(magic) =>        AttackEffect::Damage(die!(D10,magic), AttackBonus::Strength,    Damage::Slashing),
             Weapon::Greataxe(magic) =>      AttackEffect::Damage(die!(D12,magic), AttackBonus::Strength,    Damage::Slashing),
@@ -845,7 +1130,7 @@ impl Weapon {
             Weapon::Lance(magic) =>         AttackEffect::Damage(die!(D12,magic), AttackBonus::Strength,    Damage::Piercing),
             Weapon::Longsword(magic) =>     AttackEffect::Or(die!(D8,magic), AttackBonus::Strength,    Damage::Slashing,
                                                              die!(D10,magic), AttackBonus::Strength,    Damage::Slashing,
-                                                             "if used with two hands to make a melee attack".to_owned()),
+                                                             Self::TWO_HANDED_CONDITION.to_owned()),
             Weapon::Maul(magic) =>          AttackEffect::Damage(dice!(2,D6,magic), AttackBonus::Strength,    Damage::Bludgeoning),
             Weapon::Morningstar(magic) =>   AttackEffect::Damage(die!(D8,magic), AttackBonus::Strength,    Damage::Piercing),
             Weapon::Pike(magic) =>          AttackEffect::Damage(die!(D10,magic), AttackBonus::Strength,    Damage::Piercing),
@@ -854,11 +1139,11 @@ impl Weapon {
             Weapon::Shortsword(magic) =>    AttackEffect::Damage(die!(D6,magic), AttackBonus::Best,    Damage::Piercing),
             Weapon::Trident(magic) =>       AttackEffect::Or(die!(D6,magic), AttackBonus::Strength, Damage::Piercing,
                                                              die!(D8,magic), AttackBonus::Strength, Damage::Piercing,
-                                                             "if used with two hands to make a melee attack".to_owned()),
+                                                             Self::TWO_HANDED_CONDITION.to_owned()),
             Weapon::WarPick(magic) =>       AttackEffect::Damage(die!(D8,magic), AttackBonus::Strength, Damage::Piercing),
             Weapon::Warhammer(magic) =>     AttackEffect::Or(die!(D8,magic), AttackBonus::Strength, Damage::Bludgeoning,
                                                              die!(D10,magic), AttackBonus::Strength, Damage::Bludgeoning,
-                                                             "if used with two hands to make a melee attack".to_owned()),
+                                                             Self::TWO_HANDED_CONDITION.to_owned()),
             Weapon::Whip(magic) =>          AttackEffect::Damage(die!(D4,magic), AttackBonus::Best,    Damage::Slashing),
             Weapon::Blowgun(magic) =>       AttackEffect::FixedDamage(fixed!(magic), AttackBonus::Default, Damage::Piercing),
             Weapon::HandCrossbow(magic) =>  AttackEffect::Damage(die!(D6,magic), AttackBonus::Default, Damage::Piercing),
@@ -868,6 +1153,16 @@ impl Weapon {
         }
 
     }
+
+    /**
+    Returns the effect of the weapon when it is held in one hand. For versatile weapons, such as the longsword, the damage when used with two hands is left out. Other weapons have the same effect as [`Self::get_effect`].
+    */
+    pub fn get_one_handed_effect(&self, size: &CreatureSize) -> AttackEffect {
+        match self.get_effect(size) {
+            AttackEffect::Or(dice,bonus,damage,_,_,_,condition) if condition == Self::TWO_HANDED_CONDITION => AttackEffect::Damage(dice,bonus,damage),
+            effect => effect
+        }
+    }
 }
 
 impl std::fmt::Display for Weapon {
@@ -921,6 +1216,57 @@ impl std::fmt::Display for Weapon {
 
 }
 
+#[derive(PartialEq,Debug,Clone)]
+#[derive(Serialize,Deserialize)]
+/**
+The distance at which a custom weapon can hit, which also determines whether it is a melee or ranged weapon.
+*/
+pub enum WeaponReach {
+    /// `Reach(<integer>)`: a melee weapon with the specified reach.
+    Reach(u8),
+    /// `Range(<integer>,<integer>)`: a ranged weapon with the specified normal and long range.
+    Range(u16,u16),
+    /// `Thrown(<integer>,<integer>,<integer>)`: a melee weapon with the specified reach, which can also be thrown with the specified normal and long range.
+    Thrown(u8,u16,u16)
+}
+
+#[derive(PartialEq,Debug,Clone)]
+/**
+A weapon which isn't one of the built-in [`Weapon`]s, such as a claw or an invented weapon, defined by its reach, damage and the ability used for its attack and damage bonuses. Unlike the built-in weapons, the damage dice are used exactly as given, they are not increased for the size of the creature.
+*/
+pub struct CustomWeapon {
+    pub reach: WeaponReach,
+    pub dice: DiceExpression,
+    pub damage: Damage,
+    pub bonus: AttackBonus
+}
+
+impl CustomWeapon {
+
+    pub fn get_attack(&self) -> Attack {
+        let (reach,range,long_range) = match self.reach {
+            WeaponReach::Reach(reach) => (Some(reach),None,None),
+            WeaponReach::Range(range,long_range) => (None,Some(range),Some(long_range)),
+            WeaponReach::Thrown(reach,range,long_range) => (Some(reach),Some(range),Some(long_range))
+        };
+
+        Attack {
+            type_: Some(AttackType::Weapon),
+            bonus: self.bonus.clone(),
+            magic: None,
+            reach,
+            range,
+            long_range,
+            target: "one target".to_owned(),
+            targeting: None
+        }
+    }
+
+    pub fn get_effect(&self) -> AttackEffect {
+        AttackEffect::Damage(self.dice.clone(),self.bonus.clone(),self.damage.clone())
+    }
+}
+
 #[derive(Debug)]
 pub struct ParseWeaponError;
 
@@ -0,0 +1,141 @@
+/*
+ * Copyright © 2022 Neil M. Sheldon
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+/*!
+Challenge ratings are calculated with the method for creating a monster from the Dungeon Master's Guide. A defensive challenge rating is found from the effective hit points, then adjusted by one for every two points the armor class is above or below the armor class expected at that rating. An offensive challenge rating is found from the damage per round, then adjusted in the same way by the attack bonus, or the save DC if the creature's best action doesn't have an attack. The final challenge rating is the average of the two.
+
+The rows of the table are referred to by index, which is 0 for challenge 0, 1 to 3 for the fractions, and the number plus three after that. Adjustments move between rows, so an adjustment of one from challenge 1/2 is 1/4 or 1.
+*/
+
+use crate::stats::ChallengeRating;
+
+// Monster statistics by challenge rating, from the Dungeon Master's Guide: armor class, most hit points, attack bonus, most damage per round, save DC
+const CHALLENGE_RATING_TABLE: [(u8, u16, i8, u16, u8); 34] = [
+    (13, 6, 3, 1, 13),
+    (13, 35, 3, 3, 13),
+    (13, 49, 3, 5, 13),
+    (13, 70, 3, 8, 13),
+    (13, 85, 3, 14, 13),
+    (13, 100, 3, 20, 13),
+    (13, 115, 4, 26, 13),
+    (14, 130, 5, 32, 14),
+    (15, 145, 6, 38, 15),
+    (15, 160, 6, 44, 15),
+    (15, 175, 6, 50, 15),
+    (16, 190, 7, 56, 16),
+    (16, 205, 7, 62, 16),
+    (17, 220, 7, 68, 16),
+    (17, 235, 8, 74, 17),
+    (17, 250, 8, 80, 17),
+    (18, 265, 8, 86, 18),
+    (18, 280, 8, 92, 18),
+    (18, 295, 8, 98, 18),
+    (18, 310, 9, 104, 18),
+    (19, 325, 10, 110, 19),
+    (19, 340, 10, 116, 19),
+    (19, 355, 10, 122, 19),
+    (19, 400, 10, 140, 19),
+    (19, 445, 11, 158, 20),
+    (19, 490, 11, 176, 20),
+    (19, 535, 11, 194, 20),
+    (19, 580, 12, 212, 21),
+    (19, 625, 12, 230, 21),
+    (19, 670, 12, 248, 21),
+    (19, 715, 13, 266, 22),
+    (19, 760, 13, 284, 22),
+    (19, 805, 13, 302, 22),
+    (19, 850, 14, 320, 23),
+];
+
+fn challenge_rating_at(index: usize) -> ChallengeRating {
+    match index {
+        0 => ChallengeRating::Whole(0),
+        1 => ChallengeRating::Eighth,
+        2 => ChallengeRating::Quarter,
+        3 => ChallengeRating::Half,
+        index => ChallengeRating::Whole((index - 3) as u8)
+    }
+}
+
+fn challenge_rating_value(index: usize) -> f32 {
+    match index {
+        0 => 0.0,
+        1 => 0.125,
+        2 => 0.25,
+        3 => 0.5,
+        index => (index - 3) as f32
+    }
+}
+
+// finds the first row where the value is no more than the maximum in the column, values past the end of the table use the last row.
+fn find_row(value: u16, column: fn(&(u8, u16, i8, u16, u8)) -> u16) -> usize {
+    CHALLENGE_RATING_TABLE.iter().position(|row| value <= column(row)).unwrap_or(CHALLENGE_RATING_TABLE.len() - 1)
+}
+
+fn adjust_row(index: usize, difference: isize) -> usize {
+    // one step for every two points of difference, partial steps are dropped.
+    (index as isize + (difference / 2)).clamp(0, CHALLENGE_RATING_TABLE.len() as isize - 1) as usize
+}
+
+/**
+Returns the multiplier for the hit points of a creature with resistance or immunity to several kinds of damage, especially from weapons, by the challenge rating found from its hit points.
+*/
+pub fn effective_hit_points_multiplier(hit_points: u16, resistant: bool, immune: bool) -> f32 {
+    let index = find_row(hit_points, |row| row.1);
+    match (index, immune, resistant) {
+        (_, false, false) => 1.0,
+        (0..=7, _, _) => 2.0,
+        (8..=13, true, _) => 2.0,
+        (8..=13, false, true) => 1.5,
+        (14..=19, true, _) => 1.5,
+        (14..=19, false, true) => 1.25,
+        (_, true, _) => 1.25,
+        (_, false, true) => 1.0
+    }
+}
+
+/**
+Returns the row for the defensive challenge rating from the effective hit points and armor class.
+*/
+pub fn defensive_challenge_rating(effective_hit_points: u16, armor_class: u8) -> usize {
+    let index = find_row(effective_hit_points, |row| row.1);
+    adjust_row(index, armor_class as isize - CHALLENGE_RATING_TABLE[index].0 as isize)
+}
+
+/**
+Returns the row for the offensive challenge rating from the damage per round, and either an attack bonus or a save DC. If neither is given, the rating isn't adjusted.
+*/
+pub fn offensive_challenge_rating(damage_per_round: u16, attack_bonus: Option<i8>, save_dc: Option<u8>) -> usize {
+    let index = find_row(damage_per_round, |row| row.3);
+    let (_, _, expected_attack_bonus, _, expected_save_dc) = CHALLENGE_RATING_TABLE[index];
+    match (attack_bonus, save_dc) {
+        (Some(attack_bonus), _) => adjust_row(index, attack_bonus as isize - expected_attack_bonus as isize),
+        (None, Some(save_dc)) => adjust_row(index, save_dc as isize - expected_save_dc as isize),
+        (None, None) => index
+    }
+}
+
+/**
+Averages the defensive and offensive challenge ratings, rounding to the nearest challenge rating. Exactly half-way rounds up.
+*/
+pub fn average_challenge_rating(defensive: usize, offensive: usize) -> ChallengeRating {
+    let average = (challenge_rating_value(defensive) + challenge_rating_value(offensive)) / 2.0;
+    let (low, high) = if defensive < offensive {
+        (defensive, offensive)
+    } else {
+        (offensive, defensive)
+    };
+    let nearest = (low..=high).fold(low, |nearest, index| {
+        if (challenge_rating_value(index) - average).abs() <= (challenge_rating_value(nearest) - average).abs() {
+            index
+        } else {
+            nearest
+        }
+    });
+    challenge_rating_at(nearest)
+}
@@ -7,22 +7,28 @@
  */
 
 use std::rc::Rc;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
 use std::convert::TryFrom;
 
 use crate::dice::Die;
 use crate::dice::Dice;
 use crate::dice_expression::DiceExpression;
+use crate::dice_expression::AverageRounding;
 use crate::utils::AndJoin;
 use crate::utils::Capitalize;
 use crate::interpolation::InterpolationObject;
 use crate::interpolation::InterpolationValue;
 use crate::interpolation::interpolate_str_for_statblock;
+use crate::interpolation::evaluate_expression_str;
 use crate::errors::InterpolationErrorDetails;
 use crate::errors::CreatureError;
 use crate::stats::Damage;
 use crate::stats::Ability;
 use crate::stats::Skill;
+use crate::stats::CharacterClass;
 use crate::stats::Condition;
 use crate::stats::Language;
 use crate::attacks::Attack;
@@ -30,12 +36,19 @@ use crate::attacks::AttackEffect;
 use crate::attacks::CompoundAttackEffect;
 use crate::attacks::Multiattack;
 use crate::attacks::Weapon;
+use crate::attacks::CustomWeapon;
+use crate::attacks::AttackType;
+use crate::challenge_rating::effective_hit_points_multiplier;
+use crate::challenge_rating::defensive_challenge_rating;
+use crate::challenge_rating::offensive_challenge_rating;
+use crate::challenge_rating::average_challenge_rating;
 use crate::stats::Armor;
 use crate::actions::UsageLimit;
 use crate::actions::Action;
 use crate::spellcasting::Spellcasting;
 use crate::spellcasting::InnateSpellcasting;
 use crate::actions::LegendaryAction;
+use crate::actions::LegendaryActionsStyle;
 use crate::reactions::Reaction;
 use crate::features::Feature;
 use crate::stats::CreatureSize;
@@ -43,20 +56,46 @@ use crate::stats::CreatureType;
 use crate::stats::Alignment;
 use crate::stats::ChallengeRating;
 use crate::stat_block::StatBlockLegendary;
+use crate::stat_block::StatBlockLairActions;
+use crate::stat_block::StatBlockRegionalEffects;
 use crate::stat_block::StatBlockFeature;
 use crate::stat_block::CreatureStatBlock;
 use crate::structured_text::TextBlock;
+use crate::structured_text::TextSpan;
 
 
 pub struct CreatureFeature {
     pub name: String,
     pub description: String,
-    pub usage_limit: Option<UsageLimit>
+    pub usage_limit: Option<UsageLimit>,
+    pub save_ability: Option<Ability>, // if set, the description can reference `save_dc`, calculated from this ability
+    pub trigger: Option<String> // reactions only, kept apart from the description for tools which prompt on it
+}
+
+// Provides variables which are only available while interpolating a single feature, falling back to the creature's properties.
+struct FeatureInterpolationScope<'a> {
+    creature: &'a Creature,
+    save_dc: isize
+}
+
+impl InterpolationObject for FeatureInterpolationScope<'_> {
+
+    fn get_property(&self, property: &Rc<str>) -> Option<InterpolationValue> {
+        match property.as_ref() {
+            "save_dc" => Some(InterpolationValue::Number(self.save_dc,false)),
+            _ => self.creature.get_property(property)
+        }
+    }
+
+    fn average_rounding(&self) -> AverageRounding {
+        self.creature.average_rounding()
+    }
+
 }
 
 impl CreatureFeature {
 
-    fn feature_to_text_block(name: &str, description: &str, usage_limit: &Option<UsageLimit>, data: &Rc<Creature>) -> Result<Vec<TextBlock>,InterpolationErrorDetails> {
+    fn feature_to_text_block<Data: InterpolationObject>(name: &str, description: &str, usage_limit: &Option<UsageLimit>, data: &Data) -> Result<Vec<TextBlock>,InterpolationErrorDetails> {
         let source = format!("${{par(}}{}{}.${{)}}{}",name,if let Some(usage_limit) = usage_limit {
             format!(" ({})",usage_limit)
         } else {
@@ -65,10 +104,37 @@ impl CreatureFeature {
         interpolate_str_for_statblock(&source, name, data, true)
     }
 
+    // the trigger is interpolated like the description, but is output as plain text.
+    fn trigger_to_text<Data: InterpolationObject>(name: &str, trigger: &Option<String>, data: &Data) -> Result<Option<String>,InterpolationErrorDetails> {
+        trigger.as_ref().map(|trigger| {
+            interpolate_str_for_statblock(trigger, name, data, true).map(|blocks| blocks.iter().flat_map(|block| match block {
+                TextBlock::Paragraph { body, .. } |
+                TextBlock::SubParagraph { body, .. } => body.iter()
+            }).map(|span| match span {
+                TextSpan::Normal(text) |
+                TextSpan::Italic(text) |
+                TextSpan::Bold(text) |
+                TextSpan::BoldItalic(text) => text.as_str()
+            }).collect())
+        }).transpose()
+    }
+
 
     fn to_stat_block(&self, data: &Rc<Creature>) -> Result<StatBlockFeature,InterpolationErrorDetails> {
-        Ok(StatBlockFeature {
-            text: Self::feature_to_text_block(&self.name, &self.description, &self.usage_limit, data)?
+        Ok(if let Some(save_ability) = &self.save_ability {
+            let scope = FeatureInterpolationScope {
+                creature: data.as_ref(),
+                save_dc: data.get_save_dc(save_ability)
+            };
+            StatBlockFeature {
+                text: Self::feature_to_text_block(&self.name, &self.description, &self.usage_limit, &scope)?,
+                trigger: Self::trigger_to_text(&self.name, &self.trigger, &scope)?
+            }
+        } else {
+            StatBlockFeature {
+                text: Self::feature_to_text_block(&self.name, &self.description, &self.usage_limit, data)?,
+                trigger: Self::trigger_to_text(&self.name, &self.trigger, data)?
+            }
         })
     }
 }
@@ -169,7 +235,7 @@ impl CreatureLegendaryAction {
 }
 
 pub struct CreatureLegendaryActions {
-    pub description: String,
+    pub count: u8,
     pub actions: Vec<CreatureLegendaryAction>
 }
 
@@ -177,7 +243,7 @@ impl CreatureLegendaryActions {
 
     pub fn to_stat_block(&self, data: &Rc<Creature>) -> Result<StatBlockLegendary,InterpolationErrorDetails> {
         Ok(StatBlockLegendary {
-            description: interpolate_str_for_statblock(&self.description,"legendary actions: description",data.as_ref(),true)?,
+            description: interpolate_str_for_statblock(&LegendaryAction::get_main_description(&self.count,&data.legendary_actions_style),"legendary actions: description",data.as_ref(),true)?,
             actions: self.actions.iter().map(|a| {
                 // NOTE: this is different from regular actions, it's a subparagraph block, and has cost instead of usage limit
                 let source = format!("${{sub(}}{}{}.${{)}}{}",&a.name,if a.cost != 1 {
@@ -186,7 +252,8 @@ impl CreatureLegendaryActions {
                     "".to_owned()
                 },&a.description);
                 Ok(StatBlockFeature {
-                    text: interpolate_str_for_statblock(&source, &a.name, data, true)?
+                    text: interpolate_str_for_statblock(&source, &a.name, data, true)?,
+                    trigger: None
                 })
             }).collect::<Result<Vec<StatBlockFeature>,InterpolationErrorDetails>>()?
         })
@@ -194,6 +261,55 @@ impl CreatureLegendaryActions {
 
     
 
+}
+
+pub struct CreatureLairActions {
+    pub foreword: String,
+    pub actions: Vec<String>,
+    pub afterword: String
+}
+
+impl CreatureLairActions {
+
+    pub fn to_stat_block(&self, data: &Rc<Creature>) -> Result<StatBlockLairActions,InterpolationErrorDetails> {
+        Ok(StatBlockLairActions {
+            foreword: interpolate_str_for_statblock(&self.foreword,"lair actions: description",data.as_ref(),true)?,
+            actions: self.actions.iter().map(|a| {
+                // lair actions don't have names, so this is a list item without a heading.
+                let source = format!("${{sub()}}{}",a);
+                Ok(StatBlockFeature {
+                    text: interpolate_str_for_statblock(&source, "lair action", data.as_ref(), true)?,
+                    trigger: None
+                })
+            }).collect::<Result<Vec<StatBlockFeature>,InterpolationErrorDetails>>()?,
+            afterword: interpolate_str_for_statblock(&self.afterword,"lair actions: afterword",data.as_ref(),true)?
+        })
+    }
+
+}
+
+pub struct CreatureRegionalEffects {
+    pub foreword: String,
+    pub effects: Vec<String>,
+    pub afterword: String
+}
+
+impl CreatureRegionalEffects {
+
+    pub fn to_stat_block(&self, data: &Rc<Creature>) -> Result<StatBlockRegionalEffects,InterpolationErrorDetails> {
+        Ok(StatBlockRegionalEffects {
+            foreword: interpolate_str_for_statblock(&self.foreword,"regional effects: description",data.as_ref(),true)?,
+            effects: self.effects.iter().map(|a| {
+                let source = format!("${{sub()}}{}",a);
+                Ok(StatBlockFeature {
+                    text: interpolate_str_for_statblock(&source, "regional effect", data.as_ref(), true)?,
+                    trigger: None
+                })
+            }).collect::<Result<Vec<StatBlockFeature>,InterpolationErrorDetails>>()?,
+            afterword: interpolate_str_for_statblock(&self.afterword,"regional effects: afterword",data.as_ref(),true)?
+        })
+    }
+
 }
 
 pub struct CreatureSpeed {
@@ -291,6 +407,58 @@ pub struct CreatureProficiencies {
 
 impl CreatureProficiencies {
 
+    // the skill for an interpolation property, these are named like the fields.
+    fn skill_for_property(property: &str) -> Option<Skill> {
+        match property {
+            "athletics" => Some(Skill::Athletics),
+            "acrobatics" => Some(Skill::Acrobatics),
+            "sleight_of_hand" => Some(Skill::SleightOfHand),
+            "stealth" => Some(Skill::Stealth),
+            "arcana" => Some(Skill::Arcana),
+            "history" => Some(Skill::History),
+            "investigation" => Some(Skill::Investigation),
+            "nature" => Some(Skill::Nature),
+            "religion" => Some(Skill::Religion),
+            "animal_handling" => Some(Skill::AnimalHandling),
+            "insight" => Some(Skill::Insight),
+            "medicine" => Some(Skill::Medicine),
+            "perception" => Some(Skill::Perception),
+            "survival" => Some(Skill::Survival),
+            "deception" => Some(Skill::Deception),
+            "intimidation" => Some(Skill::Intimidation),
+            "performance" => Some(Skill::Performance),
+            "persuasion" => Some(Skill::Persuasion),
+            _ => None
+        }
+    }
+
+    fn get_proficiency(&self, skill: &Skill) -> Option<bool> {
+        match skill {
+            Skill::Athletics => self.athletics,
+            Skill::Acrobatics => self.acrobatics,
+            Skill::SleightOfHand => self.sleight_of_hand,
+            Skill::Stealth => self.stealth,
+            Skill::Arcana => self.arcana,
+            Skill::History => self.history,
+            Skill::Investigation => self.investigation,
+            Skill::Nature => self.nature,
+            Skill::Religion => self.religion,
+            Skill::AnimalHandling => self.animal_handling,
+            Skill::Insight => self.insight,
+            Skill::Medicine => self.medicine,
+            Skill::Perception => self.perception,
+            Skill::Survival => self.survival,
+            Skill::Deception => self.deception,
+            Skill::Intimidation => self.intimidation,
+            Skill::Performance => self.performance,
+            Skill::Persuasion => self.persuasion
+        }
+    }
+
+    // the bonus for the skill, if the creature is proficient in it.
+    fn get_skill_bonus(&self, skill: &Skill, creature: &Creature) -> Option<i8> {
+        self.get_proficiency(skill).map(|expertise| Self::calculate_skill_bonus(skill, creature, &expertise))
+    }
 
     fn calculate_skill_bonus(skill: &Skill, creature: &Creature, expertise: &bool) -> i8 {
         let mod_bonus = Ability::score_to_mod(match skill {
@@ -314,9 +482,9 @@ impl CreatureProficiencies {
             Skill::Persuasion => creature.charisma      
         });
         if *expertise {
-            mod_bonus + (creature.challenge_rating.get_proficiency_bonus() as i8 * 2)
+            mod_bonus + (creature.get_proficiency_bonus() as i8 * 2)
         } else {
-            mod_bonus + creature.challenge_rating.get_proficiency_bonus() as i8
+            mod_bonus + creature.get_proficiency_bonus() as i8
         }
 
     }
@@ -384,7 +552,11 @@ pub struct CreatureSenses {
     pub darkvision: Option<u8>,
     pub tremorsense: Option<u8>,
     pub truesight: Option<u8>,
-    pub custom: HashMap<String,u8>
+    pub custom: HashMap<String,u8>,
+    pub separator: Option<String>,
+    pub passive_perception_label: Option<String>,
+    pub passive_perception_bonus: i8,
+    pub passive_perception_override: Option<u8>
 }
 
 impl CreatureSenses {
@@ -417,17 +589,38 @@ impl CreatureSenses {
             result.push(format!("{} {} ft.",sense,distance))
         }
 
-        result.push(format!("passive Perception {}",if let Some(perception) = perception {
-            10 + perception
+        let passive_perception = if let Some(passive_perception) = self.passive_perception_override {
+            passive_perception as i8
+        } else if let Some(perception) = perception {
+            10 + perception + self.passive_perception_bonus
         } else {
-            10 + Ability::score_to_mod(wisdom)
-        }));
-        result.join(", ")
+            10 + Ability::score_to_mod(wisdom) + self.passive_perception_bonus
+        };
+        let label = self.passive_perception_label.as_deref().unwrap_or("passive Perception");
+        result.push(if label.is_empty() {
+            passive_perception.to_string()
+        } else {
+            format!("{} {}",label,passive_perception)
+        });
+        result.join(self.separator.as_deref().unwrap_or(", "))
 
     }
 
 }
 
+/**
+A part of the damage resistances, immunities or vulnerabilities, which a note can be attached to.
+*/
+#[derive(Clone,PartialEq,Debug)]
+pub enum DamageClause {
+    Damage(Damage),
+    Type(String),
+    All,
+    NonMagicalAttacks,
+    NonSilveredAttacks,
+    NonAdamantineAttacks
+}
+
 #[derive(Default)]
 pub struct CreatureResistances {
     pub bludgeoning: bool,
@@ -448,23 +641,57 @@ pub struct CreatureResistances {
     pub non_silvered_attacks: bool,
     pub non_adamantine_attacks: bool,
     pub custom: Option<String>,
+    pub custom_types: Vec<String>, // homebrew damage types, listed after the standard ones
+    pub last_added: Option<DamageClause>, // the clause a note will be attached to
+    pub notes: Vec<(DamageClause,String)>, // parenthetical notes placed after their clause, such as "from stoneskin"
     
 }
 
 impl CreatureResistances {
 
+    // the Dungeon Master's Guide only increases effective hit points for resistance to several damage types, especially weapon damage.
+    fn covers_several_damage_types(&self) -> bool {
+        let types = [self.bludgeoning,self.piercing,self.slashing,self.cold,self.fire,self.thunder,self.radiant,self.force,self.lightning,self.poison,self.acid,self.necrotic,self.psychic];
+        self.all || self.non_magical_attacks || self.non_adamantine_attacks || self.non_silvered_attacks ||
+            (self.bludgeoning && self.piercing && self.slashing) || 
+            ((types.iter().filter(|a| **a).count() + self.custom_types.len()) >= 3)
+    }
+
+    /**
+    Attaches a note to the clause most recently added, replacing any note it already had. Returns false if nothing has been added yet.
+    */
+    pub fn add_note(&mut self, note: &str) -> bool {
+        if let Some(clause) = &self.last_added {
+            let clause = clause.clone();
+            self.notes.retain(|(noted,_)| noted != &clause);
+            self.notes.push((clause,note.to_owned()));
+            true
+        } else {
+            false
+        }
+    }
+
+    fn get_note(&self, clause: &DamageClause) -> Option<&str> {
+        self.notes.iter().find(|(noted,_)| noted == clause).map(|(_,note)| note.as_str())
+    }
+
     fn to_stat_block(&self) -> Option<String> {
 
         if let Some(custom) = &self.custom {
             Some(custom.clone())
         } else {
             let mut regular_damage = vec![];
+            // damage types with a note are set apart, so the note only applies to them
+            let mut noted_damage = vec![];
             let mut special_damage = vec![];
-    
+
             macro_rules! push_regular {
                 ($prop: ident, $damage: ident) => {
                     if self.all || self.$prop {
-                        regular_damage.push(Damage::$damage.to_string())
+                        match self.get_note(&DamageClause::Damage(Damage::$damage)) {
+                            Some(note) => noted_damage.push(format!("{} ({})",Damage::$damage,note)),
+                            _ => regular_damage.push(Damage::$damage.to_string())
+                        }
                     }
                 };
             }
@@ -476,29 +703,48 @@ impl CreatureResistances {
             push_regular!(fire,Fire);
             push_regular!(thunder,Thunder);
             push_regular!(radiant,Radiant);
+            push_regular!(force,Force);
             push_regular!(lightning,Lightning);
             push_regular!(poison,Poison);
             push_regular!(acid,Acid);
             push_regular!(necrotic,Necrotic);
             push_regular!(psychic,Psychic);
-            
-            if self.non_magical_attacks {
-                special_damage.push("bludgeoning, piercing, and slashing from nonmagical attacks");
+
+            for name in &self.custom_types {
+                match self.get_note(&DamageClause::Type(name.clone())) {
+                    Some(note) => noted_damage.push(format!("{} ({})",name,note)),
+                    None => regular_damage.push(name.clone())
+                }
             }
-            if self.non_adamantine_attacks {
-                special_damage.push("bludgeoning, piercing, and slashing from nonmagical attacks that aren't adamantine");
+
+            macro_rules! push_special {
+                ($prop: ident, $clause: ident, $text: expr) => {
+                    if self.$prop {
+                        match self.get_note(&DamageClause::$clause) {
+                            Some(note) => special_damage.push(format!("{} ({})",$text,note)),
+                            None => special_damage.push($text.to_owned())
+                        }
+                    }
+                };
             }
-            if self.non_silvered_attacks {
-                special_damage.push("bludgeoning, piercing, and slashing from nonmagical attacks that aren't silvered");
+
+            push_special!(non_magical_attacks,NonMagicalAttacks,"bludgeoning, piercing, and slashing from nonmagical attacks");
+            push_special!(non_adamantine_attacks,NonAdamantineAttacks,"bludgeoning, piercing, and slashing from nonmagical attacks that aren't adamantine");
+            push_special!(non_silvered_attacks,NonSilveredAttacks,"bludgeoning, piercing, and slashing from nonmagical attacks that aren't silvered");
+
+            let mut clauses = vec![];
+            if regular_damage.len() > 0 {
+                let mut regular = regular_damage.join(", ");
+                if let (true,Some(note)) = (self.all,self.get_note(&DamageClause::All)) {
+                    regular.push_str(&format!(" ({})",note))
+                }
+                clauses.push(regular)
             }
-    
-            if (regular_damage.len() > 0) || (special_damage.len() > 0) {
-                let mut result = regular_damage.join(", ");
-                if special_damage.len() > 0 {
-                    result.push_str("; ");
-                    result.push_str(&special_damage.join("; "))
-                };
-                Some(result)
+            clauses.append(&mut noted_damage);
+            clauses.append(&mut special_damage);
+
+            if clauses.len() > 0 {
+                Some(clauses.join("; "))
             } else {
                 None
             }
@@ -527,6 +773,7 @@ pub struct CreatureConditionImmunities {
     pub restrained: bool,
     pub stunned: bool,
     pub unconscious: bool,
+    pub custom: Vec<String>, // homebrew conditions, listed after the standard ones
 }
 
 impl CreatureConditionImmunities {
@@ -560,6 +807,8 @@ impl CreatureConditionImmunities {
         add_condition!(stunned,Stunned);
         add_condition!(unconscious,Unconscious);
 
+        result.extend(self.custom.iter().cloned());
+
         if result.len() > 0 {
             Some(result.join(", "))
         } else {
@@ -593,6 +842,7 @@ impl CreatureArmor {
             Some(Armor::Splint) => 17,
             Some(Armor::Plate) => 18,
             Some(Armor::Natural(a)) => (10 + a + dexterity_mod) as u8, // natural bonus
+            Some(Armor::NaturalFlat(a,_)) => a, // armor class, note
             Some(Armor::Armor(a,_)) => (10 + a as i8 + dexterity_mod) as u8, // value, description
             _ => (10 + dexterity_mod) as u8
         }) + if self.shield {
@@ -606,21 +856,23 @@ impl CreatureArmor {
         let mut result = Vec::new();
         if let Some(armor) = &self.armor {
             result.push(match armor {
-                Armor::Padded => "padded armor",
-                Armor::Leather => "leather armor",
-                Armor::StuddedLeather => "studded leather armor",
-                Armor::Hide => "hide armor",
-                Armor::ChainShirt => "chain shirt",
-                Armor::ScaleMail => "scale mail armor",
-                Armor::Breastplate => "breastplate",
-                Armor::HalfPlate => "half plate armor",
-                Armor::RingMail => "ring mail armor",
-                Armor::ChainMail => "chain mail armor",
-                Armor::Splint => "splint armor",
-                Armor::Plate => "plate armor",
-                Armor::Natural(_) => "natural armor",
-                Armor::Armor(_,b) => b, // value, description
-            }.to_owned())
+                Armor::Padded => "padded armor".to_owned(),
+                Armor::Leather => "leather armor".to_owned(),
+                Armor::StuddedLeather => "studded leather armor".to_owned(),
+                Armor::Hide => "hide armor".to_owned(),
+                Armor::ChainShirt => "chain shirt".to_owned(),
+                Armor::ScaleMail => "scale mail armor".to_owned(),
+                Armor::Breastplate => "breastplate".to_owned(),
+                Armor::HalfPlate => "half plate armor".to_owned(),
+                Armor::RingMail => "ring mail armor".to_owned(),
+                Armor::ChainMail => "chain mail armor".to_owned(),
+                Armor::Splint => "splint armor".to_owned(),
+                Armor::Plate => "plate armor".to_owned(),
+                Armor::Natural(_) |
+                Armor::NaturalFlat(_,None) => "natural armor".to_owned(),
+                Armor::NaturalFlat(_,Some(note)) => format!("natural armor, {}",note),
+                Armor::Armor(_,b) => b.clone(), // value, description
+            })
         }
 
         
@@ -665,8 +917,10 @@ The following properties can be retrieved during interpolation. Their value type
 * `group (string)`: The creature's group, or an empty string.
 * `alignment (string)`: The alignment of the creature
 * `hit_dice (dice)`: The calculated hit dice for the creature.
-* `hit_points (number)`: The calculate hit points for the creature.
+* `hit_points (number)`: The calculate hit points for the creature, or the hit points set with `HitPoints` if they were overridden.
+* `hit_points_average (number)`: The average of the hit dice, even if the hit points were overridden. This is the same as `hit_dice.avg`.
 * `armor_class (number)`: The calculated armor class for the creature.
+* `initiative (number)`: The initiative bonus for the creature, whether or not it is shown in the stat block.
 * `strength (number)`: The strength score for the creature.
 * `dexterity (number)`: The dexterity score for the creature.
 * `constitution (number)`: The constitution score for the creature.
@@ -674,7 +928,10 @@ The following properties can be retrieved during interpolation. Their value type
 * `wisdom (number)`: The wisdom score for the creature.
 * `charisma (number)`: The charisma score for the creature.
 * `atk (number)`: The best of the dexterity and strength modifiers.
-* `spell_atk (number)`: The calculated spell attack bonus. If the creature has both innate and regular spellcasting, this will return the innate bonus. If the creature has neither, this property will not be available, and an error will occur.
+* `spell_atk (number)`: The calculated spell attack bonus. If the creature has both innate and regular spellcasting, this will return the innate bonus, unless a regular spellcasting feature is marked with the `Primary` command. If the creature has neither, this property will not be available, and an error will occur.
+* `spell_dc (number)`: The calculated spell save DC. As with `spell_atk`, this is the innate save DC if the creature has both kinds of spellcasting, and is not available if it has neither.
+* `caster_level (number)`: The caster level from the creature's regular spellcasting, using the one marked `Primary` if there are several. Innate spellcasting has no caster level, so this is not available if the creature has no regular spellcasting.
+* `spell_class (string)`: The class whose spell list the creature's regular spellcasting uses, such as "wizard". This is not available if the creature has no regular spellcasting.
 * `str (number)`: The calculated strength modifier.
 * `dex (number)`: The calculated dexterity modifier.
 * `con (number)`: The calculated constitution modifier.
@@ -687,11 +944,18 @@ The following properties can be retrieved during interpolation. Their value type
 * `int_save (number)`: The calculated save bonus for intelligence.
 * `wis_save (number)`: The calculated save bonus for wisdom.
 * `cha_save (number)`: The calculated save bonus for charisma.
-* `prof (number)`: The proficiency bonus, taken from the creatures challenge rating.
+* `athletics (number)`, `acrobatics`, `sleight_of_hand`, `stealth`, `arcana`, `history`, `investigation`, `nature`, `religion`, `animal_handling`, `insight`, `medicine`, `perception`, `survival`, `deception`, `intimidation`, `performance`, `persuasion`: The calculated bonus for the skill, including expertise. These are only available for skills the creature is proficient in, an error will occur for any other skill.
+* `prof (number)`: The proficiency bonus, taken from the creatures challenge rating, unless it was set with the `ProficiencyBonus` command.
+
+Custom properties defined with the `DefineProperty` command are also available, under the name they were given. Their expressions are evaluated each time they are referenced, so they reflect any changes made to the creature after they were defined. A custom property which refers to itself, directly or through other custom properties, is not available. The properties listed above take precedence if a custom property has the same name.
+
+Any parameters passed in when the creature is built (such as with the `--param` option on the command line) are also available as string variables, under the name they were given. The properties listed above, and custom properties, take precedence if a parameter has the same name.
 
 */
 pub struct Creature {
     pub name: String,
+    pub display_name: Option<String>, // shown as the title of the stat-block instead of the name
+    pub proper_name: bool, // the name is used as-is for the subject, without the article
     pub subject: Option<String>,
     pub subject_cap: Option<String>,
     pub possessive: Option<String>,
@@ -710,7 +974,12 @@ pub struct Creature {
     pub hit_die: Die,
     pub hit_dice_count: u8,
     pub override_hit_points: Option<u16>,
+    pub minion: bool,
+    pub one_handed: bool, // versatile weapons are added without their two-handed damage
+    pub average_rounding: AverageRounding,
     pub armor: CreatureArmor,
+    pub initiative: Option<u8>, // number of times the proficiency bonus is added, the initiative is not shown if this is None
+    pub override_initiative: Option<i8>,
     pub speed: CreatureSpeed,
     pub strength: u8,
     pub dexterity: u8,
@@ -732,15 +1001,43 @@ pub struct Creature {
     pub senses: CreatureSenses,
     pub languages: Vec<(Language,bool)>, // language, whether it is spoken or just understood, a hashmap would be better, but it doesn't keeep the order.
     pub challenge_rating: ChallengeRating,
+    pub proficiency_bonus: Option<u8>, // overrides the proficiency bonus for the challenge rating
+    pub show_proficiency_bonus: bool,
     pub multiattack: Option<Multiattack>,
     pub special_abilities: Vec<CreatureSpecialAbility>,
     pub actions: Vec<CreatureAction>,
     pub reactions: Vec<CreatureFeature>,
     pub legendary_actions: Option<CreatureLegendaryActions>,
-    pub source: Option<String>
+    pub legendary_actions_style: LegendaryActionsStyle,
+    pub lair_actions: Option<CreatureLairActions>,
+    pub regional_effects: Option<CreatureRegionalEffects>,
+    pub companions: Vec<String>,
+    pub extra: BTreeMap<String,String>,
+    pub notes: Vec<String>,
+    pub environments: Vec<String>,
+    pub habitat: Vec<String>,
+    pub treasure: Option<String>,
+    pub source: Option<String>,
+    pub custom_properties: HashMap<String,String>, // name, expression
+    evaluating_properties: RefCell<Vec<String>>, // custom properties currently being evaluated, to catch recursion
+    pub parameters: HashMap<String,String> // values passed in when the creature was built, such as with the '--param' option on the command line
 
 }
 
+// The properties supplied by `get_property`, in the order they are documented. This should be kept in sync with the match there.
+const INTERPOLATION_PROPERTIES: [&str; 63] = [
+    "name", "subj", "Subj", "poss", "Poss", "subjpro", "Subjpro", "objpro", "refpro", "posspro", "Posspro",
+    "size", "type", "subtype", "group", "alignment",
+    "hit_dice", "hit_points", "hit_points_average", "armor_class", "initiative",
+    "strength", "dexterity", "constitution", "intelligence", "wisdom", "charisma",
+    "atk", "spell_atk", "spell_dc", "caster_level", "spell_class",
+    "str", "dex", "con", "int", "wis", "cha",
+    "str_save", "dex_save", "con_save", "int_save", "wis_save", "cha_save",
+    "athletics", "acrobatics", "sleight_of_hand", "stealth", "arcana", "history", "investigation", "nature", "religion",
+    "animal_handling", "insight", "medicine", "perception", "survival", "deception", "intimidation", "performance", "persuasion",
+    "prof"
+];
+
 impl InterpolationObject for Creature {
 
 
@@ -763,9 +1060,11 @@ impl InterpolationObject for Creature {
             "group" => Some(InterpolationValue::String(Rc::from(self.group.as_deref().unwrap_or("")))),
             "alignment" => Some(InterpolationValue::String(Rc::from(self.alignment.to_string()))),
             "hit_dice" => Some(InterpolationValue::Dice(self.get_hit_dice(),false)), 
-            "hit_points" => Some(InterpolationValue::Number(self.override_hit_points.map(|n| n as isize).unwrap_or(self.get_hit_dice().average()),false)),
+            "hit_points" => Some(InterpolationValue::Number(self.override_hit_points.map(|n| n as isize).unwrap_or(self.get_hit_dice().rounded_average(&self.average_rounding)),false)),
+            "hit_points_average" => Some(InterpolationValue::Number(self.get_hit_dice().rounded_average(&self.average_rounding),false)),
             //"shield" => FUTURE: Can I do anything with this? I can't handle booleans right now, but maybe...
             "armor_class" => Some(InterpolationValue::Number(self.get_armor_class() as isize,false)),
+            "initiative" => Some(InterpolationValue::Number(self.get_initiative_bonus() as isize,true)),
             // "speed" -- FUTURE: Perhaps support a 'walk' or other speed?
             "strength" => Some(InterpolationValue::Number(self.strength as isize,false)),
             "dexterity" => Some(InterpolationValue::Number(self.dexterity as isize,false)),
@@ -779,6 +1078,9 @@ impl InterpolationObject for Creature {
                 Some(InterpolationValue::Number(Ability::score_to_mod(self.dexterity) as isize,false))
             },
             "spell_atk" => self.get_spell_attack_bonus().map(|a| InterpolationValue::Number(a as isize,false)),
+            "spell_dc" => self.get_spell_save_dc().map(|a| InterpolationValue::Number(a as isize,false)),
+            "caster_level" => self.get_spellcasting().map(|a| InterpolationValue::Number(a.caster_level as isize,false)),
+            "spell_class" => self.get_spellcasting().map(|a| InterpolationValue::String(Rc::from(a.class.as_str()))),
             "str" => Some(InterpolationValue::Number(Ability::score_to_mod(self.strength) as isize,false)),
             "dex" => Some(InterpolationValue::Number(Ability::score_to_mod(self.dexterity) as isize,false)),
             "con" => Some(InterpolationValue::Number(Ability::score_to_mod(self.constitution) as isize,false)),
@@ -791,12 +1093,23 @@ impl InterpolationObject for Creature {
             "int_save" => Some(InterpolationValue::Number(Creature::saving_throw_to_stat_block(Ability::Intelligence, self) as isize,false)),
             "wis_save" => Some(InterpolationValue::Number(Creature::saving_throw_to_stat_block(Ability::Wisdom, self) as isize,false)),
             "cha_save" => Some(InterpolationValue::Number(Creature::saving_throw_to_stat_block(Ability::Charisma, self) as isize,false)),
-            // FUTURE: skills? vulnerabilitys, resistance, immunities, sense, languages, etc. access data on other actions, reactions, etc?
-            "prof" => Some(InterpolationValue::Number(self.challenge_rating.get_proficiency_bonus() as isize,false)),
-            _ => None
+            // FUTURE: vulnerabilitys, resistance, immunities, sense, languages, etc. access data on other actions, reactions, etc?
+            "prof" => Some(InterpolationValue::Number(self.get_proficiency_bonus() as isize,false)),
+            _ => if let Some(skill) = CreatureProficiencies::skill_for_property(property) {
+                self.skills.get_skill_bonus(&skill, self).map(|bonus| InterpolationValue::Number(bonus as isize,false))
+            } else {
+                match self.custom_properties.get(property.as_ref()) {
+                    Some(expression) => self.evaluate_custom_property(property, expression),
+                    None => self.parameters.get_property(property)
+                }
+            }
         }
     }
 
+    fn average_rounding(&self) -> AverageRounding {
+        self.average_rounding
+    }
+
 
 }
 
@@ -805,6 +1118,8 @@ impl Default for Creature {
     fn default() -> Self {
         Self {
             name: "".to_owned(), //String,
+            display_name: None,
+            proper_name: false,
             subject: None,
             subject_cap: None,
             possessive: None,
@@ -823,7 +1138,12 @@ impl Default for Creature {
             hit_die: Die::D6,
             hit_dice_count: 1,
             override_hit_points: None, //Option<u16>,
+            minion: false,
+            one_handed: false,
+            average_rounding: AverageRounding::default(),
             armor: CreatureArmor::default(), //Armor,
+            initiative: None,
+            override_initiative: None,
             speed: CreatureSpeed::default(),
             strength: 10, //u8,
             dexterity: 10,//u8,
@@ -845,12 +1165,26 @@ impl Default for Creature {
             senses: CreatureSenses::default(), //Vec<Sense>,
             languages: Vec::new(), //Vec<(Language,bool)>, // language, whether it is spoken or just understood
             challenge_rating: ChallengeRating::None, //ChallengeRating,
+            proficiency_bonus: None,
+            show_proficiency_bonus: false,
             multiattack: None,
             actions: Vec::new(),//Vec<CreatureAction>,
             reactions: Vec::new(), //Vec<ReactionData>,
             legendary_actions: None, //Option<CreatureLegendaryActions>,
+            legendary_actions_style: LegendaryActionsStyle::default(),
+            lair_actions: None, //Option<CreatureLairActions>,
+            regional_effects: None, //Option<CreatureRegionalEffects>,
             special_abilities: Vec::new(), //Vec<CreatureFeature>
-            source: None            
+            companions: Vec::new(),
+            extra: BTreeMap::new(),
+            notes: vec![],
+            environments: Vec::new(),
+            habitat: Vec::new(),
+            treasure: None,
+            source: None,
+            custom_properties: HashMap::new(),
+            evaluating_properties: RefCell::new(vec![]),
+            parameters: HashMap::new()
         }
         
     }    
@@ -859,18 +1193,26 @@ impl Default for Creature {
 
 impl Creature {
 
+    /// The name of the feature added to minions, which follow a variant rule rather than the official rules.
+    pub const MINION_FEATURE_NAME: &'static str = "Minion (Variant Rule)";
+    pub const MINION_DESCRIPTION: &'static str = "If ${subj} takes any damage, ${subjpro} drops to 0 hit points.";
+
     fn get_subject(&self, capitalize: bool) -> String {
         if capitalize {
             if let Some(subject) = &self.subject_cap {
                 subject.clone()
             } else if let Some(subject) = &self.subject {
                 subject.capitalize_first_letter()
+            } else if self.proper_name {
+                self.name.clone()
             } else {
                 format!("The {}",self.name.to_lowercase())
             }
         } else {
             if let Some(subject) = &self.subject {
                 subject.clone()
+            } else if self.proper_name {
+                self.name.clone()
             } else {
                 format!("the {}",self.name.to_lowercase())
             }
@@ -984,7 +1326,7 @@ impl Creature {
         };
         let modifier = Ability::score_to_mod(score);
         if proficient {
-            modifier + creature.challenge_rating.get_proficiency_bonus() as i8
+            modifier + creature.get_proficiency_bonus() as i8
         } else {
             modifier
         }
@@ -1027,8 +1369,8 @@ impl Creature {
         let mut telepathy = None;
 
         for (language,speaks) in list {
-            if let Language::Telepathy(distance) = language {
-                telepathy = Some(distance)
+            if let Language::Telepathy(..) = language {
+                telepathy = Some(language)
             } else if *speaks {
                 spoken_languages.push(language.to_string())
             } else {
@@ -1043,7 +1385,7 @@ impl Creature {
         }
 
         if let Some(telepathy) = telepathy {
-            spoken_languages.push(Language::Telepathy(*telepathy).to_string())
+            spoken_languages.push(telepathy.to_string())
         }
 
         if spoken_languages.len() > 0 {
@@ -1059,7 +1401,8 @@ impl Creature {
 
         vec.iter().map(|a| {
             Ok(StatBlockFeature {
-                text: CreatureFeature::feature_to_text_block(&a.name, &a.description, &a.usage_limit, data)?
+                text: CreatureFeature::feature_to_text_block(&a.name, &a.description, &a.usage_limit, data)?,
+                trigger: None
             })
     
         }).collect()
@@ -1074,13 +1417,15 @@ impl Creature {
 
     fn spellcasting_to_stat_block(spells: &Spellcasting, data: &Rc<Creature>) -> Result<StatBlockFeature,InterpolationErrorDetails> {
         Ok(StatBlockFeature {
-            text: CreatureFeature::feature_to_text_block(Spellcasting::FEATURE_NAME, &spells.get_description(), &None, data)?
+            text: CreatureFeature::feature_to_text_block(spells.feature_name(), &spells.get_description(), &None, data)?,
+            trigger: None
         })
     }
 
     fn innate_spellcasting_to_stat_block(spells: &InnateSpellcasting, data: &Rc<Creature>) -> Result<StatBlockFeature,InterpolationErrorDetails> {
         Ok(StatBlockFeature {
-            text: CreatureFeature::feature_to_text_block(InnateSpellcasting::FEATURE_NAME, &spells.get_description(), &None, data)?
+            text: CreatureFeature::feature_to_text_block(InnateSpellcasting::FEATURE_NAME, &spells.get_description(), &None, data)?,
+            trigger: None
         })
     }
 
@@ -1121,11 +1466,62 @@ impl Creature {
     pub fn set_source(&mut self, name: &str) {
         self.source = Some(name.to_owned())
     }
+
+    pub fn add_companion(&mut self, name: &str) {
+        self.companions.push(name.to_owned())
+    }
+
+    pub fn set_extra(&mut self, key: &str, value: &str) {
+        self.extra.insert(key.to_owned(), value.to_owned());
+    }
+
+    pub fn add_note(&mut self, note: &str) {
+        self.notes.push(note.to_owned())
+    }
+
+    pub fn define_property(&mut self, name: &str, expression: &str) {
+        self.custom_properties.insert(name.to_owned(), expression.to_owned());
+    }
+
+    fn evaluate_custom_property(&self, name: &str, expression: &str) -> Option<InterpolationValue> {
+        if self.evaluating_properties.borrow().iter().any(|a| a == name) {
+            // the property refers to itself
+            return None
+        }
+        self.evaluating_properties.borrow_mut().push(name.to_owned());
+        let result = evaluate_expression_str(expression, name, self).ok();
+        self.evaluating_properties.borrow_mut().pop();
+        result
+    }
+
+    pub fn set_environments(&mut self, environments: &[String]) {
+        self.environments = environments.to_vec()
+    }
+
+    pub fn set_habitat(&mut self, habitat: &[String]) {
+        self.habitat = habitat.to_vec()
+    }
+
+    pub fn set_treasure(&mut self, treasure: &str) {
+        self.treasure = if treasure == "" {
+            None
+        } else {
+            Some(treasure.to_owned())
+        }
+    }
  
     pub fn set_name(&mut self, name: &str) {
         self.name = name.to_owned()
     }
 
+    pub fn set_display_name(&mut self, name: &str) {
+        self.display_name = Some(name.to_owned())
+    }
+
+    pub fn set_proper_name(&mut self) {
+        self.proper_name = true
+    }
+
     pub fn set_subject(&mut self, name: &str) {
         self.subject = Some(name.to_owned())
     }
@@ -1344,6 +1740,21 @@ impl Creature {
         self.alignment = Alignment::Custom(name.to_owned())
     }
 
+    pub fn set_alignment_chance(&mut self, chances: &[(u8,Alignment)]) -> Result<(),CreatureError> {
+        if chances.is_empty() {
+            Err(CreatureError::InvalidAlignmentChance("there must be at least one alignment.".to_owned()))?
+        }
+        if chances.iter().any(|(_,alignment)| matches!(alignment,Alignment::Chance(_))) {
+            Err(CreatureError::InvalidAlignmentChance("an alignment can't be another list of chances.".to_owned()))?
+        }
+        let total = chances.iter().map(|(chance,_)| *chance as u32).sum::<u32>();
+        if total != 100 {
+            Err(CreatureError::InvalidAlignmentChance(format!("the chances add up to {}%, instead of 100%.",total)))?
+        }
+        self.alignment = Alignment::Chance(chances.to_vec());
+        Ok(())
+    }
+
     pub fn set_hit_die(&mut self, die: &Die) {
         self.hit_die = die.clone()
     }
@@ -1356,6 +1767,78 @@ impl Creature {
         self.override_hit_points = Some(*points)
     }
 
+    pub fn set_minion(&mut self) {
+        self.minion = true
+    }
+
+    pub fn set_one_handed(&mut self) {
+        self.one_handed = true
+    }
+
+    fn hit_points_to_stat_block(&self) -> String {
+        let override_hit_points = self.override_hit_points.map(|a| a as isize);
+        if self.minion {
+            // minions don't roll for hit points, so the dice aren't shown.
+            format!("{} (minion)",override_hit_points.unwrap_or(self.get_hit_dice().rounded_average(&self.average_rounding)))
+        } else {
+            self.get_hit_dice().display_with_alternate_average(override_hit_points,&self.average_rounding)
+        }
+    }
+
+    fn minion_to_stat_block(data: &Rc<Creature>) -> Result<StatBlockFeature,InterpolationErrorDetails> {
+        Ok(StatBlockFeature {
+            text: CreatureFeature::feature_to_text_block(Self::MINION_FEATURE_NAME, Self::MINION_DESCRIPTION, &None, data)?,
+            trigger: None
+        })
+    }
+
+    /// Lists the names and values of all of the interpolation properties for the creature, followed by any custom properties and then any parameters, each in alphabetical order. A value is `None` if the property isn't available for this creature.
+    pub fn list_properties(&self) -> Vec<(String,Option<String>)> {
+        let mut result: Vec<(String,Option<String>)> = INTERPOLATION_PROPERTIES.iter().map(|name| {
+            let value = self.get_property(&Rc::from(*name)).map(|value| value.to_string_with_rounding(&self.average_rounding));
+            ((*name).to_owned(),value)
+        }).collect();
+        let mut custom_properties: Vec<&String> = self.custom_properties.keys().filter(|name| !INTERPOLATION_PROPERTIES.contains(&name.as_str())).collect();
+        custom_properties.sort();
+        for name in custom_properties {
+            let value = self.get_property(&Rc::from(name.as_str())).map(|value| value.to_string_with_rounding(&self.average_rounding));
+            result.push((name.clone(),value))
+        }
+        let mut parameters: Vec<(&String,&String)> = self.parameters.iter().filter(|(name,_)| !INTERPOLATION_PROPERTIES.contains(&name.as_str()) && !self.custom_properties.contains_key(name.as_str())).collect();
+        parameters.sort();
+        for (name,value) in parameters {
+            result.push((name.clone(),Some(value.clone())))
+        }
+        result
+    }
+
+    pub fn set_average_rounding(&mut self, rounding: &AverageRounding) {
+        self.average_rounding = *rounding
+    }
+
+    pub fn set_initiative(&mut self, proficiency: &u8) {
+        self.initiative = Some(*proficiency)
+    }
+
+    pub fn set_initiative_override(&mut self, bonus: &i8) {
+        self.override_initiative = Some(*bonus)
+    }
+
+    fn get_initiative_bonus(&self) -> i8 {
+        self.override_initiative.unwrap_or_else(|| {
+            Ability::score_to_mod(self.dexterity) + (self.get_proficiency_bonus() as i8 * self.initiative.unwrap_or(0) as i8)
+        })
+    }
+
+    fn initiative_to_stat_block(&self) -> Option<String> {
+        if self.initiative.is_some() || self.override_initiative.is_some() {
+            let bonus = self.get_initiative_bonus();
+            Some(format!("{:+} ({})",bonus,10 + bonus))
+        } else {
+            None
+        }
+    }
+
     pub fn set_armor(&mut self, armor: &Armor) {
         self.armor.armor = Some(armor.clone())
     }
@@ -1463,6 +1946,13 @@ impl Creature {
         }
     }
 
+    pub fn add_class_proficiencies(&mut self, class: &CharacterClass) {
+        self.add_saves(&class.get_saves());
+        // skills the creature already has, possibly with expertise, are left alone.
+        let skills: Vec<Skill> = class.get_skills().into_iter().filter(|skill| self.skills.get_proficiency(skill).is_none()).collect();
+        self.add_skills(&skills)
+    }
+
     pub fn add_skills(&mut self, skills: &[Skill]) {
         for skill in skills {
             match skill {
@@ -1573,6 +2063,18 @@ impl Creature {
         }
     }
 
+    pub fn add_custom_condition_immunity(&mut self, condition: &str) {
+        if !self.condition_immunities.custom.iter().any(|a| a == condition) {
+            self.condition_immunities.custom.push(condition.to_owned())
+        }
+    }
+
+    pub fn remove_custom_condition_immunity(&mut self, condition: &str) -> bool {
+        let count = self.condition_immunities.custom.len();
+        self.condition_immunities.custom.retain(|a| a != condition);
+        self.condition_immunities.custom.len() < count
+    }
+
     pub fn add_vulnerability(&mut self, damage: &Damage) {
         match damage {
             Damage::Bludgeoning => self.vulnerabilities.bludgeoning = true,
@@ -1600,6 +2102,12 @@ impl Creature {
         self.vulnerabilities.custom = Some(name.to_owned())
     }
 
+    pub fn add_vulnerability_type(&mut self, name: &str) {
+        if !self.vulnerabilities.custom_types.iter().any(|a| a == name) {
+            self.vulnerabilities.custom_types.push(name.to_owned())
+        }
+    }
+
     pub fn remove_vulnerability(&mut self, damage: &Damage) {
         // also turn off all vulnerabilitys if possible.
         self.vulnerabilities.all = false;
@@ -1636,27 +2144,38 @@ impl Creature {
             Damage::Necrotic => self.resistances.necrotic = true,
             Damage::Psychic => self.resistances.psychic = true,                
         }
-
+        self.resistances.last_added = Some(DamageClause::Damage(damage.clone()));
     }
 
     pub fn add_all_resistance(&mut self) {
         self.resistances.all = true;
+        self.resistances.last_added = Some(DamageClause::All);
     }
 
     pub fn add_custom_resistance(&mut self, name: &str) {
         self.resistances.custom = Some(name.to_owned())
     }
 
+    pub fn add_resistance_type(&mut self, name: &str) {
+        if !self.resistances.custom_types.iter().any(|a| a == name) {
+            self.resistances.custom_types.push(name.to_owned())
+        }
+        self.resistances.last_added = Some(DamageClause::Type(name.to_owned()));
+    }
+
     pub fn add_nonmagical_resistance(&mut self) {
         self.resistances.non_magical_attacks = true;
+        self.resistances.last_added = Some(DamageClause::NonMagicalAttacks);
     }
 
     pub fn add_nonsilvered_resistance(&mut self) {
         self.resistances.non_silvered_attacks = true;
+        self.resistances.last_added = Some(DamageClause::NonSilveredAttacks);
     }
 
     pub fn add_nonadamantine_resistance(&mut self) {
         self.resistances.non_adamantine_attacks = true;
+        self.resistances.last_added = Some(DamageClause::NonAdamantineAttacks);
     }
 
     pub fn remove_resistance(&mut self, damage: &Damage) {
@@ -1679,6 +2198,10 @@ impl Creature {
         }
     }
 
+    pub fn add_resistance_note(&mut self, note: &str) -> bool {
+        self.resistances.add_note(note)
+    }
+
     pub fn remove_special_resistance(&mut self) {
         self.resistances.non_magical_attacks = false;
         self.resistances.non_silvered_attacks = false;
@@ -1703,29 +2226,44 @@ impl Creature {
             Damage::Necrotic => self.immunities.necrotic = true,
             Damage::Psychic => self.immunities.psychic = true,                
         }
-
+        self.immunities.last_added = Some(DamageClause::Damage(damage.clone()));
     }
 
     pub fn add_all_immunities(&mut self) {
         self.immunities.all = true;
+        self.immunities.last_added = Some(DamageClause::All);
     }
 
     pub fn add_custom_immunity(&mut self, name: &str) {
         self.immunities.custom = Some(name.to_owned())
     }
 
+    pub fn add_immunity_type(&mut self, name: &str) {
+        if !self.immunities.custom_types.iter().any(|a| a == name) {
+            self.immunities.custom_types.push(name.to_owned())
+        }
+        self.immunities.last_added = Some(DamageClause::Type(name.to_owned()));
+    }
+
     pub fn add_nonmagical_immunity(&mut self) {
         self.immunities.non_magical_attacks = true;
+        self.immunities.last_added = Some(DamageClause::NonMagicalAttacks);
     }
 
     pub fn add_nonsilvered_immunity(&mut self) {
         self.immunities.non_silvered_attacks = true;
+        self.immunities.last_added = Some(DamageClause::NonSilveredAttacks);
     }
 
     pub fn add_nonadamantine_immunity(&mut self) {
         self.immunities.non_adamantine_attacks = true;
+        self.immunities.last_added = Some(DamageClause::NonAdamantineAttacks);
     }    
 
+    pub fn add_immunity_note(&mut self, note: &str) -> bool {
+        self.immunities.add_note(note)
+    }
+
     pub fn remove_immunity(&mut self, damage: &Damage) {
         // also turn off all vulnerabilitys if possible.
         self.immunities.all = false;
@@ -1762,6 +2300,17 @@ impl Creature {
 
     }
 
+    pub fn set_telepathy(&mut self, distance: u8, note: &Option<String>) {
+        self.languages.retain(|(language,_)| !matches!(language,Language::Telepathy(..)));
+        self.languages.push((Language::Telepathy(distance,note.clone()),true));
+    }
+
+    pub fn add_languages_knew_in_life(&mut self) {
+        if !self.languages.contains(&(Language::KnewInLife,true)) {
+            self.languages.push((Language::KnewInLife,true));
+        }
+    }
+
     pub fn add_unspoken_languages(&mut self, languages: &[Language]) {
         for language in languages {
             self.languages.push((language.clone(), false));
@@ -1817,6 +2366,26 @@ impl Creature {
         }
     }
 
+    pub fn set_legendary_actions_style(&mut self, style: &LegendaryActionsStyle) {
+        self.legendary_actions_style = style.clone()
+    }
+
+    pub fn set_senses_separator(&mut self, separator: &str) {
+        self.senses.separator = Some(separator.to_owned())
+    }
+
+    pub fn set_passive_perception_label(&mut self, label: &str) {
+        self.senses.passive_perception_label = Some(label.to_owned())
+    }
+
+    pub fn set_passive_perception_bonus(&mut self, bonus: &i8) {
+        self.senses.passive_perception_bonus = *bonus
+    }
+
+    pub fn override_passive_perception(&mut self, passive_perception: &u8) {
+        self.senses.passive_perception_override = Some(*passive_perception)
+    }
+
     pub fn set_no_challenge_rating(&mut self) {
         self.challenge_rating = ChallengeRating::None
     }
@@ -1837,6 +2406,26 @@ impl Creature {
         self.challenge_rating = ChallengeRating::Eighth
     }
 
+    pub fn set_proficiency_bonus(&mut self, proficiency_bonus: u8) {
+        self.proficiency_bonus = Some(proficiency_bonus)
+    }
+
+    pub fn set_show_proficiency_bonus(&mut self) {
+        self.show_proficiency_bonus = true
+    }
+
+    fn proficiency_bonus_to_stat_block(&self) -> Option<String> {
+        if self.show_proficiency_bonus {
+            Some(format!("+{}",self.get_proficiency_bonus()))
+        } else {
+            None
+        }
+    }
+
+    pub fn get_proficiency_bonus(&self) -> u8 {
+        self.proficiency_bonus.unwrap_or_else(|| self.challenge_rating.get_proficiency_bonus())
+    }
+
     fn find_action(&self, name: &str) -> Option<&CreatureAction> {
         for action in &self.actions {
             if action.name == name {
@@ -1863,6 +2452,109 @@ impl Creature {
         self.find_action_mut(&weapon.to_string())
     }
 
+    // evaluates an expression for a number or dice, returning its average.
+    fn evaluate_average(&self, expression: &str, name: &str) -> Option<isize> {
+        match evaluate_expression_str(expression, name, self) {
+            Ok(InterpolationValue::Number(number,_)) => Some(number),
+            Ok(InterpolationValue::Dice(dice,_)) => Some(dice.rounded_average(&self.average_rounding)),
+            _ => None
+        }
+    }
+
+    fn get_action_damage(&self, action: &CreatureAction) -> isize {
+        let default_bonus = action.attack.as_ref().map(|attack| attack.get_default_bonus()).unwrap_or("0");
+        let mut effects: Vec<&AttackEffect> = action.effect.iter().collect();
+        if let Some(compound) = &action.compound {
+            effects.extend(compound.get_effects());
+        }
+        effects.iter().map(|effect| {
+            let damage = effect.get_damage_expr(default_bonus).and_then(|expression| self.evaluate_average(&expression, &action.name)).unwrap_or(0).max(0);
+            // area effects are assumed to hit two targets.
+            if effect.is_area() {
+                damage * 2
+            } else {
+                damage
+            }
+        }).sum()
+    }
+
+    fn get_best_attack_damage<Filter: Fn(&Attack,&str) -> bool>(&self, filter: Filter) -> isize {
+        self.actions.iter().filter(|action| match &action.attack {
+            Some(attack) => filter(attack,&action.name),
+            None => false
+        }).map(|action| self.get_action_damage(action)).max().unwrap_or(0)
+    }
+
+    fn get_best_multiattack_damage(&self, list: &[Multiattack]) -> isize {
+        list.iter().map(|multiattack| self.get_multiattack_damage(multiattack)).max().unwrap_or(0)
+    }
+
+    fn get_multiattack_damage(&self, details: &Multiattack) -> isize {
+        match details {
+            Multiattack::Any => self.get_best_attack_damage(|_,_| true),
+            Multiattack::Melee => self.get_best_attack_damage(|attack,_| attack.reach.is_some()),
+            Multiattack::Ranged => self.get_best_attack_damage(|attack,_| attack.range.is_some()),
+            Multiattack::Spell => self.get_best_attack_damage(|attack,_| matches!(attack.type_,Some(AttackType::Spell))),
+            Multiattack::Attack(name) => self.find_action(name).map(|action| self.get_action_damage(action)).unwrap_or(0),
+            Multiattack::Weapon(weapon) => self.find_weapon(weapon).map(|action| self.get_action_damage(action)).unwrap_or(0),
+            Multiattack::Except(list) => self.get_best_attack_damage(|_,name| !list.iter().any(|multiattack| match multiattack {
+                Multiattack::Attack(except) => except == name,
+                Multiattack::Weapon(weapon) => weapon.to_string() == name,
+                _ => false
+            })),
+            Multiattack::Or(list) => self.get_best_multiattack_damage(list),
+            Multiattack::Count(count,list) => *count as isize * self.get_best_multiattack_damage(list),
+            Multiattack::And(list) => list.iter().map(|multiattack| self.get_multiattack_damage(multiattack)).sum(),
+            Multiattack::Dice(dice,list) => dice.rounded_average(&self.average_rounding) * self.get_best_multiattack_damage(list)
+        }
+    }
+
+    /**
+    Calculates the effective hit points of the creature, used for the defensive challenge rating, see [`crate::challenge_rating::effective_hit_points_multiplier`]. The hit points are multiplied if the creature has resistance or immunity to the damage that is common at its level, which is assumed to be any of: bludgeoning, piercing and slashing together; weapon attacks that aren't magical, silvered or adamantine; all damage; or at least three damage types of any kind. A resistance or immunity to one or two damage types, such as only fire, is ignored, as are vulnerabilities and condition immunities.
+    */
+    pub fn get_effective_hit_points(&self) -> u16 {
+        let hit_points = self.override_hit_points.map(|a| a as isize).unwrap_or(self.get_hit_dice().rounded_average(&self.average_rounding)).clamp(0,u16::MAX as isize) as u16;
+        let multiplier = effective_hit_points_multiplier(hit_points, self.resistances.covers_several_damage_types(), self.immunities.covers_several_damage_types());
+        (hit_points as f32 * multiplier).round().min(u16::MAX as f32) as u16
+    }
+
+    /**
+    Calculates the challenge rating from the creature's statistics, following the Dungeon Master's Guide, see [`crate::challenge_rating`]. The damage per round is the best of the multiattack, or any single action, with area effects assumed to hit two targets. The attack bonus, or save DC, comes from the action which does the most damage on its own. 
+
+    This is a first version of the calculation. Spells, legendary actions, and features which change the effective armor class, hit points or damage, are ignored. The proficiency bonus used for the attack bonus is the creature's current one, from its challenge rating or the `ProficiencyBonus` command.
+    */
+    pub fn calculate_challenge_rating(&self) -> ChallengeRating {
+        let defensive = defensive_challenge_rating(self.get_effective_hit_points(), self.get_armor_class());
+
+        let best_action = self.actions.iter().max_by_key(|action| self.get_action_damage(action));
+        let mut damage_per_round = best_action.map(|action| self.get_action_damage(action)).unwrap_or(0);
+        if let Some(multiattack) = &self.multiattack {
+            damage_per_round = damage_per_round.max(self.get_multiattack_damage(multiattack))
+        }
+        let attack_bonus = best_action.and_then(|action| action.attack.as_ref()).and_then(|attack| self.evaluate_average(&attack.get_attack_bonus_expr(), "challenge rating")).map(|bonus| bonus as i8);
+        let save_dc = best_action.and_then(|action| action.effect.as_ref()).and_then(|effect| effect.get_save_dc());
+        let offensive = offensive_challenge_rating(damage_per_round.clamp(0,u16::MAX as isize) as u16, attack_bonus, save_dc);
+
+        average_challenge_rating(defensive, offensive)
+    }
+
+    /**
+    Estimates how fully the creature has been specified, as a percentage of the things a finished creature would usually have, which are left at their defaults in a stub: a name, ability scores other than 10, a source for its armor class, hit dice or hit points, at least one action, a challenge rating, languages and special senses. Some finished creatures lack a few of these, such as beasts without languages, so this is only meant for sorting a collection of creatures to find the ones which still need work.
+    */
+    pub fn get_completeness(&self) -> u8 {
+        let checks = [
+            !self.name.is_empty(),
+            [self.strength,self.dexterity,self.constitution,self.intelligence,self.wisdom,self.charisma].iter().any(|score| *score != 10),
+            self.armor.armor.is_some() || self.armor.shield || self.armor.mage_armor,
+            (self.hit_die != Die::D6) || (self.hit_dice_count != 1) || self.override_hit_points.is_some(),
+            !self.actions.is_empty(),
+            self.challenge_rating != ChallengeRating::None,
+            !self.languages.is_empty(),
+            self.senses.blindsight.is_some() || self.senses.darkvision.is_some() || self.senses.tremorsense.is_some() || self.senses.truesight.is_some() || !self.senses.custom.is_empty()
+        ];
+        (checks.iter().filter(|check| **check).count() * 100 / checks.len()) as u8
+    }
+
     pub fn check_multiattack(&self, details: &Multiattack) -> Result<(),CreatureError> {
         match details {
             Multiattack::Any |
@@ -1892,6 +2584,107 @@ impl Creature {
         }
     }
 
+    /**
+    Looks for multiattack structures which are valid, but probably don't mean what was intended, such as a multiattack which refers to the Multiattack action itself, a `Count` of zero, or the same attack listed more than once in a list, which should usually be written with a `Count` instead. The number of attacks in the structure is also compared with the number given in the description of the Multiattack action, such as "three" in "makes three attacks", or two and one in "makes two claw attacks and one bite attack". Returns a warning for each problem found.
+
+    The number of attacks is only compared when both can be found: references to actions without an attack, such as Frightful Presence, don't count, `Or` counts only if every choice has the same number of attacks, and a `Dice` count can't be known. Descriptions which don't give a number, such as "attacks twice", aren't compared.
+    */
+    pub fn lint_multiattack(&self, details: &Multiattack) -> Vec<String> {
+        let mut warnings = vec![];
+        self.lint_multiattack_into(details, &mut warnings);
+        let description = self.find_action("Multiattack").map(|action| action.description.as_str()).unwrap_or_default();
+        if let (Some(counted),Some(described)) = (self.count_multiattack_attacks(details),count_described_attacks(description)) {
+            if counted != described {
+                warnings.push(format!("Multiattack: the description calls for {} attacks, but the multiattack allows {}.",described,counted))
+            }
+        }
+        warnings
+    }
+
+    // the number of attacks made with the multiattack, or None if that can't be known.
+    fn count_multiattack_attacks(&self, details: &Multiattack) -> Option<u32> {
+        match details {
+            Multiattack::Any |
+            Multiattack::Ranged |
+            Multiattack::Melee |
+            Multiattack::Spell |
+            Multiattack::Weapon(_) |
+            Multiattack::Except(_) => Some(1),
+            // an action such as Frightful Presence can be part of a multiattack without being an attack.
+            Multiattack::Attack(name) => Some(if self.find_action(name).map(|action| action.attack.is_none()).unwrap_or(false) {
+                0
+            } else {
+                1
+            }),
+            Multiattack::Or(list) => {
+                let counts = list.iter().map(|multiattack| self.count_multiattack_attacks(multiattack)).collect::<Option<Vec<u32>>>()?;
+                match counts.split_first() {
+                    Some((first,rest)) if rest.iter().all(|count| count == first) => Some(*first),
+                    _ => None
+                }
+            },
+            Multiattack::Count(count,_) => Some(*count as u32),
+            Multiattack::And(list) => list.iter().map(|multiattack| self.count_multiattack_attacks(multiattack)).sum(),
+            Multiattack::Dice(..) => None
+        }
+    }
+
+    fn lint_multiattack_into(&self, details: &Multiattack, warnings: &mut Vec<String>) {
+        match details {
+            Multiattack::Any |
+            Multiattack::Ranged |
+            Multiattack::Melee |
+            Multiattack::Spell |
+            Multiattack::Weapon(_) => (),
+            Multiattack::Attack(name) => if name == "Multiattack" {
+                warnings.push("Multiattack: the multiattack refers to the Multiattack action itself.".to_owned())
+            },
+            Multiattack::Except(list) |
+            Multiattack::Or(list) |
+            Multiattack::Count(_,list) |
+            Multiattack::And(list) |
+            Multiattack::Dice(_,list) => {
+                if let Multiattack::Count(0,_) = details {
+                    warnings.push("Multiattack: a Count of 0 allows no attacks.".to_owned())
+                }
+                for (i,multiattack) in list.iter().enumerate() {
+                    if list[..i].contains(multiattack) {
+                        warnings.push(format!("Multiattack: {:?} is listed more than once in the same list, use a Count instead.",multiattack))
+                    }
+                    self.lint_multiattack_into(multiattack, warnings)
+                }
+            }
+        }
+    }
+
+    /**
+    Returns a warning for each name shared by more than one feature, action, reaction or legendary action. Commands which find these by name, such as `RemoveAction`, only change the first one, so a duplicate, which is easy to end up with when a creature is composed from included files, usually means the wrong one is being changed.
+    */
+    pub fn find_duplicate_names(&self) -> Vec<String> {
+        fn find_duplicates<'a>(kind: &str, names: impl Iterator<Item = &'a str>, warnings: &mut Vec<String>) {
+            let mut found = HashSet::new();
+            let mut reported = HashSet::new();
+            for name in names {
+                if !found.insert(name) && reported.insert(name) {
+                    warnings.push(format!("There is more than one {} named '{}'.",kind,name))
+                }
+            }
+        }
+
+        let mut warnings = vec![];
+        find_duplicates("feature", self.special_abilities.iter().map(|ability| match ability {
+            CreatureSpecialAbility::Feature(feature) => feature.name.as_str(),
+            CreatureSpecialAbility::Spellcasting(spellcasting) => spellcasting.feature_name(),
+            CreatureSpecialAbility::InnateSpellcasting(_) => InnateSpellcasting::FEATURE_NAME
+        }), &mut warnings);
+        find_duplicates("action", self.actions.iter().map(|action| action.name.as_str()), &mut warnings);
+        find_duplicates("reaction", self.reactions.iter().map(|reaction| reaction.name.as_str()), &mut warnings);
+        if let Some(legendary_actions) = &self.legendary_actions {
+            find_duplicates("legendary action", legendary_actions.actions.iter().map(|action| action.name.as_str()), &mut warnings);
+        }
+        warnings
+    }
+
     pub fn set_multiattack(&mut self, description: String, details: &Multiattack) {
         self.remove_action("Multiattack");
         if description.len() > 0 {
@@ -1910,10 +2703,104 @@ impl Creature {
 
     }
 
+    /**
+    Multiplies the hit points and the damage of attack actions and legendary actions by the scale, for a quick way to make a tougher or easier version of the creature. Hit points are scaled by changing the number of hit dice, or the hit points override if there is one. Attack bonuses, save DCs and the ability bonuses added to damage are not changed, and neither is damage written into descriptions by hand, which includes all reactions. An action description which was overridden is kept as written. An error is returned if the scale is zero, negative or not a number, as it would leave the creature with nonsense hit points and damage, if the hit dice or hit points become too many to record, or if an action's damage comes from an expression, which can't be scaled.
+    */
+    pub fn scale(&mut self, scale: f32) -> Result<(),CreatureError> {
+        if !(scale.is_finite() && (scale > 0.0)) {
+            Err(CreatureError::InvalidScale(scale))?
+        }
+
+        // checked before anything is changed, so the creature isn't left half scaled.
+        let legendary_actions = self.legendary_actions.iter().flat_map(|legendary| legendary.actions.iter().map(|action| (&action.name,&action.effect,&action.compound)));
+        for (name,effect,compound) in self.actions.iter().map(|action| (&action.name,&action.effect,&action.compound)).chain(legendary_actions) {
+            let mut effects = effect.iter().collect::<Vec<&AttackEffect>>();
+            effects.extend(compound.iter().flat_map(|compound| compound.get_effects()));
+            if effects.iter().any(|effect| effect.is_expression_damage()) {
+                Err(CreatureError::CantScaleExpressionDamage(name.clone()))?
+            }
+        }
+
+        let hit_dice_count = (self.hit_dice_count as f32 * scale).round();
+        if hit_dice_count > u8::MAX as f32 {
+            Err(CreatureError::ScaledHitPointsTooLarge(scale))?
+        }
+        let override_hit_points = self.override_hit_points.map(|points| (points as f32 * scale).round());
+        if override_hit_points.map(|points| points > u16::MAX as f32).unwrap_or(false) {
+            Err(CreatureError::ScaledHitPointsTooLarge(scale))?
+        }
+        self.hit_dice_count = (hit_dice_count as u8).max(1);
+        self.override_hit_points = override_hit_points.map(|points| (points as u16).max(1));
+
+        for action in &mut self.actions {
+            Self::scale_action_effect(&action.attack, &mut action.effect, &mut action.compound, &mut action.description, scale)
+        }
+        if let Some(legendary_actions) = &mut self.legendary_actions {
+            for action in &mut legendary_actions.actions {
+                Self::scale_action_effect(&action.attack, &mut action.effect, &mut action.compound, &mut action.description, scale)
+            }
+        }
+        Ok(())
+    }
+
+    fn scale_action_effect(attack: &Option<Attack>, effect: &mut Option<AttackEffect>, compound: &mut Option<CompoundAttackEffect>, description: &mut String, scale: f32) {
+        if let Some(original) = effect.as_ref() {
+            // only rebuild descriptions that were built from the effect, so hand-written ones aren't lost.
+            let generated = match attack {
+                Some(attack) => attack.get_description(Some(original), compound),
+                None => original.get_description("0", compound)
+            };
+            let scaled = original.scale(scale);
+            let scaled_compound = compound.as_ref().map(|compound| compound.scale(scale));
+            if *description == generated {
+                *description = match attack {
+                    Some(attack) => attack.get_description(Some(&scaled), &scaled_compound),
+                    None => scaled.get_description("0", &scaled_compound)
+                };
+            }
+            *effect = Some(scaled);
+            *compound = scaled_compound;
+        }
+    }
+
+    /**
+    Sorts the actions and features by the orders given for their names. Anything without an order counts as 0, and the sort is stable, so items with the same order keep the order they were added in.
+    */
+    pub fn sort_by_order(&mut self, action_order: &HashMap<String,i32>, feature_order: &HashMap<String,i32>) {
+        self.actions.sort_by_key(|a| action_order.get(&a.name).copied().unwrap_or(0));
+        self.special_abilities.sort_by_key(|a| feature_order.get(match a {
+            CreatureSpecialAbility::Feature(feature) => feature.name.as_str(),
+            CreatureSpecialAbility::Spellcasting(spellcasting) => spellcasting.feature_name(),
+            CreatureSpecialAbility::InnateSpellcasting(_) => InnateSpellcasting::FEATURE_NAME
+        }).copied().unwrap_or(0));
+    }
+
+    pub fn canonicalize_layout(&mut self) {
+        // these sorts are stable, so the order in which items were added is otherwise kept.
+        self.special_abilities.sort_by_key(|a| match a {
+            CreatureSpecialAbility::Feature(_) => 0,
+            CreatureSpecialAbility::InnateSpellcasting(_) => 1,
+            CreatureSpecialAbility::Spellcasting(_) => 2
+        });
+        self.actions.sort_by_key(|a| if a.name == "Multiattack" {
+            0
+        } else {
+            1
+        });
+    }
+
     pub fn add_weapon(&mut self, weapon: &Weapon, compound: &Option<CompoundAttackEffect>) {
-        let name = weapon.to_string();
+        self.add_named_weapon(&weapon.to_string(), weapon, compound)
+    }
+
+    pub fn add_named_weapon(&mut self, name: &str, weapon: &Weapon, compound: &Option<CompoundAttackEffect>) {
+        let name = name.to_owned();
         let attack = weapon.get_attack();
-        let effect = weapon.get_effect(&self.size);
+        let effect = if self.one_handed {
+            weapon.get_one_handed_effect(&self.size)
+        } else {
+            weapon.get_effect(&self.size)
+        };
         let description = attack.get_description(Some(&effect), compound);
         self.actions.push(CreatureAction {
             name,
@@ -1925,6 +2812,21 @@ impl Creature {
         })            
     }
 
+    pub fn add_custom_weapon(&mut self, name: &str, weapon: &CustomWeapon, compound: &Option<CompoundAttackEffect>) {
+        let name = name.to_owned();
+        let attack = weapon.get_attack();
+        let effect = weapon.get_effect();
+        let description = attack.get_description(Some(&effect), compound);
+        self.actions.push(CreatureAction {
+            name,
+            description,
+            attack: Some(attack),
+            effect: Some(effect),
+            compound: compound.clone(),
+            usage_limit: None
+        })
+    }
+
     pub fn expect_weapon_attack(&self, weapon: &Weapon, attack: &Attack) -> Result<(),CreatureError> {
         if let Some(action) = self.find_weapon(weapon) {
             if let Some(actual_attack) = &action.attack {
@@ -1999,7 +2901,7 @@ impl Creature {
         }
     }
 
-    pub fn remove_weapon(&mut self, weapon: &Weapon) {
+    pub fn remove_weapon(&mut self, weapon: &Weapon) -> bool {
         let name = weapon.to_string();
         self.remove_action(&name)
     }
@@ -2009,7 +2911,7 @@ impl Creature {
         self.move_action(&name,delta)
     }
 
-    pub fn remove_action(&mut self, name: &str) {
+    pub fn remove_action(&mut self, name: &str) -> bool {
         let mut found = false;
         self.actions.retain(|a| 
             if !found && (a.name == name) {
@@ -2018,7 +2920,21 @@ impl Creature {
             } else {
                 true
             }
-        )
+        );
+        found
+    }
+
+    pub fn remove_actions_matching(&mut self, prefix: &str) -> bool {
+        let count = self.actions.len();
+        self.actions.retain(|a| !a.name.starts_with(prefix));
+        if !self.actions.iter().any(|a| a.name == "Multiattack") {
+            self.multiattack = None
+        }
+        self.actions.len() < count
+    }
+
+    pub fn remove_all_actions(&mut self) -> bool {
+        self.remove_actions_matching("")
     }
 
     pub fn move_action(&mut self, name: &str, delta: &i8) -> Result<(),CreatureError> {
@@ -2041,12 +2957,14 @@ impl Creature {
         self.reactions.push(CreatureFeature {
             name: reaction.get_name(),
             description: reaction.get_description(),
-            usage_limit: usage_limit.clone()
+            usage_limit: usage_limit.clone(),
+            save_ability: None,
+            trigger: reaction.get_trigger()
         })
 
     }
 
-    pub fn remove_reaction(&mut self, name: &str) {
+    pub fn remove_reaction(&mut self, name: &str) -> bool {
         let mut found = false;
         self.reactions.retain(|a| 
             if !found && (a.name == name) {
@@ -2055,19 +2973,28 @@ impl Creature {
             } else {
                 true
             }
-        )
+        );
+        found
+    }
+
+    pub fn remove_all_reactions(&mut self) -> bool {
+        let found = !self.reactions.is_empty();
+        self.reactions.clear();
+        found
     }
 
     pub fn add_feature(&mut self, feature: Feature, usage_limit: Option<UsageLimit>) {
         self.special_abilities.push(CreatureSpecialAbility::Feature(CreatureFeature {
             name: feature.get_name(),
             description: feature.get_description(),
-            usage_limit: usage_limit.clone()
+            usage_limit: usage_limit.clone(),
+            save_ability: feature.get_save_ability(),
+            trigger: None
         }));
 
     }
 
-    pub fn remove_feature(&mut self, name: &str) {
+    pub fn remove_feature(&mut self, name: &str) -> bool {
         let mut found = false;
         self.special_abilities.retain(|a| 
             if found {
@@ -2076,7 +3003,7 @@ impl Creature {
             } else {
                 if match a {
                     CreatureSpecialAbility::Feature(feature) => feature.name == name,
-                    CreatureSpecialAbility::Spellcasting(_) => name == Spellcasting::FEATURE_NAME,
+                    CreatureSpecialAbility::Spellcasting(spellcasting) => name == spellcasting.feature_name(),
                     CreatureSpecialAbility::InnateSpellcasting(_) => name == InnateSpellcasting::FEATURE_NAME
                 } {
                     found = true;
@@ -2085,10 +3012,11 @@ impl Creature {
                     true
                 }
             }
-        )
+        );
+        found
     }
 
-    pub fn remove_legendary_action(&mut self, name: &str) {
+    pub fn remove_legendary_action(&mut self, name: &str) -> bool {
         if let Some(legendary) = &mut self.legendary_actions {
             let mut found = false;
             legendary.actions.retain(|a| 
@@ -2098,31 +3026,62 @@ impl Creature {
                 } else {
                     true
                 }
-            )
-    
+            );
+            found
+        } else {
+            false
         }
 
     }
 
+    /// Lists the names of every spell in the creature's spellcasting and innate spellcasting, sorted and without duplicates.
+    pub fn get_spell_names(&self) -> Vec<String> {
+        let mut result = vec![];
+        for feature in &self.special_abilities {
+            let spells: Vec<&String> = match feature {
+                CreatureSpecialAbility::Spellcasting(spellcasting) => spellcasting.spells.values().flatten().collect(),
+                CreatureSpecialAbility::InnateSpellcasting(spellcasting) => spellcasting.spells.values().flatten().collect(),
+                CreatureSpecialAbility::Feature(_) => vec![]
+            };
+            for spell in spells {
+                if !result.contains(spell) {
+                    result.push(spell.clone())
+                }
+            }
+        }
+        result.sort();
+        result
+    }
+
+    /// Returns the spellcasting feature marked as primary, or the first one if none are marked.
     pub fn get_spellcasting(&self) -> Option<&Spellcasting> {
+        let mut result = None;
         for feature in &self.special_abilities {
             if let CreatureSpecialAbility::Spellcasting(spellcasting) = feature {
-                return Some(&spellcasting)
+                if spellcasting.primary {
+                    return Some(spellcasting)
+                } else if result.is_none() {
+                    result = Some(spellcasting)
+                }
             }
         }
-        None
+        result
     }
 
-    pub fn get_or_add_spellcasting_mut(&mut self) -> &mut Spellcasting {
-        let index = self.special_abilities.iter().position(|a| if let CreatureSpecialAbility::Spellcasting(_) = a {
-            true
+    fn get_primary_spellcasting(&self) -> Option<&Spellcasting> {
+        self.get_spellcasting().filter(|spellcasting| spellcasting.primary)
+    }
+
+    pub fn get_or_add_spellcasting_mut(&mut self, name: Option<&str>) -> &mut Spellcasting {
+        let index = self.special_abilities.iter().position(|a| if let CreatureSpecialAbility::Spellcasting(spellcasting) = a {
+            spellcasting.name.as_deref() == name
         } else {
             false
         });
         let index = if let Some(index) = index {
             index
         } else {
-            self.special_abilities.push(CreatureSpecialAbility::Spellcasting(Spellcasting::default()));
+            self.special_abilities.push(CreatureSpecialAbility::Spellcasting(Spellcasting::new(name.map(str::to_owned))));
             self.special_abilities.len() - 1
         };
         if let CreatureSpecialAbility::Spellcasting(spellcasting) = self.special_abilities.get_mut(index).unwrap() {
@@ -2161,9 +3120,23 @@ impl Creature {
         }
     }
 
+    pub fn get_save_dc(&self, ability: &Ability) -> isize {
+        let score = match ability {
+            Ability::Strength => self.strength,
+            Ability::Dexterity => self.dexterity,
+            Ability::Constitution => self.constitution,
+            Ability::Intelligence => self.intelligence,
+            Ability::Wisdom => self.wisdom,
+            Ability::Charisma => self.charisma,
+        };
+        8 + self.get_proficiency_bonus() as isize + Ability::score_to_mod(score) as isize
+    }
+
     pub fn get_spell_attack_bonus(&self) -> Option<i8> {
-        // prefer innate spellcasting if there's a choice
-        let bonus_ability = if let Some(spellcasting) = self.get_innate_spellcasting() {
+        // prefer spellcasting marked as primary, then innate spellcasting if there's a choice
+        let bonus_ability = if let Some(spellcasting) = self.get_primary_spellcasting() {
+            Some((spellcasting.attack_bonus,&spellcasting.ability))
+        } else if let Some(spellcasting) = self.get_innate_spellcasting() {
             Some((spellcasting.attack_bonus,&spellcasting.ability))
         } else if let Some(spellcasting) = self.get_spellcasting() {
             Some((spellcasting.attack_bonus,&spellcasting.ability))
@@ -2185,14 +3158,49 @@ impl Creature {
         }
     }
 
-    pub fn set_legendary_actions(&mut self, description: String, actions: Vec<CreatureLegendaryAction>) {
+    pub fn get_spell_save_dc(&self) -> Option<u8> {
+        // prefer innate spellcasting if there's a choice, as with the attack bonus
+        let save_dc_ability = if let Some(spellcasting) = self.get_primary_spellcasting() {
+            Some((spellcasting.save_dc,&spellcasting.ability))
+        } else if let Some(spellcasting) = self.get_innate_spellcasting() {
+            Some((spellcasting.save_dc,&spellcasting.ability))
+        } else if let Some(spellcasting) = self.get_spellcasting() {
+            Some((spellcasting.save_dc,&spellcasting.ability))
+        } else {
+            // There is no spell save DC
+            None
+        };
+        match save_dc_ability {
+            Some((Some(num),_)) => Some(num),
+            Some((None,ability)) => Some(self.get_save_dc(ability) as u8),
+            None => None
+        }
+    }
+
+    pub fn set_legendary_actions(&mut self, count: u8, actions: Vec<CreatureLegendaryAction>) {
         self.legendary_actions = Some(CreatureLegendaryActions {
-            description,
+            count,
             actions
         })
 
     }
 
+    pub fn set_lair_actions(&mut self, foreword: &str, actions: &[String], afterword: &str) {
+        self.lair_actions = Some(CreatureLairActions {
+            foreword: foreword.to_owned(),
+            actions: actions.to_vec(),
+            afterword: afterword.to_owned()
+        })
+    }
+
+    pub fn set_regional_effects(&mut self, foreword: &str, effects: &[String], afterword: &str) {
+        self.regional_effects = Some(CreatureRegionalEffects {
+            foreword: foreword.to_owned(),
+            effects: effects.to_vec(),
+            afterword: afterword.to_owned()
+        })
+    }
+
 
 }
 
@@ -2210,22 +3218,37 @@ impl TryFrom<Creature> for CreatureStatBlock {
         let senses = me.senses.to_stat_block(perception,me.wisdom);
         let actions = Creature::actions_to_stat_block(&me.actions,&me)?;
         let reactions = Creature::features_to_stat_block(&me.reactions,&me)?;
-        let special_abilities = Creature::special_abilities_to_stat_block(&me.special_abilities,&me)?;
+        let mut special_abilities = Creature::special_abilities_to_stat_block(&me.special_abilities,&me)?;
+        if me.minion {
+            special_abilities.insert(0,Creature::minion_to_stat_block(&me)?);
+        }
         let legendary_actions = if let Some(legendary_actions) = &me.legendary_actions {
             Some(CreatureLegendaryActions::to_stat_block(legendary_actions,&me)?)
         } else {
             None
         };
+        let lair_actions = if let Some(lair_actions) = &me.lair_actions {
+            Some(CreatureLairActions::to_stat_block(lair_actions,&me)?)
+        } else {
+            None
+        };
+        let regional_effects = if let Some(regional_effects) = &me.regional_effects {
+            Some(CreatureRegionalEffects::to_stat_block(regional_effects,&me)?)
+        } else {
+            None
+        };
 
         Ok(CreatureStatBlock {
             name: me.name.clone(),
+            display_name: me.display_name.clone(),
             size: me.size.to_string(),
             type_: me.type_.to_string(),
             subtype: me.subtype.clone(),
             group: me.group.clone(),
             alignment: me.alignment.to_string(),
-            hit_points: me.get_hit_dice().display_with_alternate_average(me.override_hit_points.map(|a| a as isize)),
+            hit_points: me.hit_points_to_stat_block(),
             armor, 
+            initiative: me.initiative_to_stat_block(),
             speed: me.speed.to_stat_block(),
             strength: Ability::to_stat_block(me.strength), // format!("{} ({})",self.strength,Ability::score_to_mod(self.strength)),
             dexterity: Ability::to_stat_block(me.dexterity),
@@ -2242,15 +3265,60 @@ impl TryFrom<Creature> for CreatureStatBlock {
             senses,
             languages: Creature::languages_to_stat_block(&me.languages),
             challenge_rating: me.challenge_rating.display_with_xp(),
+            proficiency_bonus: me.proficiency_bonus_to_stat_block(),
             actions,
             reactions,
             legendary_actions,
             special_abilities,
-            // FUTURE: Not currently supported
-            lair_actions: None,
-            regional_effects: None,
-            source: me.source.clone()
+            lair_actions,
+            regional_effects,
+            companions: me.companions.clone(),
+            habitat: if me.habitat.is_empty() {
+                None
+            } else {
+                Some(me.habitat.join(", "))
+            },
+            treasure: me.treasure.clone(),
+            source: me.source.clone(),
+            extra: me.extra.clone(),
+            minion: me.minion,
+            notes: me.notes.clone(),
+            diagnostics: None
         })
     }
 
-}
\ No newline at end of file
+}
+
+fn words_to_count(word: &str) -> Option<u32> {
+    match word {
+        "one" => Some(1),
+        "two" => Some(2),
+        "three" => Some(3),
+        "four" => Some(4),
+        "five" => Some(5),
+        "six" => Some(6),
+        "seven" => Some(7),
+        "eight" => Some(8),
+        "nine" => Some(9),
+        "ten" => Some(10),
+        word => word.parse().ok()
+    }
+}
+
+// the number of attacks given in a multiattack description, either as a total, "makes three attacks: one with its bite and two with its claws", or as the sum of each kind, "makes two claw attacks and one bite attack". Returns None if no number of attacks is given.
+fn count_described_attacks(description: &str) -> Option<u32> {
+    let words: Vec<String> = description.split_whitespace().map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase()).collect();
+    let mut total = None;
+    for (i,word) in words.iter().enumerate() {
+        if let Some(count) = words_to_count(word) {
+            // the number is for attacks if one of the next few words is "attack", before any other number.
+            let attack = words[i + 1..].iter().take(3).take_while(|word| words_to_count(word).is_none()).position(|word| word == "attack" || word == "attacks");
+            match attack {
+                Some(0) if total.is_none() => return Some(count),
+                Some(_) => total = Some(total.unwrap_or(0) + count),
+                None => ()
+            }
+        }
+    }
+    total
+}
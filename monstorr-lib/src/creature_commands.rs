@@ -61,10 +61,35 @@ Some of the structures allow for strings containing interpolation variables, whi
 
 For a description fo the basic creature document structure, see [`CreatureCreator`], which is the structure deserialized from a creature file.
 
+# TOML
+
+If Monstorr is compiled with the 'toml-format' feature, creature files can also be written in TOML, using the `toml-creature` input format. The commands are the same, but TOML has no tagged variants and no tuple structs, so they are represented differently:
+
+* TOML requires a table at the top of the document, so the commands go in a list called `commands`.
+* A variant without data, such as `Small` or `CanonicalizeLayout`, is written as a string: `"Small"`.
+* A variant with a single value is written as a table with one key, the name of the variant: `{ Name = "Goblin" }`.
+* A variant with more than one value is written the same way, but the values are in a list: `{ Extra = ["author", "N. M. Sheldon"] }`.
+* Mapped structs are written as tables with the same property names.
+* TOML has no way to represent `None`. Optional values which are left out in RON can be left out here as well, but an optional value which must be given in the middle of a list, such as the first of two optional arguments, can't be written. Use RON for those creatures.
+
+```toml
+commands = [
+    { Monstorr = [1.0] },
+    { Name = "Goblin" },
+    "Small",
+    "Humanoid",
+    { Dex = 14 },
+    { Weapon = [{ Scimitar = 0 }] },
+]
+```
+
+Files included from a TOML creature file with `Include` are still read as RON. The `validate` and `preprocess` commands can write a creature in either format, which is the easiest way to convert an existing creature.
+
 */
 
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::path::Path;
 use std::fs;
 
 use serde::Deserialize;
@@ -73,17 +98,24 @@ use serde::Serialize;
 use crate::stats::Ability;
 use crate::spellcasting::Spellcasting;
 use crate::errors::CreatureError;
+use crate::errors::CommandSyntaxError;
 use crate::spellcasting::SpellcastingStyle;
 use crate::spellcasting::InnateSpellcasting;
 use crate::dice::Die;
+use crate::dice_expression::AverageRounding;
 use crate::stats::Armor;
 use crate::stats::Skill;
+use crate::stats::CharacterClass;
 use crate::stats::Condition;
 use crate::stats::Damage;
 use crate::stats::Language;
 use crate::stats::ChallengeRating;
+use crate::stats::Alignment;
 use crate::attacks::Multiattack;
 use crate::attacks::Weapon;
+use crate::attacks::WeaponReach;
+use crate::attacks::CustomWeapon;
+use crate::dice_expression::DiceExpression;
 use crate::attacks::CompoundAttackEffect;
 use crate::attacks::Attack;
 use crate::attacks::AttackBonus;
@@ -93,8 +125,10 @@ use crate::actions::UsageLimit;
 use crate::reactions::Reaction;
 use crate::features::Feature;
 use crate::actions::LegendaryAction;
+use crate::actions::LegendaryActionsStyle;
 use crate::creature::Creature;
 use crate::interpolation::interpolate_str_for_deserialization;
+use crate::interpolation::check_expression_str;
 use crate::creature::CreatureLegendaryAction;
 
 
@@ -204,6 +238,13 @@ pub enum SpellcastingCommand {
     Removes the specified spells from the creature, which is useful when basing the creature off another.
     */
     RemoveSpells(Vec<String>), // overrides the existence of spells if the spellcasting trait was previously provided
+
+    /**
+    `Primary`
+
+    Marks this as the spellcasting feature used for the `spell_atk`, `spell_dc`, `caster_level` and `spell_class` properties, when the creature has more than one. Without this, the first spellcasting feature is used for those, and innate spellcasting is preferred for the attack bonus and save DC.
+    */
+    Primary,
 }
 
 impl SpellcastingCommand {
@@ -251,6 +292,9 @@ impl SpellcastingCommand {
             },
             Self::RemoveSpells(spells) => {
                 data.remove_spells(spells);
+            },
+            Self::Primary => {
+                data.set_primary();
             }
 
         }
@@ -317,7 +361,7 @@ pub enum InnateSpellcastingCommand {
     /**
     `Components(<string>)`
 
-    Replaces the phrase "requiring no material components" with a custom description for components. For example, the pixie uses pixie dust for its components.
+    Replaces the phrase "requiring no material components" with a custom description for components. For example, the pixie uses pixie dust for its components. An empty string leaves the phrase out, for creatures whose spells still need their usual components.
      */
     Components(String),
 
@@ -344,7 +388,56 @@ impl InnateSpellcastingCommand {
 struct CreatureCreationHooks {
     multiattacks: Vec<Multiattack>,
     features: Vec<Feature>,
-    expected_challenge_rating: Option<ChallengeRating>
+    expected_challenge_rating: Option<ChallengeRating>,
+    challenge_rating_overridden: bool,
+    weapons: HashMap<String,(Weapon,Option<CompoundAttackEffect>)>,
+    warnings: Vec<String>,
+    mistakes: Vec<String>, // warnings about the finished creature, reported even when the warnings about commands are not
+    canonicalize_layout: bool,
+    strict_multiattack: bool,
+    scale: Option<f32>,
+    action_order: HashMap<String,i32>,
+    feature_order: HashMap<String,i32>,
+    lint_overrides: bool,
+    statistics_set: Vec<(&'static str,usize)> // statistic, number of times it was set, in the order first set
+}
+
+impl CreatureCreationHooks {
+
+    fn warn_if_not_found(&mut self, found: bool, command: &str, name: &str) {
+        if !found {
+            self.warnings.push(format!("{}: could not find '{}', nothing was removed.",command,name))
+        }
+    }
+
+    fn warn_if_none_found(&mut self, found: bool, command: &str) {
+        if !found {
+            self.warnings.push(format!("{}: there was nothing to remove.",command))
+        }
+    }
+
+    fn record_statistic_set(&mut self, statistic: &'static str) {
+        if let Some((_,count)) = self.statistics_set.iter_mut().find(|(set,_)| *set == statistic) {
+            *count += 1
+        } else {
+            self.statistics_set.push((statistic,1))
+        }
+    }
+
+    fn warn_if_set_more_than_once(&mut self) {
+        for (statistic,count) in &self.statistics_set {
+            if *count > 1 {
+                self.warnings.push(format!("The {} was set {} times, only the last one is used.",statistic,count))
+            }
+        }
+    }
+
+    // a multiattack that was removed along with its action shouldn't be checked against the actions that remain.
+    fn forget_removed_multiattack(&mut self, creature: &Creature) {
+        if creature.multiattack.is_none() {
+            self.multiattacks.clear()
+        }
+    }
 }
 
 
@@ -378,7 +471,9 @@ pub enum CreatureCommand {
      
     This allows you to include commands from another creature file on disk. With this command, you can either take an existing creature and modify it, or add a common set of features to more than one creature.
 
-    The file is not expected to be a pure creature format. It will be interpolated in a similar way to how feature descriptions are interpolated. The second argument allows you to pass named arguments to that interpolation, letting you create more dynamic content. 
+    The path is relative to the directory containing the including file. For the top-level creature, this can be changed with the `--base-dir` option on the command line, which is needed if the creature is read from stdin.
+
+    The file is not expected to be a pure creature format. It will be interpolated in a similar way to how feature descriptions are interpolated. The second argument allows you to pass named arguments to that interpolation, letting you create more dynamic content. If an argument is optional, the included file can give it a default with the `??` operator, such as `$<name ?? "goblin">`, so the file still works when the argument isn't passed.
     
     The output of this interpolation *is* expected to be in valid Monstorr creature format. 
     
@@ -402,6 +497,83 @@ pub enum CreatureCommand {
     */
     Source(String),
 
+    /**
+    `Companion(<string>)`
+
+    Some creatures summon or come with companions, such as familiars or steeds. This command records a reference to another creature, by slug or file name, which will be listed in the stat-block as a creature that this one can summon. The referenced creature is not loaded or checked, the reference is simply passed on to the output so that other tools can look it up. Use the command more than once to reference more than one companion.
+    */
+    Companion(String),
+
+    /**
+    `Environments([<string>])`
+
+    Sets the environments (arctic, forest, urban, etc.) in which the creature can be found. These do not appear in the stat-block, but stored creatures can be filtered by them when listing creatures or generating encounters.
+    */
+    Environments(Vec<String>),
+
+    /**
+    `Habitat([<string>])`
+
+    Sets the habitats (forest, hill, underdark, etc.) listed at the bottom of stat blocks following the 2024 rules. Unlike `Environments`, these are shown in the stat-block, and are written exactly as given. Templates which don't support them leave them out. An empty list removes the habitat.
+    */
+    Habitat(Vec<String>),
+
+    /**
+    `Treasure(<string>)`
+
+    Sets the kind of treasure (such as "Arcana" or "Any") listed at the bottom of stat blocks following the 2024 rules. Templates which don't support it leave it out. An empty string removes the treasure.
+    */
+    Treasure(String),
+
+    /**
+    `Extra(<string>,<string>)`
+
+    Adds an arbitrary key and value to the `extra` property of the stat-block, which is output in JSON for use by other tools, such as a campaign id or a custom flag. The standard templates ignore these values. The key is used exactly as given, and if the same key is used more than once, the last value is kept.
+    */
+    Extra(String,String),
+
+    /**
+    `Note(<string>)`
+
+    Records a note about the creature, such as the reasoning behind a change, which, unlike a comment, is kept when the creature file is read and written again. Notes have no effect on the creature, and are only added to the `notes` property of the stat-block if requested, such as with the `--with-notes` option on the command line, so they are not accidentally shown to players.
+    */
+    Note(String),
+
+    /**
+    `DefineProperty(<string>,<string>)`
+
+    Defines a custom interpolation property with the given name, whose value is calculated from the given expression, written as it would be inside `${...}` in a description. The expression is evaluated each time the property is referenced, not when it is defined, so `DefineProperty("aura_dc","8 + prof + cha")` will still be correct after the challenge rating or charisma are changed by later commands. Defining a property with the same name again replaces the expression.
+    */
+    DefineProperty(String,String),
+
+    /**
+    `CanonicalizeLayout`
+
+    Rearranges the features and actions into the order used in the official books, no matter what order they were added in. This is useful when a creature is composed from several files with `Include`, where the order of the commands might not be the order you want to present them in. Special abilities are kept in the order added, except that innate spellcasting and then spellcasting are moved to the end. Actions are kept in the order added, except that multiattack is moved to the beginning. Reactions and legendary actions are kept in the order added. This is done after the creature is finished, so it doesn't matter where the command is placed.
+    */
+    CanonicalizeLayout,
+
+    /**
+    `StrictMultiattack`
+
+    Reports an error, instead of a warning, when the structure of a multiattack is valid, but probably a mistake. This includes a multiattack which refers to the Multiattack action itself, a `Count` of zero, and the same attack listed twice in the same list, such as `And([Weapon(Scimitar),Weapon(Scimitar)])`, which should be written as `Count(2,[Weapon(Scimitar)])`. It also includes a multiattack which allows a different number of attacks than its description gives, such as `Count(3,[Attack("Claw")])` for "makes two claw attacks". Without this command, these are reported as warnings, unless the `--strict` option is used, which also makes them errors.
+    */
+    StrictMultiattack,
+
+    /**
+    `LintOverrides`
+
+    Reports a warning when a statistic which has only one value, such as the size, type, alignment, armor, hit dice or an ability score, is set more than once, counting the commands in included files. Overriding a statistic is often deliberate, such as when customizing an included creature, but it can also be a mistake when two included files both set it. The `--lint` option on the command line does the same thing for any creature. Like other warnings, these are only reported if the `--warn-noop` option is used, which `--lint` turns on. This is done after the creature is finished, so it doesn't matter where the command is placed.
+    */
+    LintOverrides,
+
+    /**
+    `Scale(<number>)`
+
+    Multiplies the hit points and the damage of attacks by the specified number, for quickly making a tougher or easier version of a creature while playtesting. `Scale(1.5)` makes it about half again as tough, `Scale(0.5)` about half as tough. Hit points are scaled by changing the number of hit dice, and damage by changing the number of dice and adding or subtracting a bonus to make up the difference, so a `2d6` attack scaled by 1.5 becomes `3d6`, and a `1d6` attack becomes `1d6 + 2`. Legendary actions are scaled along with the actions. Attack bonuses, save DCs and the ability bonus added to damage are not changed. Damage written into descriptions by hand, such as in a feature or a reaction, is not changed either. This is done after the creature is finished, so it doesn't matter where the command is placed. If the command is used more than once, the last one is used. The number must be greater than zero, otherwise an error is reported when the creature is created. An error is also reported if the scaled hit dice are more than 255, or if an attack uses `ExpressionDamage`, which can't be scaled.
+    */
+    Scale(f32),
+
     /**
     `Name(<string>)`
 
@@ -409,6 +581,20 @@ pub enum CreatureCommand {
     */
     Name(String),
 
+    /**
+    `DisplayName(<string>)`
+
+    Sets a title to show on the stat-block in place of the name, such as "Goblin Boss (Skabgut)" for a named individual who is otherwise a goblin boss. The name set with `Name` is still the creature's identity: it is used to find the creature, to create its slug, and for the `name` variable in descriptions. The title is output as `display_name`, and the stat-block templates show it instead of the name when it is set.
+    */
+    DisplayName(String),
+
+    /**
+    `ProperName`
+
+    Indicates that the name of the creature is a proper name, such as 'Tiamat', for named creatures and NPCs. The default value of the `subj` variable, and everything based on it, is then the name exactly as given, without the definite article and without changing it to lower case. A name set with `SubjectName` still takes precedence.
+    */
+    ProperName,
+
     /**
     `SubjectName(<string>)`
 
@@ -790,6 +976,13 @@ pub enum CreatureCommand {
     */
     Alignment(String), // custom alignment, such as (50% good and 50% evil)
 
+    /**
+    `AlignmentChance(<list(<number>,<alignment>)>)`
+
+    Sets the alignment of the creature to a choice of alignments, each with the percent chance of it, overriding any previous alignment set. For example, `AlignmentChance([(75,NeutralEvil),(25,LawfulEvil)])` appears as "neutral evil (75%) or lawful evil (25%)". The alignments are named the same as the commands which set them, such as `LawfulGood` or `AnyNonGood`, and `Custom("...")` can be used for any other. Unlike the `Alignment` command, the creature can be found by any one of these alignments when filtering the creature list. The chances must add up to 100, and an alignment in the list can't be another list of chances, otherwise an error is reported. By default, creatures have an alignment of "any".
+    */
+    AlignmentChance(Vec<(u8,Alignment)>),
+
     /**
     `HitDie(<die-string>)`
 
@@ -810,10 +1003,24 @@ pub enum CreatureCommand {
     /**
     `HitPoints(<integer>)`
 
-    Sets the actual hit points for the creature as shown in the stat block. This does not change the dice expression used for calculation, use `HitDie` and `HitDiceCount` for that. By default, the hit points are calculated as the average of the dice expression calculated from those values.
+    Sets the actual hit points for the creature as shown in the stat block. This does not change the dice expression used for calculation, use `HitDie` and `HitDiceCount` for that. By default, the hit points are calculated as the average of the dice expression calculated from those values. The hit dice are still shown beside the overridden hit points, and their average is still available in descriptions as `hit_points_average`.
     */
     HitPoints(u16), // If you want custom hit point count, say you rolled a different value, put them here.
 
+    /**
+    `Minion`
+
+    Makes the creature a minion, following a variant rule for running large groups of weak creatures, which is not part of the official rules. A minion drops to 0 hit points when it takes any damage. The hit points are shown without dice, followed by "(minion)", a "Minion (Variant Rule)" feature explaining this is added before the other features, and the `minion` property of the stat-block is set, for tools which track hit points. This is different from a swarm, which is a single creature made up of many tiny ones, and has a custom type such as "swarm of Tiny beasts".
+    */
+    Minion,
+
+    /**
+    `AverageRounding(<AverageRounding>)`
+
+    Changes how averages of dice are rounded for the hit points and the dice in descriptions, such as damage. See [`crate::dice_expression::AverageRounding`] for the choices. By default, averages are rounded down, as in the core books.
+    */
+    AverageRounding(AverageRounding),
+
     /**
     `Armor(<Armor>)`
 
@@ -821,6 +1028,20 @@ pub enum CreatureCommand {
     */
     Armor(Armor),
 
+    /**
+    `Initiative(<integer>)`
+
+    Shows the creature's initiative in the stat block, as in the 2024 rules, in a form like "+5 (15)". The bonus is calculated from the dexterity modifier, plus the proficiency bonus multiplied by the number given here. Use 0 if the creature isn't proficient in initiative, 1 if it is, and 2 for expertise. By default, the initiative is not shown.
+    */
+    Initiative(u8),
+
+    /**
+    `OverrideInitiative(<integer>)`
+
+    Sets the initiative bonus shown in the stat block, instead of calculating it. This also causes the initiative to be shown.
+    */
+    OverrideInitiative(i8),
+
     /**
     `Shield`
 
@@ -972,6 +1193,13 @@ pub enum CreatureCommand {
     Removes skill proficiencies and expertise for the specified Skills. See [`crate::stats::Skill`] for possible values.
     */
     RemoveSkills(Vec<Skill>), 
+
+    /**
+    `ClassProficiencies(<CharacterClass>)`
+
+    Adds the saving throw proficiencies of a character class, and a typical choice of its skill proficiencies, such as Strength and Constitution saves with Athletics and Perception for a `Fighter`. This is a shortcut for NPCs built with class levels, and works the same as adding them with `Saves` and `Skills`, except that a skill which already has expertise keeps it. They can be changed afterwards with `RemoveSaves`, `RemoveSkills` and the other commands. See [`crate::stats::CharacterClass`] for possible values.
+    */
+    ClassProficiencies(CharacterClass),
     
     /**
     `ConditionImmunity(<Condition>)`
@@ -979,6 +1207,20 @@ pub enum CreatureCommand {
     Adds a condition immunity to the creature. See [`crate::stats::Condition`] for possible values.
     */
     ConditionImmunity(Condition),
+
+    /**
+    `CustomConditionImmunity(<string>)`
+
+    Adds immunity to a condition which isn't one of the standard conditions, such as a homebrew "cursed" condition. These are listed after the standard conditions, in the order they were added.
+    */
+    CustomConditionImmunity(String),
+
+    /**
+    `RemoveCustomConditionImmunity(<string>)`
+
+    Removes a condition immunity added with `CustomConditionImmunity`. If nothing matches, the command does nothing, but a warning will be reported if the `--warn-noop` option is used.
+    */
+    RemoveCustomConditionImmunity(String),
     
     /**
     `Vulnerability(<Damage>)`
@@ -1008,6 +1250,13 @@ pub enum CreatureCommand {
     Overrides the text of the vulnerabilities property.
     */
     CustomVulnerability(String),
+
+    /**
+    `VulnerabilityType(<string>)`
+
+    Adds vulnerability to a damage type which isn't one of the standard types, such as a homebrew "sonic" damage. These are listed after the standard damage types.
+    */
+    VulnerabilityType(String),
     
     /**
     `Resistance(<Damage>)`
@@ -1064,6 +1313,20 @@ pub enum CreatureCommand {
     Overrides the text of the resistances property.
     */
     CustomResistance(String),
+
+    /**
+    `ResistanceType(<string>)`
+
+    Adds resistance to a damage type which isn't one of the standard types. These are listed after the standard damage types.
+    */
+    ResistanceType(String),
+
+    /**
+    `ResistanceNote(<string>)`
+
+    Adds a note in parentheses after the resistance added just before this command, such as "from stoneskin" after `NonmagicalResistance`, for resistances granted by a spell or magic item. The note only applies to that resistance, so a damage type with a note is listed separately from the others, as in "cold; fire (from its ring of fire resistance)". A resistance can only have one note, a second replaces the first. If no resistance has been added yet, the command does nothing, but a warning will be reported if the `--warn-noop` option is used.
+    */
+    ResistanceNote(String),
     
     /**
     `Immunity(<Damage>)`
@@ -1121,6 +1384,20 @@ pub enum CreatureCommand {
     */
     CustomImmunity(String),
 
+    /**
+    `ImmunityType(<string>)`
+
+    Adds immunity to a damage type which isn't one of the standard types. These are listed after the standard damage types.
+    */
+    ImmunityType(String),
+
+    /**
+    `ImmunityNote(<string>)`
+
+    Adds a note in parentheses after the immunity added just before this command. This works the same as `ResistanceNote`.
+    */
+    ImmunityNote(String),
+
     /**
     `Languages([<Language>])`
 
@@ -1137,6 +1414,23 @@ pub enum CreatureCommand {
     */
     UnspokenLanguages(Vec<Language>),
 
+    /**
+    `LanguagesKnewInLife`
+
+    Adds "the languages it knew in life" to the languages the creature speaks, for undead such as ghosts. For undead which only understand them, such as zombies, use `UnspokenLanguages([KnewInLife])` instead. Since `Languages` replaces all of the languages, this must come after it.
+    */
+    LanguagesKnewInLife,
+
+    /**
+    `Telepathy(<integer>,<string>?)`
+
+    Gives the creature telepathy with the specified range in feet, replacing any telepathy it already had. The optional string is a note written in parentheses after the range, such as `Telepathy(120,"works only with creatures that understand a language")`. Telepathy is always listed at the end of the languages. Since `Languages` replaces all of the languages, this must come after it.
+    */
+    Telepathy(u8,
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        Option<String>),
+
     /**
     `Darkvision(<integer>)`
     
@@ -1179,13 +1473,41 @@ pub enum CreatureCommand {
     */
     CustomSense(String,u8),
 
+    /**
+    `SensesSeparator(<string>)`
+
+    Changes the text placed between the senses in the stat-block, which is ", " by default. The passive perception is always the last sense, so the separator also comes before it: `SensesSeparator("; ")` gives "darkvision 60 ft.; passive Perception 9". To change the words before the score, see `PassivePerceptionLabel`.
+    */
+    SensesSeparator(String),
+
+    /**
+    `PassivePerceptionLabel(<string>)`
+
+    Changes the label shown before the passive perception score, which appears at the end of the senses. By default, this is "passive Perception". If the value is empty, only the score will be shown.
+    */
+    PassivePerceptionLabel(String),
+
+    /**
+    `PassivePerceptionBonus(<integer>)`
+
+    Adds a bonus to the passive perception score, such as the +5 from the Observant feat, without changing the creature's Perception skill.
+    */
+    PassivePerceptionBonus(i8),
+
+    /**
+    `OverridePassivePerception(<integer>)`
+
+    Overrides the passive perception score, for when the calculated score isn't correct. This replaces the calculated score, including any `PassivePerceptionBonus`.
+    */
+    OverridePassivePerception(u8),
+
 
     /**
     `ExpectNoChallenge`
 
     "Expects" the creature to have no challenge rating (0 and 0 XP) when the creature is complete.
 
-    The challenge rating expectations will cause an error to occur if the calculated challenge rating is different from expected when creature creation is complete. Further expectations will override previous ones. Note that currently the only way to establish a challenge rating is with the Override challenge rating commands. Calculating challenge ratings remains a goal of Monstorr, but has been deferred to some time in the future.
+    The challenge rating expectations will cause an error to occur if the challenge rating is different from expected when creature creation is complete. Further expectations will override previous ones. If one of the Override challenge rating commands was used, the overridden challenge rating is checked. Otherwise, the challenge rating is calculated from the creature's hit points, armor class, damage per round and attack bonus, following the Dungeon Master's Guide. This calculation ignores spells, legendary actions, and features which would change those values.
     
     */
     ExpectNoChallenge, 
@@ -1195,7 +1517,7 @@ pub enum CreatureCommand {
 
     "Expects" the creature to have a challenge rating of the specified whole number when complete. This number can be 0, which is different from no challenge, as it still provides 10 XP.
     
-    The challenge rating expectations will cause an error to occur if the calculated challenge rating is different from expected when creature creation is complete. Further expectations will override previous ones. Note that currently the only way to establish a challenge rating is with the Override challenge rating commands. Calculating challenge ratings remains a goal of Monstorr, but has been deferred to some time in the future.
+    The challenge rating expectations will cause an error to occur if the challenge rating is different from expected when creature creation is complete. Further expectations will override previous ones. If one of the Override challenge rating commands was used, the overridden challenge rating is checked. Otherwise, the challenge rating is calculated from the creature's hit points, armor class, damage per round and attack bonus, following the Dungeon Master's Guide. This calculation ignores spells, legendary actions, and features which would change those values.
     
     */
     ExpectChallenge(u8),
@@ -1205,7 +1527,7 @@ pub enum CreatureCommand {
 
     "Expects" the creature to have a challenge rating of 1/2 when complete.
     
-    The challenge rating expectations will cause an error to occur if the calculated challenge rating is different from expected when creature creation is complete. Further expectations will override previous ones. Note that currently the only way to establish a challenge rating is with the Override challenge rating commands. Calculating challenge ratings remains a goal of Monstorr, but has been deferred to some time in the future.
+    The challenge rating expectations will cause an error to occur if the challenge rating is different from expected when creature creation is complete. Further expectations will override previous ones. If one of the Override challenge rating commands was used, the overridden challenge rating is checked. Otherwise, the challenge rating is calculated from the creature's hit points, armor class, damage per round and attack bonus, following the Dungeon Master's Guide. This calculation ignores spells, legendary actions, and features which would change those values.
     
     */
     ExpectHalfChallenge,
@@ -1215,7 +1537,7 @@ pub enum CreatureCommand {
     
     "Expects" the creature to have a challenge rating of 1/4 when complete.
     
-    The challenge rating expectations will cause an error to occur if the calculated challenge rating is different from expected when creature creation is complete. Further expectations will override previous ones. Note that currently the only way to establish a challenge rating is with the Override challenge rating commands. Calculating challenge ratings remains a goal of Monstorr, but has been deferred to some time in the future.
+    The challenge rating expectations will cause an error to occur if the challenge rating is different from expected when creature creation is complete. Further expectations will override previous ones. If one of the Override challenge rating commands was used, the overridden challenge rating is checked. Otherwise, the challenge rating is calculated from the creature's hit points, armor class, damage per round and attack bonus, following the Dungeon Master's Guide. This calculation ignores spells, legendary actions, and features which would change those values.
     
     */
     ExpectQuarterChallenge,
@@ -1225,7 +1547,7 @@ pub enum CreatureCommand {
     
     "Expects" the creature to have a challenge rating of 1/8 when complete.
     
-    The challenge rating expectations will cause an error to occur if the calculated challenge rating is different from expected when creature creation is complete. Further expectations will override previous ones. Note that currently the only way to establish a challenge rating is with the Override challenge rating commands. Calculating challenge ratings remains a goal of Monstorr, but has been deferred to some time in the future.
+    The challenge rating expectations will cause an error to occur if the challenge rating is different from expected when creature creation is complete. Further expectations will override previous ones. If one of the Override challenge rating commands was used, the overridden challenge rating is checked. Otherwise, the challenge rating is calculated from the creature's hit points, armor class, damage per round and attack bonus, following the Dungeon Master's Guide. This calculation ignores spells, legendary actions, and features which would change those values.
     
     */
     ExpectEighthChallenge,
@@ -1268,16 +1590,44 @@ pub enum CreatureCommand {
     */
     OverrideEighthChallenge,
 
+    /**
+    `ProficiencyBonus(<integer>)`
+
+    Sets the proficiency bonus, instead of taking it from the challenge rating. This is useful for creatures built from class levels, whose proficiency bonus follows their level. The bonus is used everywhere the proficiency bonus is, including skills, saving throws, attacks, spellcasting and the `prof` interpolation property.
+    */
+    ProficiencyBonus(u8),
+
+    /**
+    `ShowProficiencyBonus`
+
+    Shows the proficiency bonus in the stat block, on its own line after the challenge rating, as some 2024-style stat blocks do. This is the bonus from the challenge rating, or from `ProficiencyBonus` if that was used. By default, the proficiency bonus is not shown.
+    */
+    ShowProficiencyBonus,
+
 
     /**
     `Multiattack(<string>,<Multiattack>)`
 
-    Adds a "Multiattack" action to the creature. The first argument is the description to use for the action, which can't be easily calculated from the other data given. The second argument is a structure which describes what actions are possible in the multiattack, which will be used someday by Monstorr to calculate challenge ratings. Specifying them now will make it easier to get that functionality when it is available.
+    Adds a "Multiattack" action to the creature. The first argument is the description to use for the action, which can't be easily calculated from the other data given. The second argument is a structure which describes what actions are possible in the multiattack, which is used to find the damage per round when calculating the challenge rating.
 
     For more information on the Multiattack argument, see [`crate::attacks::Multiattack`].
     */
     Multiattack(String,Multiattack), // description, details for cr calculation
 
+    /**
+    `AutoMultiattack(<Multiattack>)`
+
+    Adds a "Multiattack" action to the creature, as with `Multiattack`, but the description is generated from the multiattack structure, such as "The goblin makes two scimitar attacks." for `Count(2,[Weapon(Scimitar(0))])`. This keeps the description in step with the structure, but the generated wording is plain, so use `Multiattack` for anything unusual. See [`crate::attacks::Multiattack::get_description`].
+    */
+    AutoMultiattack(Multiattack),
+
+
+    /**
+    `OneHanded`
+
+    Versatile weapons, such as the longsword and spear, are shown with their one-handed damage only, leaving out the damage "if used with two hands to make a melee attack". This is for creatures which can't use two hands, such as one carrying a shield. By default, versatile weapons show both. This only changes the weapons added after it, so it should be placed before them.
+    */
+    OneHanded,
 
     /**
     `Weapon(<Weapon>)`
@@ -1290,6 +1640,39 @@ pub enum CreatureCommand {
         #[serde(skip_serializing_if = "Option::is_none")] 
         Option<CompoundAttackEffect>),
 
+    /**
+    `CustomWeapon(<string>,<WeaponReach>,<dice-expression-string>,<Damage>,<AttackBonus>)`
+    `CustomWeapon(<string>,<WeaponReach>,<dice-expression-string>,<Damage>,<AttackBonus>,optional(<CompoundAttackEffect>))`
+
+    Adds a weapon attack action with the given name for a weapon that isn't built in, such as a claw, tentacle or invented weapon. The to-hit and damage bonuses are calculated from the ability given by the [`crate::attacks::AttackBonus`], just as for the built-in weapons, and whether it is a melee or ranged attack comes from the [`crate::attacks::WeaponReach`]. Unlike the built-in weapons, the damage dice are used as given, and are not increased for the creature's size. The optional compound attack effect works as it does for `Weapon`.
+
+    ```ron
+    CustomWeapon("Claw",Reach(5),"2d6",Slashing,Strength),
+    ```
+    */
+    CustomWeapon(String,WeaponReach,DiceExpression,Damage,AttackBonus,
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")] 
+        Option<CompoundAttackEffect>),
+
+    /**
+    `DefineWeapon(<string>,<Weapon>)`
+    `DefineWeapon(<string>,<Weapon>,optional(<CompoundAttackEffect>))`
+
+    Defines a named weapon profile, which can be added to the creature later with `AddWeapon`. This does not add an action by itself. It is useful for themed equipment shared by several creatures, which can put their definitions into a file and `Include` it, instead of repeating the same compound effect everywhere. A later definition with the same name replaces an earlier one. The arguments are the same as for `Weapon`.
+    */
+    DefineWeapon(String,Weapon,
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")] 
+        Option<CompoundAttackEffect>),
+
+    /**
+    `AddWeapon(<string>)`
+
+    Adds a weapon attack action using a profile previously defined with `DefineWeapon`. The action will have the name given in the definition, so use `RemoveAction` and `OverrideActionDescription`, rather than the weapon commands, to change it later. An error will occur if the weapon has not been defined.
+    */
+    AddWeapon(String),
+
     /**
     `ExpectWeaponAttack(<Weapon>,<Attack>)`
 
@@ -1348,7 +1731,7 @@ pub enum CreatureCommand {
     /**
     `RemoveWeapon(<Weapon>)`
 
-    Removes the first action matching the specified weapon from the list of actions. See [`crate::attacks::Weapon`] for possible values.
+    Removes the first action matching the specified weapon from the list of actions. If nothing matches, the command does nothing, but a warning will be reported if the `--warn-noop` option is used. See [`crate::attacks::Weapon`] for possible values.
     */
     RemoveWeapon(Weapon),
 
@@ -1362,10 +1745,24 @@ pub enum CreatureCommand {
     /**
     `RemoveAction(<string>)`
 
-    Removes the first action with the specified name from the list of actions.
+    Removes the first action with the specified name from the list of actions. If nothing matches, the command does nothing, but a warning will be reported if the `--warn-noop` option is used.
     */
     RemoveAction(String),
 
+    /**
+    `RemoveActionsMatching(<string>)`
+
+    Removes every action whose name starts with the specified text, such as `"Breath"` to remove all of the breath weapons. This includes weapons and the Multiattack action. If nothing matches, the command does nothing, but a warning will be reported if the `--warn-noop` option is used.
+    */
+    RemoveActionsMatching(String),
+
+    /**
+    `RemoveAllActions`
+
+    Removes every action, including weapons and the Multiattack action. This is useful when deriving a simpler creature from one with many actions, before adding the actions it should have. If there are no actions, the command does nothing, but a warning will be reported if the `--warn-noop` option is used.
+    */
+    RemoveAllActions,
+
     /**
     `MoveAction(<string>,<integer>)
 
@@ -1373,6 +1770,13 @@ pub enum CreatureCommand {
      */
     MoveAction(String,i8),
 
+    /**
+    `OrderAction(<string>,<integer>)`
+
+    Gives an order to the action with the specified name. Once all of the commands have been applied, the actions are sorted by their order, with actions that weren't given one counting as 0. Actions with the same order keep the order in which they were added. Unlike `MoveAction`, this doesn't depend on where the action is when the command is applied, so an included file can place its actions without knowing what else will be included. Weapons can be ordered by the name of their action, such as "Scimitar".
+    */
+    OrderAction(String,i32),
+
     /**
     `Reaction(<Reaction>)`
     `Reaction(<Reaction>,option(<UsageLimit>))`
@@ -1387,10 +1791,17 @@ pub enum CreatureCommand {
     /**
     `RemoveReaction(<string>)`
 
-    Removes the first reaction with the specified name.
+    Removes the first reaction with the specified name. If nothing matches, the command does nothing, but a warning will be reported if the `--warn-noop` option is used.
     */
     RemoveReaction(String),
 
+    /**
+    `RemoveAllReactions`
+
+    Removes every reaction. If there are no reactions, the command does nothing, but a warning will be reported if the `--warn-noop` option is used.
+    */
+    RemoveAllReactions,
+
     /**
     `Feature(<Feature>)`
     `Feature(<Feature>,option(<UsageLimit>))`
@@ -1405,16 +1816,27 @@ pub enum CreatureCommand {
     /**
     `RemoveFeature(<string>)`
 
-    Removes the first feature with the specified name. This can also be used to remove spellcasting and innate spellcasting.
+    Removes the first feature with the specified name. This can also be used to remove spellcasting and innate spellcasting. If nothing matches, the command does nothing, but a warning will be reported if the `--warn-noop` option is used.
     */
     RemoveFeature(String),
 
+    /**
+    `OrderFeature(<string>,<integer>)`
+
+    Gives an order to the feature with the specified name. The features are sorted as with `OrderAction`.
+    */
+    OrderFeature(String,i32),
+
     /**
     `Spellcasting([<SpellcastingCommand>...])`
+    `Spellcasting([<SpellcastingCommand>...],optional(<string>))`
 
-    Calls the listed commands to add spellcasting features to the creature. For more information on what commands are available, see [`SpellcastingCommand`].
+    Calls the listed commands to add spellcasting features to the creature. For more information on what commands are available, see [`SpellcastingCommand`]. The optional name allows a creature to have more than one spellcasting feature, such as a class's spellcasting and a separate one granted by a subclass. Commands with the same name add to the same feature, and the name is shown as the name of the feature. Without a name, the commands add to the feature named "Spellcasting". Use the `Primary` command to choose which one is used for the `spell_atk` and related properties.
     */
-    Spellcasting(Vec<SpellcastingCommand>),
+    Spellcasting(Vec<SpellcastingCommand>,
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        Option<String>),
 
     /**
     `InnateSpellcasting([<InnateSpellcastingCommand>...])`
@@ -1432,13 +1854,28 @@ pub enum CreatureCommand {
     /**
     `RemoveLegendaryAction(<string>)`
 
-    Removes the specified legendary action by name from the creature. When the last one is removed, will remove the legendary action feature.
+    Removes the specified legendary action by name from the creature. When the last one is removed, will remove the legendary action feature. If nothing matches, the command does nothing, but a warning will be reported if the `--warn-noop` option is used.
     */
     RemoveLegendaryAction(String),
 
-    /** Currently Unsupported */
+    /**
+    `LegendaryActionsStyle(<LegendaryActionsStyle>)`
+
+    Changes the wording of the introduction to the legendary actions, such as `Uses` for the 2024 "Legendary Action Uses: 3." in place of the classic "can take 3 legendary actions". Only the generated introduction changes, the legendary actions themselves are written the same way. This can be specified before or after `LegendaryActions`, and has no effect on a creature without them. For the wording of each style, see [`crate::actions::LegendaryActionsStyle`].
+    */
+    LegendaryActionsStyle(LegendaryActionsStyle),
+
+    /**
+    `LairActions(<string>,[<string>],<string>)`
+
+    Adds lair actions to the creature. The first string is the description that introduces them, followed by the list of lair actions, and a note that comes after them, which can be an empty string. All of these are interpolated as with features, so they can use `${subj}` and dice expressions. The lair actions are shown as a list, without headings.
+    */
     LairActions(String,Vec<String>,String), // beginning description, list of lair actions, ending description
-    /** Currently Unsupported */
+    /**
+    `RegionalEffects(<string>,[<string>],<string>)`
+
+    Adds regional effects to the creature. The first string introduces the effects, followed by the list of effects, and a closing note, usually describing how the effects fade when the creature dies, which can be an empty string. These are interpolated in the same way as `LairActions`.
+    */
     RegionalEffects(String,Vec<String>,String), // beginning description, list of regional effects, ending description
 
 
@@ -1448,7 +1885,71 @@ pub const MONSTORR_VERSION: f32 = 1.0;
 
 impl CreatureCommand {
 
+    // the statistic set by commands which replace a single value, for the `LintOverrides` command.
+    fn statistic_set(&self) -> Option<&'static str> {
+        match self {
+            CreatureCommand::Name(_) => Some("name"),
+            CreatureCommand::Tiny |
+            CreatureCommand::Small |
+            CreatureCommand::Medium |
+            CreatureCommand::Large |
+            CreatureCommand::Huge |
+            CreatureCommand::Gargantuan => Some("size"),
+            CreatureCommand::Aberration |
+            CreatureCommand::Beast |
+            CreatureCommand::Celestial |
+            CreatureCommand::Construct |
+            CreatureCommand::Dragon |
+            CreatureCommand::Elemental |
+            CreatureCommand::Fey |
+            CreatureCommand::Fiend |
+            CreatureCommand::Giant |
+            CreatureCommand::Humanoid |
+            CreatureCommand::Monstrosity |
+            CreatureCommand::Ooze |
+            CreatureCommand::Plant |
+            CreatureCommand::Undead |
+            CreatureCommand::CreatureType(_) => Some("type"),
+            CreatureCommand::AnyAlignment |
+            CreatureCommand::AnyNonGood |
+            CreatureCommand::AnyNonEvil |
+            CreatureCommand::AnyNonLawful |
+            CreatureCommand::AnyNonChaotic |
+            CreatureCommand::AnyGood |
+            CreatureCommand::AnyEvil |
+            CreatureCommand::AnyLawful |
+            CreatureCommand::AnyChaotic |
+            CreatureCommand::LawfulGood |
+            CreatureCommand::NeutralGood |
+            CreatureCommand::ChaoticGood |
+            CreatureCommand::LawfulNeutral |
+            CreatureCommand::Neutral |
+            CreatureCommand::ChaoticNeutral |
+            CreatureCommand::LawfulEvil |
+            CreatureCommand::NeutralEvil |
+            CreatureCommand::ChaoticEvil |
+            CreatureCommand::Unaligned |
+            CreatureCommand::Alignment(_) |
+            CreatureCommand::AlignmentChance(_) => Some("alignment"),
+            CreatureCommand::HitDie(_) => Some("hit die"),
+            CreatureCommand::HitDiceCount(_) => Some("hit dice count"),
+            CreatureCommand::HitPoints(_) => Some("hit points"),
+            CreatureCommand::Armor(_) => Some("armor"),
+            CreatureCommand::Str(_) => Some("strength"),
+            CreatureCommand::Dex(_) => Some("dexterity"),
+            CreatureCommand::Con(_) => Some("constitution"),
+            CreatureCommand::Int(_) => Some("intelligence"),
+            CreatureCommand::Wis(_) => Some("wisdom"),
+            CreatureCommand::Cha(_) => Some("charisma"),
+            _ => None
+        }
+    }
+
     fn execute(&self, working_dir: &PathBuf, creature: &mut Creature, hooks: &mut CreatureCreationHooks) -> Result<(),CreatureError> {
+
+        if let Some(statistic) = self.statistic_set() {
+            hooks.record_statistic_set(statistic)
+        }
         
         match self {
             CreatureCommand::Monstorr(min,max) => {
@@ -1462,26 +1963,11 @@ impl CreatureCommand {
                 }
             },
             CreatureCommand::Include(file,parameters) => {
-                // make it absolute
-                let mut source_file = working_dir.clone();
-                source_file.push(file);
-                // get the data from the file
-                let source = fs::read_to_string(&source_file).map_err(|a| CreatureError::include_error(file,a))?;
-                // interpolate the text
-                let interpolated = interpolate_str_for_deserialization(&source, file, parameters,false).map_err(|a| CreatureError::include_error(file,a))?;
-                // deserialize the commands
-                let commands = CreatureCreator::load_from_str(&interpolated).map_err(|a| CreatureError::include_error(file,a))?;
-                source_file.pop();
-                commands.apply_commands(&source_file,creature,hooks).map_err(|a| CreatureError::include_error(file,a))?
+                let (commands,source_dir) = CreatureCreator::load_include(working_dir,file,parameters)?;
+                commands.apply_commands(&source_dir,creature,hooks).map_err(|a| CreatureError::include_error(file,a))?
             },
             CreatureCommand::IncludeStored(creature_name) => {
-                let source = if let Some(entry) = monstorr_data::creatures::STORED_CREATURES.iter().find(|(creature,_)| (creature.slug == creature_name) || (creature.name == creature_name)) {
-                    entry.1
-                } else {
-                    Err(CreatureError::StoredCreatureNotFound(creature_name.clone()))?
-                };
-                // deserialize the commands
-                let commands = CreatureCreator::load_from_str(&source).map_err(|a| CreatureError::include_error(creature_name,a))?;
+                let commands = CreatureCreator::load_stored(creature_name)?;
                 commands.apply_commands(&PathBuf::default(),creature,hooks).map_err(|a| CreatureError::include_error(creature_name,a))?
 
 /*
@@ -1495,7 +1981,24 @@ impl CreatureCommand {
 */
             },
             CreatureCommand::Source(name) => creature.set_source(name),
+            CreatureCommand::Companion(name) => creature.add_companion(name),
+            CreatureCommand::Environments(environments) => creature.set_environments(environments),
+            CreatureCommand::Habitat(habitat) => creature.set_habitat(habitat),
+            CreatureCommand::Treasure(treasure) => creature.set_treasure(treasure),
+            CreatureCommand::Extra(key,value) => creature.set_extra(key,value),
+            CreatureCommand::Note(note) => creature.add_note(note),
+            CreatureCommand::DefineProperty(name,expression) => {
+                // catch syntax errors now, since errors while evaluating just make the property unavailable.
+                check_expression_str(expression, name).map_err(|a| CreatureError::InvalidPropertyExpression(name.clone(),a.to_string()))?;
+                creature.define_property(name,expression)
+            },
+            CreatureCommand::CanonicalizeLayout => hooks.canonicalize_layout = true,
+            CreatureCommand::StrictMultiattack => hooks.strict_multiattack = true,
+            CreatureCommand::LintOverrides => hooks.lint_overrides = true,
+            CreatureCommand::Scale(scale) => hooks.scale = Some(*scale),
             CreatureCommand::Name(name) => creature.set_name(name),
+            CreatureCommand::DisplayName(name) => creature.set_display_name(name),
+            CreatureCommand::ProperName => creature.set_proper_name(),
             CreatureCommand::SubjectName(name) => creature.set_subject(name),
             CreatureCommand::CapitalizeSubjectName(name) => creature.set_capitalized_subject(name),
             CreatureCommand::PossessiveName(name) => creature.set_possessive(name),
@@ -1564,9 +2067,14 @@ impl CreatureCommand {
             CreatureCommand::ChaoticEvil => creature.set_chaotic_evil(),
             CreatureCommand::Unaligned => creature.set_unaligned(),
             CreatureCommand::Alignment(alignment) => creature.set_custom_alignment(alignment),
+            CreatureCommand::AlignmentChance(chances) => creature.set_alignment_chance(chances)?,
             CreatureCommand::HitDie(die) => creature.set_hit_die(die),
             CreatureCommand::HitDiceCount(count) => creature.set_hit_dice_count(count),
             CreatureCommand::HitPoints(points) => creature.set_hit_points_override(points),
+            CreatureCommand::Minion => creature.set_minion(),
+            CreatureCommand::AverageRounding(rounding) => creature.set_average_rounding(rounding),
+            CreatureCommand::Initiative(proficiency) => creature.set_initiative(proficiency),
+            CreatureCommand::OverrideInitiative(bonus) => creature.set_initiative_override(bonus),
             CreatureCommand::Armor(armor) => creature.set_armor(armor),
             CreatureCommand::Shield => creature.enable_shield(),
             CreatureCommand::NoShield => creature.disable_shield(),
@@ -1589,11 +2097,15 @@ impl CreatureCommand {
             CreatureCommand::Expertise(skills) => creature.add_expertise(skills),
             CreatureCommand::RemoveSaves(abilities) => creature.remove_saves(abilities),
             CreatureCommand::RemoveSkills(skills) => creature.remove_skills(skills),
+            CreatureCommand::ClassProficiencies(class) => creature.add_class_proficiencies(class),
             CreatureCommand::ConditionImmunity(condition) => creature.add_condition_immunity(condition),
+            CreatureCommand::CustomConditionImmunity(condition) => creature.add_custom_condition_immunity(condition),
+            CreatureCommand::RemoveCustomConditionImmunity(condition) => hooks.warn_if_not_found(creature.remove_custom_condition_immunity(condition),"RemoveCustomConditionImmunity",condition),
             CreatureCommand::Vulnerability(damage) => creature.add_vulnerability(damage),
             CreatureCommand::AllVulnerability => creature.add_all_vulnerability(),
             CreatureCommand::RemoveVulnerability(damage) => creature.remove_vulnerability(damage),
             CreatureCommand::CustomVulnerability(name) => creature.add_custom_vulnerability(name),
+            CreatureCommand::VulnerabilityType(name) => creature.add_vulnerability_type(name),
             CreatureCommand::Resistance(damage) => creature.add_resistance(damage),
             CreatureCommand::AllResistance => creature.add_all_resistance(),
             CreatureCommand::NonmagicalResistance => creature.add_nonmagical_resistance(),
@@ -1602,6 +2114,10 @@ impl CreatureCommand {
             CreatureCommand::RemoveResistance(damage) => creature.remove_resistance(damage),
             CreatureCommand::RemoveSpecialResistance => creature.remove_special_resistance(),
             CreatureCommand::CustomResistance(custom) => creature.add_custom_resistance(custom),
+            CreatureCommand::ResistanceType(name) => creature.add_resistance_type(name),
+            CreatureCommand::ResistanceNote(note) => if !creature.add_resistance_note(note) {
+                hooks.warnings.push("ResistanceNote: there is no resistance to add the note to.".to_owned())
+            },
             CreatureCommand::Immunity(damage) => creature.add_immunity(damage),
             CreatureCommand::AllImmunity => creature.add_all_immunities(),
             CreatureCommand::NonmagicalImmunity => creature.add_nonmagical_immunity(),
@@ -1610,29 +2126,75 @@ impl CreatureCommand {
             CreatureCommand::RemoveImmunity(damage) => creature.remove_immunity(damage),
             CreatureCommand::RemoveSpecialImmunity => creature.remove_special_immunity(),
             CreatureCommand::CustomImmunity(custom) => creature.add_custom_immunity(custom),
+            CreatureCommand::ImmunityType(name) => creature.add_immunity_type(name),
+            CreatureCommand::ImmunityNote(note) => if !creature.add_immunity_note(note) {
+                hooks.warnings.push("ImmunityNote: there is no immunity to add the note to.".to_owned())
+            },
             CreatureCommand::Languages(languages) => creature.set_languages(languages),
             CreatureCommand::UnspokenLanguages(languages) => creature.add_unspoken_languages(languages),
+            CreatureCommand::LanguagesKnewInLife => creature.add_languages_knew_in_life(),
+            CreatureCommand::Telepathy(distance,note) => creature.set_telepathy(*distance,note),
             CreatureCommand::Darkvision(distance) => creature.add_darkvision(distance),
             CreatureCommand::Blindsight(distance) => creature.add_blindsight(distance),
             CreatureCommand::BlindsightBlindBeyond(distance) => creature.add_blindsight_blind_beyond(distance),
             CreatureCommand::Truesight(distance) => creature.add_truesight(distance),
             CreatureCommand::Tremorsense(distance) => creature.add_tremorsense(distance),
             CreatureCommand::CustomSense(sense,distance) => creature.add_custom_sense(sense, distance),
+            CreatureCommand::SensesSeparator(separator) => creature.set_senses_separator(separator),
+            CreatureCommand::PassivePerceptionLabel(label) => creature.set_passive_perception_label(label),
+            CreatureCommand::PassivePerceptionBonus(bonus) => creature.set_passive_perception_bonus(bonus),
+            CreatureCommand::OverridePassivePerception(passive_perception) => creature.override_passive_perception(passive_perception),
             CreatureCommand::ExpectNoChallenge => hooks.expected_challenge_rating = Some(ChallengeRating::None),
             CreatureCommand::ExpectChallenge(cr) => hooks.expected_challenge_rating = Some(ChallengeRating::Whole(*cr)),
             CreatureCommand::ExpectHalfChallenge => hooks.expected_challenge_rating = Some(ChallengeRating::Half),
             CreatureCommand::ExpectQuarterChallenge => hooks.expected_challenge_rating = Some(ChallengeRating::Quarter),
             CreatureCommand::ExpectEighthChallenge => hooks.expected_challenge_rating = Some(ChallengeRating::Eighth),
-            CreatureCommand::OverrideNoChallenge => creature.set_no_challenge_rating(),
-            CreatureCommand::OverrideChallenge(cr) => creature.set_challenge_rating(cr),
-            CreatureCommand::OverrideHalfChallenge => creature.set_half_challenge_rating(),
-            CreatureCommand::OverrideQuarterChallenge => creature.set_quarter_challenge_rating(),
-            CreatureCommand::OverrideEighthChallenge => creature.set_eighth_challenge_rating(),
+            CreatureCommand::OverrideNoChallenge => {
+                hooks.challenge_rating_overridden = true;
+                creature.set_no_challenge_rating()
+            },
+            CreatureCommand::OverrideChallenge(cr) => {
+                hooks.challenge_rating_overridden = true;
+                creature.set_challenge_rating(cr)
+            },
+            CreatureCommand::OverrideHalfChallenge => {
+                hooks.challenge_rating_overridden = true;
+                creature.set_half_challenge_rating()
+            },
+            CreatureCommand::OverrideQuarterChallenge => {
+                hooks.challenge_rating_overridden = true;
+                creature.set_quarter_challenge_rating()
+            },
+            CreatureCommand::OverrideEighthChallenge => {
+                hooks.challenge_rating_overridden = true;
+                creature.set_eighth_challenge_rating()
+            },
+            CreatureCommand::ProficiencyBonus(proficiency_bonus) => creature.set_proficiency_bonus(*proficiency_bonus),
+            CreatureCommand::ShowProficiencyBonus => creature.set_show_proficiency_bonus(),
             CreatureCommand::Multiattack(description,details) => {
                 hooks.multiattacks.push(details.clone());
                 creature.set_multiattack(description.to_owned(), details);
             }
+            CreatureCommand::AutoMultiattack(details) => {
+                hooks.multiattacks.push(details.clone());
+                creature.set_multiattack(details.get_description(), details);
+            }
+            CreatureCommand::OneHanded => creature.set_one_handed(),
             CreatureCommand::Weapon(weapon,compound) => creature.add_weapon(weapon, compound),
+            CreatureCommand::CustomWeapon(name,reach,dice,damage,bonus,compound) => creature.add_custom_weapon(name, &CustomWeapon {
+                reach: reach.clone(),
+                dice: dice.clone(),
+                damage: damage.clone(),
+                bonus: bonus.clone()
+            }, compound),
+            CreatureCommand::DefineWeapon(name,weapon,compound) => {
+                hooks.weapons.insert(name.clone(),(weapon.clone(),compound.clone()));
+            },
+            CreatureCommand::AddWeapon(name) => if let Some((weapon,compound)) = hooks.weapons.get(name) {
+                creature.add_named_weapon(name, weapon, compound)
+            } else {
+                Err(CreatureError::WeaponDefinitionNotFound(name.clone()))?
+            },
             CreatureCommand::ExpectWeaponAttack(weapon,attack) => creature.expect_weapon_attack(weapon, attack)?,
             CreatureCommand::ExpectWeaponEffect(weapon,effect) => creature.expect_weapon_effect(weapon, effect)?,
             CreatureCommand::OverrideWeaponAttack(weapon,attack) => creature.override_weapon_attack(weapon, attack.clone())?,
@@ -1640,19 +2202,34 @@ impl CreatureCommand {
             CreatureCommand::OverrideWeaponDescription(weapon,description) => creature.override_weapon_description(weapon, description.to_owned())?,
             CreatureCommand::Action(action,usage_limit) => creature.add_action(action, usage_limit),
             CreatureCommand::OverrideActionDescription(name,description) => creature.override_action_description(name, description.to_owned())?,
-            CreatureCommand::RemoveWeapon(weapon) => creature.remove_weapon(weapon),
+            CreatureCommand::RemoveWeapon(weapon) => hooks.warn_if_not_found(creature.remove_weapon(weapon),"RemoveWeapon",&weapon.to_string()),
             CreatureCommand::MoveWeapon(weapon,delta) => creature.move_weapon(weapon,delta)?,
-            CreatureCommand::RemoveAction(name) => creature.remove_action(name),
+            CreatureCommand::RemoveAction(name) => hooks.warn_if_not_found(creature.remove_action(name),"RemoveAction",name),
+            CreatureCommand::RemoveActionsMatching(prefix) => {
+                hooks.warn_if_not_found(creature.remove_actions_matching(prefix),"RemoveActionsMatching",prefix);
+                hooks.forget_removed_multiattack(creature)
+            },
+            CreatureCommand::RemoveAllActions => {
+                hooks.warn_if_none_found(creature.remove_all_actions(),"RemoveAllActions");
+                hooks.forget_removed_multiattack(creature)
+            },
             CreatureCommand::MoveAction(name,delta) => creature.move_action(name,delta)?,
+            CreatureCommand::OrderAction(name,order) => {
+                hooks.action_order.insert(name.clone(),*order);
+            },
             CreatureCommand::Reaction(reaction,usage_limit) => creature.add_reaction(reaction.clone(), usage_limit.clone()),
-            CreatureCommand::RemoveReaction(name) => creature.remove_reaction(name),
+            CreatureCommand::RemoveReaction(name) => hooks.warn_if_not_found(creature.remove_reaction(name),"RemoveReaction",name),
+            CreatureCommand::RemoveAllReactions => hooks.warn_if_none_found(creature.remove_all_reactions(),"RemoveAllReactions"),
             CreatureCommand::Feature(feature,usage_limit) => {
                 hooks.features.push(feature.clone());
                 creature.add_feature(feature.clone(), usage_limit.clone());
             },
-            CreatureCommand::RemoveFeature(name) => creature.remove_feature(name),
-            CreatureCommand::Spellcasting(spellcasting_commands) => {
-                let spellcasting = creature.get_or_add_spellcasting_mut();
+            CreatureCommand::RemoveFeature(name) => hooks.warn_if_not_found(creature.remove_feature(name),"RemoveFeature",name),
+            CreatureCommand::OrderFeature(name,order) => {
+                hooks.feature_order.insert(name.clone(),*order);
+            },
+            CreatureCommand::Spellcasting(spellcasting_commands,name) => {
+                let spellcasting = creature.get_or_add_spellcasting_mut(name.as_deref());
                 
                 for command in spellcasting_commands {
                     command.execute(spellcasting)?;
@@ -1666,20 +2243,16 @@ impl CreatureCommand {
                 }
             },
             CreatureCommand::LegendaryActions(total,actions) => {
-                let description = LegendaryAction::get_main_description(total);
                 let actions = actions.iter().map(|a| {
                     Ok(CreatureLegendaryAction::new(a,creature)?)
                 }).collect::<Result<Vec<CreatureLegendaryAction>,CreatureError>>()?;
-                creature.set_legendary_actions(description,actions);
+                creature.set_legendary_actions(*total,actions);
                 
             },
-            CreatureCommand::RemoveLegendaryAction(name) => creature.remove_legendary_action(name),
-            CreatureCommand::LairActions(..) => {
-                Err(CreatureError::LairActionsNotSupportedYet)?
-            },
-            CreatureCommand::RegionalEffects(..) => {
-                Err(CreatureError::RegionalEffectsNotSupportedYet)?
-            }
+            CreatureCommand::RemoveLegendaryAction(name) => hooks.warn_if_not_found(creature.remove_legendary_action(name),"RemoveLegendaryAction",name),
+            CreatureCommand::LegendaryActionsStyle(style) => creature.set_legendary_actions_style(style),
+            CreatureCommand::LairActions(foreword,actions,afterword) => creature.set_lair_actions(foreword,actions,afterword),
+            CreatureCommand::RegionalEffects(foreword,effects,afterword) => creature.set_regional_effects(foreword,effects,afterword),
 
         
         
@@ -1696,6 +2269,77 @@ impl CreatureCommand {
 }
 
 
+/**
+Builds a creature one command at a time, for programs which use monstorr as a library and would rather construct creatures directly than write a creature file and load it with [`CreatureCreator`]. Each command is applied as soon as it is given, so an error is reported by the command which caused it. As at the end of a creature file, some checks and changes wait until the creature is built, such as checking the attacks referred to by a multiattack, applying features which change actions, and checking the expected challenge rating.
+
+```
+use monstorr_lib::CreatureBuilder;
+use monstorr_lib::CreatureCommand;
+use monstorr_lib::attacks::Weapon;
+
+let mut builder = CreatureBuilder::new();
+builder.apply(CreatureCommand::Name("Goblin".to_owned()))?
+       .apply(CreatureCommand::Dex(14))?
+       .apply(CreatureCommand::Weapon(Weapon::Scimitar(0),None))?;
+let creature = builder.build()?;
+# Ok::<(),monstorr_lib::errors::CreatureError>(())
+```
+
+`Include` commands find their files relative to the working directory, which is the current directory unless another is given with `with_working_dir`.
+*/
+pub struct CreatureBuilder {
+    working_dir: PathBuf,
+    creature: Creature,
+    hooks: CreatureCreationHooks
+}
+
+impl CreatureBuilder {
+
+    pub fn new() -> Self {
+        Self {
+            working_dir: PathBuf::from("."),
+            creature: Creature::default(),
+            hooks: CreatureCreationHooks::default()
+        }
+    }
+
+    /// Sets the directory which files in `Include` commands are found relative to.
+    pub fn with_working_dir(mut self, working_dir: &Path) -> Self {
+        self.working_dir = working_dir.to_path_buf();
+        self
+    }
+
+    /// Makes the parameters available as variables during interpolation of descriptions, as with [`CreatureCreator::create_creature_with_parameters`].
+    pub fn with_parameters(mut self, parameters: &HashMap<String,String>) -> Self {
+        self.creature.parameters = parameters.clone();
+        self
+    }
+
+    /// Applies the command to the creature.
+    pub fn apply(&mut self, command: CreatureCommand) -> Result<&mut Self,CreatureError> {
+        command.execute(&self.working_dir,&mut self.creature,&mut self.hooks)?;
+        Ok(self)
+    }
+
+    /// Completes the creature.
+    pub fn build(self) -> Result<Creature,CreatureError> {
+        self.build_with_warnings().map(|(creature,_)| creature)
+    }
+
+    /// Completes the creature, also returning warnings, see [`CreatureCreator::create_creature_with_warnings`].
+    pub fn build_with_warnings(self) -> Result<(Creature,Vec<String>),CreatureError> {
+        CreatureCreator::complete_creature(self.creature,self.hooks).map(CreatureCreator::join_warnings)
+    }
+
+}
+
+impl Default for CreatureBuilder {
+
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(PartialEq,Debug)]
 #[derive(Serialize,Deserialize)]
 /**
@@ -1714,21 +2358,84 @@ pub struct CreatureCreator(pub Vec<CreatureCommand>);
 impl CreatureCreator {
 
     pub fn create_creature(&self, working_dir: &PathBuf) -> Result<Creature,CreatureError> {
+        self.create_creature_with_warnings(working_dir).map(|(creature,_)| creature)
+    }
+
+    /**
+    Creates the creature, also returning warnings about commands which had no effect, such as a `RemoveAction` for an action that could not be found, followed by warnings about the finished creature, such as a suspicious multiattack or two actions with the same name. These usually indicate a typo in the creature file, but are not errors.
+    */
+    pub fn create_creature_with_warnings(&self, working_dir: &PathBuf) -> Result<(Creature,Vec<String>),CreatureError> {
+        self.create_creature_with_parameters(working_dir, &HashMap::new())
+    }
+
+    /**
+    Creates the creature, making the specified parameters available as variables during interpolation of descriptions. Also returns warnings as for `create_creature_with_warnings`.
+    */
+    pub fn create_creature_with_parameters(&self, working_dir: &PathBuf, parameters: &HashMap<String,String>) -> Result<(Creature,Vec<String>),CreatureError> {
+        self.create_creature_with_separate_warnings(working_dir, parameters).map(Self::join_warnings)
+    }
+
+    /**
+    Creates the creature as for `create_creature_with_parameters`, but returns the warnings about commands which had no effect separately from the warnings about the finished creature, since only the latter are reported by default.
+    */
+    pub(crate) fn create_creature_with_separate_warnings(&self, working_dir: &PathBuf, parameters: &HashMap<String,String>) -> Result<(Creature,Vec<String>,Vec<String>),CreatureError> {
         let mut result = Creature::default();
+        result.parameters = parameters.clone();
         let mut hooks = CreatureCreationHooks::default();
         self.apply_commands(working_dir,&mut result,&mut hooks)?;
+        Self::complete_creature(result,hooks)
+    }
+
+    fn join_warnings((creature,mut warnings,mistakes): (Creature,Vec<String>,Vec<String>)) -> (Creature,Vec<String>) {
+        warnings.extend(mistakes);
+        (creature,warnings)
+    }
+
+    // the checks and changes which can only be done once all of the commands have been applied.
+    fn complete_creature(mut result: Creature, mut hooks: CreatureCreationHooks) -> Result<(Creature,Vec<String>,Vec<String>),CreatureError> {
+
+        for multiattack in &hooks.multiattacks {
+            result.check_multiattack(multiattack)?;
+            for warning in result.lint_multiattack(multiattack) {
+                if hooks.strict_multiattack {
+                    Err(CreatureError::SuspiciousMultiattack(warning))?
+                }
+                hooks.mistakes.push(warning)
+            }
+        }
 
-        for multiattack in hooks.multiattacks {
-            result.check_multiattack(&multiattack)?
+        hooks.mistakes.extend(result.find_duplicate_names());
+
+        if hooks.lint_overrides {
+            hooks.warn_if_set_more_than_once()
         }
 
         for feature in hooks.features {
             Self::apply_feature(&mut result,feature)?
         }
 
+        if let Some(scale) = hooks.scale {
+            result.scale(scale)?
+        }
+
+        if !(hooks.action_order.is_empty() && hooks.feature_order.is_empty()) {
+            result.sort_by_order(&hooks.action_order,&hooks.feature_order)
+        }
+
+        if hooks.canonicalize_layout {
+            result.canonicalize_layout()
+        }
+
         if let Some(expected_challenge_rating) = hooks.expected_challenge_rating {
-            if result.challenge_rating != expected_challenge_rating {
-                Err(CreatureError::ChallengeRatingNotAsExpected(expected_challenge_rating.to_string(),result.challenge_rating.to_string()))?
+            if hooks.challenge_rating_overridden {
+                if result.challenge_rating != expected_challenge_rating {
+                    Err(CreatureError::ChallengeRatingNotAsExpected(expected_challenge_rating.to_string(),result.challenge_rating.to_string()))?
+                }
+            } else {
+                let calculated = result.calculate_challenge_rating();
+                if calculated != expected_challenge_rating {
+                    Err(CreatureError::CalculatedChallengeRatingNotAsExpected(expected_challenge_rating.to_string(),calculated.to_string()))?
+                }
             }
 
         }
@@ -1736,7 +2443,7 @@ impl CreatureCreator {
         if result.name == "" {
             Err(CreatureError::CreatureHasNoName)
         } else {
-            Ok(result)
+            Ok((result,hooks.warnings,hooks.mistakes))
         }
 
 
@@ -1844,11 +2551,105 @@ impl CreatureCreator {
 
     }
 
+    // loads the commands from an included file, returning them along with the directory the file's own includes are relative to.
+    fn load_include(working_dir: &PathBuf, file: &str, parameters: &HashMap<String,String>) -> Result<(Self,PathBuf),CreatureError> {
+        // make it absolute
+        let mut source_file = working_dir.clone();
+        source_file.push(file);
+        // get the data from the file
+        let source = fs::read_to_string(&source_file).map_err(|a| CreatureError::include_error(file,a))?;
+        // interpolate the text
+        let interpolated = interpolate_str_for_deserialization(&source, file, parameters,false).map_err(|a| CreatureError::include_error(file,a))?;
+        // deserialize the commands
+        let commands = CreatureCreator::load_from_str(&interpolated).map_err(|a| CreatureError::include_error(file,a))?;
+        source_file.pop();
+        Ok((commands,source_file))
+    }
+
+    fn load_stored(creature_name: &str) -> Result<Self,CreatureError> {
+        let source = if let Some(entry) = monstorr_data::creatures::STORED_CREATURES.iter().find(|(creature,_)| (creature.slug == creature_name) || (creature.name == creature_name)) {
+            entry.1
+        } else {
+            Err(CreatureError::StoredCreatureNotFound(creature_name.to_owned()))?
+        };
+        // deserialize the commands
+        CreatureCreator::load_from_str(&source).map_err(|a| CreatureError::include_error(creature_name,a))
+    }
+
+    /**
+    Returns the files loaded by `Include` commands, including those included by the included files. This is meant for watching the files a creature is built from, so an included file which can't be loaded, such as while it is being edited, is still listed, although the files it includes can't be found.
+    */
+    pub fn find_included_files(&self, working_dir: &PathBuf) -> Vec<PathBuf> {
+        let mut result = Vec::new();
+        self.find_included_files_into(working_dir, &mut result);
+        result
+    }
+
+    fn find_included_files_into(&self, working_dir: &PathBuf, result: &mut Vec<PathBuf>) {
+        for command in &self.0 {
+            if let CreatureCommand::Include(file,parameters) = command {
+                let mut source_file = working_dir.clone();
+                source_file.push(file);
+                // a file that was already found has already been searched, this also keeps a file that includes itself from looping forever.
+                if !result.contains(&source_file) {
+                    result.push(source_file);
+                    if let Ok((commands,source_dir)) = CreatureCreator::load_include(working_dir,file,parameters) {
+                        commands.find_included_files_into(&source_dir, result)
+                    }
+                }
+            }
+        }
+    }
+
+    /**
+    Replaces the `Include` and `IncludeStored` commands with the commands they load, recursively, leaving a flat list of commands which has the same effect without needing the included files. This is useful for seeing why an included command didn't have the expected effect.
+    */
+    pub fn expand_includes(self, working_dir: &PathBuf) -> Result<Self,CreatureError> {
+        let mut result = Vec::new();
+        for command in self.0 {
+            match command {
+                CreatureCommand::Include(file,parameters) => {
+                    let (commands,source_dir) = CreatureCreator::load_include(working_dir,&file,&parameters)?;
+                    let commands = commands.expand_includes(&source_dir).map_err(|a| CreatureError::include_error(&file,a))?;
+                    result.extend(commands.0)
+                },
+                CreatureCommand::IncludeStored(creature_name) => {
+                    let commands = CreatureCreator::load_stored(&creature_name)?;
+                    let commands = commands.expand_includes(&PathBuf::default()).map_err(|a| CreatureError::include_error(&creature_name,a))?;
+                    result.extend(commands.0)
+                },
+                command => result.push(command)
+            }
+        }
+        Ok(Self(result))
+    }
+
     pub fn save_to_string(&self) -> Result<String,ron::Error> {
         ron::ser::to_string_pretty(&self,ron::ser::PrettyConfig::new())
     }
 
-    pub fn load_from_str(data: &str) -> Result<Self,ron::Error> {
-        ron::de::from_str(data)
+    /**
+    Loads the commands from a string in RON format. If the string can't be loaded, the error gives the line and column where the problem was found, and the command it was found in.
+    */
+    pub fn load_from_str(data: &str) -> Result<Self,CommandSyntaxError> {
+        ron::de::from_str(data).map_err(|error| CommandSyntaxError::from_ron(data,error))
+    }
+
+    #[cfg(feature = "toml-format")]
+    pub fn save_to_toml_string(&self) -> Result<String,toml::ser::Error> {
+        toml::to_string_pretty(&TOMLCreatureDocument { commands: &self.0 })
     }
+
+    #[cfg(feature = "toml-format")]
+    pub fn load_from_toml_str(data: &str) -> Result<Self,toml::de::Error> {
+        let document: TOMLCreatureDocument<Vec<CreatureCommand>> = toml::from_str(data)?;
+        Ok(Self(document.commands))
+    }
+}
+
+#[cfg(feature = "toml-format")]
+#[derive(Serialize,Deserialize)]
+// TOML documents must be a table, so the list of commands can't be the whole document as it is in RON.
+struct TOMLCreatureDocument<Commands> {
+    commands: Commands
 }
@@ -50,6 +50,18 @@ impl Die {
         }
     }
 
+    fn sides(&self) -> u8 {
+        match self {
+            Die::D4 => 4,
+            Die::D6 => 6,
+            Die::D8 => 8,
+            Die::D10 => 10,
+            Die::D12 => 12,
+            Die::D20 => 20,
+            Die::DCustom(sides) => *sides
+        }
+    }
+
     fn to_string(&self) -> String {
         match self {
             Die::D4 => format!("d4"),
@@ -141,8 +153,17 @@ impl Dice {
     }
 
     pub fn average(&self) -> u16 {
-        let result = (self.coefficient as f32) * self.die.average();
-        result.floor() as u16
+        self.exact_average().floor() as u16
+    }
+
+    // the average before rounding, so that expressions with several dice can round only the final result.
+    pub fn exact_average(&self) -> f32 {
+        (self.coefficient as f32) * self.die.average()
+    }
+
+    // the highest possible roll, the lowest is just the coefficient.
+    pub fn maximum(&self) -> u16 {
+        (self.coefficient as u16) * (self.die.sides() as u16)
     }
 
 }
@@ -72,10 +72,21 @@ impl FactoredDice {
     }
 
     fn average(&self) -> f64 {
-        let average = self.dice.average();
+        let average = self.dice.exact_average();
         (average as f64) * (self.factor as f64)
     }
 
+    // the lowest and highest results, a negative factor swaps them.
+    fn range(&self) -> (isize,isize) {
+        let lowest = (self.dice.coefficient as isize) * self.factor;
+        let highest = (self.dice.maximum() as isize) * self.factor;
+        if self.factor < 0 {
+            (highest,lowest)
+        } else {
+            (lowest,highest)
+        }
+    }
+
     fn multiply(&self, factor: &isize) -> Self {
         FactoredDice {
             dice: self.dice.clone(),
@@ -100,6 +111,13 @@ impl FactoredDice {
     fn is_negative(&self) -> bool {
         self.factor < 0
     }
+
+    fn scale(&self, scale: f32) -> Self {
+        FactoredDice {
+            dice: Dice::new((self.dice.coefficient as f32 * scale).floor() as u8,&self.dice.die),
+            factor: self.factor
+        }
+    }
 }
 
 
@@ -111,7 +129,9 @@ pub enum ParseDiceExpressionError {
     ExpectedDiceAfterOpenParen,
     ExpectedCloseParen,
     ExpectedDiceAfterFirstMinus,
+    ExpectedDiceOrParenAfterFirstPlus,
     ExpectedMinusParenOrDiceAsFirstToken,
+    EmptyExpression,
     UnexpectedContentAfterAddend,
     ExpectedNumberOrDiceAfterMinus,
     ExpectedNumberDiceOrParenAfterPlus,
@@ -136,7 +156,9 @@ impl std::fmt::Display for ParseDiceExpressionError {
             ParseDiceExpressionError::ExpectedDiceAfterOpenParen => write!(f,"Expected dice term after open parenthesis in factored dice"),
             ParseDiceExpressionError::ExpectedCloseParen => write!(f,"Expected closing parenthesis in factored dice"),
             ParseDiceExpressionError::ExpectedDiceAfterFirstMinus => write!(f,"Expected dice after initial minus"),
-            ParseDiceExpressionError::ExpectedMinusParenOrDiceAsFirstToken => write!(f,"Expected minus, open parenthesis or dice term as first token"),
+            ParseDiceExpressionError::ExpectedDiceOrParenAfterFirstPlus => write!(f,"Expected dice or open parenthesis after initial plus"),
+            ParseDiceExpressionError::ExpectedMinusParenOrDiceAsFirstToken => write!(f,"Expected plus, minus, open parenthesis or dice term as first token"),
+            ParseDiceExpressionError::EmptyExpression => write!(f,"Expected a dice expression, such as '2d6 + 3', but found nothing"),
             ParseDiceExpressionError::UnexpectedContentAfterAddend => write!(f,"No content is allowed after the final addend"),
             ParseDiceExpressionError::ExpectedNumberOrDiceAfterMinus => write!(f,"Expected number or dice after minus symbol in term"),
             ParseDiceExpressionError::ExpectedNumberDiceOrParenAfterPlus => write!(f,"Expected number, dice or open parenthesis after plus symbol in term"),
@@ -148,6 +170,49 @@ impl std::fmt::Display for ParseDiceExpressionError {
 
 
 
+#[derive(PartialEq,Clone,Copy,Debug)]
+#[derive(Serialize,Deserialize)]
+/**
+Specifies how the average of a dice expression is rounded when it isn't a whole number, such as the 4.5 average of a d8. The core books always round down, which is the default.
+*/
+pub enum AverageRounding {
+    /// `Down`: Rounds down, so 4.5 becomes 4. This is what the core books do.
+    Down,
+    /// `HalfUp`: Rounds to the nearest whole number, with halves rounded up, so 4.5 becomes 5.
+    HalfUp,
+    /// `HalfEven`: Rounds to the nearest whole number, with halves rounded to the even number, so 4.5 becomes 4 and 5.5 becomes 6.
+    HalfEven
+}
+
+impl AverageRounding {
+
+    fn round(&self, value: f64) -> isize {
+        match self {
+            AverageRounding::Down => value.floor() as isize,
+            AverageRounding::HalfUp => (value + 0.5).floor() as isize,
+            AverageRounding::HalfEven => {
+                let floor = value.floor();
+                if value - floor == 0.5 {
+                    if (floor as isize) % 2 == 0 {
+                        floor as isize
+                    } else {
+                        floor as isize + 1
+                    }
+                } else {
+                    value.round() as isize
+                }
+            }
+        }
+    }
+}
+
+impl Default for AverageRounding {
+
+    fn default() -> Self {
+        Self::Down
+    }
+}
+
 #[derive(PartialEq,Clone,Debug)]
 #[derive(Deserialize)]
 #[serde(try_from = "String")]
@@ -185,16 +250,36 @@ impl DiceExpression {
     }
 
     pub fn average(&self) -> isize {
+        self.rounded_average(&AverageRounding::default())
+    }
+
+    pub fn rounded_average(&self, rounding: &AverageRounding) -> isize {
+        // rounding is only done at the end, 1d8 + 1d4 is 7, not 4 + 2.
+        rounding.round(self.exact_average())
+    }
+
+    fn exact_average(&self) -> f64 {
         let mut average = self.head.average();
         for factored in &self.medial {
             average += factored.average();
         }
-        average += self.addend as f64;
-        average.floor() as isize
+        average + self.addend as f64
+    }
+
+    pub fn minimum(&self) -> isize {
+        self.head.range().0 + self.medial.iter().map(|factored| factored.range().0).sum::<isize>() + self.addend
+    }
+
+    pub fn maximum(&self) -> isize {
+        self.head.range().1 + self.medial.iter().map(|factored| factored.range().1).sum::<isize>() + self.addend
     }
 
-    pub fn display_with_alternate_average(&self, average: Option<isize>) -> String {
-        format!("{} ({})",average.unwrap_or(self.average()),&self.serialize_to_string())
+    pub fn display_with_alternate_average(&self, average: Option<isize>, rounding: &AverageRounding) -> String {
+        format!("{} ({})",average.unwrap_or(self.rounded_average(rounding)),&self.serialize_to_string())
+    }
+
+    pub fn display_with_rounding(&self, rounding: &AverageRounding) -> String {
+        self.display_with_alternate_average(None, rounding)
     }
 
     pub fn multiply(&self, factor: &isize) -> Self {
@@ -305,6 +390,25 @@ impl DiceExpression {
         self.head.is_negative()
     }
 
+    /**
+    Returns an expression whose average is close to this one's multiplied by the scale. The number of each die is multiplied and rounded down, keeping at least one of the first die, and the addend makes up the difference, so 1d6 scaled by 1.5 becomes 1d6 + 2, and 3d6 becomes 4d6 + 2. The scale should be greater than zero, which [`crate::creature::Creature::scale`] checks before scaling any dice.
+    */
+    pub fn scale(&self, scale: f32) -> Self {
+        let target = self.exact_average() * scale as f64;
+        let mut head = self.head.scale(scale);
+        if head.dice.coefficient == 0 {
+            head.dice.coefficient = 1;
+        }
+        let medial = self.medial.iter().map(|a| a.scale(scale)).filter(|a| a.dice.coefficient > 0).collect();
+        let mut result = Self {
+            head,
+            medial,
+            addend: 0
+        };
+        result.addend = (target - result.exact_average()).round() as isize;
+        result
+    }
+
 
 }
 
@@ -402,13 +506,14 @@ impl<Source: Iterator<Item=char>> DiceExpressionTokenizer<Source> {
 
     fn parse(&mut self) -> Result<DiceExpression,ParseDiceExpressionError> {
         // dice_expression = head predicate?
-        // head = ('-' dice) | dice | factored_dice
+        // head = ('-' dice) | ('+'? (dice | factored_dice))
         // predicate = '-' number | (dice predicate?)
         //             '+' number | (dice predicate?) | (factored_dice predicate?)
         // factored_dice = '(' dice '×' '-'? number ')'
 
 
-        // head = ('-' dice) | dice | factored_dice
+        // head = ('-' dice) | ('+'? (dice | factored_dice))
+        // whitespace is skipped by the tokenizer, so the operators don't need to be spaced.
         let head = match self.next_ok()? {
             Some(Token::Minus) => match self.next_ok()? {
                 Some(Token::Dice(dice)) => FactoredDice {
@@ -417,11 +522,20 @@ impl<Source: Iterator<Item=char>> DiceExpressionTokenizer<Source> {
                 }, 
                 _ => Err(ParseDiceExpressionError::ExpectedDiceAfterFirstMinus)?, 
             },
+            Some(Token::Plus) => match self.next_ok()? {
+                Some(Token::Dice(dice)) => FactoredDice {
+                    dice,
+                    factor: 1
+                }, 
+                Some(Token::OpenParenthesis) => self.parse_factored_dice()?, 
+                _ => Err(ParseDiceExpressionError::ExpectedDiceOrParenAfterFirstPlus)?, 
+            },
             Some(Token::Dice(dice)) => FactoredDice {
                 dice,
                 factor: 1
             }, 
             Some(Token::OpenParenthesis) => self.parse_factored_dice()?, 
+            None => Err(ParseDiceExpressionError::EmptyExpression)?,
             _ => Err(ParseDiceExpressionError::ExpectedMinusParenOrDiceAsFirstToken)? 
         };
 
@@ -0,0 +1,206 @@
+/*
+ * Copyright © 2022 Neil M. Sheldon
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+/*!
+Exports a stat block as JSON in the shape of the monster data used by D&D Beyond, for tools which import homebrew monsters into it. D&D Beyond doesn't publish an import format of its own, so the field names follow those seen in the monster data on its site, such as `stats` with a `statId` from 1 for strength to 6 for charisma, `armorClass`, `averageHitPoints`, `hitPointDice` and a numeric `challengeRating` id. Since that data isn't documented, check the output against the importer being used.
+
+The basics are filled in: the ability scores, armor class and its description, hit points and hit dice, speeds, senses, languages and challenge rating. The features, actions, reactions and legendary actions are written as HTML descriptions, the same as in D&D Beyond, and the actions are also listed one by one with their activation. The roll to hit, reach or range, save DC and damage dice of an attack are read from its description, such as "+4 to hit, reach 5 ft." and "5 (1d6 + 2) slashing damage", so they are found for creatures from any input format.
+
+Spells, saving throws, skills and damage traits are only written as the text of the stat block.
+*/
+
+use serde_json::json;
+use serde_json::Value;
+
+use crate::foundry::leading_number;
+use crate::foundry::split_feature;
+use crate::stat_block::CreatureStatBlock;
+use crate::stat_block::StatBlockFeature;
+use crate::stats::ChallengeRating;
+use crate::structured_text::TextBlock;
+use crate::structured_text::spans_to_text;
+
+// the description of a feature as plain text, without its name, for reading the numbers out of it.
+fn feature_text(feature: &StatBlockFeature) -> String {
+    feature.text.iter().map(|block| match block {
+        TextBlock::Paragraph { body, .. } |
+        TextBlock::SubParagraph { body, .. } => spans_to_text(body)
+    }).collect::<Vec<String>>().join(" ")
+}
+
+// the number following some text in a description, such as the 5 after "reach " in "reach 5 ft.".
+fn number_after(text: &str, prefix: &str) -> Option<u16> {
+    text.split_once(prefix).and_then(|(_,rest)| leading_number(rest))
+}
+
+// the attack bonus in "+4 to hit", with its sign.
+fn to_hit(text: &str) -> Option<i16> {
+    let (before,_) = text.split_once(" to hit")?;
+    before.rsplit(' ').next()?.trim_start_matches('+').parse().ok()
+}
+
+// the damage rolls, written as "5 (1d6 + 2) slashing damage".
+fn damage_rolls(text: &str) -> Vec<Value> {
+    text.split('(').skip(1).filter_map(|part| {
+        let (dice,rest) = part.split_once(')')?;
+        if !(dice.starts_with(|c: char| c.is_ascii_digit()) && dice.contains('d')) {
+            return None
+        }
+        let mut words = rest.split_whitespace();
+        let damage_type = words.next()?;
+        if !words.next()?.starts_with("damage") {
+            return None
+        }
+        Some(json!({ "diceString": dice.trim(), "damageType": damage_type }))
+    }).collect()
+}
+
+fn attack_value(text: &str) -> Option<Value> {
+    let to_hit = to_hit(text);
+    let damage = damage_rolls(text);
+    let save_dc = number_after(text, "DC ");
+    if to_hit.is_none() && damage.is_empty() && save_dc.is_none() {
+        return None
+    }
+    // a ranged attack is written as "range 80/320 ft.", or "range 60 ft." without a long range.
+    let long_range = text.split_once("range ").and_then(|(_,rest)| rest.split_whitespace().next()).and_then(|range| range.split_once('/')).and_then(|(_,long_range)| leading_number(long_range));
+    Some(json!({
+        "toHit": to_hit,
+        "reach": number_after(text, "reach "),
+        "range": number_after(text, "range "),
+        "longRange": long_range,
+        "damage": damage,
+        "saveDc": save_dc
+    }))
+}
+
+// the ids D&D Beyond uses for each kind of movement.
+fn movement_id(kind: &str) -> Option<u8> {
+    match kind {
+        "walk" => Some(1),
+        "burrow" => Some(2),
+        "climb" => Some(3),
+        "fly" => Some(4),
+        "swim" => Some(5),
+        _ => None
+    }
+}
+
+fn movements_value(speed: &str) -> Value {
+    let movements: Vec<Value> = speed.split(',').filter_map(|part| {
+        let part = part.trim();
+        let (kind,distance) = match part.split_once(' ') {
+            Some((kind,distance)) if kind.chars().all(|c| c.is_alphabetic()) => (kind,distance),
+            _ => ("walk",part)
+        };
+        Some(json!({ "movementId": movement_id(kind)?, "speed": leading_number(distance)? }))
+    }).collect();
+    json!(movements)
+}
+
+// the ids D&D Beyond uses for each challenge rating, counting up from 1 for CR 0, so CR 1 is 5 and CR 30 is 34.
+pub(crate) fn challenge_rating_id(challenge_rating: &str) -> Option<u8> {
+    match challenge_rating.split_whitespace().next()?.parse::<ChallengeRating>().ok()? {
+        ChallengeRating::None |
+        ChallengeRating::Whole(0) => Some(1),
+        ChallengeRating::Eighth => Some(2),
+        ChallengeRating::Quarter => Some(3),
+        ChallengeRating::Half => Some(4),
+        ChallengeRating::Whole(rating) => Some(rating + 4)
+    }
+}
+
+// the hit dice from the hit points, such as "27 (5d8 + 5)".
+fn hit_point_dice_value(hit_points: &str) -> Value {
+    let dice = hit_points.split_once('(').map(|(_,dice)| dice.trim_end_matches(')').trim()).unwrap_or_default();
+    let (count,rest) = dice.split_once('d').unwrap_or(("0",""));
+    let value = leading_number(rest).unwrap_or(0);
+    // the sign is kept with the number, such as "+5" or "-1"
+    let fixed = match rest.find(|c: char| c == '+' || c == '-') {
+        Some(index) => rest[index..].replace(' ',"").parse::<i32>().unwrap_or(0),
+        None => 0
+    };
+    json!({
+        "diceCount": count.trim().parse::<u16>().unwrap_or(0),
+        "diceValue": value,
+        "fixedValue": fixed,
+        "diceString": dice
+    })
+}
+
+// the armor description, such as "leather armor, shield" in "15 (leather armor, shield)".
+fn armor_description(armor: &str) -> String {
+    armor.split_once('(').map(|(_,description)| description.trim_end_matches(')').to_owned()).unwrap_or_default()
+}
+
+fn features_description(features: &[StatBlockFeature]) -> String {
+    features.iter().map(|feature| {
+        let (name,description) = split_feature(feature);
+        format!("<p><em><strong>{}.</strong></em></p>{}",name,description)
+    }).collect()
+}
+
+fn action_value(feature: &StatBlockFeature, activation: &str) -> Value {
+    let (name,description) = split_feature(feature);
+    json!({
+        "name": name,
+        "description": description,
+        "activation": { "activationType": activation },
+        "attack": attack_value(&feature_text(feature))
+    })
+}
+
+/**
+Writes the stat block as D&D Beyond monster data.
+*/
+pub fn write_dndbeyond_monster(stat_block: &CreatureStatBlock, ugly: bool) -> Result<String,String> {
+    let stat = |id: u8, score: &str| json!({ "statId": id, "value": leading_number(score).unwrap_or(10) });
+
+    let mut actions: Vec<Value> = stat_block.actions.iter().map(|feature| action_value(feature,"action")).collect();
+    actions.extend(stat_block.reactions.iter().map(|feature| action_value(feature,"reaction")));
+    if let Some(legendary) = &stat_block.legendary_actions {
+        actions.extend(legendary.actions.iter().map(|feature| action_value(feature,"legendary")));
+    }
+
+    let monster = json!({
+        "name": stat_block.display_name.as_ref().unwrap_or(&stat_block.name),
+        "size": stat_block.size,
+        "type": stat_block.type_,
+        "subtype": stat_block.subtype,
+        "alignment": stat_block.alignment,
+        "stats": [
+            stat(1,&stat_block.strength),
+            stat(2,&stat_block.dexterity),
+            stat(3,&stat_block.constitution),
+            stat(4,&stat_block.intelligence),
+            stat(5,&stat_block.wisdom),
+            stat(6,&stat_block.charisma)
+        ],
+        "armorClass": leading_number(&stat_block.armor),
+        "armorClassDescription": armor_description(&stat_block.armor),
+        "averageHitPoints": leading_number(&stat_block.hit_points).unwrap_or(0),
+        "hitPointDice": hit_point_dice_value(&stat_block.hit_points),
+        "movements": movements_value(&stat_block.speed),
+        "savingThrowsHtml": stat_block.saving_throws.clone().unwrap_or_default(),
+        "skillsHtml": stat_block.skills.clone().unwrap_or_default(),
+        "sensesHtml": stat_block.senses,
+        "languageDescription": stat_block.languages.clone().unwrap_or_default(),
+        "challengeRating": challenge_rating_id(&stat_block.challenge_rating),
+        "specialTraitsDescription": features_description(&stat_block.special_abilities),
+        "actionsDescription": features_description(&stat_block.actions),
+        "reactionsDescription": features_description(&stat_block.reactions),
+        "legendaryActionsDescription": stat_block.legendary_actions.as_ref().map(|legendary| features_description(&legendary.actions)).unwrap_or_default(),
+        "actions": actions
+    });
+
+    if ugly {
+        serde_json::to_string(&monster)
+    } else {
+        serde_json::to_string_pretty(&monster)
+    }.map_err(|e| format!("Error writing D&D Beyond monster: {}",e))
+}
@@ -0,0 +1,232 @@
+/*
+ * Copyright © 2022 Neil M. Sheldon
+ * 
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+/*!
+Random encounters are generated from a pool of creatures using the encounter building rules from the Dungeon Master's Guide. The party's XP thresholds determine the budget for the target difficulty, and creatures are picked randomly until the adjusted XP (which includes the multiplier for the number of monsters) reaches that budget, without going over the threshold for the next difficulty.
+
+The generator uses a seeded random number generator, so the same pool, party and seed will always produce the same encounter.
+*/
+
+use monstorr_data::creatures::CreatureSummary;
+
+use crate::stats::ChallengeRating;
+use crate::utils::SeededRandom;
+use crate::utils::DisplayWithThousands;
+
+// The number of attempts made at building an encounter before giving up and taking the closest one.
+const ENCOUNTER_ATTEMPTS: usize = 100;
+// More than this and it's not an encounter, it's a war.
+const MAX_ENCOUNTER_CREATURES: usize = 15;
+
+// XP thresholds per character, by level, from the Dungeon Master's Guide: easy, medium, hard, deadly
+const XP_THRESHOLDS: [[u32; 4]; 20] = [
+    [25, 50, 75, 100],
+    [50, 100, 150, 200],
+    [75, 150, 225, 400],
+    [125, 250, 375, 500],
+    [250, 500, 750, 1100],
+    [300, 600, 900, 1400],
+    [350, 750, 1100, 1700],
+    [450, 900, 1400, 2100],
+    [550, 1100, 1600, 2400],
+    [600, 1200, 1900, 2800],
+    [800, 1600, 2400, 3600],
+    [1000, 2000, 3000, 4500],
+    [1100, 2200, 3400, 5100],
+    [1250, 2500, 3800, 5700],
+    [1400, 2800, 4300, 6400],
+    [1600, 3200, 4800, 7200],
+    [2000, 3900, 5900, 8800],
+    [2100, 4200, 6300, 9500],
+    [2400, 4900, 7300, 10900],
+    [2800, 5700, 8500, 12700]
+];
+
+#[derive(PartialEq,PartialOrd,Clone,Copy,Debug)]
+/**
+The difficulty of an encounter, as defined in the Dungeon Master's Guide.
+*/
+pub enum EncounterDifficulty {
+    Easy,
+    Medium,
+    Hard,
+    Deadly
+}
+
+impl EncounterDifficulty {
+
+    fn index(&self) -> usize {
+        match self {
+            Self::Easy => 0,
+            Self::Medium => 1,
+            Self::Hard => 2,
+            Self::Deadly => 3
+        }
+    }
+
+    fn from_index(index: usize) -> Self {
+        match index {
+            0 => Self::Easy,
+            1 => Self::Medium,
+            2 => Self::Hard,
+            _ => Self::Deadly
+        }
+    }
+
+}
+
+impl std::fmt::Display for EncounterDifficulty {
+
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(),std::fmt::Error> {
+        match self {
+            Self::Easy => write!(f,"easy"),
+            Self::Medium => write!(f,"medium"),
+            Self::Hard => write!(f,"hard"),
+            Self::Deadly => write!(f,"deadly")
+        }
+    }
+}
+
+/**
+The result of generating an encounter. Creatures picked more than once are grouped together with a count.
+*/
+pub struct Encounter {
+    pub creatures: Vec<(CreatureSummary<String>,usize)>,
+    pub total_xp: u32,
+    pub adjusted_xp: u32,
+    pub difficulty: Option<EncounterDifficulty>, // None if the encounter is below the 'easy' threshold.
+    pub thresholds: [u32; 4]
+}
+
+fn get_party_thresholds(party_size: u8, party_level: u8) -> Result<[u32; 4],String> {
+    if party_size == 0 {
+        Err("Party size must be at least 1.".to_owned())?
+    }
+    if !(1..=20).contains(&party_level) {
+        Err("Party level must be from 1 to 20.".to_owned())?
+    }
+    let mut result = XP_THRESHOLDS[party_level as usize - 1];
+    for threshold in &mut result {
+        *threshold *= party_size as u32
+    }
+    Ok(result)
+}
+
+fn get_encounter_multiplier(count: usize) -> f32 {
+    match count {
+        0..=1 => 1.0,
+        2 => 1.5,
+        3..=6 => 2.0,
+        7..=10 => 2.5,
+        11..=14 => 3.0,
+        _ => 4.0
+    }
+}
+
+fn get_adjusted_xp(total_xp: u32, count: usize) -> u32 {
+    (total_xp as f32 * get_encounter_multiplier(count)) as u32
+}
+
+fn get_difficulty(adjusted_xp: u32, thresholds: &[u32; 4]) -> Option<EncounterDifficulty> {
+    thresholds.iter().rposition(|threshold| adjusted_xp >= *threshold).map(EncounterDifficulty::from_index)
+}
+
+/**
+Generates a random encounter from the pool of creatures, for the specified party and difficulty. Creatures with no challenge rating, or which would go over the budget on their own, are never chosen.
+*/
+pub fn generate_encounter(pool: Vec<CreatureSummary<String>>, party_size: u8, party_level: u8, target: EncounterDifficulty, seed: u64) -> Result<Encounter,String> {
+
+    let thresholds = get_party_thresholds(party_size, party_level)?;
+    let budget = thresholds[target.index()];
+    // an encounter which goes past the next threshold is no longer the requested difficulty. Deadly encounters
+    // don't have a next threshold, so allow them to go up to twice the deadly threshold.
+    let limit = if target == EncounterDifficulty::Deadly {
+        budget * 2
+    } else {
+        thresholds[target.index() + 1] - 1
+    };
+
+    let mut candidates = Vec::new();
+    for creature in pool {
+        let xp = creature.challenge_rating.parse::<ChallengeRating>().
+                    map_err(|e| format!("Could not parse cr on creature '{}': {}",creature.name,e))?.get_xp();
+        if (xp > 0) && (xp <= limit) {
+            candidates.push((creature,xp))
+        }
+    }
+
+    if candidates.is_empty() {
+        Err(format!("No creatures in the pool can fit in a {} encounter.",target))?
+    }
+
+    let mut random = SeededRandom::new(seed);
+    let mut best: Option<(Vec<usize>,u32)> = None;
+
+    for _ in 0..ENCOUNTER_ATTEMPTS {
+        let mut picks: Vec<usize> = Vec::new();
+        let mut total_xp = 0;
+        let mut adjusted_xp = 0;
+        while (adjusted_xp < budget) && (picks.len() < MAX_ENCOUNTER_CREATURES) {
+            let pick = random.next_below(candidates.len());
+            let new_total_xp = total_xp + candidates[pick].1;
+            let new_adjusted_xp = get_adjusted_xp(new_total_xp, picks.len() + 1);
+            if new_adjusted_xp > limit {
+                break;
+            }
+            picks.push(pick);
+            total_xp = new_total_xp;
+            adjusted_xp = new_adjusted_xp;
+        }
+
+        let is_better = match &best {
+            Some((_,best_xp)) => adjusted_xp > *best_xp,
+            None => true
+        };
+        if is_better {
+            best = Some((picks,adjusted_xp))
+        }
+        if adjusted_xp >= budget {
+            break;
+        }
+
+    }
+
+    let (mut picks,adjusted_xp) = best.unwrap_or_default();
+    picks.sort_unstable();
+    let total_xp = picks.iter().map(|pick| candidates[*pick].1).sum();
+
+    // group the picks into counts, taking them out of the candidate list in reverse order so the indexes stay valid.
+    let mut counts: Vec<(usize,usize)> = Vec::new();
+    for pick in picks {
+        match counts.last_mut() {
+            Some((last,count)) if *last == pick => *count += 1,
+            _ => counts.push((pick,1))
+        }
+    }
+    let mut creatures = Vec::new();
+    for (pick,count) in counts.into_iter().rev() {
+        creatures.push((candidates.remove(pick).0,count))
+    }
+    creatures.reverse();
+
+    Ok(Encounter {
+        creatures,
+        total_xp,
+        adjusted_xp,
+        difficulty: get_difficulty(adjusted_xp, &thresholds),
+        thresholds
+    })
+
+}
+
+impl Encounter {
+
+    pub fn describe_thresholds(&self) -> String {
+        (0..4).map(|i| format!("{} {}",EncounterDifficulty::from_index(i),self.thresholds[i].display_with_thousands())).collect::<Vec<String>>().join(", ")
+    }
+}
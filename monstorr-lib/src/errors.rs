@@ -15,7 +15,10 @@ pub enum TokenError {
     InvalidDice,
     UnterminatedString,
     UnexpectedCharacter,
-    SlashIsNotValid
+    SlashIsNotValid,
+    EqualsIsNotValid,
+    ExclamationIsNotValid,
+    QuestionIsNotValid
 }
 
 impl std::fmt::Display for TokenError {
@@ -27,7 +30,10 @@ impl std::fmt::Display for TokenError {
             TokenError::InvalidDice => write!(f,"Invalid dice"),
             TokenError::UnterminatedString => write!(f,"Unterminated string"),
             TokenError::UnexpectedCharacter => write!(f,"Unexpected character"),
-            TokenError::SlashIsNotValid => write!(f,"Slash is not a valid token, must be followed by '>' or '<'")
+            TokenError::SlashIsNotValid => write!(f,"Slash is not a valid token, must be followed by '>' or '<'"),
+            TokenError::EqualsIsNotValid => write!(f,"Equals is not a valid token, must be followed by another '='"),
+            TokenError::ExclamationIsNotValid => write!(f,"Exclamation point is not a valid token, must be followed by '='"),
+            TokenError::QuestionIsNotValid => write!(f,"Question mark is not a valid token, must be followed by another '?'")
         }
     }
 }
@@ -45,7 +51,10 @@ pub enum InterpolationError {
     ScanError(TokenError),
     ExpectedIdentifier(String), // found
     ExpectedCloseParen(String), // found
+    ExpectedComma(String), // found
     ExpectedExpression(String), // found
+    UnknownFunction(String), // name
+    ExpectedEndOfExpression(String), // found
     CantNegateString,
     CantNegateObject,
     CantMultiplyDice,
@@ -65,6 +74,22 @@ pub enum InterpolationError {
     InvalidIndex,
     CantSignObject,
     CantSignString,
+    CantAverageString,
+    CantAverageObject,
+    CantGetExpressionOfString,
+    CantGetExpressionOfObject,
+    CantNegateBoolean,
+    CantSignBoolean,
+    CantAverageBoolean,
+    CantGetExpressionOfBoolean,
+    CantMultiplyBooleans,
+    CantDivideBooleans,
+    CantAddBooleans,
+    CantSubtractBooleans,
+    CantCompareNonStrings,
+    CantCheckEquality,
+    CantOrderNonNumbers,
+    ConditionIsNotBoolean,
     EmptyStack(String),
     UnexpectedStructuredText,
     TextIsAlreadyItalic,
@@ -81,6 +106,9 @@ impl std::fmt::Display for InterpolationError {
             Self::ExpectedIdentifier(found) => write!(f,"Expected identifier, found {}",found),
             Self::ExpectedCloseParen(found) => write!(f,"Expected ')', found {}",found),
             Self::ExpectedExpression(found) => write!(f,"Expected expression, found {}",found),
+            Self::ExpectedComma(found) => write!(f,"Expected comma, found {}",found),
+            Self::UnknownFunction(name) => write!(f,"Unknown function '{}'",name),
+            Self::ExpectedEndOfExpression(found) => write!(f,"Expected end of expression, found {}",found),
             Self::CantNegateString => write!(f,"Strings can't be negated"),
             Self::CantNegateObject => write!(f,"Objects can't be negated"),
             Self::CantMultiplyDice => write!(f,"Terms can't be multiplied by a dice expression"),
@@ -100,6 +128,22 @@ impl std::fmt::Display for InterpolationError {
             Self::InvalidIndex => write!(f,"Invalid index"),
             Self::CantSignObject => write!(f,"Objects can't be signed"),
             Self::CantSignString => write!(f,"Strings can't be signed"),
+            Self::CantAverageString => write!(f,"Strings can't be averaged"),
+            Self::CantAverageObject => write!(f,"Objects can't be averaged"),
+            Self::CantGetExpressionOfString => write!(f,"Strings don't have a dice expression"),
+            Self::CantGetExpressionOfObject => write!(f,"Objects don't have a dice expression"),
+            Self::CantNegateBoolean => write!(f,"Booleans can't be negated"),
+            Self::CantSignBoolean => write!(f,"Booleans can't be signed"),
+            Self::CantAverageBoolean => write!(f,"Booleans can't be averaged"),
+            Self::CantGetExpressionOfBoolean => write!(f,"Booleans don't have a dice expression"),
+            Self::CantMultiplyBooleans => write!(f,"Booleans can't be multiplied"),
+            Self::CantDivideBooleans => write!(f,"Booleans can't be divided"),
+            Self::CantAddBooleans => write!(f,"Booleans can't be added"),
+            Self::CantSubtractBooleans => write!(f,"Booleans can't be subtracted"),
+            Self::CantCompareNonStrings => write!(f,"Only strings can be compared"),
+            Self::CantCheckEquality => write!(f,"Only two numbers, dice, strings or booleans can be checked for equality"),
+            Self::CantOrderNonNumbers => write!(f,"Only numbers can be compared with '<' or '>', use avg(...) to compare dice"),
+            Self::ConditionIsNotBoolean => write!(f,"The condition of 'if' must be a boolean"),
             Self::EmptyStack(operation) => write!(f,"Internal error: stack is empty at operation {}",operation),
             Self::UnexpectedStructuredText => write!(f,"Internal error: unexpected structured text in deserialization"),
             Self::TextIsAlreadyBold => write!(f,"Text is already bold"),
@@ -187,11 +231,109 @@ impl From<&TokenErrorDetails> for InterpolationErrorDetails {
 
 
 
+/**
+An error in the syntax of a list of creature commands, with the line and column it was found at, and the name of the command it was found in, if there was one, since the error from the parser alone can be hard to find in a long file.
+*/
+#[derive(Clone,Debug,PartialEq)]
+pub struct CommandSyntaxError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    pub command: Option<String>
+}
+
+impl CommandSyntaxError {
+
+    pub fn from_ron(source: &str, error: ron::Error) -> Self {
+        let line = error.position.line;
+        let column = error.position.col;
+        // the column is counted in bytes, starting at 1
+        let offset = source.split('\n').take(line.saturating_sub(1)).map(|text| text.len() + 1).sum::<usize>() + column.saturating_sub(1);
+        Self {
+            message: error.code.to_string(),
+            line,
+            column,
+            command: if line > 0 {
+                Self::find_command(source,offset)
+            } else {
+                None
+            }
+        }
+    }
+
+    // finds the last command started before the offset, which is an identifier directly inside the list of commands.
+    fn find_command(source: &str, offset: usize) -> Option<String> {
+        let mut chars = source.char_indices().take_while(|(index,_)| *index < offset).map(|(_,char)| char).peekable();
+        let mut depth = 0;
+        let mut list_depth = None;
+        let mut command = None;
+        while let Some(char) = chars.next() {
+            match char {
+                '"' => {
+                    while let Some(char) = chars.next() {
+                        match char {
+                            '\\' => { chars.next(); },
+                            '"' => break,
+                            _ => ()
+                        }
+                    }
+                },
+                '/' if chars.peek() == Some(&'/') => {
+                    for char in chars.by_ref() {
+                        if char == '\n' {
+                            break
+                        }
+                    }
+                },
+                '/' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    let mut last = None;
+                    for char in chars.by_ref() {
+                        if (last == Some('*')) && (char == '/') {
+                            break
+                        }
+                        last = Some(char);
+                    }
+                },
+                '[' | '(' | '{' => {
+                    depth += 1;
+                    if list_depth.is_none() && (char == '[') {
+                        list_depth = Some(depth)
+                    }
+                },
+                ']' | ')' | '}' => depth -= 1,
+                char if char.is_ascii_alphabetic() || (char == '_') => {
+                    let mut identifier = char.to_string();
+                    while let Some(char) = chars.next_if(|char| char.is_ascii_alphanumeric() || (*char == '_')) {
+                        identifier.push(char)
+                    }
+                    if Some(depth) == list_depth {
+                        command = Some(identifier)
+                    }
+                },
+                _ => ()
+            }
+        }
+        command
+    }
+}
+
+impl std::fmt::Display for CommandSyntaxError {
+
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(),std::fmt::Error> {
+        match (self.line,&self.command) {
+            (0,_) => write!(f,"{}",self.message),
+            (line,Some(command)) => write!(f,"{} at line {}, column {}, near the '{}' command",self.message,line,self.column,command),
+            (line,None) => write!(f,"{} at line {}, column {}",self.message,line,self.column)
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum IncludeError {
     FileError(std::io::Error),
     InterpolationError(InterpolationErrorDetails),
-    DeserializationError(ron::Error),
+    DeserializationError(CommandSyntaxError),
     CreatureError(Box<CreatureError>),
 
 }
@@ -225,9 +367,9 @@ impl std::convert::From<InterpolationErrorDetails> for IncludeError {
 
 }
 
-impl std::convert::From<ron::Error> for IncludeError {
+impl std::convert::From<CommandSyntaxError> for IncludeError {
 
-    fn from(error: ron::Error) -> Self {
+    fn from(error: CommandSyntaxError) -> Self {
         IncludeError::DeserializationError(error)
     }
 
@@ -242,11 +384,17 @@ pub enum CreatureError {
    WeaponAttackDoesNotMatchExpectation(String),
    WeaponEffectDoesNotMatchExpectation(String),
    WeaponNotFound(String,String), // name, action
+   WeaponDefinitionNotFound(String),
    ActionNotFound(String,String), // name, action
-   LairActionsNotSupportedYet,
-   RegionalEffectsNotSupportedYet,
    ChallengeRatingNotAsExpected(String,String), // expected, found
-   InvalidStateForFeature(String)
+   CalculatedChallengeRatingNotAsExpected(String,String), // expected, calculated
+   InvalidStateForFeature(String),
+   InvalidPropertyExpression(String,String), // name, error
+   SuspiciousMultiattack(String),
+   InvalidScale(f32),
+   ScaledHitPointsTooLarge(f32),
+   CantScaleExpressionDamage(String), // name of the action
+   InvalidAlignmentChance(String)
 }
 
 
@@ -268,11 +416,17 @@ impl std::fmt::Display for CreatureError {
             Self::WeaponAttackDoesNotMatchExpectation(name) => write!(f,"Weapon {} attack does not match expected.",name),
             Self::WeaponEffectDoesNotMatchExpectation(name) => write!(f,"Weapon {} effect does not match expected.",name),
             Self::WeaponNotFound(name,action) => write!(f,"Could not find weapon named {} {}.",name,action),
+            Self::WeaponDefinitionNotFound(name) => write!(f,"Could not find weapon definition named {}.",name),
             Self::ActionNotFound(name,action) => write!(f,"Could not find action named {} {}.",name,action),
-            Self::LairActionsNotSupportedYet => write!(f,"Lair actions are not supported yet."),
-            Self::RegionalEffectsNotSupportedYet => write!(f,"Regional effects are not supported yet."),
             Self::ChallengeRatingNotAsExpected(expected,found) => write!(f,"Challenge rating {} did not match expected {}",found,expected),
+            Self::CalculatedChallengeRatingNotAsExpected(expected,calculated) => write!(f,"Calculated challenge rating {} did not match expected {} (spells, legendary actions and special features are not included in the calculation, use an Override challenge rating command if they should be)",calculated,expected),
             Self::InvalidStateForFeature(error) => write!(f,"{}",error),
+            Self::InvalidPropertyExpression(name,error) => write!(f,"Invalid expression for property '{}': {}",name,error),
+            Self::SuspiciousMultiattack(warning) => write!(f,"{}",warning),
+            Self::InvalidScale(scale) => write!(f,"The scale must be a number greater than zero, found {}",scale),
+            Self::ScaledHitPointsTooLarge(scale) => write!(f,"Scaling by {} gives the creature more hit dice or hit points than can be recorded",scale),
+            Self::CantScaleExpressionDamage(name) => write!(f,"The damage of {} comes from an expression, which can't be scaled",name),
+            Self::InvalidAlignmentChance(error) => write!(f,"Invalid alignment chance: {}",error),
         }
     }
 
@@ -12,6 +12,7 @@ use serde::Serialize;
 use crate::utils::AndJoin;
 use crate::dice_expression::DiceExpression;
 use crate::stats::Damage;
+use crate::stats::Ability;
 use crate::attacks::AttackEffect;
 use crate::attacks::CompoundAttackEffect;
 
@@ -371,6 +372,13 @@ pub enum Feature {
     Adds a custom feature to the creature, with the specified name and description. These will be interpolated.
     */
     Feature(String,String), // name, description
+
+    /**
+    `SaveFeature(<string>,<Ability>,<string>)`
+
+    Adds a custom feature to the creature, with the specified name and description, which calls for a saving throw. The second argument is the ability which the saving throw DC is based on. While interpolating the description, a `save_dc` variable is available which is calculated as 8 + the proficiency bonus + the modifier for that ability, so it will stay in sync with the creature's stats. For example: `"Each creature within 10 feet must succeed on a DC ${save_dc} Constitution saving throw."`
+    */
+    SaveFeature(String,Ability,String), // name, ability for save DC, description
 }
 
 impl Feature {
@@ -437,6 +445,7 @@ impl Feature {
             Feature::WebSense => "Web Sense".to_owned(),
             Feature::WebWalker => "Web Walker".to_owned(),
             Feature::Feature(name,..) => name.clone(),
+            Feature::SaveFeature(name,..) => name.clone(),
        
         }
     }
@@ -518,8 +527,16 @@ impl Feature {
             Feature::WebSense => format!("While in contact with a web, ${{subj}} knows the exact location of any other creature in contact with the same web."),
             Feature::WebWalker => format!("${{Subj}} ignores movement restrictions caused by webbing."),
             Feature::Feature(_,description) => description.clone(), // name, description
+            Feature::SaveFeature(_,_,description) => description.clone(), // name, ability for save DC, description
         }
         
     }
 
+    pub fn get_save_ability(&self) -> Option<Ability> {
+        match self {
+            Feature::SaveFeature(_,ability,_) => Some(ability.clone()),
+            _ => None
+        }
+    }
+
 }
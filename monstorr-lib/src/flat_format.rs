@@ -0,0 +1,165 @@
+/*
+ * Copyright © 2022 Neil M. Sheldon
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+/*!
+The flat creature format is a terse, line-based way of writing the same commands as a creature file, for jotting down simple creatures quickly. Each line is one command, written in lower case as words instead of RON. For example:
+
+```text
+# a comment
+name Goblin
+small
+humanoid
+subtype goblinoid
+neutral evil
+armor leather
+shield
+hd 2d6
+walk 30
+str 8
+dex 14
+skills stealth
+darkvision 60
+languages common, goblin
+cr 1/4
+feature nimble escape
+weapon scimitar
+weapon shortbow +1
+action Sneer: The goblin sneers.
+```
+
+Most commands are written as their name, split into words, followed by a number if they take one, so `str 16` is `Str(16)`, `hit dice count 2` is `HitDiceCount(2)`, `neutral evil` is `NeutralEvil` and `hover` is `Hover`. The commands which take text or lists have their own keywords:
+
+* `name`, `source`, `subtype`, `group` and `note` take the rest of the line as text.
+* `type` and `alignment` take one of the usual types or alignments, anything else becomes a custom one.
+* `armor` takes the name of the armor, such as `leather`, `natural 2` or `natural flat 17`.
+* `hd` takes the hit dice, such as `2d6`, and `hp` overrides the hit points.
+* `saves`, `skills`, `expertise` and `languages` take a list separated by commas. Saves can use the short names of the abilities.
+* `cr` takes the challenge rating, such as `3` or `1/4`.
+* `weapon` takes the name of a weapon, with an optional magic bonus, such as `longsword +1`.
+* `feature` takes either a built-in feature, such as `pack tactics`, or a name and description separated by a colon. `action` takes a name and description separated by a colon.
+
+Any line starting with a capital letter is read as a command in the usual RON syntax, such as `Feature(PackTactics)`, so every command is still available.
+*/
+
+use crate::creature_commands::CreatureCommand;
+use crate::creature_commands::CreatureCreator;
+use crate::dice::Dice;
+use crate::stats::Ability;
+use crate::stats::ChallengeRating;
+use crate::stats::Language;
+use crate::stats::Skill;
+use crate::stats::Armor;
+use crate::features::Feature;
+use crate::actions::Action;
+
+// turns words into the name of a variant, such as "hit dice count" into "HitDiceCount".
+fn to_variant_name(words: &str) -> String {
+    words.split(|c: char| c.is_whitespace() || c == '-' || c == '_').map(|word| {
+        let mut chars = word.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+            None => String::new()
+        }
+    }).collect()
+}
+
+// turns words, with an optional number at the end, into RON for a variant, such as "natural 2" into "Natural(2)".
+fn to_ron_variant(words: &str) -> String {
+    match words.trim().rsplit_once(' ') {
+        Some((name,number)) if number.parse::<i16>().is_ok() => format!("{}({})",to_variant_name(name),number.trim_start_matches('+')),
+        _ => to_variant_name(words.trim())
+    }
+}
+
+fn parse_ron<Value: serde::de::DeserializeOwned>(source: &str) -> Option<Value> {
+    ron::de::from_str(source).ok()
+}
+
+fn parse_list<Value, Parse: Fn(&str) -> Option<Value>>(source: &str, parse: Parse) -> Option<Vec<Value>> {
+    source.split(',').map(|item| parse(item.trim())).collect()
+}
+
+fn parse_ability(source: &str) -> Option<Ability> {
+    match source.to_lowercase().as_str() {
+        "str" => Some(Ability::Strength),
+        "dex" => Some(Ability::Dexterity),
+        "con" => Some(Ability::Constitution),
+        "int" => Some(Ability::Intelligence),
+        "wis" => Some(Ability::Wisdom),
+        "cha" => Some(Ability::Charisma),
+        ability => ability.parse().ok()
+    }
+}
+
+fn parse_challenge_rating(source: &str) -> Option<CreatureCommand> {
+    Some(match source.parse::<ChallengeRating>().ok()? {
+        ChallengeRating::None => CreatureCommand::OverrideNoChallenge,
+        ChallengeRating::Eighth => CreatureCommand::OverrideEighthChallenge,
+        ChallengeRating::Quarter => CreatureCommand::OverrideQuarterChallenge,
+        ChallengeRating::Half => CreatureCommand::OverrideHalfChallenge,
+        ChallengeRating::Whole(challenge) => CreatureCommand::OverrideChallenge(challenge)
+    })
+}
+
+fn parse_line(line: &str) -> Option<Vec<CreatureCommand>> {
+    if line.starts_with(|c: char| c.is_uppercase()) {
+        return Some(vec![parse_ron(line)?])
+    }
+    let (keyword,rest) = line.split_once(char::is_whitespace).map(|(keyword,rest)| (keyword,rest.trim())).unwrap_or((line,""));
+    Some(vec![match (keyword,rest) {
+        (_,"") => parse_ron(&to_variant_name(keyword))?,
+        ("name",name) => CreatureCommand::Name(name.to_owned()),
+        ("source",source) => CreatureCommand::Source(source.to_owned()),
+        ("subtype",subtype) => CreatureCommand::Subtype(subtype.to_owned()),
+        ("group",group) => CreatureCommand::Group(group.to_owned()),
+        ("note",note) => CreatureCommand::Note(note.to_owned()),
+        ("size",size) => parse_ron(&to_variant_name(size))?,
+        ("type",type_) => parse_ron(&to_variant_name(type_)).unwrap_or_else(|| CreatureCommand::CreatureType(type_.to_owned())),
+        ("alignment",alignment) => parse_ron(&to_variant_name(alignment)).unwrap_or_else(|| CreatureCommand::Alignment(alignment.to_owned())),
+        ("armor",armor) => CreatureCommand::Armor(parse_ron::<Armor>(&to_ron_variant(armor))?),
+        ("hd",dice) => {
+            let dice = dice.parse::<Dice>().ok()?;
+            return Some(vec![CreatureCommand::HitDie(dice.die),CreatureCommand::HitDiceCount(dice.coefficient)])
+        },
+        ("hp",hit_points) => CreatureCommand::HitPoints(hit_points.parse().ok()?),
+        ("saves",saves) => CreatureCommand::Saves(parse_list(saves,parse_ability)?),
+        ("skills",skills) => CreatureCommand::Skills(parse_list(skills,|skill| parse_ron::<Skill>(&to_variant_name(skill)))?),
+        ("expertise",skills) => CreatureCommand::Expertise(parse_list(skills,|skill| parse_ron::<Skill>(&to_variant_name(skill)))?),
+        ("languages",languages) => CreatureCommand::Languages(parse_list(languages,|language| language.parse::<Language>().ok())?),
+        ("cr",challenge) => parse_challenge_rating(challenge)?,
+        ("weapon",weapon) => CreatureCommand::Weapon(weapon.parse().ok()?,None),
+        ("feature",feature) => match feature.split_once(':') {
+            Some((name,description)) => CreatureCommand::Feature(Feature::Feature(name.trim().to_owned(),description.trim().to_owned()),None),
+            None => CreatureCommand::Feature(parse_ron::<Feature>(&to_ron_variant(feature))?,None)
+        },
+        ("action",action) => {
+            let (name,description) = action.split_once(':')?;
+            CreatureCommand::Action(Action::Action(name.trim().to_owned(),description.trim().to_owned(),None,None),None)
+        },
+        // anything else is a command with an optional number
+        _ => parse_ron(&to_ron_variant(line))?
+    }])
+}
+
+/**
+Reads creature commands from the flat format. Errors give the line number of the first line which couldn't be understood.
+*/
+pub fn load_flat_creature_commands(source: &str) -> Result<CreatureCreator,String> {
+    let mut result = Vec::new();
+    for (index,line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match parse_line(line) {
+            Some(commands) => result.extend(commands),
+            None => Err(format!("Error loading creature commands: line {} could not be understood: {}",index + 1,line))?
+        }
+    }
+    Ok(CreatureCreator(result))
+}
@@ -0,0 +1,387 @@
+/*
+ * Copyright © 2022 Neil M. Sheldon
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+/*!
+Exports a stat block as an actor for the dnd5e system of the Foundry virtual tabletop, which can be imported into Foundry with "Import Data" on an NPC actor.
+
+The ability scores, armor class, hit points, speeds, size, type, alignment, challenge rating, damage and condition traits, languages and senses are filled in from the stat block. Every feature and action becomes an item on the actor, with its description written as HTML. Actions with an attack, from a creature file, become weapon items with their attack bonus, reach or range, damage parts and saving throw, so they can be rolled in Foundry. The finished roll formulas, such as `1d20 + 4` to hit and `1d6 + 2` for damage, are also kept in the item's `flags.monstorr.rolls`, for writing roll macros. A reaction with a separate trigger has it set as the item's activation condition. Actions from other input formats only have their descriptions, so they become feat items.
+
+This is a first version of the export. Saving throw and skill proficiencies, spells and the biography are left out, and are left to Foundry to calculate or to be added by hand.
+*/
+
+use serde_json::json;
+use serde_json::Value;
+
+use crate::attacks::AttackBonus;
+use crate::attacks::AttackEffect;
+use crate::attacks::AttackType;
+use crate::creature::Creature;
+use crate::interpolation::evaluate_expression_str;
+use crate::interpolation::InterpolationValue;
+use crate::stat_block::CreatureStatBlock;
+use crate::stat_block::StatBlockFeature;
+use crate::stats::Damage;
+use crate::structured_text::TextBlock;
+use crate::structured_text::TextSpan;
+use crate::structured_text::spans_to_text;
+
+/**
+The details of an attack action needed to make it rollable in Foundry. These come from the creature, since the stat block only has the description.
+*/
+pub struct FoundryAttack {
+    pub name: String,
+    pub action_type: &'static str, // mwak, rwak, msak, rsak, or save
+    pub ability: &'static str,
+    pub attack_bonus: Option<i8>, // added to the ability and proficiency bonus
+    pub attack_roll: Option<String>, // the complete roll to hit, such as "1d20 + 4"
+    pub proficient: bool,
+    pub reach: Option<u8>,
+    pub range: Option<u16>,
+    pub long_range: Option<u16>,
+    pub damage: Vec<(String,String)>, // formula, damage type
+    pub save: Option<(u8,&'static str)> // DC, ability
+}
+
+fn effect_damage_type(effect: &AttackEffect) -> Option<&Damage> {
+    match effect {
+        AttackEffect::Special(_) |
+        AttackEffect::Escape(..) => None,
+        AttackEffect::Damage(_,_,damage) |
+        AttackEffect::FixedDamage(_,_,damage) |
+        AttackEffect::ExpressionDamage(_,_,damage) |
+        AttackEffect::Or(_,_,damage,..) |
+        AttackEffect::DoubleOr(_,_,damage,..) |
+        AttackEffect::DjinnisChoice(_,_,damage,_) |
+        AttackEffect::SaveAll(_,_,_,_,damage) |
+        AttackEffect::SaveHalf(_,_,_,_,damage) |
+        AttackEffect::AreaDamage(_,_,damage) |
+        AttackEffect::AreaSaveAll(_,_,_,_,damage) |
+        AttackEffect::AreaSaveHalf(_,_,_,_,damage) => Some(damage)
+    }
+}
+
+fn effect_save(effect: &AttackEffect) -> Option<(u8,&'static str)> {
+    match effect {
+        AttackEffect::SaveAll(save_dc,ability,..) |
+        AttackEffect::SaveHalf(save_dc,ability,..) |
+        AttackEffect::AreaSaveAll(save_dc,ability,..) |
+        AttackEffect::AreaSaveHalf(save_dc,ability,..) => Some((*save_dc,ability.to_short_str())),
+        _ => None
+    }
+}
+
+// the damage is evaluated against the creature, so the ability bonus is already included in the formula.
+fn evaluate_damage(creature: &Creature, expression: &str, name: &str) -> Option<String> {
+    match evaluate_expression_str(expression, name, creature) {
+        Ok(InterpolationValue::Number(number,_)) => Some(number.to_string()),
+        Ok(InterpolationValue::Dice(dice,_)) => Some(dice.serialize_to_string()),
+        _ => None
+    }
+}
+
+fn evaluate_attack_roll(creature: &Creature, expression: &str, name: &str) -> Option<String> {
+    match evaluate_expression_str(expression, name, creature) {
+        Ok(InterpolationValue::Number(bonus,_)) if bonus < 0 => Some(format!("1d20 - {}",-bonus)),
+        Ok(InterpolationValue::Number(bonus,_)) => Some(format!("1d20 + {}",bonus)),
+        _ => None
+    }
+}
+
+/**
+Collects the attack details for the actions of a creature which have an attack or a damaging effect.
+*/
+pub fn get_foundry_attacks(creature: &Creature) -> Vec<FoundryAttack> {
+    creature.actions.iter().filter(|action| action.attack.is_some() || action.effect.is_some()).map(|action| {
+        let default_bonus = action.attack.as_ref().map(|attack| attack.get_default_bonus()).unwrap_or("0");
+        let mut effects: Vec<&AttackEffect> = action.effect.iter().collect();
+        if let Some(compound) = &action.compound {
+            effects.extend(compound.get_effects());
+        }
+        let damage = effects.iter().filter_map(|effect| {
+            let damage_type = effect_damage_type(effect)?;
+            let formula = evaluate_damage(creature, &effect.get_damage_expr(default_bonus)?, &action.name)?;
+            Some((formula,damage_type.to_string()))
+        }).collect();
+        let save = effects.iter().find_map(|effect| effect_save(effect));
+
+        match &action.attack {
+            Some(attack) => {
+                let spell = matches!(attack.type_,Some(AttackType::Spell));
+                let (ability,attack_bonus,proficient) = match &attack.bonus {
+                    AttackBonus::Default | AttackBonus::Best => ("",attack.magic,true),
+                    AttackBonus::Strength => ("str",attack.magic,true),
+                    AttackBonus::Dexterity => ("dex",attack.magic,true),
+                    AttackBonus::Constitution => ("con",attack.magic,true),
+                    AttackBonus::Intelligence => ("int",attack.magic,true),
+                    AttackBonus::Wisdom => ("wis",attack.magic,true),
+                    AttackBonus::Charisma => ("cha",attack.magic,true),
+                    AttackBonus::Spell => ("spellcasting",attack.magic,true),
+                    AttackBonus::Fixed(bonus) => ("none",Some(bonus + attack.magic.unwrap_or(0)),false),
+                    AttackBonus::Zero => ("none",attack.magic,true)
+                };
+                FoundryAttack {
+                    name: action.name.clone(),
+                    action_type: match (attack.reach.is_some(),spell) {
+                        (true,false) => "mwak",
+                        (false,false) => "rwak",
+                        (true,true) => "msak",
+                        (false,true) => "rsak"
+                    },
+                    ability,
+                    attack_bonus,
+                    attack_roll: evaluate_attack_roll(creature, &attack.get_attack_bonus_expr(), &action.name),
+                    proficient,
+                    reach: attack.reach,
+                    range: attack.range,
+                    long_range: attack.long_range,
+                    damage,
+                    save
+                }
+            },
+            None => FoundryAttack {
+                name: action.name.clone(),
+                action_type: if save.is_some() { "save" } else { "other" },
+                ability: "",
+                attack_bonus: None,
+                attack_roll: None,
+                proficient: false,
+                reach: None,
+                range: None,
+                long_range: None,
+                damage,
+                save
+            }
+        }
+    }).collect()
+}
+
+fn escape_html(source: &str) -> String {
+    source.replace('&',"&amp;").replace('<',"&lt;").replace('>',"&gt;")
+}
+
+fn spans_to_html(spans: &[TextSpan]) -> String {
+    spans.iter().map(|span| match span {
+        TextSpan::Normal(text) => escape_html(text),
+        TextSpan::Italic(text) => format!("<em>{}</em>",escape_html(text)),
+        TextSpan::Bold(text) => format!("<strong>{}</strong>",escape_html(text)),
+        TextSpan::BoldItalic(text) => format!("<strong><em>{}</em></strong>",escape_html(text))
+    }).collect()
+}
+
+fn blocks_to_html(blocks: &[TextBlock]) -> String {
+    blocks.iter().map(|block| match block {
+        TextBlock::Paragraph { heading: Some(heading), body } |
+        TextBlock::SubParagraph { heading: Some(heading), body } => format!("<p><strong>{}</strong> {}</p>",spans_to_html(heading),spans_to_html(body)),
+        TextBlock::Paragraph { heading: None, body } |
+        TextBlock::SubParagraph { heading: None, body } => format!("<p>{}</p>",spans_to_html(body))
+    }).collect()
+}
+
+// the name of a feature is the heading of its first paragraph, which is left out of the description.
+pub(crate) fn split_feature(feature: &StatBlockFeature) -> (String,String) {
+    match feature.text.split_first() {
+        Some((TextBlock::Paragraph { heading: Some(heading), body },rest)) |
+        Some((TextBlock::SubParagraph { heading: Some(heading), body },rest)) => {
+            let name = spans_to_text(heading).trim().trim_end_matches('.').to_owned();
+            (name,format!("<p>{}</p>{}",spans_to_html(body),blocks_to_html(rest)))
+        },
+        _ => (String::new(),blocks_to_html(&feature.text))
+    }
+}
+
+fn feat_item(name: String, description: String, activation: &str, cost: Option<u8>) -> Value {
+    json!({
+        "name": name,
+        "type": "feat",
+        "system": {
+            "description": { "value": description },
+            "activation": { "type": activation, "cost": cost }
+        }
+    })
+}
+
+fn attack_item(name: String, description: String, attack: &FoundryAttack) -> Value {
+    let (range,long_range) = match (attack.reach,attack.range) {
+        (Some(reach),_) => (Some(reach as u16),None),
+        (None,Some(range)) => (Some(range),attack.long_range),
+        (None,None) => (None,None)
+    };
+    json!({
+        "name": name,
+        "type": if attack.action_type == "other" || attack.action_type == "save" { "feat" } else { "weapon" },
+        "system": {
+            "description": { "value": description },
+            "activation": { "type": "action", "cost": 1 },
+            "actionType": attack.action_type,
+            "ability": attack.ability,
+            "attackBonus": attack.attack_bonus.map(|bonus| bonus.to_string()).unwrap_or_default(),
+            "proficient": attack.proficient,
+            "equipped": true,
+            "weaponType": "natural",
+            "range": { "value": range, "long": long_range, "units": if range.is_some() { "ft" } else { "" } },
+            "damage": { "parts": attack.damage.iter().map(|(formula,damage_type)| json!([formula,damage_type])).collect::<Vec<Value>>() },
+            "save": {
+                "ability": attack.save.map(|(_,ability)| ability).unwrap_or(""),
+                "dc": attack.save.map(|(save_dc,_)| save_dc),
+                "scaling": "flat"
+            }
+        },
+        "flags": {
+            "monstorr": {
+                "rolls": {
+                    "attack": attack.attack_roll,
+                    "damage": attack.damage.iter().map(|(formula,_)| formula.as_str()).collect::<Vec<&str>>()
+                }
+            }
+        }
+    })
+}
+
+// the leading number of a stat-block value, such as the 15 in "15 (leather armor)".
+pub(crate) fn leading_number(source: &str) -> Option<u16> {
+    source.split(|c: char| !c.is_ascii_digit()).next().and_then(|number| number.parse().ok())
+}
+
+fn challenge_rating_value(source: &str) -> f32 {
+    match source.split_whitespace().next() {
+        Some("1/8") => 0.125,
+        Some("1/4") => 0.25,
+        Some("1/2") => 0.5,
+        Some(number) => number.parse().unwrap_or(0.0),
+        None => 0.0
+    }
+}
+
+fn size_value(size: &str) -> &'static str {
+    match size.to_lowercase().as_str() {
+        "tiny" => "tiny",
+        "small" => "sm",
+        "large" => "lg",
+        "huge" => "huge",
+        "gargantuan" => "grg",
+        _ => "med"
+    }
+}
+
+fn movement_value(speed: &str) -> Value {
+    let mut result = json!({ "units": "ft", "hover": speed.contains("hover") });
+    for part in speed.split(',') {
+        let part = part.trim();
+        let (kind,distance) = match part.split_once(' ') {
+            Some((kind,distance)) if kind.chars().all(|c| c.is_alphabetic()) => (kind,distance),
+            _ => ("walk",part)
+        };
+        if let Some(distance) = leading_number(distance) {
+            result[kind] = json!(distance);
+        }
+    }
+    result
+}
+
+// damage types that Foundry knows go in the value, anything else, such as the weapon restrictions, is kept as written.
+fn damage_trait_value(source: &Option<String>) -> Value {
+    let mut value = Vec::new();
+    let mut custom = Vec::new();
+    for part in source.iter().flat_map(|source| source.split(';')).flat_map(|part| part.split(',')) {
+        let part = part.trim();
+        if part.parse::<Damage>().is_ok() {
+            value.push(part.to_lowercase())
+        } else if !part.is_empty() {
+            custom.push(part)
+        }
+    }
+    json!({ "value": value, "custom": custom.join("; ") })
+}
+
+fn condition_trait_value(source: &Option<String>) -> Value {
+    let value: Vec<String> = source.iter().flat_map(|source| source.split(',')).map(|part| part.trim().to_lowercase()).filter(|part| !part.is_empty()).collect();
+    json!({ "value": value, "custom": "" })
+}
+
+/**
+Writes the stat block as a Foundry dnd5e actor. The attacks, from [`get_foundry_attacks`], are matched to the actions by name.
+*/
+pub fn write_foundry_actor(stat_block: &CreatureStatBlock, attacks: &[FoundryAttack], ugly: bool) -> Result<String,String> {
+    let ability = |score: &str| json!({ "value": leading_number(score).unwrap_or(10) });
+    let hit_points = leading_number(&stat_block.hit_points).unwrap_or(0);
+    let hit_points_formula = stat_block.hit_points.split_once('(').map(|(_,formula)| formula.trim_end_matches(')').to_owned()).unwrap_or_default();
+
+    let mut items = Vec::new();
+    for feature in &stat_block.special_abilities {
+        let (name,description) = split_feature(feature);
+        items.push(feat_item(name,description,"",None));
+    }
+    for feature in &stat_block.actions {
+        let (name,description) = split_feature(feature);
+        // the heading may include a usage limit, such as "Fire Breath (Recharge 5–6)".
+        let attack = attacks.iter().find(|attack| name == attack.name || name.starts_with(&format!("{} (",attack.name)));
+        items.push(match attack {
+            Some(attack) => attack_item(name,description,attack),
+            None => feat_item(name,description,"action",Some(1))
+        });
+    }
+    for feature in &stat_block.reactions {
+        let (name,description) = split_feature(feature);
+        let mut item = feat_item(name,description,"reaction",Some(1));
+        // foundry prompts for the reaction with the activation condition.
+        if let Some(trigger) = &feature.trigger {
+            item["system"]["activation"]["condition"] = json!(trigger);
+        }
+        items.push(item);
+    }
+    if let Some(legendary) = &stat_block.legendary_actions {
+        for feature in &legendary.actions {
+            let (name,description) = split_feature(feature);
+            // the cost is written in the name, such as "Wing Attack (Costs 2 Actions)".
+            let cost = name.split_once("(Costs ").and_then(|(_,cost)| leading_number(cost)).unwrap_or(1) as u8;
+            items.push(feat_item(name,description,"legendary",Some(cost)));
+        }
+    }
+
+    let actor = json!({
+        "name": stat_block.display_name.as_ref().unwrap_or(&stat_block.name),
+        "type": "npc",
+        "system": {
+            "abilities": {
+                "str": ability(&stat_block.strength),
+                "dex": ability(&stat_block.dexterity),
+                "con": ability(&stat_block.constitution),
+                "int": ability(&stat_block.intelligence),
+                "wis": ability(&stat_block.wisdom),
+                "cha": ability(&stat_block.charisma)
+            },
+            "attributes": {
+                "ac": { "calc": "flat", "flat": leading_number(&stat_block.armor) },
+                "hp": { "value": hit_points, "max": hit_points, "formula": hit_points_formula },
+                "movement": movement_value(&stat_block.speed),
+                "senses": { "special": stat_block.senses }
+            },
+            "details": {
+                "alignment": stat_block.alignment,
+                "type": { "value": stat_block.type_.to_lowercase(), "subtype": stat_block.subtype.clone().unwrap_or_default() },
+                "cr": challenge_rating_value(&stat_block.challenge_rating),
+                "source": stat_block.source.clone().unwrap_or_default()
+            },
+            "traits": {
+                "size": size_value(&stat_block.size),
+                "dv": damage_trait_value(&stat_block.damage_vulnerabilities),
+                "dr": damage_trait_value(&stat_block.damage_resistances),
+                "di": damage_trait_value(&stat_block.damage_immunities),
+                "ci": condition_trait_value(&stat_block.condition_immunities),
+                "languages": { "value": [], "custom": stat_block.languages.clone().unwrap_or_default() }
+            }
+        },
+        "items": items
+    });
+
+    if ugly {
+        serde_json::to_string(&actor)
+    } else {
+        serde_json::to_string_pretty(&actor)
+    }.map_err(|e| format!("Error writing Foundry actor: {}",e))
+}
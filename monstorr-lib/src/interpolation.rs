@@ -24,7 +24,7 @@ All text outside these delimiters are treated as normal text. While you can use
 
 The expression syntax is fairly simple. The following values operations are supported:
 
-*Variable References.* A plain identifier is a variable reference. Variables are provided by Monstorr according to the mode of interpolation. In inclusion, they are string values provided as arguments by the `Include` command, see [`crate::creature_commands::CreatureCommand::Include`]. In descriptions, the variables are properties of the creature, which may be strings, numbers or dice. For available creature properties, see [`crate::creature::Creature`]. If the variable does not exist, or returns no value, an error will occur when it is referenced. 
+*Variable References.* A plain identifier is a variable reference. Variables are provided by Monstorr according to the mode of interpolation. In inclusion, they are string values provided as arguments by the `Include` command, see [`crate::creature_commands::CreatureCommand::Include`]. In descriptions, the variables are properties of the creature, which may be strings, numbers or dice, as well as any string parameters passed in when the creature was built (see the `--param` option on the command line). For available creature properties, see [`crate::creature::Creature`]. If the variable does not exist, or returns no value, an error will occur when it is referenced. 
 
 *Strings*. Strings are delimited by double-quotes. They return a string value.
 
@@ -34,8 +34,22 @@ The expression syntax is fairly simple. The following values operations are supp
 
 *Parenthesis.* Expressions in between parentheses '(..)' will be calculated first.
 
+*Dice Functions.* When an identifier is followed by parentheses, it is a function call instead of a variable reference. Two functions are available for formatting dice in other ways than the average followed by the expression. `avg(...)` returns the average of a dice value as a number, so it can still be used in math, such as `avg(hit_dice) /< 2`. `expr(...)` returns just the dice expression, such as "2d6 + 3", as a string. Numbers are also accepted by both functions, which return the number itself, or the number as a string. Strings and objects can not be passed to them.
+
+*Default Function.* `default(value, fallback)` returns the value, unless it is an empty string, or it references a variable, property or index which doesn't exist, in which case the fallback is returned instead. Other errors in the value, such as adding a string to a number, are still reported. This allows descriptions which work whether or not an optional property or parameter is given, such as `default(group, "monster")`, or `default(title, name)` for a `title` parameter which may not be passed. Both arguments are full expressions, but the fallback is only evaluated if it is needed.
+
+*Default Operator.* `value ?? fallback` is a shorter way of writing `default(value, fallback)`. It has the lowest precedence of all the operators, so `str + 2 ?? 0` falls back if the whole sum can't be calculated, and it can be chained, so `title ?? group ?? "monster"` tries each value in turn. This is most useful in files included with the `Include` command, which can give a default to any argument that doesn't have to be passed, such as `$<name ?? "goblin">`.
+
+*String Comparison Functions.* `contains(text, part)`, `startswith(text, prefix)` and `endswith(text, suffix)` return a boolean value, true if the first string contains, starts with or ends with the second. Comparisons are case-sensitive. Both arguments must be strings, use the '$' operator to compare numbers or dice. Booleans can't be used in math, and are written out as "true" or "false".
+
+*Comparison Operators.* `==` and `!=` check whether two values are equal, and `<`, `<=`, `>` and `>=` compare two numbers. They return a boolean value, and have a lower precedence than the other operators, so `str + 2 > dex` compares the sum. Equality can be checked between two numbers, dice, strings or booleans of the same type, comparing a number to a string is an error. Dice can't be compared with `<` or `>`, use `avg(...)` on them first. As '>' ends an expression delimited by '$<...>', `>` and `>=` can only be used in descriptions, switch the order of the values and use `<` or `<=` when including files.
+
+*Conditional Function.* `if(condition, then, else)` returns the value of `then` if the condition is true, and the value of `else` if it is false. The condition must be a boolean, such as the result of a comparison or `contains(...)`. Only the value that is chosen is evaluated, so the other may reference a property the creature doesn't have. For example, `if(size == "Large", "its massive claws", "its claws")`, or `if(armor_class >= 18, "deflects the blow", "takes the hit")`.
+
 *Properties and Index.* Some values returned by variables are objects that have properties, or lists that have indexes. Properties are accessed by using the '.' operator followed by the property's identifier. List items are accessed by using dot operator followed by a number indicating the index to access. 
 
+*Dice Properties.* Dice values have three properties, which return numbers: `min` is the lowest possible roll, `max` is the highest possible roll, and `avg` is the average, the same as the `avg(...)` function. For example, with a `hit_dice` of "2d8 + 2", `hit_dice.min` is 4, `hit_dice.max` is 18 and `hit_dice.avg` is 11.
+
 *Negating Numbers.* The '-' can be used before a number to negate it. The value must be a number, no other value types can be negated. Remember that if you negate a value that occurs after another expression, it may be confused with the minus operator. Use parentheses or replace it with '+ -' to remove this ambiguity.
 
 *Signing Numbers and Dice.* Numbers and dice expressions can be signed, so that when they are formatted as strings, a '+' appears before them if they are positive. The '-' will always appear if they are negative. The '+' operator which appears before the value will ensure that that takes place. It is infectious, so if you add other values to the expression, the final result of the expression will be formatted that way. Remember that if you sign a value that occurs after another expression, it may be confused with the plus operator. Use parentheses or replace it with '+ +' to remove this ambiguity.
@@ -73,6 +87,7 @@ use std::collections::HashMap;
 
 use crate::dice::Dice;
 use crate::dice_expression::DiceExpression;
+use crate::dice_expression::AverageRounding;
 use crate::tokenizer::Token;
 use crate::parse_position::Position;
 use crate::parse_position::PositionRange;
@@ -102,8 +117,13 @@ enum InterpolationState {
     Continuing
 }
 
-enum InterpolationMode {
+/**
+Decides what an interpolated string is used for, which changes what is allowed in it and what [`interpolate_str`] returns.
+*/
+pub enum InterpolationMode {
+    /// The string is interpolated to plain text, as with the arguments of creature commands. Expressions are written inside `$<...>` instead of `${...}`, and functions which create structured text, such as `italic`, are not allowed.
     DeserializeCreatureCommands,
+    /// The string is interpolated to structured text, as with feature descriptions in a stat-block.
     CalculateStatBlock
 }
 
@@ -266,10 +286,47 @@ impl<Source: Iterator<Item=char>> Iterator for InterpolationTokenizer<Source> {
                     Some(')') => self.stop_token(Ok(Token::CloseParenthesis)),
                     Some('.') => self.stop_token(Ok(Token::Dot)),
                     Some('$') => self.stop_token(Ok(Token::Dollar)),
+                    Some(',') => self.stop_token(Ok(Token::Comma)),
                     Some('>') if matches!(self.mode,InterpolationMode::DeserializeCreatureCommands) => {
                         let text = self.template_text(&'<');
                         self.stop_token(text)
                     },
+                    // in the other mode, a '>' ends the expression, so it can't be used for comparisons.
+                    Some('>') => match self.peek_char() {
+                        Some('=') => {
+                            self.next_char();
+                            self.stop_token(Ok(Token::GreaterThanOrEqual))
+                        },
+                        _ => self.stop_token(Ok(Token::GreaterThan))
+                    },
+                    Some('<') => match self.peek_char() {
+                        Some('=') => {
+                            self.next_char();
+                            self.stop_token(Ok(Token::LessThanOrEqual))
+                        },
+                        _ => self.stop_token(Ok(Token::LessThan))
+                    },
+                    Some('=') => match self.peek_char() {
+                        Some('=') => {
+                            self.next_char();
+                            self.stop_token(Ok(Token::EqualEqual))
+                        },
+                        _ => self.stop_token(Err(TokenError::EqualsIsNotValid))
+                    },
+                    Some('!') => match self.peek_char() {
+                        Some('=') => {
+                            self.next_char();
+                            self.stop_token(Ok(Token::NotEqual))
+                        },
+                        _ => self.stop_token(Err(TokenError::ExclamationIsNotValid))
+                    },
+                    Some('?') => match self.peek_char() {
+                        Some('?') => {
+                            self.next_char();
+                            self.stop_token(Ok(Token::QuestionQuestion))
+                        },
+                        _ => self.stop_token(Err(TokenError::QuestionIsNotValid))
+                    },
                     Some('}') if matches!(self.mode,InterpolationMode::CalculateStatBlock) => {
                         let text = self.template_text(&'{');
                         self.stop_token(text)
@@ -287,14 +344,39 @@ impl<Source: Iterator<Item=char>> Iterator for InterpolationTokenizer<Source> {
 
 
 
+/**
+The data which the variables in an interpolated string are looked up in. A [`Creature`](crate::Creature) is one of these, but it can be implemented for other data to use the interpolation engine with it.
+*/
 pub trait InterpolationObject {
 
+    /// Returns the value of a variable, or a property of an object value, if there is one by that name.
     fn get_property(&self, property: &Rc<str>) -> Option<InterpolationValue>;
 
-    // this only needs to be implemented by an array object
+    /// Returns the value at an index. This only needs to be implemented by an array object.
     fn get_index(&self, _index: &usize) -> Option<InterpolationValue> {
         None
     }
+
+    /// Returns the rounding used when dice are averaged, either in converting them to strings or with the 'avg' function.
+    fn average_rounding(&self) -> AverageRounding {
+        AverageRounding::default()
+    }
+}
+
+impl<T: InterpolationObject + ?Sized> InterpolationObject for &T {
+
+    fn get_property(&self, property: &Rc<str>) -> Option<InterpolationValue> {
+        (*self).get_property(property)
+    }
+
+    fn get_index(&self, index: &usize) -> Option<InterpolationValue> {
+        (*self).get_index(index)
+    }
+
+    fn average_rounding(&self) -> AverageRounding {
+        (*self).average_rounding()
+    }
+
 }
 
 impl InterpolationObject for () {
@@ -333,6 +415,10 @@ impl InterpolationObject for OverriddenInterpolationObject {
         }
     }
 
+    fn average_rounding(&self) -> AverageRounding {
+        self.overridden.average_rounding()
+    }
+
 }
 
 impl<T: InterpolationObject> InterpolationObject for Rc<T> {
@@ -345,6 +431,10 @@ impl<T: InterpolationObject> InterpolationObject for Rc<T> {
         self.as_ref().get_index(index)
     }
 
+    fn average_rounding(&self) -> AverageRounding {
+        self.as_ref().average_rounding()
+    }
+
 }
 
 impl InterpolationObject for HashMap<String,String> {
@@ -361,10 +451,14 @@ impl InterpolationObject for HashMap<String,String> {
 
 
 #[derive(Clone)]
+/**
+The value of a variable, or of an expression, in an interpolated string.
+*/
 pub enum InterpolationValue {
     String(Rc<str>),
     Number(isize,bool), // value, whether to display sign in string
     Dice(DiceExpression,bool), // value, whether to display sign in string
+    Boolean(bool),
     #[allow(dead_code)] // FUTURE: I'm leaving this because it might come in handy some day.
     Object(Rc<dyn InterpolationObject>) 
 }
@@ -372,9 +466,15 @@ pub enum InterpolationValue {
 
 impl InterpolationValue {
 
-    fn get_property(&self, property: &Rc<str>) -> Option<InterpolationValue> {
+    fn get_property(&self, property: &Rc<str>, rounding: &AverageRounding) -> Option<InterpolationValue> {
         match self {
             InterpolationValue::Object(object) => object.get_property(property),
+            InterpolationValue::Dice(dice,sign) => match property.as_ref() {
+                "min" => Some(InterpolationValue::Number(dice.minimum(),*sign)),
+                "max" => Some(InterpolationValue::Number(dice.maximum(),*sign)),
+                "avg" => Some(InterpolationValue::Number(dice.rounded_average(rounding),*sign)),
+                _ => None
+            },
             _ => None
         }
     }
@@ -391,15 +491,17 @@ impl InterpolationValue {
             InterpolationValue::Number(num,sign) => Ok(InterpolationValue::Number(-num,*sign)),
             InterpolationValue::Dice(dice,sign) => Ok(InterpolationValue::Dice(dice.multiply(&-1),*sign)),
             InterpolationValue::String(_) => Err(InterpolationError::CantNegateString),
+            InterpolationValue::Boolean(_) => Err(InterpolationError::CantNegateBoolean),
             InterpolationValue::Object(_) => Err(InterpolationError::CantNegateObject)
                 
         }
     }
 
-    fn stringify(&self) -> Result<InterpolationValue,InterpolationError> {
+    fn stringify(&self, rounding: &AverageRounding) -> Result<InterpolationValue,InterpolationError> {
         match self {
             InterpolationValue::Number(_,_) |
-            InterpolationValue::Dice(_,_) => Ok(InterpolationValue::String(Rc::from(format!("{}",self)))),
+            InterpolationValue::Dice(_,_) |
+            InterpolationValue::Boolean(_) => Ok(InterpolationValue::String(Rc::from(self.to_string_with_rounding(rounding)))),
             InterpolationValue::String(_) => Err(InterpolationError::StringIsAlreadyStringified),
             InterpolationValue::Object(_) => Err(InterpolationError::CantStringifyObjects)
                 
@@ -411,18 +513,44 @@ impl InterpolationValue {
             InterpolationValue::Number(num,_) => Ok(InterpolationValue::Number(*num,true)),
             InterpolationValue::Dice(dice,_) => Ok(InterpolationValue::Dice(dice.clone(),true)),
             InterpolationValue::String(_) => Err(InterpolationError::CantSignString),
+            InterpolationValue::Boolean(_) => Err(InterpolationError::CantSignBoolean),
             InterpolationValue::Object(_) => Err(InterpolationError::CantSignObject)
                 
         }
 
     }
 
+    fn average(&self, rounding: &AverageRounding) -> Result<InterpolationValue, InterpolationError> {
+        match self {
+            InterpolationValue::Number(num,sign) => Ok(InterpolationValue::Number(*num,*sign)),
+            InterpolationValue::Dice(dice,sign) => Ok(InterpolationValue::Number(dice.rounded_average(rounding),*sign)),
+            InterpolationValue::String(_) => Err(InterpolationError::CantAverageString),
+            InterpolationValue::Boolean(_) => Err(InterpolationError::CantAverageBoolean),
+            InterpolationValue::Object(_) => Err(InterpolationError::CantAverageObject)
+        }
+    }
+
+    fn expression(&self) -> Result<InterpolationValue, InterpolationError> {
+        match self {
+            InterpolationValue::Number(..) => Ok(InterpolationValue::String(Rc::from(format!("{}",self)))),
+            InterpolationValue::Dice(dice,sign) => Ok(InterpolationValue::String(Rc::from(if *sign && !dice.is_negative() {
+                format!("+{}",dice.serialize_to_string())
+            } else {
+                dice.serialize_to_string()
+            }))),
+            InterpolationValue::String(_) => Err(InterpolationError::CantGetExpressionOfString),
+            InterpolationValue::Boolean(_) => Err(InterpolationError::CantGetExpressionOfBoolean),
+            InterpolationValue::Object(_) => Err(InterpolationError::CantGetExpressionOfObject)
+        }
+    }
+
     fn multiply(&self,rhs: &InterpolationValue) -> Result<InterpolationValue,InterpolationError> {
         match (self,rhs) {
             (InterpolationValue::Number(num,sign),InterpolationValue::Number(rhs,_)) => Ok(InterpolationValue::Number(num*rhs,*sign)),
             (InterpolationValue::Dice(dice,sign),InterpolationValue::Number(rhs,_)) |
             (InterpolationValue::Number(rhs,sign),InterpolationValue::Dice(dice,_)) => Ok(InterpolationValue::Dice(dice.multiply(rhs),*sign)),
             (InterpolationValue::String(_),_) | (_,InterpolationValue::String(_)) => Err(InterpolationError::CantMultiplyStrings),
+            (InterpolationValue::Boolean(_),_) | (_,InterpolationValue::Boolean(_)) => Err(InterpolationError::CantMultiplyBooleans),
             (InterpolationValue::Object(_),_) | (_,InterpolationValue::Object(_)) => Err(InterpolationError::CantMultiplyObjects),
             (InterpolationValue::Dice(..),InterpolationValue::Dice(..)) => Err(InterpolationError::CantMultiplyDice),
             
@@ -434,6 +562,7 @@ impl InterpolationValue {
             (InterpolationValue::Number(num,sign),InterpolationValue::Number(rhs,_)) => Ok(InterpolationValue::Number(num.div_ceiling(rhs),*sign)),
             (InterpolationValue::Dice(dice,sign),InterpolationValue::Number(rhs,_)) => Ok(InterpolationValue::Dice(dice.div_ceiling(rhs),*sign)),
             (InterpolationValue::String(_),_) | (_,InterpolationValue::String(_)) => Err(InterpolationError::CantDivideStrings),
+            (InterpolationValue::Boolean(_),_) | (_,InterpolationValue::Boolean(_)) => Err(InterpolationError::CantDivideBooleans),
             (InterpolationValue::Object(_),_) | (_,InterpolationValue::Object(_)) => Err(InterpolationError::CantDivideObjects),
             (_,InterpolationValue::Dice(..)) => Err(InterpolationError::CantDivideByDice),
 
@@ -445,6 +574,7 @@ impl InterpolationValue {
             (InterpolationValue::Number(num,sign),InterpolationValue::Number(rhs,_)) => Ok(InterpolationValue::Number(num.nms_div_floor(rhs),*sign)),
             (InterpolationValue::Dice(dice,sign),InterpolationValue::Number(rhs,_)) => Ok(InterpolationValue::Dice(dice.div_floor(rhs),*sign)),
             (InterpolationValue::String(_),_) | (_,InterpolationValue::String(_)) => Err(InterpolationError::CantDivideStrings),
+            (InterpolationValue::Boolean(_),_) | (_,InterpolationValue::Boolean(_)) => Err(InterpolationError::CantDivideBooleans),
             (InterpolationValue::Object(_),_) | (_,InterpolationValue::Object(_)) => Err(InterpolationError::CantDivideObjects),
             (_,InterpolationValue::Dice(..)) => Err(InterpolationError::CantDivideByDice),
 
@@ -459,6 +589,7 @@ impl InterpolationValue {
             (InterpolationValue::String(lhs),InterpolationValue::String(rhs)) => Ok(InterpolationValue::String(Rc::from(lhs.as_ref().to_owned() + rhs))),
             (InterpolationValue::Dice(lhs,sign),InterpolationValue::Dice(rhs,_)) => Ok(InterpolationValue::Dice(lhs.add_dice(rhs),*sign)),
             (InterpolationValue::Object(_),_) | (_,InterpolationValue::Object(_)) => Err(InterpolationError::CantAddObjects),
+            (InterpolationValue::Boolean(_),_) | (_,InterpolationValue::Boolean(_)) => Err(InterpolationError::CantAddBooleans),
             (InterpolationValue::String(_),_) | (_,InterpolationValue::String(_)) => Err(InterpolationError::CantConcatenateNonStrings),
 
         }
@@ -472,12 +603,52 @@ impl InterpolationValue {
             (InterpolationValue::Dice(lhs,sign),InterpolationValue::Dice(rhs,_)) => Ok(InterpolationValue::Dice(lhs.subtract_dice(rhs),*sign)),
             (InterpolationValue::Object(_),_) | (_,InterpolationValue::Object(_)) => Err(InterpolationError::CantSubtractObjects),
             (InterpolationValue::String(_),_) | (_,InterpolationValue::String(_)) => Err(InterpolationError::CantSubtractStrings),
+            (InterpolationValue::Boolean(_),_) | (_,InterpolationValue::Boolean(_)) => Err(InterpolationError::CantSubtractBooleans),
+
+        }
+    }
+
+    fn compare(&self,rhs: &InterpolationValue,predicate: fn(&str,&str) -> bool) -> Result<InterpolationValue,InterpolationError> {
+        match (self,rhs) {
+            (InterpolationValue::String(lhs),InterpolationValue::String(rhs)) => Ok(InterpolationValue::Boolean(predicate(lhs,rhs))),
+            _ => Err(InterpolationError::CantCompareNonStrings)
+        }
+    }
 
+    fn equals(&self,rhs: &InterpolationValue) -> Result<bool,InterpolationError> {
+        match (self,rhs) {
+            (InterpolationValue::Number(lhs,_),InterpolationValue::Number(rhs,_)) => Ok(lhs == rhs),
+            (InterpolationValue::Dice(lhs,_),InterpolationValue::Dice(rhs,_)) => Ok(lhs == rhs),
+            (InterpolationValue::String(lhs),InterpolationValue::String(rhs)) => Ok(lhs == rhs),
+            (InterpolationValue::Boolean(lhs),InterpolationValue::Boolean(rhs)) => Ok(lhs == rhs),
+            _ => Err(InterpolationError::CantCheckEquality)
+        }
+    }
+
+    fn order(&self,rhs: &InterpolationValue,predicate: fn(&isize,&isize) -> bool) -> Result<InterpolationValue,InterpolationError> {
+        match (self,rhs) {
+            (InterpolationValue::Number(lhs,_),InterpolationValue::Number(rhs,_)) => Ok(InterpolationValue::Boolean(predicate(lhs,rhs))),
+            _ => Err(InterpolationError::CantOrderNonNumbers)
         }
     }
 
 }
 
+impl InterpolationValue {
+
+    // Dice are formatted with their average, which depends on the rounding, so this is used instead of Display when converting to strings.
+    pub fn to_string_with_rounding(&self, rounding: &AverageRounding) -> String {
+        match self {
+            Self::Dice(dice,sign) => if *sign && !dice.is_negative() {
+                format!("+{}",dice.display_with_rounding(rounding))
+            } else {
+                dice.display_with_rounding(rounding)
+            },
+            _ => self.to_string()
+        }
+    }
+}
+
 impl std::fmt::Display for InterpolationValue {
 
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(),std::fmt::Error> {
@@ -497,6 +668,7 @@ impl std::fmt::Display for InterpolationValue {
                 write!(f,"{}",num)
             }
             Self::String(str) => write!(f,"{}",str),
+            Self::Boolean(bool) => write!(f,"{}",bool),
             Self::Object(_) => write!(f,"<object>")
         }
    }
@@ -522,6 +694,10 @@ enum InterpolationOperation {
     Stringify,
     // replaces the value at the top of the stack with the result of calling 'sign' on it
     Sign,
+    // replaces the value at the top of the stack with the result of calling 'average' on it
+    Average,
+    // replaces the value at the top of the stack with the result of calling 'expression' on it
+    Expression,
     // takes two values off the stack and replaces with the result of calling multiply on them.
     Multiply,
     // takes two values off the stack and replaces with the result of calling floor_divide on them.
@@ -532,8 +708,31 @@ enum InterpolationOperation {
     Add,
     // takes two values off the stack and replaces with the result of calling subtract on them.
     Subtract,
+    // takes two values off the stack and replaces with whether the first contains the second.
+    Contains,
+    // takes two values off the stack and replaces with whether the first starts with the second.
+    StartsWith,
+    // takes two values off the stack and replaces with whether the first ends with the second.
+    EndsWith,
+    // takes two values off the stack and replaces with whether they are equal.
+    Equal,
+    // takes two values off the stack and replaces with whether they are not equal.
+    NotEqual,
+    // takes two values off the stack and replaces with whether the first is less than the second.
+    LessThan,
+    // takes two values off the stack and replaces with whether the first is less than or equal to the second.
+    LessThanOrEqual,
+    // takes two values off the stack and replaces with whether the first is greater than the second.
+    GreaterThan,
+    // takes two values off the stack and replaces with whether the first is greater than or equal to the second.
+    GreaterThanOrEqual,
     // takes value off the stack, stringifies it if necessary, and appends it to the current string
     Append,
+    // evaluates the first document and puts the result on the stack, unless it is an empty string or a lookup in it fails,
+    // in which case the result of evaluating the second document is put on the stack.
+    Default(Document,Document),
+    // takes a boolean off the stack, and evaluates the first document if it is true, or the second if it is false, putting the result on the stack.
+    If(Document,Document),
     
     // structured text operations
     // if italic mode is on, then throw an error, otherwise:
@@ -573,6 +772,7 @@ enum InterpolationOperation {
 /*
 document = expression+ 
 */
+#[derive(Debug)]
 struct Document {
     operations: Vec<(InterpolationOperation,PositionRange)>
 }
@@ -583,8 +783,8 @@ impl Document {
 variable_expression = identifier ('.' identifier)*
 */
     fn parse_variable_expression<Source: Iterator<Item=char>>(tokenizer: &mut InterpolationTokenizer<Source>, operations: &mut Vec<(InterpolationOperation,PositionRange)>, identifier: Rc<str>, position: PositionRange) -> Result<(),InterpolationErrorDetails> {
+        // the identifier has already been consumed by the caller, in order to check for a function call
         operations.push((InterpolationOperation::GetVariable(identifier.clone()),position.clone()));
-        tokenizer.next_ok()?;
         while let Some(Ok((token,_))) = &tokenizer.current {
             match token {
                 Token::Dot => {
@@ -609,7 +809,110 @@ variable_expression = identifier ('.' identifier)*
 
 
 /*
-term = string_literal | number_literal | dice_literal | variable_reference | '(' expression ')'
+function_call = identifier '(' expression ')'
+*/
+    fn parse_function_call<Source: Iterator<Item=char>>(tokenizer: &mut InterpolationTokenizer<Source>, operations: &mut Vec<(InterpolationOperation,PositionRange)>, identifier: Rc<str>, position: PositionRange) -> Result<(),InterpolationErrorDetails> {
+        let operation = match identifier.as_ref() {
+            "avg" => InterpolationOperation::Average,
+            "expr" => InterpolationOperation::Expression,
+            "default" => return Self::parse_default_call(tokenizer,operations,position),
+            "if" => return Self::parse_if_call(tokenizer,operations,position),
+            "contains" => return Self::parse_comparison_call(tokenizer,operations,InterpolationOperation::Contains,position),
+            "startswith" => return Self::parse_comparison_call(tokenizer,operations,InterpolationOperation::StartsWith,position),
+            "endswith" => return Self::parse_comparison_call(tokenizer,operations,InterpolationOperation::EndsWith,position),
+            _ => Err(InterpolationError::UnknownFunction(identifier.to_string()).details(&tokenizer.source_name,&position))?
+        };
+        // the identifier has already been consumed by the caller, skip the open parenthesis
+        tokenizer.next_ok()?;
+        Self::parse_expression(tokenizer,operations)?;
+        if let Some(Ok((Token::CloseParenthesis, ..))) = &tokenizer.current {
+            tokenizer.next_ok()?;
+        } else {
+            Err(InterpolationError::ExpectedCloseParen(format!("{:?}",tokenizer.current)).details(&tokenizer.source_name,&tokenizer.position))?
+        }
+        operations.push((operation,position));
+        Ok(())
+    }
+
+/*
+default_call = 'default' '(' expression ',' expression ')'
+*/
+    fn parse_default_call<Source: Iterator<Item=char>>(tokenizer: &mut InterpolationTokenizer<Source>, operations: &mut Vec<(InterpolationOperation,PositionRange)>, position: PositionRange) -> Result<(),InterpolationErrorDetails> {
+        // the identifier has already been consumed by the caller, skip the open parenthesis
+        tokenizer.next_ok()?;
+        // the arguments are kept separate, since the value is allowed to fail, and the fallback is only needed if it does
+        let mut value = Vec::new();
+        Self::parse_expression(tokenizer,&mut value)?;
+        if let Some(Ok((Token::Comma, ..))) = &tokenizer.current {
+            tokenizer.next_ok()?;
+        } else {
+            Err(InterpolationError::ExpectedComma(format!("{:?}",tokenizer.current)).details(&tokenizer.source_name,&tokenizer.position))?
+        }
+        let mut fallback = Vec::new();
+        Self::parse_expression(tokenizer,&mut fallback)?;
+        if let Some(Ok((Token::CloseParenthesis, ..))) = &tokenizer.current {
+            tokenizer.next_ok()?;
+        } else {
+            Err(InterpolationError::ExpectedCloseParen(format!("{:?}",tokenizer.current)).details(&tokenizer.source_name,&tokenizer.position))?
+        }
+        operations.push((InterpolationOperation::Default(Self { operations: value },Self { operations: fallback }),position));
+        Ok(())
+    }
+
+/*
+if_call = 'if' '(' expression ',' expression ',' expression ')'
+*/
+    fn parse_if_call<Source: Iterator<Item=char>>(tokenizer: &mut InterpolationTokenizer<Source>, operations: &mut Vec<(InterpolationOperation,PositionRange)>, position: PositionRange) -> Result<(),InterpolationErrorDetails> {
+        // the identifier has already been consumed by the caller, skip the open parenthesis
+        tokenizer.next_ok()?;
+        Self::parse_expression(tokenizer,operations)?;
+        // the branches are kept separate, so only the one which is chosen is evaluated
+        let mut branches = Vec::new();
+        for _ in 0..2 {
+            if let Some(Ok((Token::Comma, ..))) = &tokenizer.current {
+                tokenizer.next_ok()?;
+            } else {
+                Err(InterpolationError::ExpectedComma(format!("{:?}",tokenizer.current)).details(&tokenizer.source_name,&tokenizer.position))?
+            }
+            let mut branch = Vec::new();
+            Self::parse_expression(tokenizer,&mut branch)?;
+            branches.push(Self { operations: branch });
+        }
+        if let Some(Ok((Token::CloseParenthesis, ..))) = &tokenizer.current {
+            tokenizer.next_ok()?;
+        } else {
+            Err(InterpolationError::ExpectedCloseParen(format!("{:?}",tokenizer.current)).details(&tokenizer.source_name,&tokenizer.position))?
+        }
+        let otherwise = branches.pop().unwrap();
+        let then = branches.pop().unwrap();
+        operations.push((InterpolationOperation::If(then,otherwise),position));
+        Ok(())
+    }
+
+/*
+comparison_call = ('contains' | 'startswith' | 'endswith') '(' expression ',' expression ')'
+*/
+    fn parse_comparison_call<Source: Iterator<Item=char>>(tokenizer: &mut InterpolationTokenizer<Source>, operations: &mut Vec<(InterpolationOperation,PositionRange)>, operation: InterpolationOperation, position: PositionRange) -> Result<(),InterpolationErrorDetails> {
+        // the identifier has already been consumed by the caller, skip the open parenthesis
+        tokenizer.next_ok()?;
+        Self::parse_expression(tokenizer,operations)?;
+        if let Some(Ok((Token::Comma, ..))) = &tokenizer.current {
+            tokenizer.next_ok()?;
+        } else {
+            Err(InterpolationError::ExpectedComma(format!("{:?}",tokenizer.current)).details(&tokenizer.source_name,&tokenizer.position))?
+        }
+        Self::parse_expression(tokenizer,operations)?;
+        if let Some(Ok((Token::CloseParenthesis, ..))) = &tokenizer.current {
+            tokenizer.next_ok()?;
+        } else {
+            Err(InterpolationError::ExpectedCloseParen(format!("{:?}",tokenizer.current)).details(&tokenizer.source_name,&tokenizer.position))?
+        }
+        operations.push((operation,position));
+        Ok(())
+    }
+
+/*
+term = string_literal | number_literal | dice_literal | variable_reference | function_call | '(' expression ')'
 */
     fn parse_term<Source: Iterator<Item=char>>(tokenizer: &mut InterpolationTokenizer<Source>, operations: &mut Vec<(InterpolationOperation,PositionRange)>) -> Result<(),InterpolationErrorDetails> {
 
@@ -638,7 +941,12 @@ term = string_literal | number_literal | dice_literal | variable_reference | '('
                 Token::Identifier(str) => {
                     let str = str.clone();
                     let position = position.clone();
-                    Self::parse_variable_expression(tokenizer,operations,str,position)?
+                    tokenizer.next_ok()?;
+                    if let Some(Ok((Token::OpenParenthesis,_))) = &tokenizer.current {
+                        Self::parse_function_call(tokenizer,operations,str,position)?
+                    } else {
+                        Self::parse_variable_expression(tokenizer,operations,str,position)?
+                    }
                 },
                 _ => Err(InterpolationError::ExpectedExpression(format!("{:?}",token)).details(&tokenizer.source_name,&tokenizer.position))?
             }),
@@ -790,7 +1098,54 @@ add_expression = mul_expression (('+' | '-') mul_expression)*
 
 
 /*
-expression = text | add_expression
+comparison_expression = add_expression (('==' | '!=' | '<' | '<=' | '>' | '>=') add_expression)?
+*/
+    fn parse_comparison<Source: Iterator<Item=char>>(tokenizer: &mut InterpolationTokenizer<Source>, operations: &mut Vec<(InterpolationOperation,PositionRange)>) -> Result<(),InterpolationErrorDetails> {
+        Self::parse_add(tokenizer,operations)?;
+
+        if let Some(Ok((token,position))) = &tokenizer.current {
+            let position = position.clone();
+            let operation = match token {
+                Token::EqualEqual => InterpolationOperation::Equal,
+                Token::NotEqual => InterpolationOperation::NotEqual,
+                Token::LessThan => InterpolationOperation::LessThan,
+                Token::LessThanOrEqual => InterpolationOperation::LessThanOrEqual,
+                Token::GreaterThan => InterpolationOperation::GreaterThan,
+                Token::GreaterThanOrEqual => InterpolationOperation::GreaterThanOrEqual,
+                _ => return Ok(())
+            };
+            tokenizer.next_ok()?;
+            Self::parse_add(tokenizer,operations)?;
+            operations.push((operation,position));
+        }
+
+        Ok(())
+    }
+
+/*
+default_expression = comparison_expression ('??' default_expression)?
+*/
+    fn parse_default<Source: Iterator<Item=char>>(tokenizer: &mut InterpolationTokenizer<Source>, operations: &mut Vec<(InterpolationOperation,PositionRange)>) -> Result<(),InterpolationErrorDetails> {
+        // the value is parsed separately, in case it needs to be wrapped in a default operation
+        let mut value = Vec::new();
+        Self::parse_comparison(tokenizer,&mut value)?;
+
+        if let Some(Ok((Token::QuestionQuestion,position))) = &tokenizer.current {
+            let position = position.clone();
+            tokenizer.next_ok()?;
+            // the operator is right-associative, so 'a ?? b ?? c' tries each in turn
+            let mut fallback = Vec::new();
+            Self::parse_default(tokenizer,&mut fallback)?;
+            operations.push((InterpolationOperation::Default(Self { operations: value },Self { operations: fallback }),position));
+        } else {
+            operations.append(&mut value);
+        }
+
+        Ok(())
+    }
+
+/*
+expression = text | default_expression
 */
     fn parse_expression<Source: Iterator<Item=char>>(tokenizer: &mut InterpolationTokenizer<Source>, operations: &mut Vec<(InterpolationOperation,PositionRange)>) -> Result<(),InterpolationErrorDetails> {
         match &tokenizer.current {
@@ -799,7 +1154,7 @@ expression = text | add_expression
                 tokenizer.next_ok()?;
                 Ok(())
             },
-            _ => Ok(Self::parse_add(tokenizer,operations)?)
+            _ => Ok(Self::parse_default(tokenizer,operations)?)
         }
 
 
@@ -837,13 +1192,43 @@ document = (command | expression)*
         result
     }
 
+    // parses a lone expression, without the template text and delimiters around it. The value is left on the stack.
+    fn parse_expression_str(source: &str, source_name: &str) -> Result<Document,InterpolationErrorDetails> {
+        let mut tokenizer = InterpolationTokenizer::new(source_name, source.chars(), InterpolationMode::CalculateStatBlock);
+        // skip the initial template text
+        tokenizer.state = InterpolationState::Continuing;
+        let mut operations = vec![];
+        tokenizer.next_ok()?;
+        Self::parse_default(&mut tokenizer,&mut operations)?;
+        match &tokenizer.current {
+            None => Ok(Self {
+                operations
+            }),
+            Some(Ok((token,position))) => Err(InterpolationError::ExpectedEndOfExpression(format!("{:?}",token)).details(source_name,position)),
+            Some(Err(err)) => Err(err.clone().into())
+        }
+    }
+
     fn interpolate<Data: InterpolationObject>(&self, source_name: &str, data: &Data) -> Result<Vec<TextBlock>,InterpolationErrorDetails> {
+        self.execute(source_name, data).map(|(result,_)| result)
+    }
+
+    fn evaluate<Data: InterpolationObject>(&self, source_name: &str, data: &Data) -> Result<InterpolationValue,InterpolationErrorDetails> {
+        match self.execute(source_name, data)? {
+            (_,Some(value)) => Ok(value),
+            (_,None) => Err(InterpolationError::ExpectedExpression("end of expression".to_owned()).details(source_name,&PositionRange::none()))
+        }
+    }
+
+    // runs the operations, returning the structured text and whatever value is left on the stack.
+    fn execute<Data: InterpolationObject>(&self, source_name: &str, data: &Data) -> Result<(Vec<TextBlock>,Option<InterpolationValue>),InterpolationErrorDetails> {
 
 
         let mut result = Vec::new();
         let mut current_string = String::new();
         let mut current_spans = Vec::new();
         let mut stack = Vec::new();
+        let rounding = data.average_rounding();
         let mut italic_mode = false;
         let mut bold_mode = false;
         let mut list_mode = false;
@@ -957,7 +1342,7 @@ document = (command | expression)*
                 },
                 InterpolationOperation::GetProperty(name) => {
                     let value = pop!();
-                    if let Some(value) = value.get_property(name) {
+                    if let Some(value) = value.get_property(name,&rounding) {
                         stack.push(value);
                     } else {
                         error!(UnknownProperty)
@@ -969,13 +1354,21 @@ document = (command | expression)*
                 },
                 InterpolationOperation::Stringify => {
                     let value = pop!();
-                    stack.push(map_err!(value.stringify()))  
+                    stack.push(map_err!(value.stringify(&rounding)))  
                 },
                 InterpolationOperation::Sign => {
                     let value = pop!();
                     stack.push(map_err!(value.signed()))
                     
                 },
+                InterpolationOperation::Average => {
+                    let value = pop!();
+                    stack.push(map_err!(value.average(&rounding)))
+                },
+                InterpolationOperation::Expression => {
+                    let value = pop!();
+                    stack.push(map_err!(value.expression()))
+                },
                 InterpolationOperation::Multiply => {
                     let rhs = pop!();
                     let lhs = pop!();
@@ -1004,9 +1397,75 @@ document = (command | expression)*
                     let lhs = pop!();
                     stack.push(map_err!(lhs.subtract(&rhs)))
                 },
+                InterpolationOperation::Contains => {
+                    let rhs = pop!();
+                    let lhs = pop!();
+                    stack.push(map_err!(lhs.compare(&rhs,|lhs,rhs| lhs.contains(rhs))))
+                },
+                InterpolationOperation::StartsWith => {
+                    let rhs = pop!();
+                    let lhs = pop!();
+                    stack.push(map_err!(lhs.compare(&rhs,|lhs,rhs| lhs.starts_with(rhs))))
+                },
+                InterpolationOperation::EndsWith => {
+                    let rhs = pop!();
+                    let lhs = pop!();
+                    stack.push(map_err!(lhs.compare(&rhs,|lhs,rhs| lhs.ends_with(rhs))))
+                },
+                InterpolationOperation::Equal => {
+                    let rhs = pop!();
+                    let lhs = pop!();
+                    stack.push(InterpolationValue::Boolean(map_err!(lhs.equals(&rhs))))
+                },
+                InterpolationOperation::NotEqual => {
+                    let rhs = pop!();
+                    let lhs = pop!();
+                    stack.push(InterpolationValue::Boolean(!map_err!(lhs.equals(&rhs))))
+                },
+                InterpolationOperation::LessThan => {
+                    let rhs = pop!();
+                    let lhs = pop!();
+                    stack.push(map_err!(lhs.order(&rhs,|lhs,rhs| lhs < rhs)))
+                },
+                InterpolationOperation::LessThanOrEqual => {
+                    let rhs = pop!();
+                    let lhs = pop!();
+                    stack.push(map_err!(lhs.order(&rhs,|lhs,rhs| lhs <= rhs)))
+                },
+                InterpolationOperation::GreaterThan => {
+                    let rhs = pop!();
+                    let lhs = pop!();
+                    stack.push(map_err!(lhs.order(&rhs,|lhs,rhs| lhs > rhs)))
+                },
+                InterpolationOperation::GreaterThanOrEqual => {
+                    let rhs = pop!();
+                    let lhs = pop!();
+                    stack.push(map_err!(lhs.order(&rhs,|lhs,rhs| lhs >= rhs)))
+                },
                 InterpolationOperation::Append => {
                     let value = pop!();
-                    current_string.push_str(&value.to_string());                    
+                    current_string.push_str(&value.to_string_with_rounding(&rounding));                    
+                },
+                InterpolationOperation::Default(value,fallback) => {
+                    let value = match value.evaluate(source_name, data) {
+                        Ok(InterpolationValue::String(str)) if str.is_empty() => None,
+                        Ok(value) => Some(value),
+                        // only a missing value falls back, other errors are still mistakes in the expression.
+                        Err(InterpolationErrorDetails { error: InterpolationError::UnknownVariable | InterpolationError::UnknownProperty | InterpolationError::InvalidIndex, .. }) => None,
+                        Err(err) => Err(err)?
+                    };
+                    if let Some(value) = value {
+                        stack.push(value)
+                    } else {
+                        stack.push(fallback.evaluate(source_name, data)?)
+                    }
+                },
+                InterpolationOperation::If(then,otherwise) => {
+                    match pop!() {
+                        InterpolationValue::Boolean(true) => stack.push(then.evaluate(source_name, data)?),
+                        InterpolationValue::Boolean(false) => stack.push(otherwise.evaluate(source_name, data)?),
+                        _ => error!(ConditionIsNotBoolean)
+                    }
                 },
 
                 InterpolationOperation::StartItalic => {
@@ -1065,7 +1524,7 @@ document = (command | expression)*
         end_span!();
         end_block!();
 
-        Ok(result)
+        Ok((result,stack.pop()))
     }
 
 
@@ -1112,6 +1571,20 @@ pub fn interpolate_str_for_deserialization<Data: InterpolationObject>(source: &s
     }
 }
 
+/**
+Checks that the source is a valid expression, as would be found between `${` and `}` in a description, without evaluating it.
+*/
+pub fn check_expression_str(source: &str, source_name: &str) -> Result<(),InterpolationErrorDetails> {
+    Document::parse_expression_str(source, source_name).map(|_| ())
+}
+
+/**
+Evaluates an expression, as would be found between `${` and `}` in a description, returning the value instead of text.
+*/
+pub fn evaluate_expression_str<Data: InterpolationObject>(source: &str, source_name: &str, data: &Data) -> Result<InterpolationValue,InterpolationErrorDetails> {
+    Document::parse_expression_str(source, source_name).and_then(|a| a.evaluate(source_name, data))
+}
+
 pub fn interpolate_str_for_statblock<Data: InterpolationObject>(source: &str, source_name: &str, data: &Data, show_text_in_error: bool) -> Result<Vec<TextBlock>,InterpolationErrorDetails> {
     match Document::parse_str(source, source_name, InterpolationMode::CalculateStatBlock).and_then(|a| a.interpolate(source_name,data)) {
         Ok(value) => Ok(value),
@@ -1124,6 +1597,26 @@ pub fn interpolate_str_for_statblock<Data: InterpolationObject>(source: &str, so
 }
 
 
+/**
+The result of [`interpolate_str`], depending on the mode.
+*/
+pub enum InterpolatedText {
+    /// From [`InterpolationMode::DeserializeCreatureCommands`]
+    Plain(String),
+    /// From [`InterpolationMode::CalculateStatBlock`]
+    Structured(Vec<TextBlock>)
+}
+
+/**
+Interpolates a string with the variables from any data, so the interpolation engine can be used for text other than creatures. The syntax is the same as for descriptions in a creature file. The `source_name` is used to identify the string in errors.
+*/
+pub fn interpolate_str(source: &str, source_name: &str, data: &dyn InterpolationObject, mode: InterpolationMode) -> Result<InterpolatedText,InterpolationErrorDetails> {
+    match mode {
+        InterpolationMode::DeserializeCreatureCommands => interpolate_str_for_deserialization(source, source_name, &data, true).map(InterpolatedText::Plain),
+        InterpolationMode::CalculateStatBlock => interpolate_str_for_statblock(source, source_name, &data, true).map(InterpolatedText::Structured)
+    }
+}
+
 pub fn interpolate_simple_markdown_naively(heading_source: &str, source: &str, source_name: &str, use_sub_block: bool, show_text_in_error: bool) -> Result<Vec<TextBlock>,InterpolationErrorDetails> {
 
     // simple parsing
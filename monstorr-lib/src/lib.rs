@@ -35,6 +35,7 @@ There are a few things that you may need to know in order to simply use Monstorr
 
 * For the Monstorr command line tool usage, run `monstorr help`.
 * For the creature file syntax, see [`crate::creature_commands`].
+* To build creatures in your own program, without a creature file, see [`CreatureBuilder`]. The stat-block is produced from the creature with [`crate::stat_block::TryIntoStatBlock`].
 <!--* For templating information, see [`crate::template`], but for its syntax use [MiniJinja](https://docs.rs/minijinja/0.13.0/minijinja/syntax/index.html)
 * For how to use this with the Open5e monster database, see [`crate::open5e_convertor`] as well as the separate `monstorr-open5e` crate.-->
 
@@ -45,7 +46,9 @@ There are a few things that you may need to know in order to simply use Monstorr
  */
 use std::fs;
 use std::path::PathBuf;
-use std::io::BufRead;
+use std::path::Path;
+use std::collections::HashMap;
+use std::io::Read;
 use std::io::Write;
 
 use monstorr_open5e::Open5eMonster;
@@ -54,51 +57,93 @@ use monstorr_data;
 use monstorr_data::creatures::CreatureSummary;
 use monstorr_data::templates::StoredTemplates;
 use monstorr_data::templates::TemplateOptions;
+pub use monstorr_data::templates::CardSize;
+pub use monstorr_data::templates::HtmlOptions;
 
 mod utils;
 mod parse_position;
-mod errors;
-mod dice;
+pub mod errors;
+pub mod dice;
 mod tokenizer;
-mod dice_expression;
-mod structured_text;
+pub mod dice_expression;
+pub mod structured_text;
 mod interpolation;
-mod stats;
-mod attacks;
-mod actions;
-mod reactions;
-mod features;
-mod spellcasting;
-mod creature_commands;
-mod stat_block;
-mod creature;
+pub mod stats;
+pub mod attacks;
+pub mod actions;
+pub mod reactions;
+pub mod features;
+pub mod spellcasting;
+pub mod creature_commands;
+pub mod stat_block;
+pub mod creature;
+mod challenge_rating;
 mod open5e_convertor;
 mod text_escaper;
 mod template;
+mod encounter;
+mod spell_index;
+mod pdf_form;
+mod foundry;
+mod dndbeyond;
+mod text_format;
+mod flat_format;
 #[cfg(test)] mod tests;
 
 
-use crate::creature_commands::CreatureCreator;
 use crate::stat_block::TryIntoStatBlock;
+use crate::stat_block::CreatureStatBlock;
+use crate::stat_block::StatBlockDiagnostics;
 use crate::stats::ChallengeRating;
+use crate::stats::Alignment;
 use crate::template::process_template;
+use crate::interpolation::interpolate_str_for_deserialization;
 use crate::utils::path_relative_from;
 use crate::utils::to_kebab_case;
 use crate::template::TemplateSourceResolver;
 use crate::text_escaper::Escapable;
 use crate::text_escaper::escape_latex;
+use crate::spell_index::SpellIndex;
+use crate::pdf_form::get_form_values;
+use crate::pdf_form::map_form_values;
+use crate::pdf_form::fill_pdf_form;
+use crate::foundry::FoundryAttack;
+use crate::foundry::get_foundry_attacks;
+use crate::foundry::write_foundry_actor;
+use crate::dndbeyond::write_dndbeyond_monster;
+use crate::text_format::write_text;
+use crate::flat_format::load_flat_creature_commands;
 
 pub use creature_commands::MONSTORR_VERSION;
+pub use creature_commands::CreatureCommand;
+pub use creature_commands::CreatureCreator;
+pub use creature_commands::CreatureBuilder;
+pub use creature::Creature;
+pub use encounter::Encounter;
+pub use encounter::EncounterDifficulty;
+pub use stat_block::StatBlockStyle;
+pub use stat_block::StatBlockLabels;
+pub use spell_index::SpellIndexFormat;
+pub use interpolation::InterpolationObject;
+pub use interpolation::InterpolationValue;
+pub use interpolation::InterpolationMode;
+pub use interpolation::InterpolatedText;
+pub use interpolation::interpolate_str;
 
 pub enum InputFormat {
     Creature(Option<String>),
+    TOMLCreature(Option<String>),
+    FlatCreature(Option<String>),
     Open5e(Option<String>),
-    Open5eList(Option<String>,String),
+    LenientOpen5e(Option<String>),
+    Open5eList(Vec<String>,String),
+    LenientOpen5eList(Vec<String>,String),
     Stored(String)
 }
 
 pub enum ListInputFormat {
     Open5eList,
+    LenientOpen5eList,
     Stored
 }
 
@@ -109,19 +154,76 @@ impl Default for InputFormat {
     }
 }
 
+impl InputFormat {
 
+    fn creature_file_format(&self) -> CreatureFileFormat {
+        match self {
+            Self::TOMLCreature(_) => CreatureFileFormat::TOML,
+            Self::FlatCreature(_) => CreatureFileFormat::Flat,
+            _ => CreatureFileFormat::RON
+        }
+    }
+}
+
+/**
+The serialization format of a creature file. See [`crate::creature_commands`] for how creature commands are represented in TOML. TOML is only available if monstorr-lib is compiled with the 'toml-format' feature. The flat format, see [`crate::flat_format`], can only be read.
+*/
+pub enum CreatureFileFormat {
+    RON,
+    TOML,
+    Flat
+}
+
+impl Default for CreatureFileFormat {
+
+    fn default() -> Self {
+        Self::RON
+    }
+}
+
+
+#[derive(Clone)]
 pub enum OutputFormat {
-    JSON(bool), // whether to print ugly
+    JSON(bool,bool), // whether to print ugly, whether to include diagnostics
+    CompactJSON(bool), // whether to print ugly
     MiniJinjaTemplate(String,Vec<String>), // path to template, paths to templates to be included
-    HTML(Option<usize>,bool), // an optional usize indicating that they want a two-column stat-block instead of one-column, a bool indicating that only a fragment should be output
-    LaTeX(),
+    HTML(HtmlOptions), // the options for the page and stat-block, such as two columns, a footer link or a card size
+    LaTeX(bool,Option<String>,Option<String>,Option<String>), // a bool indicating that a standalone document should be output instead of a fragment, an optional color for the text as six hexadecimal digits, an optional font family name, an optional page margin for the standalone document
     Plain(),
+    Text(usize), // the width to wrap the lines at
+    Discord(),
+    PDFForm(String,Option<String>), // path to the PDF form to fill, path to a mapping of form fields to stat-block properties
+    Foundry(bool), // whether to print ugly
+    DndBeyond(bool), // whether to print ugly
 }
 
+// Discord won't post messages longer than this without a subscription.
+const DISCORD_MESSAGE_LIMIT: usize = 2000;
+
 impl Default for OutputFormat {
 
     fn default() -> Self {
-        Self::JSON(false)
+        Self::JSON(false,false)
+    }
+}
+
+impl OutputFormat {
+
+    // the extension for files written in this format, a template's output is assumed to have the same extension as the template.
+    fn file_extension(&self) -> String {
+        match self {
+            Self::JSON(..) => "json".to_owned(),
+            Self::CompactJSON(..) => "json".to_owned(),
+            Self::MiniJinjaTemplate(template,_) => Path::new(template).extension().and_then(|extension| extension.to_str()).unwrap_or("txt").to_owned(),
+            Self::HTML(..) => "html".to_owned(),
+            Self::LaTeX(..) => "tex".to_owned(),
+            Self::Plain() => "txt".to_owned(),
+            Self::Text(..) => "txt".to_owned(),
+            Self::Discord() => "md".to_owned(),
+            Self::PDFForm(..) => "pdf".to_owned(),
+            Self::Foundry(..) => "json".to_owned(),
+            Self::DndBeyond(..) => "json".to_owned()
+        }
     }
 }
 
@@ -154,8 +256,11 @@ fn get_default_working_dir() -> Result<PathBuf,String> {
     std::env::current_dir().map_err(|e| format!("Error getting current directory: {}",e))
 }
 
-fn get_working_dir_relative_to_source_or_default(source: &Option<PathBuf>, default_working_dir: &PathBuf) -> PathBuf {
-    if let Some(source_file) = source {
+fn get_working_dir_relative_to_source_or_default(source: &Option<PathBuf>, base_dir: &Option<PathBuf>, default_working_dir: &PathBuf) -> PathBuf {
+    if let Some(base_dir) = base_dir {
+        // an explicit base directory takes precedence, so includes can be found when reading from stdin.
+        base_dir.clone()
+    } else if let Some(source_file) = source {
         let mut working_dir = source_file.clone();
         working_dir.pop();
         working_dir
@@ -164,25 +269,130 @@ fn get_working_dir_relative_to_source_or_default(source: &Option<PathBuf>, defau
     }
 }
 
+// the base directory, and the output file, are given relative to the working directory.
+fn resolve_base_dir_and_target(working_dir: &PathBuf, base_dir: Option<&str>, output_file: Option<&str>) -> Result<(Option<PathBuf>,Option<PathBuf>),String> {
+    let base_dir = base_dir.map(|base_dir| resolve_existing_dir(working_dir, base_dir)).transpose()?;
+    let target_file = output_file.map(|output_file| resolve_file(working_dir, output_file));
+    Ok((base_dir,target_file))
+}
+
 fn read_source(source_file: Option<&PathBuf>) -> Result<String,String> {
     if let Some(source_file) = source_file {
         fs::read_to_string(&source_file).map_err(|e| format!("Error reading input file: {}",e))
     } else {
-        let mut buffer = String::new();
         let stdin = std::io::stdin();
         let stdin = stdin.lock();
-        for line in stdin.lines() {
-            buffer.push_str(&line.map_err(|e| format!("Error reading from stdin: {}",e))?)
+        read_source_from_stdin(stdin)
+    }
+}
+
+// reads all of the input as it is, the line breaks are needed by the flat and TOML formats, and for the positions in errors.
+fn read_source_from_stdin<Reader: Read>(mut stdin: Reader) -> Result<String,String> {
+    let mut buffer = String::new();
+    stdin.read_to_string(&mut buffer).map_err(|e| format!("Error reading from stdin: {}",e))?;
+    Ok(buffer)
+}
+
+// reads each of the files as an open5e list and merges them together, or reads a single list from stdin if there are no files. If lenient, creatures which don't match the expected format are read as well as possible, and the problems are printed as warnings.
+fn load_open5e_list(working_dir: &PathBuf, input_files: &[String], lenient: bool) -> Result<Open5eMonsterList,String> {
+    let load = |source: &str, source_name: &str| -> Result<Open5eMonsterList,String> {
+        if lenient {
+            let (list,diagnostics) = Open5eMonsterList::load_from_str_lenient(source).map_err(|e| format!("Error loading open5e monster{}: {}",source_name,e))?;
+            report_warnings(diagnostics, &WarningLevel::Warn)?;
+            Ok(list)
+        } else {
+            Open5eMonsterList::load_from_str(source).map_err(|e| format!("Error loading open5e monster{}: {}",source_name,e))
         }
-        Ok(buffer)
+    };
+
+    if input_files.is_empty() {
+        let source = read_source(None)?;
+        return load(&source,"")
+    }
+
+    let mut result = Open5eMonsterList {
+        count: 0,
+        next: None,
+        previous: None,
+        results: Vec::new()
+    };
+    for input_file in input_files {
+        let source_file = resolve_existing_file(working_dir, input_file)?;
+        let source = read_source(Some(&source_file))?;
+        let list = load(&source,&format!(" from '{}'",input_file))?;
+        result.append(list)
+    }
+    Ok(result)
+}
+
+fn interpolate_source(source: String, source_name: &str, parameters: &HashMap<String,String>) -> Result<String,String> {
+    // Only interpolate if it's actually used, as the escaping rules for interpolation might otherwise change the meaning of creature files that were never meant to be interpolated.
+    if source.contains("$<") {
+        interpolate_str_for_deserialization(&source, source_name, parameters, false).map_err(|e| format!("Error interpolating creature commands: {}",e))
+    } else {
+        Ok(source)
     }
 }
 
-fn write_target(target_file: Option<PathBuf>, content: &str) -> Result<(),String> {
+fn load_creature_commands(source: &str) -> Result<CreatureCreator,String> {
+    load_creature_commands_in_format(source, &CreatureFileFormat::RON)
+}
+
+fn load_creature_commands_in_format(source: &str, format: &CreatureFileFormat) -> Result<CreatureCreator,String> {
+    // an empty file is an easy mistake to make, and the error from the parser doesn't make that obvious.
+    let empty_message = "The creature file is empty or contains no commands.";
+    if source.trim().is_empty() {
+        Err(empty_message.to_owned())
+    } else {
+        let creator = match format {
+            CreatureFileFormat::RON => CreatureCreator::load_from_str(source).map_err(|e| format!("Error loading creature commands: {}",e))?,
+            CreatureFileFormat::TOML => load_toml_creature_commands(source)?,
+            CreatureFileFormat::Flat => load_flat_creature_commands(source)?
+        };
+        if creator.0.is_empty() {
+            Err(empty_message.to_owned())
+        } else {
+            Ok(creator)
+        }
+    }
+}
+
+fn save_creature_commands(creator: &CreatureCreator, format: &CreatureFileFormat) -> Result<String,String> {
+    match format {
+        CreatureFileFormat::RON => creator.save_to_string().map_err(|e| format!("Error writing creature to string: {}",e)),
+        CreatureFileFormat::TOML => save_toml_creature_commands(creator),
+        CreatureFileFormat::Flat => Err("Creature files can't be written in the flat format.".to_owned())
+    }
+}
+
+#[cfg(feature = "toml-format")]
+fn load_toml_creature_commands(source: &str) -> Result<CreatureCreator,String> {
+    CreatureCreator::load_from_toml_str(source).map_err(|e| format!("Error loading creature commands: {}",e))
+}
+
+#[cfg(not(feature = "toml-format"))]
+fn load_toml_creature_commands(_source: &str) -> Result<CreatureCreator,String> {
+    Err(TOML_NOT_SUPPORTED.to_owned())
+}
+
+#[cfg(feature = "toml-format")]
+fn save_toml_creature_commands(creator: &CreatureCreator) -> Result<String,String> {
+    creator.save_to_toml_string().map_err(|e| format!("Error writing creature to string: {}",e))
+}
+
+#[cfg(not(feature = "toml-format"))]
+fn save_toml_creature_commands(_creator: &CreatureCreator) -> Result<String,String> {
+    Err(TOML_NOT_SUPPORTED.to_owned())
+}
+
+#[cfg(not(feature = "toml-format"))]
+const TOML_NOT_SUPPORTED: &str = "This build of monstorr can not read or write TOML creature files, it must be compiled with the 'toml-format' feature.";
+
+fn write_target<Content: AsRef<[u8]>>(target_file: Option<PathBuf>, content: Content) -> Result<(),String> {
     if let Some(target_file) = target_file {
         fs::write(target_file, content).map_err(|e| format!("Error writing file: {}",e))
     } else {
-        std::io::stdout().write_all(content.as_bytes()).map_err(|e| format!("Error writing to stdout: {}",e))
+        std::io::stdout().write_all(content.as_ref()).map_err(|e| format!("Error writing to stdout: {}",e))
     }
 }
 
@@ -196,7 +406,7 @@ fn get_source_file(working_dir: &PathBuf, input_file: Option<&str>) -> Result<Op
 }
 
 pub fn validate_creature(input_format: InputFormat, 
-                         output_file: Option<&str>) -> Result<(),String> {
+                         output_file: Option<&str>, output_format: CreatureFileFormat) -> Result<(),String> {
     let working_dir = get_default_working_dir()?;
 
     let target_file = if let Some(output_file) = output_file {
@@ -205,8 +415,12 @@ pub fn validate_creature(input_format: InputFormat,
         None
     };
 
+    let input_file_format = input_format.creature_file_format();
+
     let source = match input_format {
-        InputFormat::Creature(input_file) => {
+        InputFormat::Creature(input_file) |
+        InputFormat::TOMLCreature(input_file) |
+        InputFormat::FlatCreature(input_file) => {
             let source_file = get_source_file(&working_dir, input_file.as_deref())?;
             // get the data from the file
             read_source(source_file.as_ref())?
@@ -222,8 +436,8 @@ pub fn validate_creature(input_format: InputFormat,
     };
 
     // deserialize the commands
-    let creator = CreatureCreator::load_from_str(&source).map_err(|e| format!("Error loading creature commands: {}",e))?;
-    let output = creator.save_to_string().map_err(|e| format!("Error writing creature to string: {}",e))?;
+    let creator = load_creature_commands_in_format(&source, &input_file_format)?;
+    let output = save_creature_commands(&creator, &output_format)?;
 
 
     write_target(target_file, &output)
@@ -247,28 +461,228 @@ impl TemplateSourceResolver for PathBuf {
 
 
 
-pub fn create_stat_block(input_format: InputFormat, 
-                         output_file: Option<&str>, output_format: OutputFormat) -> Result<(),String> {
+/**
+How to report warnings about commands in a creature file which may be mistakes, such as a `RemoveAction` which didn't find anything to remove. Warnings about the finished creature, such as a suspicious multiattack or two actions with the same name, are reported even if these are ignored.
+*/
+#[derive(Clone)]
+pub enum WarningLevel {
+    Ignore,
+    Warn,
+    Error
+}
+
+impl Default for WarningLevel {
+
+    fn default() -> Self {
+        Self::Ignore
+    }
+}
+
+fn report_warnings(warnings: Vec<String>, level: &WarningLevel) -> Result<(),String> {
+    match level {
+        WarningLevel::Ignore => Ok(()),
+        WarningLevel::Warn => {
+            for warning in warnings {
+                eprintln!("Warning: {}",warning)
+            }
+            Ok(())
+        },
+        WarningLevel::Error => if warnings.is_empty() {
+            Ok(())
+        } else {
+            Err(warnings.join("\n"))
+        }
+    }
+}
+
+fn create_creature_from_source(source: String, source_name: &str, source_format: &CreatureFileFormat, working_dir: &PathBuf, warnings: &WarningLevel, lint: bool, parameters: &HashMap<String,String>) -> Result<Creature,String> {
+    // interpolate the parameters, just as with an included file
+    let source = interpolate_source(source, source_name, parameters)?;
+    // deserialize the commands
+    let mut creator = load_creature_commands_in_format(&source, source_format)?;
+    if lint {
+        creator.0.push(CreatureCommand::LintOverrides)
+    }
+    let (creature,found_warnings,mistakes) = creator.create_creature_with_separate_warnings(working_dir,parameters).map_err(|e| format!("{}",e))?;
+    report_warnings(found_warnings, warnings)?;
+    let mistake_level = match warnings {
+        WarningLevel::Ignore => &WarningLevel::Warn,
+        level => level
+    };
+    report_warnings(mistakes, mistake_level)?;
+    Ok(creature)
+}
+
+/**
+Builds the creature and writes out the interpolation properties available to its descriptions, with their values, one per line. This includes any parameters. Properties which aren't available for the creature, such as `spell_atk` for a creature without spellcasting, are listed as unavailable.
+*/
+pub fn list_creature_properties(input_format: InputFormat, output_file: Option<&str>, warnings: WarningLevel, lint: bool, parameters: &HashMap<String,String>, base_dir: Option<&str>) -> Result<(),String> {
     let working_dir = get_default_working_dir()?;
 
-    let target_file = if let Some(output_file) = output_file {
-        Some(resolve_file(&working_dir, output_file))
+    let (base_dir,target_file) = resolve_base_dir_and_target(&working_dir, base_dir, output_file)?;
+
+    let source_format = input_format.creature_file_format();
+
+    let creature = match input_format {
+        InputFormat::Creature(input_file) |
+        InputFormat::TOMLCreature(input_file) |
+        InputFormat::FlatCreature(input_file) => {
+            let source_file = get_source_file(&working_dir, input_file.as_deref())?;
+            let source = read_source(source_file.as_ref())?;
+            let source_name = source_file.as_ref().map(|a| a.display().to_string()).unwrap_or_else(|| "stdin".to_owned());
+            let working_dir = get_working_dir_relative_to_source_or_default(&source_file, &base_dir, &working_dir);
+            create_creature_from_source(source, &source_name, &source_format, &working_dir, &warnings, lint, parameters)?
+        },
+        InputFormat::Stored(creature_name) => {
+            let source = if let Some(entry) = monstorr_data::creatures::STORED_CREATURES.iter().find(|(creature,_)| (creature.slug == creature_name) || (creature.name == creature_name)) {
+                entry.1
+            } else {
+                Err("Couldn't find creature in list.".to_owned())?
+            };
+            let working_dir = get_working_dir_relative_to_source_or_default(&None, &base_dir, &working_dir);
+            create_creature_from_source(source.to_owned(), &creature_name, &CreatureFileFormat::RON, &working_dir, &warnings, lint, parameters)?
+        },
+        _ => Err("Input must be a creature file.".to_owned())?
+    };
+
+    let mut output = String::new();
+    for (name,value) in creature.list_properties() {
+        output.push_str(&format!("{}: {}\n",name,value.unwrap_or_else(|| "(unavailable)".to_owned())));
+    }
+
+    write_target(target_file, &output)
+
+}
+
+/**
+Writes out the creature commands after parameters have been interpolated and all included files have been inlined, without building the creature. Unlike `validate_creature`, this shows the flattened source, which helps track down why an included command didn't take effect.
+*/
+pub fn preprocess_creature(input_format: InputFormat, output_file: Option<&str>, output_format: CreatureFileFormat, parameters: &HashMap<String,String>, base_dir: Option<&str>) -> Result<(),String> {
+    let working_dir = get_default_working_dir()?;
+
+    let (base_dir,target_file) = resolve_base_dir_and_target(&working_dir, base_dir, output_file)?;
+
+    let source_format = input_format.creature_file_format();
+
+    let (source,source_name,working_dir) = match input_format {
+        InputFormat::Creature(input_file) |
+        InputFormat::TOMLCreature(input_file) |
+        InputFormat::FlatCreature(input_file) => {
+            let source_file = get_source_file(&working_dir, input_file.as_deref())?;
+            let source = read_source(source_file.as_ref())?;
+            let source_name = source_file.as_ref().map(|a| a.display().to_string()).unwrap_or_else(|| "stdin".to_owned());
+            let working_dir = get_working_dir_relative_to_source_or_default(&source_file, &base_dir, &working_dir);
+            (source,source_name,working_dir)
+        },
+        InputFormat::Stored(creature_name) => {
+            let source = if let Some(entry) = monstorr_data::creatures::STORED_CREATURES.iter().find(|(creature,_)| (creature.slug == creature_name) || (creature.name == creature_name)) {
+                entry.1
+            } else {
+                Err("Couldn't find creature in list.".to_owned())?
+            };
+            let working_dir = get_working_dir_relative_to_source_or_default(&None, &base_dir, &working_dir);
+            (source.to_owned(),creature_name,working_dir)
+        },
+        _ => Err("Input must be a creature file.".to_owned())?
+    };
+
+    let source = interpolate_source(source, &source_name, parameters)?;
+    let creator = load_creature_commands_in_format(&source, &source_format)?;
+    let creator = creator.expand_includes(&working_dir).map_err(|e| format!("{}",e))?;
+    let output = save_creature_commands(&creator, &output_format)?;
+
+    write_target(target_file, &output)
+
+}
+
+/**
+Returns the files which the input is read from, including the files included by a creature file. This is meant for rebuilding the output whenever one of them changes. Stored creatures, and input read from stdin, can't be watched. If the creature file can't be loaded, such as while it is being edited, only the creature file itself is returned.
+*/
+pub fn get_input_files(input_format: &InputFormat, parameters: &HashMap<String,String>, base_dir: Option<&str>) -> Result<Vec<PathBuf>,String> {
+    let working_dir = get_default_working_dir()?;
+
+    let (base_dir,_) = resolve_base_dir_and_target(&working_dir, base_dir, None)?;
+
+    match input_format {
+        InputFormat::Creature(Some(input_file)) |
+        InputFormat::TOMLCreature(Some(input_file)) |
+        InputFormat::FlatCreature(Some(input_file)) => {
+            let source_file = resolve_existing_file(&working_dir, input_file)?;
+            let mut result = vec![source_file.clone()];
+            let source_name = source_file.display().to_string();
+            let source = read_source(Some(&source_file))?;
+            let working_dir = get_working_dir_relative_to_source_or_default(&Some(source_file), &base_dir, &working_dir);
+            if let Ok(creator) = interpolate_source(source, &source_name, parameters).and_then(|source| load_creature_commands_in_format(&source, &input_format.creature_file_format())) {
+                result.extend(creator.find_included_files(&working_dir))
+            }
+            Ok(result)
+        },
+        InputFormat::Open5e(Some(input_file)) |
+        InputFormat::LenientOpen5e(Some(input_file)) => Ok(vec![resolve_existing_file(&working_dir, input_file)?]),
+        InputFormat::Open5eList(input_files,_) |
+        InputFormat::LenientOpen5eList(input_files,_) if !input_files.is_empty() => input_files.iter().map(|input_file| resolve_existing_file(&working_dir, input_file)).collect(),
+        InputFormat::Stored(_) => Err("A stored creature has no input files.".to_owned()),
+        _ => Err("The input must be read from a file, not stdin.".to_owned())
+    }
+}
+
+fn creature_to_stat_block(creature: Creature, with_diagnostics: bool, with_attacks: bool) -> Result<(CreatureStatBlock,Vec<FoundryAttack>),String> {
+    // the diagnostics and attacks come from the creature, which isn't available once it becomes a stat block.
+    let diagnostics = StatBlockDiagnostics {
+        completeness: creature.get_completeness(),
+        effective_hit_points: creature.get_effective_hit_points()
+    };
+    let attacks = if with_attacks {
+        get_foundry_attacks(&creature)
     } else {
-        None
+        Vec::new()
     };
+    let mut stat_block = creature.try_into_stat_block().map_err(|e| format!("{}",e))?;
+    if with_diagnostics {
+        stat_block.diagnostics = Some(diagnostics)
+    }
+    Ok((stat_block,attacks))
+}
 
+/**
+The options for building a creature and writing its stat block, apart from the input and the output. The defaults are the classic style, with no parameters, and warnings and notes left out.
+*/
+#[derive(Clone,Default)]
+pub struct StatBlockOptions {
+    pub style: StatBlockStyle, // the style of presentation for the stat block
+    pub warnings: WarningLevel, // how to report commands which may be mistakes
+    pub lint: bool, // if set, overrides which don't match the calculated values are also reported
+    pub with_notes: bool, // if set, the notes on the creature are kept in the output
+    pub parameters: HashMap<String,String>, // values to interpolate into the creature file
+    pub base_dir: Option<String>, // if set, included files are found relative to this directory instead of the creature file
+    pub lang_file: Option<String>, // if set, a file with the labels to use instead of the English ones
+}
 
-    let stat_block = match input_format {
-        InputFormat::Creature(input_file) => {
+pub fn create_stat_block(input_format: InputFormat, 
+                         output_file: Option<&str>, output_format: OutputFormat, options: &StatBlockOptions) -> Result<(),String> {
+    let working_dir = get_default_working_dir()?;
+
+    let (base_dir,target_file) = resolve_base_dir_and_target(&working_dir, options.base_dir.as_deref(), output_file)?;
+
+
+    let source_format = input_format.creature_file_format();
+
+    let with_diagnostics = matches!(output_format,OutputFormat::JSON(_,true));
+    let with_attacks = matches!(output_format,OutputFormat::Foundry(_));
+    let lenient_open5e = matches!(input_format,InputFormat::LenientOpen5eList(..));
+
+    let (mut stat_block,attacks) = match input_format {
+        InputFormat::Creature(input_file) |
+        InputFormat::TOMLCreature(input_file) |
+        InputFormat::FlatCreature(input_file) => {
             let source_file = get_source_file(&working_dir, input_file.as_deref())?;
             // get the data from the file
             let source = read_source(source_file.as_ref())?;
+            let source_name = source_file.as_ref().map(|a| a.display().to_string()).unwrap_or_else(|| "stdin".to_owned());
             // the final working directory should be the directory in which the source file is located.
-            let working_dir = get_working_dir_relative_to_source_or_default(&source_file, &working_dir);
-            // deserialize the commands
-            let creator = CreatureCreator::load_from_str(&source).map_err(|e| format!("Error loading creature commands: {}",e))?;
-            let creature = creator.create_creature(&working_dir).map_err(|e| format!("{}",e))?;
-            creature.try_into_stat_block().map_err(|e| format!("{}",e))?
+            let working_dir = get_working_dir_relative_to_source_or_default(&source_file, &base_dir, &working_dir);
+            let creature = create_creature_from_source(source, &source_name, &source_format, &working_dir, &options.warnings, options.lint, &options.parameters)?;
+            creature_to_stat_block(creature, with_diagnostics, with_attacks)?
         },
         InputFormat::Open5e(input_file) => {
             let source_file = get_source_file(&working_dir, input_file.as_deref())?;
@@ -276,17 +690,23 @@ pub fn create_stat_block(input_format: InputFormat,
             let source = read_source(source_file.as_ref())?;
             // deserialize the stat block
             let creature = Open5eMonster::load_from_str(&source).map_err(|e| format!("Error loading open5e monster: {}",e))?;
-            creature.try_into_stat_block().map_err(|e| format!("{}",e))?
+            (creature.try_into_stat_block().map_err(|e| format!("{}",e))?,Vec::new())
 
         },
-        InputFormat::Open5eList(input_file,creature_name) => {
+        InputFormat::LenientOpen5e(input_file) => {
             let source_file = get_source_file(&working_dir, input_file.as_deref())?;
-            // get the data from the file
             let source = read_source(source_file.as_ref())?;
-            // deserialize the stat block
-            let list = Open5eMonsterList::load_from_str(&source).map_err(|e| format!("Error loading open5e monster: {}",e))?;
+            // unknown fields and fields that can't be read are left out, with a warning for each.
+            let (creature,diagnostics) = Open5eMonster::load_from_str_lenient(&source).map_err(|e| format!("Error loading open5e monster: {}",e))?;
+            report_warnings(diagnostics, &WarningLevel::Warn)?;
+            (creature.try_into_stat_block().map_err(|e| format!("{}",e))?,Vec::new())
+
+        },
+        InputFormat::Open5eList(input_files,creature_name) |
+        InputFormat::LenientOpen5eList(input_files,creature_name) => {
+            let list = load_open5e_list(&working_dir, &input_files, lenient_open5e)?;
             if let Some(creature) = list.results.into_iter().find(|creature| (creature.slug == creature_name) || (creature.name == creature_name)) {
-                creature.try_into_stat_block().map_err(|e| format!("{}",e))?
+                (creature.try_into_stat_block().map_err(|e| format!("{}",e))?,Vec::new())
             } else {
                 Err("Couldn't find creature in list.".to_owned())?
             }
@@ -297,15 +717,31 @@ pub fn create_stat_block(input_format: InputFormat,
             } else {
                 Err("Couldn't find creature in list.".to_owned())?
             };
-            // deserialize the commands
-            let creator = CreatureCreator::load_from_str(source).map_err(|e| format!("Error loading creature commands: {}",e))?;
-            let creature = creator.create_creature(&working_dir).map_err(|e| format!("{}",e))?;
-            creature.try_into_stat_block().map_err(|e| format!("{}",e))?
+            // stored creatures don't have a location, so only the base directory applies.
+            let working_dir = get_working_dir_relative_to_source_or_default(&None, &base_dir, &working_dir);
+            let creature = create_creature_from_source(source.to_owned(), &creature_name, &CreatureFileFormat::RON, &working_dir, &options.warnings, options.lint, &options.parameters)?;
+            creature_to_stat_block(creature, with_diagnostics, with_attacks)?
         }
     };
 
+    // notes are for the people editing the creature, so keep them out of player-facing output unless asked.
+    if !options.with_notes {
+        stat_block.notes.clear();
+    }
+
+    stat_block.apply_style(&options.style);
+
+    let labels = if let Some(lang_file) = &options.lang_file {
+        let lang_file = resolve_existing_file(&working_dir, lang_file)?;
+        let source = fs::read_to_string(&lang_file).map_err(|e| format!("Error reading language file: {}",e))?;
+        StatBlockLabels::load_from_str(&source).map_err(|e| format!("Error loading language file: {}",e))?
+    } else {
+        StatBlockLabels::default()
+    };
+
     let output = match output_format {
-        OutputFormat::JSON(ugly) => stat_block.write_to_string(ugly)?,
+        OutputFormat::JSON(ugly,_) => stat_block.write_to_string(ugly)?,
+        OutputFormat::CompactJSON(ugly) => stat_block.to_compact().write_to_string(ugly)?,
         OutputFormat::MiniJinjaTemplate(template,include_files) => {
             // use the default working dir instead of making it relative to the source.
             let template_file = resolve_existing_file(&working_dir, &template)?;
@@ -339,26 +775,68 @@ pub fn create_stat_block(input_format: InputFormat,
                 includes.push(name);
             }
             
-            process_template(&template_dir,&template_name,&includes,&stat_block).map_err(|e| format!("Error processing template: {}",e))?
+            process_template(&template_dir,&template_name,&includes,&stat_block,&labels).map_err(|e| format!("Error processing template: {}",e))?
         },
-        OutputFormat::HTML(two_column_height,fragment) => {
-            let main_template = if fragment {
+        OutputFormat::HTML(options) => {
+            let main_template = if options.fragment {
                 monstorr_data::templates::STAT_BLOCK_HTML_TEMPLATE
             } else {
                 monstorr_data::templates::FULL_HTML_TEMPLATE
             };
-            process_template(&StoredTemplates::instance(TemplateOptions::html(two_column_height)), main_template, &Vec::new(), &stat_block).map_err(|e| format!("Error producing HTML: {}",e))?
+            if let Some(card_size) = &options.card_size {
+                // the plain text, wrapped to the width of a column, is a rough measure of how much room the stat block needs.
+                let (line_length,capacity) = card_size.text_capacity();
+                let lines = write_text(&stat_block, &labels, line_length).lines().count();
+                if lines > capacity {
+                    eprintln!("Warning: The stat block needs about {} lines, which is more than the {} that fit on the card. The rest will spill past the edge of the card, try a larger card size.",lines,capacity)
+                }
+            }
+            let footer = options.link.as_ref().map(|url| html_link_footer(url,options.qr)).transpose()?;
+            let css_class = options.css_class.as_ref().map(|class| escape_html_attribute(class));
+            process_template(&StoredTemplates::instance(TemplateOptions::html(HtmlOptions { css_class, ..options },footer)), main_template, &Vec::new(), &stat_block, &labels).map_err(|e| format!("Error producing HTML: {}",e))?
         },
-        OutputFormat::LaTeX() => {
-            let main_template = monstorr_data::templates::LATEX_TEMPLATE;
+        OutputFormat::LaTeX(standalone,color,font,margin) => {
+            let main_template = if standalone {
+                monstorr_data::templates::LATEX_DOCUMENT_TEMPLATE
+            } else {
+                monstorr_data::templates::LATEX_TEMPLATE
+            };
+            let (color,font,margin) = validate_latex_theme(color,font,margin)?;
             // FUTURE: Allow this as a command line option for MiniJinja?
             let stat_block = stat_block.escape(&escape_latex);
-            process_template(&StoredTemplates::instance(TemplateOptions::latex()), main_template, &Vec::new(), &stat_block).map_err(|e| format!("Error producing LaTeX: {}",e))?
+            let labels = labels.escape(&escape_latex);
+            process_template(&StoredTemplates::instance(TemplateOptions::latex(color,font,margin)), main_template, &Vec::new(), &stat_block, &labels).map_err(|e| format!("Error producing LaTeX: {}",e))?
         },
         OutputFormat::Plain() => {
             let main_template = monstorr_data::templates::PLAIN_TEMPLATE;
-            process_template(&StoredTemplates::instance(TemplateOptions::latex()), main_template, &Vec::new(), &stat_block).map_err(|e| format!("Error producing plain text: {}",e))?
-        }
+            process_template(&StoredTemplates::instance(None), main_template, &Vec::new(), &stat_block, &labels).map_err(|e| format!("Error producing plain text: {}",e))?
+        },
+        OutputFormat::Text(width) => write_text(&stat_block, &labels, width),
+        OutputFormat::Discord() => {
+            let main_template = monstorr_data::templates::DISCORD_TEMPLATE;
+            let output = process_template(&StoredTemplates::instance(None), main_template, &Vec::new(), &stat_block, &labels).map_err(|e| format!("Error producing Discord markdown: {}",e))?;
+            let length = output.chars().count();
+            if length > DISCORD_MESSAGE_LIMIT {
+                eprintln!("Warning: The stat block is {} characters long, which is more than the {} characters allowed in a Discord message. It will need to be split up before posting.",length,DISCORD_MESSAGE_LIMIT)
+            }
+            output
+        },
+        OutputFormat::PDFForm(form,mapping) => {
+            let form_file = resolve_existing_file(&working_dir, &form)?;
+            let mapping = if let Some(mapping) = mapping {
+                let mapping_file = resolve_existing_file(&working_dir, &mapping)?;
+                let source = fs::read_to_string(&mapping_file).map_err(|e| format!("Error reading form field mapping: {}",e))?;
+                Some(ron::de::from_str(&source).map_err(|e| format!("Error loading form field mapping: {}",e))?)
+            } else {
+                None
+            };
+            let values = map_form_values(get_form_values(&stat_block), &mapping);
+            // the PDF is binary, so it's written here instead of with the text formats.
+            let output = fill_pdf_form(&form_file, &values)?;
+            return write_target(target_file, output)
+        },
+        OutputFormat::Foundry(ugly) => write_foundry_actor(&stat_block, &attacks, ugly)?,
+        OutputFormat::DndBeyond(ugly) => write_dndbeyond_monster(&stat_block, ugly)?
     };
 
     write_target(target_file, &output)
@@ -366,6 +844,75 @@ pub fn create_stat_block(input_format: InputFormat,
     
 }
 
+// the theme is inserted into the templates as-is, so only values which can't break the LaTeX, or the template, are accepted.
+fn validate_latex_theme(color: Option<String>, font: Option<String>, margin: Option<String>) -> Result<(Option<String>,Option<String>,Option<String>),String> {
+    let color = color.map(|color| {
+        let color = color.trim_start_matches('#').to_uppercase();
+        if color.len() == 6 && color.chars().all(|ch| ch.is_ascii_hexdigit()) {
+            Ok(color)
+        } else {
+            Err(format!("LaTeX color must be six hexadecimal digits, such as '7A200D', not '{}'.",color))
+        }
+    }).transpose()?;
+    let font = font.map(|font| if !font.is_empty() && font.chars().all(|ch| ch.is_ascii_alphanumeric()) {
+        Ok(font)
+    } else {
+        Err(format!("LaTeX font must be the name of a font family, such as 'ppl', not '{}'.",font))
+    }).transpose()?;
+    let margin = margin.map(|margin| {
+        let unit = margin.trim_start_matches(|ch: char| ch.is_ascii_digit() || ch == '.');
+        if (unit.len() < margin.len()) && ["pt","mm","cm","in","em","ex","pc","bp"].contains(&unit) {
+            Ok(margin)
+        } else {
+            Err(format!("LaTeX margin must be a length, such as '1in' or '20mm', not '{}'.",margin))
+        }
+    }).transpose()?;
+    Ok((color,font,margin))
+}
+
+fn escape_html_attribute(source: &str) -> String {
+    let mut result = String::new();
+    for ch in source.chars() {
+        match ch {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            // the footer is inserted as a template, so braces could be mistaken for template tags.
+            '{' => result.push_str("&#123;"),
+            '}' => result.push_str("&#125;"),
+            ch => result.push(ch)
+        }
+    }
+    result
+}
+
+#[cfg(feature = "qr")]
+fn generate_qr_svg(url: &str) -> Result<String,String> {
+    let code = qrcode::QrCode::new(url.as_bytes()).map_err(|e| format!("Could not generate QR code: {}",e))?;
+    let svg = code.render::<qrcode::render::svg::Color>().quiet_zone(false).build();
+    // the renderer produces a standalone document, but this is going to be embedded in HTML.
+    Ok(match svg.find("<svg") {
+        Some(start) => svg[start..].to_owned(),
+        None => svg
+    })
+}
+
+#[cfg(not(feature = "qr"))]
+fn generate_qr_svg(_url: &str) -> Result<String,String> {
+    Err("This build of monstorr can not generate QR codes, it must be compiled with the 'qr' feature.".to_owned())
+}
+
+fn html_link_footer(url: &str, qr: bool) -> Result<String,String> {
+    let qr = if qr {
+        generate_qr_svg(url)?
+    } else {
+        String::new()
+    };
+    let url = escape_html_attribute(url);
+    Ok(format!("<div class=\"stat-block-footer\">{}<a href=\"{}\">{}</a></div>",qr,url,url))
+}
+
 pub fn list_template_names(class: Option<&str>) -> Vec<String> {
     StoredTemplates::instance(None).list(class)
 }
@@ -380,18 +927,48 @@ pub fn print_template(name: &str) -> Result<(),String> {
     }
 }
 
+pub fn export_templates(class: Option<&str>, dir: &str, overwrite: bool) -> Result<(),String> {
+    let working_dir = get_default_working_dir()?;
+    let target_dir = resolve_file(&working_dir, dir);
+
+    let resolver = StoredTemplates::instance(None);
+    // the generated templates are exported with their default content, so the other templates can still include them.
+    let mut names = resolver.list(class);
+    names.append(&mut resolver.list_generated(class));
+
+    // everything is checked before anything is written, so a conflict doesn't leave only some of the templates exported.
+    let mut targets = Vec::new();
+    for name in names {
+        let target_file = resolve_file(&target_dir, &name);
+        let template = resolver.get_template(&name)?.ok_or_else(|| format!("This program does not contain a template named '{}'",name))?;
+        targets.push((target_file,template));
+    }
+    if !overwrite {
+        if let Some((target_file,_)) = targets.iter().find(|(target_file,_)| target_file.exists()) {
+            Err(format!("File {} already exists, use the overwrite option to replace it.",target_file.display()))?
+        }
+    }
 
-pub fn list_creatures(input_file: Option<&str>, input_format: ListInputFormat, type_: Option<String>, subtype: Option<String>, size: Option<String>,alignment: Option<String>,max_cr: Option<String>,min_cr: Option<String>) -> Result<Vec<CreatureSummary<String>>,String> {
+    fs::create_dir_all(&target_dir).map_err(|e| format!("Error creating directory: {}",e))?;
+    for (target_file,template) in targets {
+        write_target(Some(target_file), &template)?
+    }
+    Ok(())
+}
+
+
+pub fn list_creatures(input_files: &[String], input_format: ListInputFormat, type_: Option<String>, subtype: Option<String>, size: Option<String>,alignment: Option<String>,environment: Option<String>,max_cr: Option<String>,min_cr: Option<String>) -> Result<Vec<CreatureSummary<String>>,String> {
 
 
     let type_ = type_.map(|a| a.trim().to_lowercase());
     let size = size.map(|a| a.trim().to_lowercase());
     let alignment = alignment.map(|a| a.trim().to_lowercase());
+    let environment = environment.map(|a| a.trim().to_lowercase());
     let max_cr = max_cr.map(|a| a.parse::<ChallengeRating>()).transpose().map_err(|e| format!("Could not parse max_cr: {}",e))?;
     let min_cr = min_cr.map(|a| a.parse::<ChallengeRating>()).transpose().map_err(|e| format!("Could not parse min_cr: {}",e))?;
 
     macro_rules! match_filter {
-        ($creature: ident) => {{
+        ($creature: ident, $environments: expr) => {{
             if let Some(type_) = &type_ {
                 if &$creature.type_.to_lowercase() != type_ {
                     continue;
@@ -414,7 +991,12 @@ pub fn list_creatures(input_file: Option<&str>, input_format: ListInputFormat, t
                 }
             }
             if let Some(alignment) = &alignment {
-                if &$creature.alignment.to_lowercase() != alignment {
+                if !Alignment::text_matches(&$creature.alignment,alignment) {
+                    continue;
+                }
+            }
+            if let Some(environment) = &environment {
+                if !$environments.iter().any(|a| &a.to_lowercase() == environment) {
                     continue;
                 }
             }
@@ -437,24 +1019,16 @@ pub fn list_creatures(input_file: Option<&str>, input_format: ListInputFormat, t
     }
 
     match input_format {
-        ListInputFormat::Open5eList => {
+        ListInputFormat::Open5eList |
+        ListInputFormat::LenientOpen5eList => {
             let working_dir = get_default_working_dir()?;
 
-            let source_file = if let Some(input_file) = input_file {
-                Some(resolve_existing_file(&working_dir, input_file)?)
-            } else {
-                None
-            };
-        
-            // get the data from the file
-            let source = read_source(source_file.as_ref())?;
-
-            // deserialize the stat block
-            let list = Open5eMonsterList::load_from_str(&source).map_err(|e| format!("Error loading open5e monster: {}",e))?;
+            let list = load_open5e_list(&working_dir, input_files, matches!(input_format,ListInputFormat::LenientOpen5eList))?;
             let mut result = Vec::new();
             for creature in list.results {
 
-                match_filter!(creature);
+                // Open5e doesn't provide environments
+                match_filter!(creature,Vec::<String>::new());
 
                 result.push(CreatureSummary {
                     name: creature.name.clone(),
@@ -463,7 +1037,8 @@ pub fn list_creatures(input_file: Option<&str>, input_format: ListInputFormat, t
                     subtype: creature.subtype.clone(),
                     size: creature.size.clone(),
                     alignment: creature.alignment.clone(),
-                    challenge_rating: creature.challenge_rating.clone()
+                    challenge_rating: creature.challenge_rating.clone(),
+                    environments: Vec::new()
                 });
             };
             Ok(result)
@@ -473,7 +1048,7 @@ pub fn list_creatures(input_file: Option<&str>, input_format: ListInputFormat, t
             let mut result = Vec::new();
             for (creature,_) in monstorr_data::creatures::STORED_CREATURES {
                 
-                match_filter!(creature);
+                match_filter!(creature,creature.environments);
 
                 result.push(CreatureSummary {
                     name: creature.name.to_owned(),
@@ -482,7 +1057,8 @@ pub fn list_creatures(input_file: Option<&str>, input_format: ListInputFormat, t
                     subtype: creature.subtype.map(|a| a.to_owned()),
                     size: creature.size.to_owned(),
                     alignment: creature.alignment.to_owned(),
-                    challenge_rating: creature.challenge_rating.to_owned()
+                    challenge_rating: creature.challenge_rating.to_owned(),
+                    environments: creature.environments.iter().map(|a| (*a).to_owned()).collect()
                 });
             };
             Ok(result)
@@ -492,19 +1068,18 @@ pub fn list_creatures(input_file: Option<&str>, input_format: ListInputFormat, t
 }
 
 
-pub fn generate_creatures_as_rust_array(search_directory: &str) -> Result<(),String> {
-    let working_dir = get_default_working_dir()?;
+pub fn generate_random_encounter(input_files: &[String], input_format: ListInputFormat, type_: Option<String>, subtype: Option<String>, size: Option<String>,alignment: Option<String>,environment: Option<String>,max_cr: Option<String>,min_cr: Option<String>,party_size: u8, party_level: u8, difficulty: EncounterDifficulty, seed: u64) -> Result<Encounter,String> {
 
-    let search_directory = resolve_existing_dir(&working_dir, search_directory)?;
+    let pool = list_creatures(input_files, input_format, type_, subtype, size, alignment, environment, max_cr, min_cr)?;
+    encounter::generate_encounter(pool, party_size, party_level, difficulty, seed)
+
+}
 
-    let mut target_file = search_directory.clone();
-    target_file.push("creature_database.rs.inc");
-    let target_file = target_file;
 
+fn find_creature_files(search_directory: &Path) -> Result<Vec<PathBuf>,String> {
     let mut files = Vec::new();
 
-    // I'm looping twice because I want to collect the files *now* before someone adds a new one or something...
-    for file in fs::read_dir(&search_directory).map_err(|e| format!("{}",e))? {
+    for file in fs::read_dir(search_directory).map_err(|e| format!("{}",e))? {
         let file = file.map_err(|e| format!("{}",e))?;
         let path: PathBuf = file.path();
         if let Some(extension) = path.extension() {
@@ -513,6 +1088,151 @@ pub fn generate_creatures_as_rust_array(search_directory: &str) -> Result<(),Str
             }
         }
     }
+    // sorted so the summary is the same on every system
+    files.sort();
+
+    Ok(files)
+}
+
+/**
+Builds every creature file in a directory, including interpolating all descriptions into a stat block, without writing any output. Each failure is printed, followed by a summary, and an error is returned if any creature failed.
+*/
+pub fn validate_creatures_in_directory(search_directory: &str) -> Result<(),String> {
+    let working_dir = get_default_working_dir()?;
+
+    let search_directory = resolve_existing_dir(&working_dir, search_directory)?;
+
+    let files = find_creature_files(&search_directory)?;
+
+    let mut failed = 0;
+
+    for file in &files {
+        let name = path_relative_from(file, &search_directory).map(|name| name.display().to_string()).unwrap_or_else(|| file.display().to_string());
+        let result = read_source(Some(file)).and_then(|source| {
+            // the final working directory should be the directory in which the source file is located.
+            let working_dir = get_working_dir_relative_to_source_or_default(&Some(file.clone()), &None, &working_dir);
+            let source = interpolate_source(source, &name, &HashMap::new())?;
+            let creator = load_creature_commands(&source)?;
+            let creature = creator.create_creature(&working_dir).map_err(|e| format!("{}",e))?;
+            creature.try_into_stat_block().map_err(|e| format!("{}",e))?;
+            Ok(())
+        });
+        if let Err(err) = result {
+            failed += 1;
+            println!("FAILED {}: {}",name,err);
+        }
+    }
+
+    println!("Validated {} creatures, {} failed.",files.len(),failed);
+
+    if failed > 0 {
+        Err(format!("{} of {} creatures failed validation.",failed,files.len()))
+    } else {
+        Ok(())
+    }
+
+}
+
+/**
+Builds every creature file in a directory and writes each stat block next to its creature file, with the same name and the extension for the output format, such as `goblin.html` for `goblin.creature`. A creature which fails is reported, and the rest are still written, followed by a summary. An error is returned if any creature failed.
+*/
+pub fn create_stat_blocks_in_directory(search_directory: &str, output_format: OutputFormat, options: &StatBlockOptions) -> Result<(),String> {
+    let working_dir = get_default_working_dir()?;
+
+    // included files are found relative to each creature file.
+    let options = StatBlockOptions {
+        base_dir: None,
+        ..options.clone()
+    };
+
+    let search_directory = resolve_existing_dir(&working_dir, search_directory)?;
+
+    let files = find_creature_files(&search_directory)?;
+
+    let extension = output_format.file_extension();
+
+    let mut failed = 0;
+
+    for file in &files {
+        let name = path_relative_from(file, &search_directory).map(|name| name.display().to_string()).unwrap_or_else(|| file.display().to_string());
+        let target_file = file.with_extension(&extension);
+        // the output format, and the language file, are still found relative to the current directory.
+        let result = create_stat_block(InputFormat::Creature(Some(file.display().to_string())), Some(&target_file.display().to_string()), output_format.clone(), &options);
+        if let Err(err) = result {
+            failed += 1;
+            println!("FAILED {}: {}",name,err);
+        }
+    }
+
+    println!("Wrote {} stat blocks, {} failed.",files.len() - failed,failed);
+
+    if failed > 0 {
+        Err(format!("{} of {} creatures failed.",failed,files.len()))
+    } else {
+        Ok(())
+    }
+
+}
+
+/**
+Builds every creature file in a directory and writes out an index of all of the spells they can cast, with the creatures which cast them. Creatures which fail to build are reported as warnings and left out of the index.
+*/
+pub fn create_spell_index(search_directory: &str, output_file: Option<&str>, format: SpellIndexFormat) -> Result<(),String> {
+    let working_dir = get_default_working_dir()?;
+
+    let search_directory = resolve_existing_dir(&working_dir, search_directory)?;
+
+    let target_file = if let Some(output_file) = output_file {
+        Some(resolve_file(&working_dir, output_file))
+    } else {
+        None
+    };
+
+    let mut index = SpellIndex::default();
+
+    for file in find_creature_files(&search_directory)? {
+        let name = path_relative_from(&file, &search_directory).map(|name| name.display().to_string()).unwrap_or_else(|| file.display().to_string());
+        let result = read_source(Some(&file)).and_then(|source| {
+            // the final working directory should be the directory in which the source file is located.
+            let working_dir = get_working_dir_relative_to_source_or_default(&Some(file.clone()), &None, &working_dir);
+            create_creature_from_source(source, &name, &CreatureFileFormat::RON, &working_dir, &WarningLevel::Ignore, false, &HashMap::new())
+        });
+        match result {
+            Ok(creature) => index.add_creature(&creature),
+            Err(err) => eprintln!("Warning: Skipping {}: {}",name,err)
+        }
+    }
+
+    let output = index.write_to_string(&format)?;
+
+    write_target(target_file, &output)
+
+}
+
+pub fn write_stat_block_schema(output_file: Option<&str>) -> Result<(),String> {
+    let working_dir = get_default_working_dir()?;
+
+    let target_file = if let Some(output_file) = output_file {
+        Some(resolve_file(&working_dir, output_file))
+    } else {
+        None
+    };
+
+    write_target(target_file, CreatureStatBlock::json_schema())
+}
+
+pub fn generate_creatures_as_rust_array(search_directory: &str) -> Result<(),String> {
+    let working_dir = get_default_working_dir()?;
+
+    let search_directory = resolve_existing_dir(&working_dir, search_directory)?;
+
+    let mut target_file = search_directory.clone();
+    target_file.push("creature_database.rs.inc");
+    let target_file = target_file;
+
+    // I'm looping twice because I want to collect the files *now* before someone adds a new one or something...
+    // These are sorted, so regenerating the file only changes the entries for creatures which changed.
+    let files = find_creature_files(&search_directory)?;
 
     let mut output = String::new();
 
@@ -524,7 +1244,7 @@ pub fn generate_creatures_as_rust_array(search_directory: &str) -> Result<(),Str
     output.push_str("\n\n\n\n");
 
     // wrap in a struct so the compiler can warn me if I'm missing something I'll need in list_creatures
-    output.push_str(&format!("pub const STORED_CREATURES: [(CreatureSummary<&'static str>, &'static str); {}] = [",files.len()));
+    output.push_str(&format!("pub const STORED_CREATURES: [(CreatureSummary<&'static str, &'static [&'static str]>, &'static str); {}] = [",files.len()));
 /*
 const TEST: CreatureSummary<&str> = CreatureSummary {
     name: "test",
@@ -533,7 +1253,8 @@ const TEST: CreatureSummary<&str> = CreatureSummary {
     subtype: Some("test"),
     size: "test",
     alignment: "test",
-    challenge_rating: "test"
+    challenge_rating: "test",
+    environments: &["test"]
 };
 */
 
@@ -551,9 +1272,9 @@ const TEST: CreatureSummary<&str> = CreatureSummary {
         // get the data from the file
         let source = read_source(Some(&file))?;
         // the final working directory should be the directory in which the source file is located.
-        let working_dir = get_working_dir_relative_to_source_or_default(&Some(file), &working_dir);
+        let working_dir = get_working_dir_relative_to_source_or_default(&Some(file), &None, &working_dir);
         // deserialize the commands
-        let creator = CreatureCreator::load_from_str(&source).map_err(|e| format!("Error loading creature commands: {}",e))?;
+        let creator = load_creature_commands(&source)?;
         let creature = creator.create_creature(&working_dir).map_err(|e| format!("{}",e))?;
         let slug = to_kebab_case(&creature.name);
         // wrap in a struct so the compiler can warn me if I'm missing something I'll need in list_creatures
@@ -564,7 +1285,8 @@ const TEST: CreatureSummary<&str> = CreatureSummary {
             subtype: creature.subtype,
             size: creature.size.to_string(),
             alignment: creature.alignment.to_string(),
-            challenge_rating: creature.challenge_rating.to_string()
+            challenge_rating: creature.challenge_rating.to_string(),
+            environments: creature.environments
         };
 
         output.push_str("\n        (");
@@ -576,6 +1298,7 @@ const TEST: CreatureSummary<&str> = CreatureSummary {
         output.push_str(&format!("\n                size: {:?},",summary.size));
         output.push_str(&format!("\n                alignment: {:?},",summary.alignment));
         output.push_str(&format!("\n                challenge_rating: {:?},",summary.challenge_rating));
+        output.push_str(&format!("\n                environments: &{:?},",summary.environments));
         output.push_str("\n             },");
         output.push_str(&format!("\n             include_str!({:?})",include_filename));
         output.push_str("\n        ),");
@@ -7,6 +7,7 @@
  */
 
 use std::convert::TryFrom;
+use std::collections::BTreeMap;
 
 use monstorr_open5e::Open5eMonster;
 use monstorr_open5e::Open5eMonsterAction;
@@ -15,7 +16,15 @@ use crate::stat_block::CreatureStatBlock;
 use crate::stat_block::StatBlockFeature;
 use crate::stat_block::StatBlockLegendary;
 use crate::stats::Ability;
+use crate::stats::Damage;
+use crate::attacks::Attack;
+use crate::attacks::AttackBonus;
+use crate::attacks::AttackEffect;
+use crate::attacks::AttackType;
+use crate::attacks::CompoundAttackEffect;
+use crate::dice_expression::DiceExpression;
 use crate::interpolation::interpolate_simple_markdown_naively;
+use crate::interpolation::interpolate_str_for_statblock;
 use crate::errors::InterpolationErrorDetails;
 
 fn str_to_option(source: String) -> Option<String> {
@@ -64,11 +73,106 @@ fn saving_throws_to_stat_block(strength: Option<i8>, dexterity: Option<i8>, cons
 
 }
 
-fn actions_to_stat_block(source: Vec<Open5eMonsterAction>) -> Result<Vec<StatBlockFeature>,InterpolationErrorDetails> {
+// parses damage such as "5 (1d6 + 2) slashing damage" or "1 piercing damage". The average is checked against the dice, so a typo in the source isn't silently replaced.
+fn parse_damage(source: &str) -> Option<AttackEffect> {
+    let (amount,damage) = source.strip_suffix(" damage")?.rsplit_once(' ')?;
+    let damage = damage.parse::<Damage>().ok()?;
+    if let Some((average,dice)) = amount.strip_suffix(')').and_then(|amount| amount.split_once(" (")) {
+        let dice = dice.parse::<DiceExpression>().ok()?;
+        if average.parse::<isize>().ok()? == dice.average() {
+            Some(AttackEffect::Damage(dice,AttackBonus::Zero,damage))
+        } else {
+            None
+        }
+    } else {
+        Some(AttackEffect::FixedDamage(amount.parse().ok()?,AttackBonus::Zero,damage))
+    }
+}
+
+// parses the usual phrasing of an attack, such as "Melee Weapon Attack: +4 to hit, reach 5 ft., one target. Hit: 5 (1d6 + 2) slashing damage.", so its description can be built the same way as for a creature file. Anything following the damage is kept as written. Returns None if the description doesn't follow that phrasing.
+fn parse_attack(source: &str) -> Option<(Attack,AttackEffect,Option<CompoundAttackEffect>)> {
+    let (kind,rest) = source.trim().split_once(" Attack: ")?;
+    let (kind,type_) = if let Some(kind) = kind.strip_suffix(" Weapon") {
+        (kind,Some(AttackType::Weapon))
+    } else if let Some(kind) = kind.strip_suffix(" Spell") {
+        (kind,Some(AttackType::Spell))
+    } else {
+        (kind,None)
+    };
+    let (bonus,rest) = rest.split_once(" to hit, ")?;
+    let bonus = bonus.parse::<i8>().ok()?;
+    let (targeting,hit) = rest.split_once(". Hit: ")?;
+    let (distances,target) = targeting.split_once(" ft., ")?;
+
+    let parse_reach = |reach: &str| reach.strip_prefix("reach ").and_then(|reach| reach.parse::<u8>().ok());
+    let parse_range = |range: &str| range.strip_prefix("range ").and_then(|range| match range.split_once('/') {
+        Some((range,long_range)) => Some((range.parse::<u16>().ok()?,Some(long_range.parse::<u16>().ok()?))),
+        None => Some((range.parse::<u16>().ok()?,None))
+    });
+    let (reach,range,long_range) = match kind {
+        "Melee" => (Some(parse_reach(distances)?),None,None),
+        "Ranged" => {
+            let (range,long_range) = parse_range(distances)?;
+            (None,Some(range),long_range)
+        },
+        "Melee or Ranged" => {
+            let (reach,range) = distances.split_once(" ft. or ")?;
+            let (range,long_range) = parse_range(range)?;
+            (Some(parse_reach(reach)?),Some(range),long_range)
+        },
+        _ => None?
+    };
+
+    let hit = hit.strip_suffix('.')?;
+    let (damage,additional) = match hit.split_once(". ") {
+        Some((damage,additional)) => (damage,Some(AttackEffect::Special(additional.to_owned()))),
+        None => (hit,None)
+    };
+    let (effect,plus) = match damage.split_once(" plus ") {
+        Some((damage,plus)) => (parse_damage(damage)?,Some(parse_damage(plus)?)),
+        None => (parse_damage(damage)?,None)
+    };
+    let compound = match (plus,additional) {
+        (Some(plus),Some(additional)) => Some(CompoundAttackEffect::PlusAdditional(plus,additional)),
+        (Some(plus),None) => Some(CompoundAttackEffect::Plus(plus)),
+        (None,Some(additional)) => Some(CompoundAttackEffect::Additional(additional)),
+        (None,None) => None
+    };
+
+    Some((Attack {
+        type_,
+        bonus: AttackBonus::Fixed(bonus),
+        magic: None,
+        reach,
+        range,
+        long_range,
+        target: target.to_owned(),
+        targeting: None
+    },effect,compound))
+}
+
+fn action_to_stat_block(action: &Open5eMonsterAction) -> Result<StatBlockFeature,InterpolationErrorDetails> {
+    // text with markdown, or anything that would be read as interpolation, is left to the naive conversion.
+    let is_plain = |text: &str| !text.contains(|c: char| matches!(c,'$' | '*' | '_' | '\n'));
+    let attack = if is_plain(&action.name) && is_plain(&action.desc) {
+        parse_attack(&action.desc)
+    } else {
+        None
+    };
+    Ok(StatBlockFeature {
+        text: if let Some((attack,effect,compound)) = attack {
+            let source = format!("${{par(}}{}.${{)}}{}",action.name,attack.get_description(Some(&effect),&compound));
+            interpolate_str_for_statblock(&source, &action.name, &(), true)?
+        } else {
+            interpolate_simple_markdown_naively(&action.name, &action.desc, &action.name, false, true)?
+        },
+        trigger: None
+    })
+}
+
+pub(crate) fn actions_to_stat_block(source: Vec<Open5eMonsterAction>) -> Result<Vec<StatBlockFeature>,InterpolationErrorDetails> {
 
-    source.iter().map(|a| Ok(StatBlockFeature {
-        text: interpolate_simple_markdown_naively(&a.name, &a.desc, &a.name, false, true)?
-    })).collect()
+    source.iter().map(action_to_stat_block).collect()
 }
 
 fn legendary_to_stat_block(description: String, actions: Vec<Open5eMonsterAction>) -> Result<Option<StatBlockLegendary>,InterpolationErrorDetails> {
@@ -79,7 +183,8 @@ fn legendary_to_stat_block(description: String, actions: Vec<Open5eMonsterAction
             description: interpolate_simple_markdown_naively("", &description, "legendary description", false, true)?,
             // this isn't exactly the same as actions_to_statblock, since we want subparagraphs
             actions: actions.iter().map(|a| Ok(StatBlockFeature {
-                text: interpolate_simple_markdown_naively(&a.name, &a.desc, "legendary action", true, true)?
+                text: interpolate_simple_markdown_naively(&a.name, &a.desc, "legendary action", true, true)?,
+                trigger: None
             })).collect::<Result<Vec<StatBlockFeature>,InterpolationErrorDetails>>()?
         })    
     })
@@ -92,12 +197,14 @@ impl TryFrom<Open5eMonster> for CreatureStatBlock {
     fn try_from(creature: Open5eMonster) -> Result<Self,Self::Error> {
         Ok(Self {
             name: creature.name,
+            display_name: None,
             size: creature.size,
             type_: creature.type_,
             subtype: creature.subtype,
             group: creature.group,
             alignment: creature.alignment,
             armor: armor_to_stat_block(creature.armor_class,creature.armor_desc),
+            initiative: None,
             hit_points: format!("{} ({})",creature.hit_points,creature.hit_dice),
             speed: creature.speed.to_string(),
             strength: Ability::to_stat_block(creature.strength),
@@ -122,13 +229,21 @@ impl TryFrom<Open5eMonster> for CreatureStatBlock {
             senses: creature.senses,
             languages: str_to_option(creature.languages),
             challenge_rating: creature.challenge_rating,
+            proficiency_bonus: None,
             actions: actions_to_stat_block(creature.actions).map_err(|e| format!("{}",e))?,
             reactions: actions_to_stat_block(creature.reactions).map_err(|e| format!("{}",e))?,
             legendary_actions: legendary_to_stat_block(creature.legendary_desc,creature.legendary_actions).map_err(|e| format!("{}",e))?,
             special_abilities: actions_to_stat_block(creature.special_abilities).map_err(|e| format!("{}",e))?,
             lair_actions: None,
             regional_effects: None,
-            source: str_to_option(creature.document_title)
+            companions: vec![],
+            habitat: None,
+            treasure: None,
+            source: str_to_option(creature.document_title),
+            extra: BTreeMap::new(),
+            minion: false,
+            notes: vec![],
+            diagnostics: None
         })
 
     }
@@ -0,0 +1,228 @@
+/*
+ * Copyright © 2022 Neil M. Sheldon
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+/*!
+Fills the text fields of a fillable PDF monster sheet from a stat block. Monstorr doesn't come with a sheet, the form is supplied by the user, so the values are found by the names of the form's fields.
+
+By default, each field is filled with the stat-block property of the same name, as it appears in the JSON output: `name`, `display_name`, `size`, `type`, `subtype`, `alignment`, `armor`, `initiative`, `hit_points`, `speed`, `strength`, `dexterity`, `constitution`, `intelligence`, `wisdom`, `charisma`, `saving_throws`, `skills`, `damage_vulnerabilities`, `damage_resistances`, `damage_immunities`, `condition_immunities`, `senses`, `languages`, `challenge_rating`, `proficiency_bonus`, `habitat`, `treasure` and `source`. The lists of features, `special_abilities`, `actions`, `reactions`, `legendary_actions`, `lair_actions` and `regional_effects`, are written as plain text with a blank line between each feature, and are meant for the larger multi-line fields of the form. Values in `extra` are available by their keys.
+
+Most forms will use other names for their fields, so a mapping file can be given. This is a RON map from the form's field names to the property names above, for example `{ "AC": "armor", "HP": "hit_points", "STR": "strength" }`. Only the fields in the mapping are filled when one is given. Fields that don't match a property, and properties that the creature doesn't have, are left alone.
+
+Filling the form requires monstorr to be compiled with the 'pdf-form' feature.
+*/
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::stat_block::CreatureStatBlock;
+use crate::stat_block::StatBlockFeature;
+use crate::structured_text::blocks_to_text;
+
+fn features_to_text(features: &Vec<StatBlockFeature>) -> String {
+    features.iter().map(|feature| blocks_to_text(&feature.text)).collect::<Vec<String>>().join("\n\n")
+}
+
+/**
+Returns the values available to fill the form, by the names of the stat-block properties.
+*/
+pub fn get_form_values(stat_block: &CreatureStatBlock) -> HashMap<String,String> {
+    let mut result: HashMap<String,String> = stat_block.extra.clone().into_iter().collect();
+
+    let mut insert = |name: &str, value: &str| {
+        result.insert(name.to_owned(), value.to_owned());
+    };
+
+    insert("name",&stat_block.name);
+    insert("size",&stat_block.size);
+    insert("type",&stat_block.type_);
+    insert("alignment",&stat_block.alignment);
+    insert("armor",&stat_block.armor);
+    insert("hit_points",&stat_block.hit_points);
+    insert("speed",&stat_block.speed);
+    insert("strength",&stat_block.strength);
+    insert("dexterity",&stat_block.dexterity);
+    insert("constitution",&stat_block.constitution);
+    insert("intelligence",&stat_block.intelligence);
+    insert("wisdom",&stat_block.wisdom);
+    insert("charisma",&stat_block.charisma);
+    insert("senses",&stat_block.senses);
+    insert("challenge_rating",&stat_block.challenge_rating);
+
+    let optionals = [
+        ("display_name",&stat_block.display_name),
+        ("subtype",&stat_block.subtype),
+        ("initiative",&stat_block.initiative),
+        ("saving_throws",&stat_block.saving_throws),
+        ("skills",&stat_block.skills),
+        ("damage_vulnerabilities",&stat_block.damage_vulnerabilities),
+        ("damage_resistances",&stat_block.damage_resistances),
+        ("damage_immunities",&stat_block.damage_immunities),
+        ("condition_immunities",&stat_block.condition_immunities),
+        ("languages",&stat_block.languages),
+        ("proficiency_bonus",&stat_block.proficiency_bonus),
+        ("habitat",&stat_block.habitat),
+        ("treasure",&stat_block.treasure),
+        ("source",&stat_block.source)
+    ];
+    for (name,value) in optionals {
+        if let Some(value) = value {
+            insert(name,value)
+        }
+    }
+
+    let features = [
+        ("special_abilities",&stat_block.special_abilities),
+        ("actions",&stat_block.actions),
+        ("reactions",&stat_block.reactions)
+    ];
+    for (name,value) in features {
+        if !value.is_empty() {
+            insert(name,&features_to_text(value))
+        }
+    }
+
+    if let Some(legendary) = &stat_block.legendary_actions {
+        let mut text = blocks_to_text(&legendary.description);
+        if !legendary.actions.is_empty() {
+            text.push_str("\n\n");
+            text.push_str(&features_to_text(&legendary.actions));
+        }
+        insert("legendary_actions",&text)
+    }
+
+    if let Some(lair) = &stat_block.lair_actions {
+        let mut text = blocks_to_text(&lair.foreword);
+        for part in [features_to_text(&lair.actions),blocks_to_text(&lair.afterword)] {
+            if !part.is_empty() {
+                text.push_str("\n\n");
+                text.push_str(&part);
+            }
+        }
+        insert("lair_actions",text.trim_start())
+    }
+
+    if let Some(regional) = &stat_block.regional_effects {
+        let mut text = blocks_to_text(&regional.foreword);
+        for part in [features_to_text(&regional.effects),blocks_to_text(&regional.afterword)] {
+            if !part.is_empty() {
+                text.push_str("\n\n");
+                text.push_str(&part);
+            }
+        }
+        insert("regional_effects",text.trim_start())
+    }
+
+    result
+}
+
+/**
+Applies a mapping of form field names to property names to the values from `get_form_values`, returning the values by form field name.
+*/
+pub fn map_form_values(values: HashMap<String,String>, mapping: &Option<HashMap<String,String>>) -> HashMap<String,String> {
+    if let Some(mapping) = mapping {
+        mapping.iter().filter_map(|(field,property)| values.get(property).map(|value| (field.clone(),value.clone()))).collect()
+    } else {
+        values
+    }
+}
+
+#[cfg(feature = "pdf-form")]
+mod fill {
+
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use lopdf::Document;
+    use lopdf::Object;
+    use lopdf::ObjectId;
+    use lopdf::StringFormat;
+
+    fn text_string(text: &str) -> Object {
+        if text.is_ascii() {
+            Object::String(text.as_bytes().to_vec(), StringFormat::Literal)
+        } else {
+            // anything else has to be UTF-16 with a byte order mark.
+            let mut bytes = vec![0xFE,0xFF];
+            for unit in text.encode_utf16() {
+                bytes.extend_from_slice(&unit.to_be_bytes());
+            }
+            Object::String(bytes, StringFormat::Hexadecimal)
+        }
+    }
+
+    fn collect_fields(document: &Document, kids: &Vec<Object>, parent: Option<&str>, result: &mut Vec<(ObjectId,String)>) {
+        for kid in kids {
+            if let Ok(id) = kid.as_reference() {
+                if let Ok(field) = document.get_object(id).and_then(Object::as_dict) {
+                    // field names are made up of the partial names of their parents, separated by periods.
+                    let name = field.get(b"T").and_then(Object::as_str).ok().map(|partial| {
+                        let partial = String::from_utf8_lossy(partial);
+                        match parent {
+                            Some(parent) => format!("{}.{}",parent,partial),
+                            None => partial.into_owned()
+                        }
+                    });
+                    if let Some(name) = &name {
+                        result.push((id,name.clone()))
+                    }
+                    if let Ok(kids) = field.get(b"Kids").and_then(Object::as_array) {
+                        collect_fields(document, kids, name.as_deref().or(parent), result)
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn fill_pdf_form(form_file: &PathBuf, values: &HashMap<String,String>) -> Result<Vec<u8>,String> {
+        let mut document = Document::load(form_file).map_err(|e| format!("Error loading PDF form: {}",e))?;
+
+        let root_id = document.trailer.get(b"Root").and_then(Object::as_reference).map_err(|e| format!("Error reading PDF form: {}",e))?;
+        let acro_form = document.get_object(root_id).and_then(Object::as_dict).and_then(|catalog| catalog.get(b"AcroForm")).map_err(|_| "The PDF does not contain a form.".to_owned())?.clone();
+        let acro_form = match acro_form {
+            Object::Reference(id) => document.get_object(id).and_then(Object::as_dict).map_err(|e| format!("Error reading PDF form: {}",e))?.clone(),
+            Object::Dictionary(dictionary) => dictionary,
+            _ => Err("The PDF does not contain a form.".to_owned())?
+        };
+
+        let mut fields = Vec::new();
+        if let Ok(kids) = acro_form.get(b"Fields").and_then(Object::as_array) {
+            collect_fields(&document, kids, None, &mut fields);
+        }
+
+        for (id,name) in fields {
+            if let Some(value) = values.get(&name) {
+                if let Ok(field) = document.get_object_mut(id).and_then(Object::as_dict_mut) {
+                    field.set("V", text_string(value));
+                }
+            }
+        }
+
+        // the appearance of the fields still shows the old values, so ask the viewer to redraw them.
+        let mut acro_form = acro_form;
+        acro_form.set("NeedAppearances", Object::Boolean(true));
+        let acro_form_id = document.add_object(acro_form);
+        if let Ok(catalog) = document.get_object_mut(root_id).and_then(Object::as_dict_mut) {
+            catalog.set("AcroForm", Object::Reference(acro_form_id));
+        }
+
+        let mut result = Vec::new();
+        document.save_to(&mut result).map_err(|e| format!("Error writing PDF form: {}",e))?;
+        Ok(result)
+    }
+
+}
+
+#[cfg(feature = "pdf-form")]
+pub fn fill_pdf_form(form_file: &PathBuf, values: &HashMap<String,String>) -> Result<Vec<u8>,String> {
+    fill::fill_pdf_form(form_file, values)
+}
+
+#[cfg(not(feature = "pdf-form"))]
+pub fn fill_pdf_form(_form_file: &PathBuf, _values: &HashMap<String,String>) -> Result<Vec<u8>,String> {
+    Err("This build of monstorr can not fill PDF forms, it must be compiled with the 'pdf-form' feature.".to_owned())
+}
@@ -27,10 +27,16 @@ pub enum Reaction {
 
     /**
     `Reaction(<string>,<string>)`
+    `Reaction(<string>,<string>,option(<string>))`
 
     Adds a custom reaction to the creature, with the specified name and description. These will be interpolated.
+
+    If the optional third argument is given, it is the trigger for the reaction, and the description is the response. They are combined for display as "*Trigger:* ... *Response:* ...", so each should be a complete sentence. The trigger is also kept as a separate property on the reaction in the stat block, for tools such as virtual tabletops which prompt when it occurs.
     */
-    Reaction(String,String), // name, description
+    Reaction(String,String,
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        Option<String>), // name, description, trigger
 
 }
 
@@ -40,14 +46,22 @@ impl Reaction {
     pub fn get_name(&self) -> String {
         match self {
             Reaction::Parry(..) => "Parry".to_owned(),
-            Reaction::Reaction(name,_) => name.clone()
+            Reaction::Reaction(name,..) => name.clone()
         }
     }
 
     pub fn get_description(&self) -> String {
         match self {
             Reaction::Parry(ac) => format!("${{Subj}} adds {} to ${{posspro}} AC against one melee attack that would hit ${{objpro}}. To do so, ${{subj}} must see the attacker and be wielding a melee weapon.",ac),
-            Reaction::Reaction(_,description) => description.clone()
+            Reaction::Reaction(_,description,None) => description.clone(),
+            Reaction::Reaction(_,description,Some(trigger)) => format!("${{italic(}}Trigger:${{)}} {} ${{italic(}}Response:${{)}} {}",trigger,description)
+        }
+    }
+
+    pub fn get_trigger(&self) -> Option<String> {
+        match self {
+            Reaction::Parry(..) => None,
+            Reaction::Reaction(_,_,trigger) => trigger.clone()
         }
     }
 
@@ -0,0 +1,68 @@
+/*
+ * Copyright © 2022 Neil M. Sheldon
+ * 
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+/*!
+A spell index is a cross-reference of every spell in a collection of creatures, from their spellcasting and innate spellcasting, with the names of the creatures which can cast it. This is meant for building the spell appendix of a bestiary, and for auditing a collection of creature files.
+
+Spell names are listed exactly as they are written in the creature files, so "Fireball" and "fireball" are listed separately. This is on purpose, since a spell used by only one creature, or one written differently than everywhere else, is often a misspelling.
+*/
+
+use std::collections::BTreeMap;
+
+use crate::creature::Creature;
+
+/**
+The format to write the spell index in.
+*/
+pub enum SpellIndexFormat {
+    /// One line per spell, with the number of creatures which use it
+    Text,
+    /// A bulleted list, with the spell names in italics
+    Markdown,
+    /// An object mapping spell names to lists of creature names
+    JSON
+}
+
+#[derive(Default)]
+pub struct SpellIndex {
+    spells: BTreeMap<String,Vec<String>> // spell, names of creatures
+}
+
+impl SpellIndex {
+
+    pub fn add_creature(&mut self, creature: &Creature) {
+        for spell in creature.get_spell_names() {
+            let creatures = self.spells.entry(spell).or_default();
+            if !creatures.contains(&creature.name) {
+                creatures.push(creature.name.clone());
+                creatures.sort();
+            }
+        }
+    }
+
+    pub fn write_to_string(&self, format: &SpellIndexFormat) -> Result<String,String> {
+        Ok(match format {
+            SpellIndexFormat::Text => {
+                let mut result = String::new();
+                for (spell,creatures) in &self.spells {
+                    result.push_str(&format!("{} ({}): {}\n",spell,creatures.len(),creatures.join(", ")));
+                }
+                result
+            },
+            SpellIndexFormat::Markdown => {
+                let mut result = String::new();
+                for (spell,creatures) in &self.spells {
+                    result.push_str(&format!("* *{}*: {}\n",spell,creatures.join(", ")));
+                }
+                result
+            },
+            SpellIndexFormat::JSON => serde_json::to_string_pretty(&self.spells).map_err(|e| e.to_string())?
+        })
+    }
+
+}
@@ -134,7 +134,9 @@ pub struct Spellcasting {
     pub save_dc: Option<u8>,
     pub attack_bonus: Option<i8>,
     pub spells: BTreeMap<u8,Vec<String>>, // level, list of spells
-    pub cast_before: HashSet<String>
+    pub cast_before: HashSet<String>,
+    pub name: Option<String>, // distinguishes this from other spellcasting features on the same creature
+    pub primary: bool
 
 }
 
@@ -150,7 +152,9 @@ impl Default for Spellcasting {
             save_dc: None, 
             attack_bonus: None, 
             spells: BTreeMap::new(),
-            cast_before: HashSet::new()
+            cast_before: HashSet::new(),
+            name: None,
+            primary: false
         }
     }
 
@@ -161,6 +165,22 @@ impl Spellcasting {
 
     pub const FEATURE_NAME: &'static str = "Spellcasting";
 
+    pub fn new(name: Option<String>) -> Self {
+        Self {
+            name,
+            ..Self::default()
+        }
+    }
+
+    /// The name shown on the feature, which is the name it was given or "Spellcasting".
+    pub fn feature_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(Self::FEATURE_NAME)
+    }
+
+    pub fn set_primary(&mut self) {
+        self.primary = true
+    }
+
     pub fn set_caster_level(&mut self, level: u8) {
         self.caster_level = level;
         self.generate_spell_slots();
@@ -399,10 +419,10 @@ impl InnateSpellcasting {
             format!("${{+prof + {}}}",self.ability.to_short_str())
         };
 
-        let components = if let Some(components) = &self.alternate_components {
-            components
-        } else {
-            "requiring no material components"
+        let components = match &self.alternate_components {
+            Some(components) if components.is_empty() => String::new(),
+            Some(components) => format!(", {}",components),
+            None => ", requiring no material components".to_owned()
         };
 
         // FUTURE: If anyone ever complains, there's a slightly different format if there's only one spell:
@@ -410,7 +430,7 @@ impl InnateSpellcasting {
         // -- one problem here is that form doesn't provide the spell save and attacks, in theory because sleep doesn't require them. I currently have no way of knowing whether I need those anyway.
         // -- The main reason I don't want to do this is because I hate the inconsistency, and I would like their save DC listed somewhere in the stats.
         
-        let mut result = format!("${{Poss}} innate spellcasting ability is {} (spell save DC {}, {} to hit with spell attacks). ${{Subjpro}} can innately cast the following spells, {}:",self.ability,save_dc,attack_bonus,components);
+        let mut result = format!("${{Poss}} innate spellcasting ability is {} (spell save DC {}, {} to hit with spell attacks). ${{Subjpro}} can innately cast the following spells{}:",self.ability,save_dc,attack_bonus,components);
 
 
         for (count,list) in &self.spells {
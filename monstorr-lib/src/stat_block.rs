@@ -13,15 +13,17 @@ All property values are strings or lists of strings. Numbers and other data have
 
 The only exception is the 'languages' property, which does not display an em-dash if it is not present.
 
-The schema for the JSON format is defined on the [`CreatureStatBlock`] struct.
+The schema for the JSON format is defined on the [`CreatureStatBlock`] struct. A formal JSON Schema for the same structure is available from [`CreatureStatBlock::json_schema`], for tools which validate the output or generate types from it.
 */
 
 use std::convert::TryInto;
+use std::collections::BTreeMap;
 
 use serde::Deserialize;
 use serde::Serialize;
 
 use crate::structured_text::TextBlock;
+use crate::utils::to_title_case;
 
 
 
@@ -29,14 +31,22 @@ use crate::structured_text::TextBlock;
 #[derive(Serialize,Deserialize)]
 #[serde(deny_unknown_fields)]
 /**
-Features such as special abilities and actions in a stat block are a simple object that contains a single 'text' property list of [`crate::structured_text::TextBlock`]. The added property makes JSON formatting of the structure a little less confusing visually, and makes it easier to wrap the object in template code.
+Features such as special abilities and actions in a stat block are a simple object that contains a 'text' property list of [`crate::structured_text::TextBlock`]. The added property makes JSON formatting of the structure a little less confusing visually, and makes it easier to wrap the object in template code.
 */
 pub struct StatBlockFeature {
     /**
     `text: list(<TextBlock>)`
 
     */
-    pub text: Vec<TextBlock>
+    pub text: Vec<TextBlock>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    /**
+    `trigger?: <string>`
+
+    The trigger of a reaction, as plain text, if it was given separately from the response. It is already included in the text, so templates can ignore it.
+    */
+    pub trigger: Option<String>
 }
 
 #[derive(PartialEq,Debug)]
@@ -52,8 +62,8 @@ pub struct StatBlockLegendary {
 #[serde(deny_unknown_fields)]
 pub struct StatBlockLairActions {
     pub foreword: Vec<TextBlock>,
-    pub actions: Vec<String>,
-    pub afterword: Option<String>
+    pub actions: Vec<StatBlockFeature>,
+    pub afterword: Vec<TextBlock>
 }
 
 #[derive(PartialEq,Debug)]
@@ -61,8 +71,28 @@ pub struct StatBlockLairActions {
 #[serde(deny_unknown_fields)]
 pub struct StatBlockRegionalEffects {
     pub foreword: Vec<TextBlock>,
-    pub effects: Vec<String>,
-    pub afterword: Option<String>
+    pub effects: Vec<StatBlockFeature>,
+    pub afterword: Vec<TextBlock>
+}
+
+#[derive(PartialEq,Debug,Clone)]
+#[derive(Serialize,Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StatBlockDiagnostics {
+    /**
+    `completeness: <integer>`
+
+    An estimate, from 0 to 100, of how fully the creature has been specified, see [`crate::creature::Creature::get_completeness`].
+
+    */
+    pub completeness: u8,
+    /**
+    `effective_hit_points: <integer>`
+
+    The hit points, increased for resistances and immunities to common damage as they are for the defensive challenge rating, see [`crate::creature::Creature::get_effective_hit_points`].
+
+    */
+    pub effective_hit_points: u16
 }
 
 // This is the final output of monstorr, except it will be in JSON form.
@@ -82,6 +112,15 @@ pub struct CreatureStatBlock {
 
     */
     pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    
+    /**
+    `display_name?: <string>`
+
+    The title to show in place of the name, if it differs from it.
+    */
+    pub display_name: Option<String>,
     
     /**
     `size: <string>`
@@ -123,6 +162,16 @@ pub struct CreatureStatBlock {
 
     */
     pub armor: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    
+    /**
+    `initiative?: <string>`
+
+    The initiative bonus, followed by the initiative score in parentheses, as shown in stat blocks following the 2024 rules. This is only set if the creature was given an initiative.
+
+    */
+    pub initiative: Option<String>,
     
     /**
     `hit_points: <string>`
@@ -241,6 +290,15 @@ pub struct CreatureStatBlock {
 
     */
     pub challenge_rating: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+
+    /**
+    `proficiency_bonus?: <string>`
+
+    The proficiency bonus, with its sign, such as "+2". This is only set if the creature was given the `ShowProficiencyBonus` command.
+    */
+    pub proficiency_bonus: Option<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(default)]
     
@@ -279,7 +337,7 @@ pub struct CreatureStatBlock {
     /**
     `lair_actions?: <StatBlockLairActions>`
 
-    Lair actions are not yet supported.
+    Each lair action is a feature with a single list item, which has no heading. The `afterword` is empty if the creature doesn't have one.
 
     */
     pub lair_actions: Option<StatBlockLairActions>,
@@ -289,10 +347,40 @@ pub struct CreatureStatBlock {
     /**
     `regional_effects: <StatBlockRegionalEffects>`
 
-    Regional effects are not yet supported.
+    Each regional effect is a feature with a single list item, which has no heading. The `afterword` is empty if the creature doesn't have one.
 
     */
     pub regional_effects: Option<StatBlockRegionalEffects>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    
+    /**
+    `companions?: list(<string>)`
+
+    References to other creatures, by slug or file name, which this creature can summon or is accompanied by. These are not resolved, it is up to the consumer of the stat block to look them up.
+
+    */
+    pub companions: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+
+    /**
+    `habitat?: <string>`
+
+    The environments in which the creature is found, as shown in stat blocks following the 2024 rules. This is only set if the creature was given a habitat.
+
+    */
+    pub habitat: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+
+    /**
+    `treasure?: <string>`
+
+    The kind of treasure the creature carries or guards, as shown in stat blocks following the 2024 rules. This is only set if the creature was given a treasure.
+
+    */
+    pub treasure: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     
@@ -300,11 +388,82 @@ pub struct CreatureStatBlock {
     `source?: <string>`
 
     */
-    pub source: Option<String>
+    pub source: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    #[serde(default)]
+
+    /**
+    `minion?: <boolean>`
+
+    True if the creature was made a minion with the `Minion` creature command, a variant rule where the creature drops to 0 hit points when it takes any damage. This is for tools which track hit points, such as virtual tabletops.
+
+    */
+    pub minion: bool,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(default)]
+
+    /**
+    `extra?: map(<string>:<string>)`
+
+    Arbitrary values added with the `Extra` creature command, for use by other tools which consume the JSON. The standard templates ignore these.
+
+    */
+    pub extra: BTreeMap<String,String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+
+    /**
+    `notes?: [<string>]`
+
+    Notes added with the `Note` creature command, recording design intent for the people editing the creature. These are removed unless specifically requested, and the standard templates ignore them.
+
+    */
+    pub notes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+
+    /**
+    `diagnostics?: <StatBlockDiagnostics>`
+
+    Information for the people editing the creature, rather than about the creature itself. This is only included in the JSON output when the `--with-diagnostics` option is used, and only for creatures built from creature files.
+
+    */
+    pub diagnostics: Option<StatBlockDiagnostics>
+}
+
+#[derive(PartialEq,Clone,Copy,Debug)]
+/**
+The style of presentation for a stat block. This doesn't change any of the data, just how some of it is formatted.
+*/
+pub enum StatBlockStyle {
+    /// The style used in the 2014 core books, where the size is capitalized, but the type and alignment are not: "Huge dragon (metallic), lawful good".
+    Classic,
+    /// The style used in the 2024 core books, where the size, type and alignment are all capitalized: "Huge Dragon (Metallic), Lawful Good".
+    Modern
+}
+
+impl Default for StatBlockStyle {
+
+    fn default() -> Self {
+        Self::Classic
+    }
 }
 
 impl CreatureStatBlock {
 
+    /// Reformats the properties affected by the style. Stat blocks are created in the classic style, so that style doesn't change anything.
+    pub fn apply_style(&mut self, style: &StatBlockStyle) {
+        match style {
+            StatBlockStyle::Classic => (),
+            StatBlockStyle::Modern => {
+                self.size = to_title_case(&self.size);
+                self.type_ = to_title_case(&self.type_);
+                self.subtype = self.subtype.as_deref().map(to_title_case);
+                self.alignment = to_title_case(&self.alignment);
+            }
+        }
+    }
+
     pub fn write_to_string(&self, ugly: bool) -> Result<String,String> {
 
         if ugly {
@@ -315,6 +474,242 @@ impl CreatureStatBlock {
 
     }
 
+    /**
+    Returns a JSON Schema (draft 7) describing the JSON output of this struct, including the structured text in features. The schema is written by hand, so it must be kept up to date when properties are added.
+    */
+    pub fn json_schema() -> &'static str {
+        include_str!("stat_block.schema.json")
+    }
+
+    /**
+    Copies the essential properties into a [`CompactStatBlock`], leaving out the features, actions and other text.
+    */
+    pub fn to_compact(&self) -> CompactStatBlock {
+        CompactStatBlock {
+            name: self.name.clone(),
+            display_name: self.display_name.clone(),
+            size: self.size.clone(),
+            type_: self.type_.clone(),
+            subtype: self.subtype.clone(),
+            alignment: self.alignment.clone(),
+            armor: self.armor.clone(),
+            hit_points: self.hit_points.clone(),
+            speed: self.speed.clone(),
+            strength: self.strength.clone(),
+            dexterity: self.dexterity.clone(),
+            constitution: self.constitution.clone(),
+            intelligence: self.intelligence.clone(),
+            wisdom: self.wisdom.clone(),
+            charisma: self.charisma.clone(),
+            challenge_rating: self.challenge_rating.clone()
+        }
+    }
+
+}
+
+#[derive(PartialEq,Debug)]
+#[derive(Serialize,Deserialize)]
+#[serde(deny_unknown_fields)]
+/**
+A smaller version of the [`CreatureStatBlock`], output by the JSON command with the `--compact` option, for encounter tools and indexes which don't need the descriptions. It has only the properties listed here, which are the same as the properties with the same names in the full stat block.
+*/
+pub struct CompactStatBlock {
+    /**
+    `name: <string>`
+
+    */
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    /**
+    `display_name?: <string>`
+
+    The title to show in place of the name, if it differs from it.
+
+    */
+    pub display_name: Option<String>,
+    /**
+    `size: <string>`
+
+    */
+    pub size: String,
+    #[serde(rename="type")]
+    /**
+    `type: <string>`
+
+    */
+    pub type_: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    /**
+    `subtype?: <string>`
+
+    */
+    pub subtype: Option<String>,
+    /**
+    `alignment: <string>`
+
+    */
+    pub alignment: String,
+    /**
+    `armor: <string>`
+
+    */
+    pub armor: String,
+    /**
+    `hit_points: <string>`
+
+    */
+    pub hit_points: String,
+    /**
+    `speed: <string>`
+
+    */
+    pub speed: String,
+    /**
+    `strength: <string>`
+
+    */
+    pub strength: String,
+    /**
+    `dexterity: <string>`
+
+    */
+    pub dexterity: String,
+    /**
+    `constitution: <string>`
+
+    */
+    pub constitution: String,
+    /**
+    `intelligence: <string>`
+
+    */
+    pub intelligence: String,
+    /**
+    `wisdom: <string>`
+
+    */
+    pub wisdom: String,
+    /**
+    `charisma: <string>`
+
+    */
+    pub charisma: String,
+    /**
+    `challenge_rating: <string>`
+
+    */
+    pub challenge_rating: String
+}
+
+impl CompactStatBlock {
+
+    pub fn write_to_string(&self, ugly: bool) -> Result<String,String> {
+
+        if ugly {
+            serde_json::to_string(self).map_err(|e| e.to_string())
+        } else {
+            serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+        }
+
+    }
+
+}
+
+/**
+The fixed labels which templates place around the stat block properties, such as "Armor Class" and "Actions". These can be replaced to produce stat blocks for tables in other languages, while the creature's own content stays as it was written. The labels are available to templates as properties of `labels`, such as `{{ labels.armor_class }}`.
+
+The ability headings in the LaTeX output are printed by the LaTeX package, so `strength` through `charisma` have no effect there.
+
+A language file is written in the same RON format as creature files, and only needs to list the labels which should be changed, the rest stay in English:
+
+```ron
+(
+    armor_class: "Classe d'armure",
+    hit_points: "Points de vie",
+    speed: "Vitesse",
+    actions: "Actions",
+)
+```
+*/
+#[derive(PartialEq,Debug,Clone,Serialize,Deserialize)]
+#[serde(default)]
+pub struct StatBlockLabels {
+    pub armor_class: String,
+    pub initiative: String,
+    pub hit_points: String,
+    pub speed: String,
+    pub strength: String,
+    pub dexterity: String,
+    pub constitution: String,
+    pub intelligence: String,
+    pub wisdom: String,
+    pub charisma: String,
+    pub saving_throws: String,
+    pub skills: String,
+    pub damage_vulnerabilities: String,
+    pub damage_resistances: String,
+    pub damage_immunities: String,
+    pub condition_immunities: String,
+    pub senses: String,
+    pub languages: String,
+    pub challenge: String,
+    pub proficiency_bonus: String,
+    pub actions: String,
+    pub reactions: String,
+    pub legendary_actions: String,
+    pub lair_actions: String,
+    pub regional_effects: String,
+    pub summons: String,
+    pub habitat: String,
+    pub treasure: String,
+    pub source: String
+}
+
+impl Default for StatBlockLabels {
+
+    fn default() -> Self {
+        Self {
+            armor_class: "Armor Class".to_owned(),
+            initiative: "Initiative".to_owned(),
+            hit_points: "Hit Points".to_owned(),
+            speed: "Speed".to_owned(),
+            strength: "STR".to_owned(),
+            dexterity: "DEX".to_owned(),
+            constitution: "CON".to_owned(),
+            intelligence: "INT".to_owned(),
+            wisdom: "WIS".to_owned(),
+            charisma: "CHA".to_owned(),
+            saving_throws: "Saving Throws".to_owned(),
+            skills: "Skills".to_owned(),
+            damage_vulnerabilities: "Damage Vulnerabilities".to_owned(),
+            damage_resistances: "Damage Resistances".to_owned(),
+            damage_immunities: "Damage Immunities".to_owned(),
+            condition_immunities: "Condition Immunities".to_owned(),
+            senses: "Senses".to_owned(),
+            languages: "Languages".to_owned(),
+            challenge: "Challenge".to_owned(),
+            proficiency_bonus: "Proficiency Bonus".to_owned(),
+            actions: "Actions".to_owned(),
+            reactions: "Reactions".to_owned(),
+            legendary_actions: "Legendary Actions".to_owned(),
+            lair_actions: "Lair Actions".to_owned(),
+            regional_effects: "Regional Effects".to_owned(),
+            summons: "Summons".to_owned(),
+            habitat: "Habitat".to_owned(),
+            treasure: "Treasure".to_owned(),
+            source: "Source".to_owned()
+        }
+    }
+}
+
+impl StatBlockLabels {
+
+    pub fn load_from_str(data: &str) -> Result<Self,ron::Error> {
+        ron::de::from_str(data)
+    }
+
 }
 
 
@@ -212,6 +212,7 @@ impl std::str::FromStr for Damage {
             "fire" => Damage::Fire,
             "thunder" => Damage::Thunder,
             "radiant" => Damage::Radiant,
+            "force" => Damage::Force,
             "lightning" => Damage::Lightning,
             "poison" => Damage::Poison,
             "acid" => Damage::Acid,
@@ -301,12 +302,21 @@ pub enum Language {
     WinterWolf,
     /// `Worg`
     Worg,
+    /**
+    `KnewInLife`
+
+    The languages the creature knew in life, for undead such as ghosts and zombies.
+    */
+    KnewInLife,
     /** 
-    `Telepathy(<integer>)`
+    `Telepathy(<integer>,<string>?)`
     
-    This language adds the telepathy modifier to the languages in your creature's stat block. The integer represents the range of the telepathy.
+    This language adds the telepathy modifier to the languages in your creature's stat block. The integer represents the range of the telepathy. The optional string is a note written in parentheses after it, such as "works only with creatures that understand a language". Telepathy is always listed last.
     */
-    Telepathy(u8),
+    Telepathy(u8,
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        Option<String>),
     /**
     `Language(<string>)`
 
@@ -351,7 +361,9 @@ impl std::fmt::Display for Language {
             Language::Druidic => write!(f,"Druidic"),
             Language::WinterWolf => write!(f,"Winter Wolf"),
             Language::Worg => write!(f,"Worg"),
-            Language::Telepathy(a) => write!(f,"telepathy {} ft.",a),
+            Language::KnewInLife => write!(f,"the languages it knew in life"),
+            Language::Telepathy(a,None) => write!(f,"telepathy {} ft.",a),
+            Language::Telepathy(a,Some(note)) => write!(f,"telepathy {} ft. ({})",a,note),
             Language::Language(a) => write!(f,"{}",a)
        
         }
@@ -406,6 +418,79 @@ pub enum Skill {
 }
 
 
+#[derive(Clone,PartialEq,Debug)]
+#[derive(Serialize,Deserialize)]
+/**
+These are used to add the proficiencies of a character class to a creature, for creatures built like player characters, see the `ClassProficiencies` creature command.
+*/
+pub enum CharacterClass {
+    /// `Barbarian`
+    Barbarian,
+    /// `Bard`
+    Bard,
+    /// `Cleric`
+    Cleric,
+    /// `Druid`
+    Druid,
+    /// `Fighter`
+    Fighter,
+    /// `Monk`
+    Monk,
+    /// `Paladin`
+    Paladin,
+    /// `Ranger`
+    Ranger,
+    /// `Rogue`
+    Rogue,
+    /// `Sorcerer`
+    Sorcerer,
+    /// `Warlock`
+    Warlock,
+    /// `Wizard`
+    Wizard,
+}
+
+impl CharacterClass {
+
+    /// The saving throw proficiencies the class gets at first level.
+    pub fn get_saves(&self) -> Vec<Ability> {
+        match self {
+            CharacterClass::Barbarian => vec![Ability::Strength, Ability::Constitution],
+            CharacterClass::Bard => vec![Ability::Dexterity, Ability::Charisma],
+            CharacterClass::Cleric => vec![Ability::Wisdom, Ability::Charisma],
+            CharacterClass::Druid => vec![Ability::Intelligence, Ability::Wisdom],
+            CharacterClass::Fighter => vec![Ability::Strength, Ability::Constitution],
+            CharacterClass::Monk => vec![Ability::Strength, Ability::Dexterity],
+            CharacterClass::Paladin => vec![Ability::Wisdom, Ability::Charisma],
+            CharacterClass::Ranger => vec![Ability::Strength, Ability::Dexterity],
+            CharacterClass::Rogue => vec![Ability::Dexterity, Ability::Intelligence],
+            CharacterClass::Sorcerer => vec![Ability::Constitution, Ability::Charisma],
+            CharacterClass::Warlock => vec![Ability::Wisdom, Ability::Charisma],
+            CharacterClass::Wizard => vec![Ability::Intelligence, Ability::Wisdom],
+        }
+    }
+
+    /// A typical choice of skill proficiencies for the class, with as many skills as the class chooses at first level.
+    pub fn get_skills(&self) -> Vec<Skill> {
+        match self {
+            CharacterClass::Barbarian => vec![Skill::Athletics, Skill::Perception],
+            CharacterClass::Bard => vec![Skill::Deception, Skill::Performance, Skill::Persuasion],
+            CharacterClass::Cleric => vec![Skill::Insight, Skill::Religion],
+            CharacterClass::Druid => vec![Skill::Nature, Skill::Perception],
+            CharacterClass::Fighter => vec![Skill::Athletics, Skill::Perception],
+            CharacterClass::Monk => vec![Skill::Acrobatics, Skill::Insight],
+            CharacterClass::Paladin => vec![Skill::Athletics, Skill::Religion],
+            CharacterClass::Ranger => vec![Skill::Perception, Skill::Stealth, Skill::Survival],
+            CharacterClass::Rogue => vec![Skill::Acrobatics, Skill::Deception, Skill::Perception, Skill::Stealth],
+            CharacterClass::Sorcerer => vec![Skill::Arcana, Skill::Persuasion],
+            CharacterClass::Warlock => vec![Skill::Arcana, Skill::Deception],
+            CharacterClass::Wizard => vec![Skill::Arcana, Skill::History],
+        }
+    }
+
+}
+
+
 #[derive(Debug)]
 pub struct ParseLanguageError;
 
@@ -445,6 +530,7 @@ impl std::str::FromStr for Language {
             "druidic" => Language::Druidic,
             "winter wolf" => Language::WinterWolf,
             "worg" => Language::Worg,
+            "the languages it knew in life" | "any languages it knew in life" | "languages it knew in life" => Language::KnewInLife,
             _ => Err(ParseLanguageError)?
         })
     }    
@@ -496,6 +582,16 @@ pub enum Armor {
     */
     Natural(i8), // natural bonus
 
+    /**
+    `NaturalFlat(<integer>,<string>?)`
+
+    Used to give a creature natural armor with the specified armor class, which the dexterity bonus is not added to, such as a heavily armored beast. The description is "natural armor", followed by the optional string as a note, such as `NaturalFlat(17,"stone hide")` for "natural armor, stone hide".
+    */
+    NaturalFlat(u8,
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        Option<String>), // armor class, note
+
     /**
     `Armor(<integer>,<string>)`
 
@@ -576,7 +672,8 @@ impl std::fmt::Display for CreatureType {
 
 
 
-
+#[derive(PartialEq,Debug,Clone)]
+#[derive(Serialize,Deserialize)]
 pub enum Alignment {
     AnyAlignment,
     AnyNonGood,
@@ -598,6 +695,7 @@ pub enum Alignment {
     ChaoticEvil,
     Unaligned,
     Custom(String), // custom alignment, such as (50% good and 50% evil)
+    Chance(Vec<(u8,Alignment)>), // alignments with the percent chance of each, such as neutral evil (75%) or lawful evil (25%)
 
 }
 
@@ -624,13 +722,41 @@ impl std::fmt::Display for Alignment {
             Alignment::NeutralEvil => write!(f,"neutral evil"),
             Alignment::ChaoticEvil => write!(f,"chaotic evil"),
             Alignment::Unaligned => write!(f,"unaligned"),
-            Alignment::Custom(a) => write!(f,"{}",a)
+            Alignment::Custom(a) => write!(f,"{}",a),
+            Alignment::Chance(chances) => {
+                let chances: Vec<String> = chances.iter().map(|(percent,alignment)| format!("{} ({}%)",alignment,percent)).collect();
+                match chances.split_last() {
+                    Some((last,rest)) if !rest.is_empty() => write!(f,"{} or {}",rest.join(", "),last),
+                    Some((last,_)) => write!(f,"{}",last),
+                    None => write!(f,"{}",Alignment::AnyAlignment)
+                }
+            }
         }
 
     }
 
 }
 
+impl Alignment {
+
+    /**
+    Checks whether an alignment, as written in a stat block, matches the one being looked for, ignoring case. An alignment with chances, such as "neutral evil (75%) or lawful evil (25%)", matches each of the alignments it could be, as well as the whole text.
+    */
+    pub fn text_matches(text: &str, alignment: &str) -> bool {
+        let text = text.trim().to_lowercase();
+        let alignment = alignment.trim().to_lowercase();
+        if text == alignment {
+            return true
+        }
+        text.split(" or ").flat_map(|a| a.split(", ")).any(|a| {
+            match a.trim().strip_suffix("%)").and_then(|a| a.rsplit_once(" (")) {
+                Some((name,percent)) if percent.parse::<u8>().is_ok() => name == alignment,
+                _ => false
+            }
+        })
+    }
+}
+
 #[derive(Debug)]
 pub enum ParseChallengeRatingError {
     InvalidDenominator,
@@ -652,7 +778,7 @@ impl std::fmt::Display for ParseChallengeRatingError {
 }
 
 
-#[derive(PartialEq)]
+#[derive(PartialEq,Eq,Debug)]
 pub enum ChallengeRating {
     None, // for CR 0 with 0 XP, CR 0 with 10 XP uses Whole()
     Eighth,
@@ -733,49 +859,26 @@ impl ChallengeRating {
 impl std::cmp::PartialOrd for ChallengeRating {
 
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(match self {
-            ChallengeRating::None => match other {
-                ChallengeRating::None => std::cmp::Ordering::Equal,
-                _ => std::cmp::Ordering::Less
-            },
-            ChallengeRating::Eighth => match other {
-                ChallengeRating::None => std::cmp::Ordering::Greater,
-                ChallengeRating::Whole(0) => std::cmp::Ordering::Greater,
-                ChallengeRating::Eighth => std::cmp::Ordering::Equal,
-                _ => std::cmp::Ordering::Less
-            },
-            ChallengeRating::Quarter => match other {
-                ChallengeRating::None | 
-                ChallengeRating::Eighth => std::cmp::Ordering::Greater,
-                ChallengeRating::Whole(0) => std::cmp::Ordering::Greater,
-                ChallengeRating::Quarter => std::cmp::Ordering::Equal,
-                _ => std::cmp::Ordering::Less
-            },
-            ChallengeRating::Half => match other {
-                ChallengeRating::None | 
-                ChallengeRating::Eighth |
-                ChallengeRating::Quarter => std::cmp::Ordering::Greater,
-                ChallengeRating::Whole(0) => std::cmp::Ordering::Greater,
-                ChallengeRating::Half => std::cmp::Ordering::Equal,
-                _ => std::cmp::Ordering::Less
-            },
-            ChallengeRating::Whole(0) =>  match other {
-                ChallengeRating::None => std::cmp::Ordering::Greater,
-                ChallengeRating::Eighth |
-                ChallengeRating::Quarter |
-                ChallengeRating::Half => std::cmp::Ordering::Less,
-                ChallengeRating::Whole(0) => std::cmp::Ordering::Equal,
-                _ => std::cmp::Ordering::Less
-            },
-            ChallengeRating::Whole(lhs) =>  match other {
-                ChallengeRating::None | 
-                ChallengeRating::Eighth |
-                ChallengeRating::Quarter |
-                ChallengeRating::Half => std::cmp::Ordering::Greater,
-                ChallengeRating::Whole(rhs) => lhs.partial_cmp(rhs)?
-            },
-        })
+        Some(self.cmp(other))
+    }
 
+}
+
+impl std::cmp::Ord for ChallengeRating {
+
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // None is CR 0 with no XP, so it sorts before CR 0 with 10 XP. The fractions come between 0 and 1.
+        fn rank(cr: &ChallengeRating) -> (u8,u8) {
+            match cr {
+                ChallengeRating::None => (0,0),
+                ChallengeRating::Whole(0) => (0,1),
+                ChallengeRating::Eighth => (0,2),
+                ChallengeRating::Quarter => (0,3),
+                ChallengeRating::Half => (0,4),
+                ChallengeRating::Whole(a) => (*a,0)
+            }
+        }
+        rank(self).cmp(&rank(other))
     }
 
 }
@@ -797,10 +900,18 @@ impl std::str::FromStr for ChallengeRating {
     type Err = ParseChallengeRatingError;
     
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let trimmed = s.trim();
+        let trimmed = s.trim().to_lowercase();
+        // word forms, as in 'one-quarter' or 'half'
+        let word = trimmed.strip_prefix("one").map(|a| a.trim_start_matches(|c: char| c == '-' || c.is_whitespace())).unwrap_or(&trimmed);
+        match word {
+            "eighth" => return Ok(ChallengeRating::Eighth),
+            "quarter" | "fourth" => return Ok(ChallengeRating::Quarter),
+            "half" => return Ok(ChallengeRating::Half),
+            _ => ()
+        }
         if let Some((numerator,denominator)) = trimmed.split_once('/') {
-            if numerator == "1" {
-                match denominator {
+            if numerator.trim() == "1" {
+                match denominator.trim() {
                     "2" => Ok(ChallengeRating::Half),
                     "4" => Ok(ChallengeRating::Quarter),
                     "8" => Ok(ChallengeRating::Eighth),
@@ -816,7 +927,13 @@ impl std::str::FromStr for ChallengeRating {
                 _ => Err(ParseChallengeRatingError::ChallengeRatingTooBig)
             }
         } else {
-            Err(ParseChallengeRatingError::CouldNotParseAsNumber)
+            // some sources, such as open5e's numeric 'cr' property, use decimals
+            match trimmed.as_str() {
+                "0.125" => Ok(ChallengeRating::Eighth),
+                "0.25" => Ok(ChallengeRating::Quarter),
+                "0.5" => Ok(ChallengeRating::Half),
+                _ => Err(ParseChallengeRatingError::CouldNotParseAsNumber)
+            }
         }
     }    
 }
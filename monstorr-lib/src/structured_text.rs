@@ -9,7 +9,7 @@
 use serde::Deserialize;
 use serde::Serialize;
 
-#[derive(PartialEq,Debug)]
+#[derive(PartialEq,Debug,Clone)]
 #[derive(Serialize,Deserialize)]
 #[serde(rename_all = "lowercase")]
 #[serde(tag="style",content="content")]
@@ -30,7 +30,7 @@ pub enum TextSpan {
     BoldItalic(String)
 }
 
-#[derive(PartialEq,Debug)]
+#[derive(PartialEq,Debug,Clone)]
 #[derive(Serialize,Deserialize)]
 #[serde(rename_all = "lowercase")]
 #[serde(tag="block")]
@@ -64,4 +64,34 @@ pub enum TextBlock {
         heading: Option<Vec<TextSpan>>,
         body: Vec<TextSpan>
     }
-}
\ No newline at end of file
+}
+/**
+Returns the text of the spans without their styles, for output formats which can't show them.
+*/
+pub fn spans_to_text(spans: &[TextSpan]) -> String {
+    spans.iter().map(|span| match span {
+        TextSpan::Normal(text) |
+        TextSpan::Italic(text) |
+        TextSpan::Bold(text) |
+        TextSpan::BoldItalic(text) => text.as_str()
+    }).collect()
+}
+
+/**
+Returns the text of a block without styles, with the heading, if there is one, followed by a space and the body.
+*/
+pub fn block_to_text(block: &TextBlock) -> String {
+    match block {
+        TextBlock::Paragraph { heading: Some(heading), body } |
+        TextBlock::SubParagraph { heading: Some(heading), body } => format!("{} {}",spans_to_text(heading),spans_to_text(body)),
+        TextBlock::Paragraph { heading: None, body } |
+        TextBlock::SubParagraph { heading: None, body } => spans_to_text(body)
+    }
+}
+
+/**
+Returns the text of the blocks without styles, as with [`block_to_text`], each on its own line.
+*/
+pub fn blocks_to_text(blocks: &[TextBlock]) -> String {
+    blocks.iter().map(block_to_text).collect::<Vec<String>>().join("\n")
+}
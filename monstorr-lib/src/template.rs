@@ -8,11 +8,13 @@
 
 use std::collections::HashMap;
 
+use serde::Serialize;
 use minijinja::Environment;
 use minijinja::meta::find_referenced_templates;
 use monstorr_data::templates::StoredTemplates;
 
 use crate::stat_block::CreatureStatBlock;
+use crate::stat_block::StatBlockLabels;
 
 
 /*
@@ -141,7 +143,15 @@ fn resolve_templates<Resolver: TemplateSourceResolver>(resolver: &Resolver, reso
 }
 
 
-pub fn process_template<Resolver: TemplateSourceResolver>(resolver: &Resolver, template: &str, includes: &Vec<String>, stat_block: &CreatureStatBlock) -> Result<String,String> {
+// The data passed to the templates: the properties of the stat block, with the fixed labels available under 'labels'.
+#[derive(Serialize)]
+struct TemplateContext<'a> {
+    #[serde(flatten)]
+    stat_block: &'a CreatureStatBlock,
+    labels: &'a StatBlockLabels
+}
+
+pub fn process_template<Resolver: TemplateSourceResolver>(resolver: &Resolver, template: &str, includes: &Vec<String>, stat_block: &CreatureStatBlock, labels: &StatBlockLabels) -> Result<String,String> {
 
     // NOTE: Even though minijinja finally provided a hook for resolving the templates at run-time, I had already gone through and done
     // this. The new mechanism requires Send and Sync and 'static, and a preliminary attempt to do that showed me that I would need
@@ -167,7 +177,10 @@ pub fn process_template<Resolver: TemplateSourceResolver>(resolver: &Resolver, t
     }
 
     let template = env.get_template(&template).map_err(|e| format!("Error parsing template '{}': {}",template,e))?;
-    template.render(stat_block).map_err(|e| format!("Template error: {}",e))
+    template.render(TemplateContext {
+        stat_block,
+        labels
+    }).map_err(|e| format!("Template error: {}",e))
 
 }
 
@@ -8,6 +8,8 @@
 
 
     use std::path::PathBuf;
+    use std::collections::HashMap;
+    use std::collections::BTreeMap;
 
     use pretty_assertions::assert_eq;
 
@@ -16,9 +18,13 @@
     use crate::features::*;
     use crate::attacks::*;
     use crate::dice::*;
+    use crate::dice_expression::*;
     use crate::actions::*;
     use crate::stat_block::*;
     use crate::structured_text::*;
+    use crate::spell_index::*;
+    use crate::reactions::*;
+    use crate::creature::CreatureSpecialAbility;
 
 
     fn goblin() -> CreatureCreator {
@@ -53,6 +59,13 @@
         ])
     }
 
+    // the goblin with some commands added to the end, built into a stat block.
+    fn goblin_with(commands: Vec<CreatureCommand>) -> CreatureStatBlock {
+        let mut creator = goblin();
+        creator.0.extend(commands);
+        creator.create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.").try_into_stat_block().expect("Stat block should have been created.")
+    }
+
     const GOBLIN: &str = "([
     Monstorr(1),
     Source(\"D&D 5E System Reference Document\"),
@@ -86,12 +99,14 @@
     fn goblin_stat_block() -> CreatureStatBlock {
         CreatureStatBlock {
             name: "Goblin".to_owned(),
+            display_name: None,
             size: "Small".to_owned(),
             type_: "humanoid".to_owned(),
             subtype: Some("goblinoid".to_owned()),
             group: None,
             alignment: "neutral evil".to_owned(),
             armor: "15 (leather armor, shield)".to_owned(),
+            initiative: None,
             hit_points: "7 (2d6)".to_owned(),
             speed: "30 ft.".to_owned(),
             strength: "8 (-1)".to_owned(),
@@ -109,6 +124,7 @@
             senses: "darkvision 60 ft., passive Perception 9".to_owned(),
             languages: Some("Common, Goblin".to_owned()),
             challenge_rating: "1/4 (50 XP)".to_owned(), 
+            proficiency_bonus: None,
             special_abilities: vec![
                 StatBlockFeature {
                     text: vec![
@@ -118,7 +134,8 @@
                                 TextSpan::Normal("The goblin can take the Disengage or Hide action as a bonus action on each of its turns.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 }
             ],
             actions: vec![
@@ -133,7 +150,8 @@
                                 TextSpan::Normal(" 5 (1d6 + 2) slashing damage.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 },
                 StatBlockFeature {
                     text: vec![
@@ -146,13 +164,21 @@
                                 TextSpan::Normal(" 5 (1d6 + 2) piercing damage.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 }            
             ],
             reactions: vec![],
             legendary_actions: None,
             lair_actions: None,
             regional_effects: None,
+            companions: vec![],
+            habitat: None,
+            treasure: None,
+            extra: BTreeMap::new(),
+            minion: false,
+            notes: vec![],
+            diagnostics: None,
             source: Some("D&D 5E System Reference Document".to_owned())        
         }
     }
@@ -234,12 +260,14 @@
     fn bugbear_stat_block() -> CreatureStatBlock {
         CreatureStatBlock {
             name: "Bugbear".to_owned(),
+            display_name: None,
             size: "Medium".to_owned(),
             type_: "humanoid".to_owned(),
             subtype: Some("goblinoid".to_owned()),
             group: None,
             alignment: "chaotic evil".to_owned(),
             armor: "16 (hide armor, shield)".to_owned(),
+            initiative: None,
             hit_points: "27 (5d8 + 5)".to_owned(),
             speed: "30 ft.".to_owned(),
             strength: "15 (+2)".to_owned(),
@@ -257,6 +285,7 @@
             senses: "darkvision 60 ft., passive Perception 10".to_owned(),
             languages: Some("Common, Goblin".to_owned()),
             challenge_rating: "1 (200 XP)".to_owned(),
+            proficiency_bonus: None,
             special_abilities: vec![
                 StatBlockFeature {
                     text: vec![
@@ -266,7 +295,8 @@
                                 TextSpan::Normal("A melee weapon deals one extra die of its damage when the bugbear hits with it (included in the attack).".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 },
                 StatBlockFeature {
                     text: vec![
@@ -276,7 +306,8 @@
                                 TextSpan::Normal("If the bugbear surprises a creature and hits it with an attack during the first round of combat, the target takes an extra 7 (2d6) damage from the attack.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 }
             ],
             actions: vec![
@@ -291,7 +322,8 @@
                                 TextSpan::Normal(" 11 (2d8 + 2) piercing damage.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 },
                 StatBlockFeature {
                     text: vec![
@@ -304,13 +336,21 @@
                                 TextSpan::Normal(" 9 (2d6 + 2) piercing damage in melee or 5 (1d6 + 2) piercing damage at range.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 }
             ],
             reactions: Vec::new(),
             legendary_actions: None,
             lair_actions: None,
             regional_effects: None,
+            companions: vec![],
+            habitat: None,
+            treasure: None,
+            extra: BTreeMap::new(),
+            minion: false,
+            notes: vec![],
+            diagnostics: None,
             source: Some("D&D 5E System Reference Document".to_owned())            
         }
     }
@@ -358,7 +398,8 @@
                 reach: Some(10),
                 range: None,
                 long_range: None,
-                target: "one target".to_owned()
+                target: "one target".to_owned(),
+                targeting: None
             },AttackEffect::Damage(Dice::new(2,&Die::D10).into(),AttackBonus::Default,Damage::Piercing),None),None),
             CreatureCommand::Action(Action::Attack("Claw".to_owned(),Attack {
                 type_: Some(AttackType::Weapon),
@@ -367,7 +408,8 @@
                 reach: Some(5),
                 range: None,
                 long_range: None,
-                target: "one target".to_owned()
+                target: "one target".to_owned(),
+                targeting: None
             },AttackEffect::Damage(Dice::new(2,&Die::D6).into(),AttackBonus::Default,Damage::Slashing),None),None),
             CreatureCommand::Action(Action::Attack("Tail".to_owned(),Attack {
                 type_: Some(AttackType::Weapon),
@@ -376,7 +418,8 @@
                 reach: Some(15),
                 range: None,
                 long_range: None,
-                target: "one target".to_owned()
+                target: "one target".to_owned(),
+                targeting: None
             },AttackEffect::Damage(Dice::new(2,&Die::D8).into(),AttackBonus::Default,Damage::Bludgeoning),None),None),
             CreatureCommand::Action(Action::FrightfulPresence(120,21,false),None),
             CreatureCommand::Action(Action::BreathWeapon("Breath Weapons".to_owned(),
@@ -477,12 +520,14 @@
     fn dragon_stat_block() -> CreatureStatBlock {
         CreatureStatBlock {
             name: "Adult Gold Dragon".to_owned(),
+            display_name: None,
             size: "Huge".to_owned(),
             type_: "dragon".to_owned(),
             subtype: None,
             group: None,
             alignment: "lawful good".to_owned(),
             armor: "19 (natural armor)".to_owned(),
+            initiative: None,
             hit_points: "256 (19d12 + 133)".to_owned(),
             speed: "40 ft., fly 80 ft., swim 40 ft.".to_owned(),
             strength: "27 (+8)".to_owned(),
@@ -500,6 +545,7 @@
             senses: "blindsight 60 ft., darkvision 120 ft., passive Perception 24".to_owned(),
             languages: Some("Common, Draconic".to_owned()),
             challenge_rating: "17 (18,000 XP)".to_owned(),
+            proficiency_bonus: None,
             special_abilities: vec![
                 StatBlockFeature {
                     text: vec![
@@ -509,7 +555,8 @@
                                 TextSpan::Normal("The dragon can breathe air and water.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 },
                 StatBlockFeature {
                     text: vec![
@@ -519,7 +566,8 @@
                                 TextSpan::Normal("If the dragon fails a saving throw, it can choose to succeed instead.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 }
             ],
             actions: vec![
@@ -531,7 +579,8 @@
                                 TextSpan::Normal("The dragon can use its Frightful Presence. It then makes three attacks: one with its bite and two with its claws.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 },
                 StatBlockFeature {
                     text: vec![
@@ -544,7 +593,8 @@
                                 TextSpan::Normal(" 19 (2d10 + 8) piercing damage.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 },
                 StatBlockFeature {
                     text: vec![
@@ -557,7 +607,8 @@
                                 TextSpan::Normal(" 15 (2d6 + 8) slashing damage.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 },
                 StatBlockFeature {
                     text: vec![
@@ -570,7 +621,8 @@
                                 TextSpan::Normal(" 17 (2d8 + 8) bludgeoning damage.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 },
                 StatBlockFeature {
                     text: vec![
@@ -580,12 +632,13 @@
                                 TextSpan::Normal("Each creature of the dragon's choice that is within 120 feet of the dragon and aware of it must succeed on a DC 21 Wisdom saving throw or become frightened for 1 minute. A creature can repeat the saving throw at the end of each of its turns, ending the effect on itself on a success. If a creature's saving throw is successful or the effect ends for it, the creature is immune to the dragon's Frightful Presence for the next 24 hours.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 },
                 StatBlockFeature {
                     text: vec![
                         TextBlock::Paragraph {
-                            heading: Some(vec![TextSpan::Normal("Breath Weapons (Recharge 5-6).".to_owned())]),
+                            heading: Some(vec![TextSpan::Normal("Breath Weapons (Recharge 5–6).".to_owned())]),
                             body: vec![
                                 TextSpan::Normal("The dragon uses one of the following breath weapons.".to_owned())
                             ]
@@ -606,7 +659,8 @@
                                 TextSpan::Normal("The dragon exhales gas in a 60-foot cone. Each creature in that area must succeed on a DC 21 Strength saving throw or have disadvantage on Strength-based attack rolls, Strength checks, and Strength saving throws for 1 minute. A creature can repeat the saving throw at the end of each of its turns, ending the effect on itself on a success.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 },
                 StatBlockFeature {
                     text: vec![
@@ -616,7 +670,8 @@
                                 TextSpan::Normal("The dragon magically polymorphs into a humanoid or beast that has a challenge rating no higher than its own, or back into its true form. It reverts to its true form if it dies. Any equipment it is wearing or carrying is absorbed or borne by the new form (the dragon's choice). In a new form, the dragon retains its alignment, hit points, Hit Dice, ability to speak, proficiencies, Legendary Resistance, lair actions, and Intelligence, Wisdom, and Charisma scores, as well as this action. Its statistics and capabilities are otherwise replaced by those of the new form, except any class features or legendary actions of that form.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 }
             ],
             reactions: Vec::new(),
@@ -638,7 +693,8 @@
                                     TextSpan::Normal("The dragon makes a Wisdom (Perception) check.".to_owned())
                                 ]
                             }
-                        ]
+                        ],
+                        trigger: None
                     },
                     StatBlockFeature {
                         text: vec![
@@ -648,7 +704,8 @@
                                     TextSpan::Normal("The dragon makes a tail attack.".to_owned())
                                 ]
                             }
-                        ]
+                        ],
+                        trigger: None
                     },
                     StatBlockFeature {
                         text: vec![
@@ -658,12 +715,20 @@
                                     TextSpan::Normal("The dragon beats its wings. Each creature within 10 feet of the dragon must succeed on a DC 22 Dexterity saving throw or take 15 (2d6 + 8) bludgeoning damage and be knocked prone. The dragon can then fly up to half its flying speed.".to_owned())
                                 ]
                             }
-                        ]
+                        ],
+                        trigger: None
                     }
                 ]
             }),
             lair_actions: None,
             regional_effects: None,
+            companions: vec![],
+            habitat: None,
+            treasure: None,
+            extra: BTreeMap::new(),
+            minion: false,
+            notes: vec![],
+            diagnostics: None,
             source: Some("D&D 5E System Reference Document".to_owned())            
         }
     }
@@ -715,7 +780,8 @@
                 reach: None,
                 range: Some(120),
                 long_range: None,
-                target: "one target".to_owned()
+                target: "one target".to_owned(),
+                targeting: None
             },AttackEffect::Damage(Dice::new(5,&Die::D6).into(),AttackBonus::Fixed(0),Damage::Fire),None),None)
 
         ])
@@ -792,12 +858,14 @@
     fn efreeti_stat_block() -> CreatureStatBlock {
         CreatureStatBlock {
             name: "Efreeti".to_owned(),
+            display_name: None,
             size: "Large".to_owned(),
             type_: "elemental".to_owned(),
             subtype: None,
             group: None,
             alignment: "lawful evil".to_owned(),
             armor: "17 (natural armor)".to_owned(),
+            initiative: None,
             hit_points: "200 (16d10 + 112)".to_owned(),
             speed: "40 ft., fly 60 ft.".to_owned(),
             strength: "22 (+6)".to_owned(),
@@ -815,6 +883,7 @@
             senses: "darkvision 120 ft., passive Perception 12".to_owned(),
             languages: Some("Ignan".to_owned()),
             challenge_rating: "11 (7,200 XP)".to_owned(),
+            proficiency_bonus: None,
             special_abilities: vec![
                 StatBlockFeature {
                     text: vec![
@@ -824,7 +893,8 @@
                                 TextSpan::Normal("If the efreeti dies, its body disintegrates in a flash of fire and puff of smoke, leaving behind only equipment the efreeti was wearing or carrying.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 },
                 StatBlockFeature {
                     text: vec![
@@ -867,7 +937,8 @@
                                 TextSpan::Italic("tongues".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 }
             ],
             actions: vec![
@@ -879,7 +950,8 @@
                                 TextSpan::Normal("The efreeti makes two scimitar attacks or uses its Hurl Flame twice.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 },
                 StatBlockFeature {
                     text: vec![
@@ -892,7 +964,8 @@
                                 TextSpan::Normal(" 13 (2d6 + 6) slashing damage plus 7 (2d6) fire damage.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 },
                 StatBlockFeature {
                     text: vec![
@@ -905,13 +978,21 @@
                                 TextSpan::Normal(" 17 (5d6) fire damage.".to_owned())
                             ]
                         }
-                    ]
+                    ],
+                    trigger: None
                 }
             ],
             reactions: Vec::new(),
             legendary_actions: None,
             lair_actions: None,
             regional_effects: None,
+            companions: vec![],
+            habitat: None,
+            treasure: None,
+            extra: BTreeMap::new(),
+            minion: false,
+            notes: vec![],
+            diagnostics: None,
             source: Some("D&D 5E System Reference Document".to_owned()),
  
         }
@@ -989,6 +1070,17 @@
 
     }
 
+    #[test]
+    #[cfg(feature = "toml-format")]
+    fn toml_round_trip() {
+        let toml = goblin().save_to_toml_string().expect("Creature should have been written as TOML.");
+        assert!(toml.contains("commands"));
+        assert_eq!(CreatureCreator::load_from_toml_str(&toml).expect("Creature should have been read from TOML."),goblin());
+        assert_eq!(CreatureCreator::load_from_toml_str("commands = [ { Name = \"Goblin\" }, \"Small\", { Str = 8 } ]").expect("Creature should have been read from TOML."),
+                   CreatureCreator(vec![CreatureCommand::Name("Goblin".to_owned()),CreatureCommand::Small,CreatureCommand::Str(8)]));
+
+    }
+
     #[test]
     fn deserialize_goblin() {
         assert_eq!(CreatureCreator::load_from_str(GOBLIN),Ok(goblin()))
@@ -1013,3 +1105,2150 @@
 
     }
 
+
+    #[test]
+    fn define_and_add_weapon() {
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::DefineWeapon("Chieftain's Axe".to_owned(),Weapon::Handaxe(1),Some(CompoundAttackEffect::Plus(AttackEffect::Damage(Dice::new(1,&Die::D6).into(),AttackBonus::Zero,Damage::Fire)))));
+        commands.push(CreatureCommand::AddWeapon("Chieftain's Axe".to_owned()));
+        let creature = CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        let action = creature.actions.iter().find(|a| a.name == "Chieftain's Axe").expect("Defined weapon should have been added.");
+        assert_eq!(action.effect,Some(Weapon::Handaxe(1).get_effect(&CreatureSize::Small)));
+
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::AddWeapon("Chieftain's Axe".to_owned()));
+        assert!(CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).is_err());
+
+    }
+
+    #[test]
+    fn seeded_encounter_is_repeatable() {
+        let generate = |seed| crate::generate_random_encounter(None, crate::ListInputFormat::Stored, None, None, None, None, None, None, None, 4, 3, crate::EncounterDifficulty::Hard, seed).expect("Encounter should have been generated.");
+        let first = generate(42);
+        let second = generate(42);
+        assert_eq!(first.creatures.iter().map(|(c,n)| (c.slug.clone(),*n)).collect::<Vec<_>>(),second.creatures.iter().map(|(c,n)| (c.slug.clone(),*n)).collect::<Vec<_>>());
+        assert_eq!(first.adjusted_xp,second.adjusted_xp);
+        assert!(first.adjusted_xp < first.thresholds[3]);
+
+    }
+
+    #[test]
+    fn parse_challenge_ratings() {
+        assert_eq!("1/8".parse::<ChallengeRating>().ok(),Some(ChallengeRating::Eighth));
+        assert_eq!(" 1 / 4 ".parse::<ChallengeRating>().ok(),Some(ChallengeRating::Quarter));
+        assert_eq!("One-Half".parse::<ChallengeRating>().ok(),Some(ChallengeRating::Half));
+        assert_eq!("quarter".parse::<ChallengeRating>().ok(),Some(ChallengeRating::Quarter));
+        assert_eq!("0.125".parse::<ChallengeRating>().ok(),Some(ChallengeRating::Eighth));
+        assert_eq!("0".parse::<ChallengeRating>().ok(),Some(ChallengeRating::Whole(0)));
+        assert_eq!("30".parse::<ChallengeRating>().ok(),Some(ChallengeRating::Whole(30)));
+        assert!("31".parse::<ChallengeRating>().is_err());
+        assert!("2/3".parse::<ChallengeRating>().is_err());
+        assert!("tough".parse::<ChallengeRating>().is_err());
+
+    }
+
+    #[test]
+    fn sort_challenge_ratings() {
+        let mut ratings: Vec<ChallengeRating> = ["10","1/2","2","0","1/8","30","1","1/4"].iter().map(|a| a.parse().expect("Challenge rating should parse.")).collect();
+        ratings.push(ChallengeRating::None);
+        ratings.sort();
+        assert_eq!(ratings.iter().map(|a| a.to_string()).collect::<Vec<String>>(),vec!["0","0","1/8","1/4","1/2","1","2","10","30"]);
+        assert_eq!(ratings[0],ChallengeRating::None);
+        assert!(ChallengeRating::Whole(0) < ChallengeRating::Eighth);
+        assert!(ChallengeRating::Half < ChallengeRating::Whole(1));
+
+    }
+
+    #[test]
+    fn parameters_in_descriptions() {
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::Feature(Feature::Feature("Scaled".to_owned(),"${Subj} is a level ${level} goblin.".to_owned()),None));
+        let parameters = HashMap::from([("level".to_owned(),"5".to_owned())]);
+        let creator = CreatureCreator(commands);
+        let (creature,_) = creator.create_creature_with_parameters(&PathBuf::from(env!("CARGO_MANIFEST_DIR")),&parameters).expect("Creature should have been created.");
+        let stat_block = creature.try_into_stat_block().expect("Stat block should have been created");
+        let feature = stat_block.special_abilities.iter().find(|a| a.text.iter().any(|b| matches!(b,TextBlock::Paragraph{ body, .. } if body.contains(&TextSpan::Normal("The goblin is a level 5 goblin.".to_owned()))))).expect("Feature should have been interpolated with the parameter.");
+        assert_eq!(feature.text.len(),1);
+
+        assert!(creator.create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.").try_into_stat_block().is_err());
+
+    }
+
+    #[test]
+    fn dice_functions() {
+        let stat_block = goblin_with(vec![CreatureCommand::Feature(Feature::Feature("Dice".to_owned(),"${avg(2d6) + 1}, ${expr(2d6 + 1)}, ${expr(+1d4)}.".to_owned()),None)]);
+        assert!(stat_block.special_abilities.iter().any(|a| a.text.iter().any(|b| matches!(b,TextBlock::Paragraph{ body, .. } if body.contains(&TextSpan::Normal("8, 2d6 + 1, +1d4.".to_owned()))))),"Dice functions should have been interpolated.");
+
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::Feature(Feature::Feature("Dice".to_owned(),"${avg(\"foo\")}".to_owned()),None));
+        assert!(CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.").try_into_stat_block().is_err());
+
+    }
+
+    #[test]
+    fn attack_targeting() {
+        let attack = |targeting: Option<AttackTargeting>| Attack {
+            type_: None,
+            bonus: AttackBonus::Default,
+            magic: None,
+            reach: Some(5),
+            range: None,
+            long_range: None,
+            target: String::new(),
+            targeting
+        }.get_description(None,&None);
+        assert_eq!(attack(None),"${italic(}Melee Attack:${)} ${+str + prof} to hit, reach 5 ft., one target.");
+        assert_eq!(attack(Some(AttackTargeting::Targets(2,Some(5)))),"${italic(}Melee Attack:${)} ${+str + prof} to hit, reach 5 ft., two targets within 5 feet of each other.");
+        assert_eq!(attack(Some(AttackTargeting::Cone(15))),"${italic(}Melee Attack:${)} ${+str + prof} to hit, reach 5 ft., each creature in a 15-foot cone.");
+        assert_eq!(attack(Some(AttackTargeting::Line(30,5))),"${italic(}Melee Attack:${)} ${+str + prof} to hit, reach 5 ft., each creature in a 30-foot line that is 5 feet wide.");
+
+    }
+
+    #[test]
+    fn oversized_reach() {
+        let attack = Attack {
+            type_: None,
+            bonus: AttackBonus::Default,
+            magic: None,
+            reach: Some(20),
+            range: None,
+            long_range: None,
+            target: String::new(),
+            targeting: None
+        };
+        assert_eq!(attack.get_description(None,&None),"${italic(}Melee Attack:${)} ${+str + prof} to hit, reach 20 ft., one target.");
+
+        // the size of the creature doesn't change the reach of its weapons.
+        let stat_block = goblin_with(vec![
+            CreatureCommand::Gargantuan,
+            CreatureCommand::CustomWeapon("Tentacle".to_owned(),WeaponReach::Reach(20),"2d6".parse::<DiceExpression>().expect("Dice expression should have parsed."),Damage::Bludgeoning,AttackBonus::Strength,None)
+        ]);
+        let output = stat_block.write_to_string(true).expect("Stat block should have been written.");
+        assert!(output.contains("reach 20 ft., one target."));
+        assert!(output.contains("reach 5 ft., one target."));
+
+    }
+
+    #[test]
+    fn expression_damage() {
+        let stat_block = goblin_with(vec![
+            CreatureCommand::DefineProperty("rogue_level".to_owned(),"5".to_owned()),
+            CreatureCommand::Weapon(Weapon::Shortsword(0),Some(CompoundAttackEffect::Plus(AttackEffect::ExpressionDamage("(rogue_level /> 2) * 1d6".to_owned(),AttackBonus::Zero,Damage::Piercing))))
+        ]);
+        assert!(stat_block.write_to_string(true).expect("Stat block should have been written.").contains("5 (1d6 + 2) piercing damage plus 10 (3d6) piercing damage."));
+
+    }
+
+    #[test]
+    fn custom_weapon() {
+        let stat_block = goblin_with(vec![
+            CreatureCommand::CustomWeapon("Claw".to_owned(),WeaponReach::Reach(5),"1d4".parse::<DiceExpression>().expect("Dice expression should have parsed."),Damage::Slashing,AttackBonus::Dexterity,None),
+            CreatureCommand::CustomWeapon("Spit".to_owned(),WeaponReach::Range(15,30),"2d4".parse::<DiceExpression>().expect("Dice expression should have parsed."),Damage::Acid,AttackBonus::Dexterity,None)
+        ]);
+        let text = stat_block.write_to_string(true).expect("Stat block should have been written.");
+        assert!(text.contains("+4 to hit, reach 5 ft., one target."));
+        assert!(text.contains("4 (1d4 + 2) slashing damage."));
+        assert!(text.contains("+4 to hit, range 15/30 ft., one target."));
+        assert!(text.contains("7 (2d4 + 2) acid damage."));
+
+    }
+
+    #[test]
+    fn multiattack_warnings() {
+        let create = |multiattack: Multiattack, strict: bool| {
+            let mut commands = efreeti().0;
+            commands.push(CreatureCommand::Multiattack("${Subj} makes two scimitar attacks.".to_owned(),multiattack));
+            if strict {
+                commands.push(CreatureCommand::StrictMultiattack);
+            }
+            CreatureCreator(commands).create_creature_with_warnings(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).map(|(_,warnings)| warnings)
+        };
+
+        let sensible = Multiattack::Count(2,vec![Multiattack::Attack("Scimitar".to_owned())]);
+        assert_eq!(create(sensible.clone(),true).expect("Creature should have been created."),Vec::<String>::new());
+
+        let repeated = Multiattack::And(vec![Multiattack::Attack("Scimitar".to_owned()),Multiattack::Attack("Scimitar".to_owned())]);
+        assert_eq!(create(repeated.clone(),false).expect("Creature should have been created."),vec![
+            "Multiattack: Attack(\"Scimitar\") is listed more than once in the same list, use a Count instead.".to_owned()
+        ]);
+        assert!(create(repeated,true).is_err());
+
+        let recursive = Multiattack::Or(vec![Multiattack::Attack("Multiattack".to_owned()),sensible]);
+        assert_eq!(create(recursive.clone(),false).expect("Creature should have been created."),vec![
+            "Multiattack: the multiattack refers to the Multiattack action itself.".to_owned()
+        ]);
+        assert!(create(recursive,true).is_err());
+
+        let zero = Multiattack::Count(0,vec![Multiattack::Attack("Scimitar".to_owned())]);
+        assert_eq!(create(zero.clone(),false).expect("Creature should have been created."),vec![
+            "Multiattack: a Count of 0 allows no attacks.".to_owned(),
+            "Multiattack: the description calls for 2 attacks, but the multiattack allows 0.".to_owned()
+        ]);
+        assert!(create(zero,true).is_err());
+
+        let too_many = Multiattack::And(vec![Multiattack::Count(2,vec![Multiattack::Attack("Scimitar".to_owned())]),Multiattack::Attack("Hurl Flame".to_owned())]);
+        assert_eq!(create(too_many.clone(),false).expect("Creature should have been created."),vec![
+            "Multiattack: the description calls for 2 attacks, but the multiattack allows 3.".to_owned()
+        ]);
+        assert!(create(too_many,true).is_err());
+
+        // the warnings about the multiattack are reported whether or not no-op warnings are, and are errors when those are.
+        let mut commands = efreeti().0;
+        commands.push(CreatureCommand::Multiattack("${Subj} makes two scimitar attacks.".to_owned(),Multiattack::And(vec![Multiattack::Attack("Scimitar".to_owned()),Multiattack::Attack("Scimitar".to_owned())])));
+        let source = crate::save_creature_commands(&CreatureCreator(commands), &crate::CreatureFileFormat::RON).expect("Creature should have been written.");
+        let from_source = |level: crate::WarningLevel| crate::create_creature_from_source(source.clone(), "efreeti", &crate::CreatureFileFormat::RON, &PathBuf::from(env!("CARGO_MANIFEST_DIR")), &level, false, &HashMap::new()).map(|_| ());
+        assert_eq!(from_source(crate::WarningLevel::Ignore),Ok(()));
+        assert_eq!(from_source(crate::WarningLevel::Error),Err("Multiattack: Attack(\"Scimitar\") is listed more than once in the same list, use a Count instead.".to_owned()));
+
+        // the dragon's Frightful Presence isn't an attack, so its three attacks match the description.
+        let (_,warnings) = dragon().create_creature_with_warnings(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        assert_eq!(warnings,Vec::<String>::new());
+
+    }
+
+    #[test]
+    fn scale() {
+        let average = |expr: &str, scale: f32| expr.parse::<DiceExpression>().map_err(|e| e.to_string()).expect("Dice expression should have parsed.").scale(scale).to_string();
+        assert_eq!(average("2d6",1.5),"10 (3d6)");
+        assert_eq!(average("1d6",1.5),"5 (1d6 + 2)");
+        assert_eq!(average("4d8 + 2",0.5),"10 (2d8 + 1)");
+        assert_eq!(average("1d4",0.5),"1 (1d4 - 1)");
+
+        let stat_block = goblin_with(vec![CreatureCommand::Scale(1.5)]);
+        assert_eq!(stat_block.hit_points,"10 (3d6)");
+        assert!(stat_block.write_to_string(true).expect("Stat block should have been written.").contains("7 (1d6 + 4) slashing damage."));
+
+        // legendary actions are scaled along with the actions.
+        let mut commands = dragon().0;
+        commands.push(CreatureCommand::Scale(2.0));
+        let creature = CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        let wing_attack = creature.legendary_actions.as_ref().expect("Legendary actions should be present.").actions.iter().find(|action| action.name == "Wing Attack").expect("Wing Attack should be present.");
+        assert!(wing_attack.effect.as_ref().and_then(|effect| effect.get_damage_expr("0")).expect("Wing Attack should do damage.").starts_with("4d6 + "));
+
+        // the hit dice can't be clamped without changing the scale.
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::Scale(200.0));
+        assert!(matches!(CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))),Err(crate::errors::CreatureError::ScaledHitPointsTooLarge(_))));
+
+        // damage from an expression isn't known until the description is built, so it can't be scaled.
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::Weapon(Weapon::Shortsword(0),Some(CompoundAttackEffect::Plus(AttackEffect::ExpressionDamage("2 * 1d6".to_owned(),AttackBonus::Zero,Damage::Piercing)))));
+        commands.push(CreatureCommand::Scale(1.5));
+        assert!(matches!(CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))),Err(crate::errors::CreatureError::CantScaleExpressionDamage(name)) if name == "Shortsword"));
+
+    }
+
+    #[test]
+    fn pdf_form_values() {
+        let values = crate::pdf_form::get_form_values(&goblin_stat_block());
+        assert_eq!(values.get("armor").map(String::as_str),Some("15 (leather armor, shield)"));
+        assert_eq!(values.get("hit_points").map(String::as_str),Some("7 (2d6)"));
+        assert!(values.get("actions").expect("Actions should have been filled.").starts_with("Scimitar. Melee Weapon Attack: +4 to hit"));
+        assert!(values.get("reactions").is_none());
+
+        let mapping = Some(HashMap::from([("AC".to_owned(),"armor".to_owned()),("Reactions".to_owned(),"reactions".to_owned())]));
+        let values = crate::pdf_form::map_form_values(values, &mapping);
+        assert_eq!(values,HashMap::from([("AC".to_owned(),"15 (leather armor, shield)".to_owned())]));
+
+    }
+
+    #[test]
+    fn duplicate_names() {
+        let (_,warnings) = goblin().create_creature_with_warnings(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        assert_eq!(warnings,Vec::<String>::new());
+
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::Weapon(Weapon::Scimitar(0),None));
+        commands.push(CreatureCommand::Weapon(Weapon::Scimitar(0),None));
+        let creator = CreatureCreator(commands);
+        let (_,warnings) = creator.create_creature_with_warnings(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        assert_eq!(warnings,vec!["There is more than one action named 'Scimitar'.".to_owned()]);
+
+        // these are reported by default, with the warnings about the finished creature, not the no-op warnings.
+        let (_,warnings,mistakes) = creator.create_creature_with_separate_warnings(&PathBuf::from(env!("CARGO_MANIFEST_DIR")),&HashMap::new()).expect("Creature should have been created.");
+        assert_eq!(warnings,Vec::<String>::new());
+        assert_eq!(mistakes,vec!["There is more than one action named 'Scimitar'.".to_owned()]);
+
+    }
+
+    #[test]
+    fn expand_includes() {
+        let creator = CreatureCreator(vec![
+            CreatureCommand::IncludeStored("goblin".to_owned()),
+            CreatureCommand::Name("Goblin Boss".to_owned())
+        ]).expand_includes(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Includes should have been expanded.");
+        assert_eq!(creator.0.first(),Some(&CreatureCommand::Monstorr(1.0,None)));
+        assert_eq!(creator.0.last(),Some(&CreatureCommand::Name("Goblin Boss".to_owned())));
+        assert!(!creator.0.iter().any(|command| matches!(command,CreatureCommand::IncludeStored(_))));
+        assert!(CreatureCreator(vec![CreatureCommand::IncludeStored("gobbo".to_owned())]).expand_includes(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).is_err());
+
+    }
+
+    #[test]
+    fn include_base_dir() {
+        let default = PathBuf::from("/work");
+        let source = Some(PathBuf::from("/creatures/goblin.creature"));
+        let base_dir = Some(PathBuf::from("/includes"));
+        assert_eq!(crate::get_working_dir_relative_to_source_or_default(&None,&None,&default),default);
+        assert_eq!(crate::get_working_dir_relative_to_source_or_default(&source,&None,&default),PathBuf::from("/creatures"));
+        assert_eq!(crate::get_working_dir_relative_to_source_or_default(&source,&base_dir,&default),PathBuf::from("/includes"));
+        assert_eq!(crate::get_working_dir_relative_to_source_or_default(&None,&base_dir,&default),PathBuf::from("/includes"));
+
+    }
+
+    #[test]
+    fn list_properties() {
+        let parameters = HashMap::from([("level".to_owned(),"5".to_owned())]);
+        let (creature,_) = goblin().create_creature_with_parameters(&PathBuf::from(env!("CARGO_MANIFEST_DIR")),&parameters).expect("Creature should have been created.");
+        let properties = creature.list_properties();
+        assert_eq!(properties.first(),Some(&("name".to_owned(),Some("Goblin".to_owned()))));
+        assert!(properties.contains(&("hit_dice".to_owned(),Some("7 (2d6)".to_owned()))));
+        assert!(properties.contains(&("armor_class".to_owned(),Some("15".to_owned()))));
+        assert!(properties.contains(&("spell_atk".to_owned(),None)));
+        assert_eq!(properties.last(),Some(&("level".to_owned(),Some("5".to_owned()))));
+
+    }
+
+    #[test]
+    fn proper_name() {
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::Name("Grik".to_owned()));
+        commands.push(CreatureCommand::ProperName);
+        let creature = CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        let properties = creature.list_properties();
+        assert!(properties.contains(&("subj".to_owned(),Some("Grik".to_owned()))));
+        assert!(properties.contains(&("Poss".to_owned(),Some("Grik's".to_owned()))));
+
+    }
+
+    #[test]
+    fn habitat_and_treasure() {
+        let stat_block = goblin_with(vec![
+            CreatureCommand::Habitat(vec!["Forest".to_owned(),"Underdark".to_owned()]),
+            CreatureCommand::Treasure("Any".to_owned())
+        ]);
+        assert_eq!((stat_block.habitat.as_deref(),stat_block.treasure.as_deref()),(Some("Forest, Underdark"),Some("Any")));
+        let output = crate::template::process_template(&monstorr_data::templates::StoredTemplates::instance(None), monstorr_data::templates::PLAIN_TEMPLATE, &Vec::new(), &stat_block, &StatBlockLabels::default()).expect("Template should have been processed.");
+        assert!(output.contains("Habitat: Forest, Underdark; Treasure: Any"));
+        assert_eq!(goblin_stat_block().habitat,None);
+
+    }
+
+    #[test]
+    fn spell_index() {
+        let create = |commands: Vec<CreatureCommand>| CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::InnateSpellcasting(vec![
+            InnateSpellcastingCommand::Ability(Ability::Charisma),
+            InnateSpellcastingCommand::AtWill(vec!["detect magic".to_owned(),"Tongues".to_owned()])
+        ]));
+        let mut index = SpellIndex::default();
+        index.add_creature(&create(efreeti().0));
+        index.add_creature(&create(commands));
+        let output = index.write_to_string(&SpellIndexFormat::Text).expect("Index should have been written.");
+        // the capitalized one is listed separately, so it stands out
+        assert!(output.starts_with("Tongues (1): Goblin\nconjure elemental (1): Efreeti\ndetect magic (2): Efreeti, Goblin\n"));
+        assert!(output.contains("\ntongues (1): Efreeti\n"));
+
+    }
+
+    #[test]
+    fn translated_labels() {
+        let labels = StatBlockLabels::load_from_str(r#"(armor_class: "Classe d'armure", actions: "Actions")"#).expect("Labels should have been loaded.");
+        assert_eq!(labels.hit_points,"Hit Points");
+        let output = crate::template::process_template(&monstorr_data::templates::StoredTemplates::instance(None), monstorr_data::templates::PLAIN_TEMPLATE, &Vec::new(), &goblin_stat_block(), &labels).expect("Template should have been processed.");
+        assert!(output.contains("Classe d'armure: 15 (leather armor, shield)"));
+        assert!(output.contains("Hit Points: 7 (2d6)"));
+
+    }
+
+    #[test]
+    fn custom_properties() {
+        let mut commands = goblin().0;
+        // defined before the charisma is set, to make sure it's evaluated when referenced.
+        commands.insert(1,CreatureCommand::DefineProperty("aura_dc".to_owned(),"8 + prof + cha".to_owned()));
+        commands.push(CreatureCommand::DefineProperty("double_dc".to_owned(),"aura_dc * 2".to_owned()));
+        commands.push(CreatureCommand::DefineProperty("forever".to_owned(),"forever + 1".to_owned()));
+        let creature = CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        let properties = creature.list_properties();
+        assert!(properties.contains(&("aura_dc".to_owned(),Some("9".to_owned()))));
+        assert!(properties.contains(&("double_dc".to_owned(),Some("18".to_owned()))));
+        assert!(properties.contains(&("forever".to_owned(),None)));
+
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::DefineProperty("broken".to_owned(),"prof +".to_owned()));
+        assert!(CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).is_err());
+
+    }
+
+    #[test]
+    fn interpolate_default() {
+        let parameters = HashMap::from([("level".to_owned(),"5".to_owned()),("title".to_owned(),"".to_owned())]);
+        assert_eq!(crate::interpolate_source("[ HitDiceCount($<default(level,\"1\")>) ]".to_owned(),"test",&parameters),Ok("[ HitDiceCount(5) ]".to_owned()));
+        assert_eq!(crate::interpolate_source("[ HitDiceCount($<default(rank,\"1\")>) ]".to_owned(),"test",&parameters),Ok("[ HitDiceCount(1) ]".to_owned()));
+        assert_eq!(crate::interpolate_source("[ Name(\"$<default(title, \"Goblin\")>\") ]".to_owned(),"test",&parameters),Ok("[ Name(\"Goblin\") ]".to_owned()));
+        assert!(crate::interpolate_source("[ HitDiceCount($<default(\"1\" * 2,\"1\")>) ]".to_owned(),"test",&parameters).is_err());
+        assert!(crate::interpolate_source("[ HitDiceCount($<default(level)>) ]".to_owned(),"test",&parameters).is_err());
+
+    }
+
+    #[test]
+    fn interpolate_string_comparison() {
+        let parameters = HashMap::from([("name".to_owned(),"Goblin Boss".to_owned()),("level".to_owned(),"5".to_owned())]);
+        assert_eq!(crate::interpolate_source("[ Name(\"$<contains(name,\"Boss\")>\") ]".to_owned(),"test",&parameters),Ok("[ Name(\"true\") ]".to_owned()));
+        assert_eq!(crate::interpolate_source("[ Name(\"$<startswith(name,\"Boss\")>\") ]".to_owned(),"test",&parameters),Ok("[ Name(\"false\") ]".to_owned()));
+        assert_eq!(crate::interpolate_source("[ Name(\"$<endswith(name,\"Boss\")>\") ]".to_owned(),"test",&parameters),Ok("[ Name(\"true\") ]".to_owned()));
+        assert!(crate::interpolate_source("[ Name(\"$<contains(name,5)>\") ]".to_owned(),"test",&parameters).is_err());
+        assert!(crate::interpolate_source("[ Name(\"$<contains(name,\"Boss\") + 1>\") ]".to_owned(),"test",&parameters).is_err());
+        assert!(crate::interpolate_source("[ Name(\"$<contains(name)>\") ]".to_owned(),"test",&parameters).is_err());
+
+    }
+
+    #[test]
+    fn interpolate_top_level_source() {
+        let parameters = HashMap::from([("level".to_owned(),"5".to_owned())]);
+        let source = r#"[ Name("Goblin \"Boss\""), Feature(Feature("Scaled","${Subj} is scaled.")) ]"#.to_owned();
+        assert_eq!(crate::interpolate_source(source.clone(),"test",&parameters),Ok(source));
+        assert_eq!(crate::interpolate_source("[ HitDiceCount($<level>) ]".to_owned(),"test",&parameters),Ok("[ HitDiceCount(5) ]".to_owned()));
+        assert!(crate::interpolate_source("[ HitDiceCount($<rank>) ]".to_owned(),"test",&parameters).is_err());
+
+    }
+
+    #[test]
+    fn legendary_actions_style() {
+        let mut commands = dragon().0;
+        commands.push(CreatureCommand::LegendaryActionsStyle(LegendaryActionsStyle::Uses));
+        let stat_block = CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.").try_into_stat_block().expect("Stat block should have been created");
+        assert_eq!(stat_block.legendary_actions.expect("Legendary actions should be present.").description,vec![
+            TextBlock::Paragraph {
+                heading: None,
+                body: vec![
+                    TextSpan::Italic("Legendary Action Uses: 3.".to_owned()),
+                    TextSpan::Normal(" Immediately after another creature's turn, the dragon can expend a use to take one of the following actions. The dragon regains all expended uses at the start of each of its turns.".to_owned())
+                ]
+            }
+        ]);
+
+    }
+
+    #[test]
+    fn save_feature_dc() {
+        let stat_block = goblin_with(vec![CreatureCommand::Feature(Feature::SaveFeature("Dust Kick".to_owned(),Ability::Dexterity,"Each creature within 5 feet must succeed on a DC ${save_dc} Constitution saving throw or be blinded.".to_owned()),None)]);
+        assert_eq!(stat_block.special_abilities.last().expect("Feature should have been added.").text,vec![
+            TextBlock::Paragraph {
+                heading: Some(vec![TextSpan::Normal("Dust Kick.".to_owned())]),
+                body: vec![
+                    TextSpan::Normal("Each creature within 5 feet must succeed on a DC 12 Constitution saving throw or be blinded.".to_owned())
+                ]
+            }
+        ]);
+
+    }
+
+    #[test]
+    fn spellcasting_follows_challenge_rating() {
+        let spellcasting_text = |commands: Vec<CreatureCommand>| {
+            let stat_block = CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.").try_into_stat_block().expect("Stat block should have been created");
+            let feature = stat_block.special_abilities.into_iter().find(|a| matches!(a.text.first(),Some(TextBlock::Paragraph{ heading: Some(heading), ..}) if heading == &vec![TextSpan::Normal("Spellcasting.".to_owned())])).expect("Spellcasting should have been added.");
+            feature.text.into_iter().flat_map(|a| match a {
+                TextBlock::Paragraph{ body, .. } | TextBlock::SubParagraph{ body, .. } => body
+            }).map(|a| match a {
+                TextSpan::Normal(a) | TextSpan::Italic(a) | TextSpan::Bold(a) | TextSpan::BoldItalic(a) => a
+            }).collect::<String>()
+        };
+
+        let commands = |challenge: Option<u8>, overridden: bool| {
+            let mut commands = goblin().0;
+            commands.push(CreatureCommand::Spellcasting(vec![
+                SpellcastingCommand::Level(1),
+                SpellcastingCommand::Class("wizard".to_owned()),
+                SpellcastingCommand::Ability(Ability::Intelligence),
+                SpellcastingCommand::Cantrips(vec!["fire bolt".to_owned()])
+            ],None));
+            if let Some(challenge) = challenge {
+                commands.push(CreatureCommand::OverrideChallenge(challenge));
+            }
+            if overridden {
+                commands.push(CreatureCommand::Spellcasting(vec![SpellcastingCommand::SaveDC(15),SpellcastingCommand::Attack(7)],None));
+            }
+            commands
+        };
+
+        assert!(spellcasting_text(commands(None,false)).contains("spell save DC 10, +2 to hit with spell attacks"));
+        // the save DC and attack bonus are calculated from the final proficiency bonus, not the one at the time the spellcasting was added.
+        assert!(spellcasting_text(commands(Some(5),false)).contains("spell save DC 11, +3 to hit with spell attacks"));
+        assert!(spellcasting_text(commands(Some(5),true)).contains("spell save DC 15, +7 to hit with spell attacks"));
+
+    }
+
+    #[test]
+    fn initiative() {
+        let initiative = |command: Option<CreatureCommand>| goblin_with(command.into_iter().collect()).initiative;
+        assert_eq!(initiative(None),None);
+        assert_eq!(initiative(Some(CreatureCommand::Initiative(0))),Some("+2 (12)".to_owned()));
+        assert_eq!(initiative(Some(CreatureCommand::Initiative(2))),Some("+6 (16)".to_owned()));
+        assert_eq!(initiative(Some(CreatureCommand::OverrideInitiative(-1))),Some("-1 (9)".to_owned()));
+
+    }
+
+    #[test]
+    fn modern_style() {
+        let mut stat_block = goblin_stat_block();
+        stat_block.apply_style(&StatBlockStyle::Classic);
+        assert_eq!(stat_block,goblin_stat_block());
+        stat_block.apply_style(&StatBlockStyle::Modern);
+        assert_eq!((stat_block.size.as_str(),stat_block.type_.as_str(),stat_block.subtype.as_deref(),stat_block.alignment.as_str()),("Small","Humanoid",Some("Goblinoid"),"Neutral Evil"));
+
+    }
+
+    #[test]
+    fn average_rounding() {
+        let average = |expr: &str, rounding: AverageRounding| expr.parse::<DiceExpression>().map_err(|e| e.to_string()).expect("Dice expression should have parsed.").rounded_average(&rounding);
+        // the core books always round down
+        assert_eq!(average("1d8",AverageRounding::default()),4);
+        assert_eq!(average("2d6",AverageRounding::default()),7);
+        assert_eq!(average("5d8 + 5",AverageRounding::default()),27);
+        // rounding should only happen on the total
+        assert_eq!(average("1d8 + 1d4",AverageRounding::default()),7);
+        assert_eq!(average("1d8",AverageRounding::HalfUp),5);
+        assert_eq!(average("1d8",AverageRounding::HalfEven),4);
+        assert_eq!(average("1d10",AverageRounding::HalfEven),6);
+        assert_eq!(average("2d6",AverageRounding::HalfUp),7);
+
+        let stat_block = goblin_with(vec![CreatureCommand::AverageRounding(AverageRounding::HalfUp)]);
+        assert_eq!(stat_block.hit_points,"7 (2d6)");
+        assert!(stat_block.actions.iter().any(|a| a.text.iter().any(|b| matches!(b,TextBlock::Paragraph{ body, .. } if body.contains(&TextSpan::Normal(" 6 (1d6 + 2) slashing damage.".to_owned()))))),"Damage should have been rounded half up.");
+
+    }
+
+    #[test]
+    fn notes_in_stat_block() {
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::Note("Bumped HP to match playtest.".to_owned()));
+        let creator = CreatureCreator(commands);
+        assert!(creator.save_to_string().expect("Creature should have been written.").contains(r#"Note("Bumped HP to match playtest.")"#));
+        let stat_block = creator.create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.").try_into_stat_block().expect("Stat block should have been created");
+        assert_eq!(stat_block.notes,vec!["Bumped HP to match playtest.".to_owned()]);
+        assert!(stat_block.write_to_string(true).expect("Stat block should have been written.").contains(r#""notes":["Bumped HP to match playtest."]"#));
+        assert!(!goblin_stat_block().write_to_string(true).expect("Stat block should have been written.").contains("notes"));
+
+    }
+
+    #[test]
+    fn extra_values_in_json() {
+        let stat_block = goblin_with(vec![
+            CreatureCommand::Extra("campaign".to_owned(),"first".to_owned()),
+            CreatureCommand::Extra("campaign".to_owned(),"second".to_owned()),
+            CreatureCommand::Extra("author".to_owned(),"me".to_owned())
+        ]);
+        assert_eq!(stat_block.extra,BTreeMap::from([("author".to_owned(),"me".to_owned()),("campaign".to_owned(),"second".to_owned())]));
+        // the keys are written in order, so the output doesn't change from one run to the next.
+        assert!(stat_block.write_to_string(true).expect("Stat block should have been written.").contains(r#""extra":{"author":"me","campaign":"second"}"#));
+        assert!(!goblin_stat_block().write_to_string(true).expect("Stat block should have been written.").contains("extra"));
+
+    }
+
+    #[test]
+    fn empty_creature_file() {
+        let message = Err("The creature file is empty or contains no commands.".to_owned());
+        assert_eq!(crate::load_creature_commands("").map(|a| a.0),message);
+        assert_eq!(crate::load_creature_commands(" \n\t ").map(|a| a.0),message);
+        assert_eq!(crate::load_creature_commands("([])").map(|a| a.0),message);
+        assert!(crate::load_creature_commands(GOBLIN).is_ok());
+
+    }
+
+    #[test]
+    fn dndbeyond_monster() {
+        let output = crate::dndbeyond::write_dndbeyond_monster(&goblin_stat_block(), true).expect("Monster should have been written.");
+        let monster: serde_json::Value = serde_json::from_str(&output).expect("Monster should be JSON.");
+        assert_eq!(monster["name"],"Goblin");
+        assert_eq!(monster["stats"][0],serde_json::json!({ "statId": 1, "value": 8 }));
+        assert_eq!(monster["stats"][1],serde_json::json!({ "statId": 2, "value": 14 }));
+        assert_eq!(monster["armorClass"],15);
+        assert_eq!(monster["armorClassDescription"],"leather armor, shield");
+        assert_eq!(monster["averageHitPoints"],7);
+        assert_eq!(monster["hitPointDice"],serde_json::json!({ "diceCount": 2, "diceValue": 6, "fixedValue": 0, "diceString": "2d6" }));
+        assert_eq!(monster["movements"],serde_json::json!([{ "movementId": 1, "speed": 30 }]));
+        assert_eq!(monster["challengeRating"],3);
+        assert_eq!(monster["actions"][0]["name"],"Scimitar");
+        assert_eq!(monster["actions"][0]["activation"]["activationType"],"action");
+        assert_eq!(monster["actions"][0]["attack"]["toHit"],4);
+        assert_eq!(monster["actions"][0]["attack"]["reach"],5);
+        assert_eq!(monster["actions"][0]["attack"]["damage"],serde_json::json!([{ "diceString": "1d6 + 2", "damageType": "slashing" }]));
+        assert_eq!(monster["actions"][1]["attack"]["range"],80);
+        assert_eq!(monster["actions"][1]["attack"]["longRange"],320);
+        assert_eq!(crate::dndbeyond::challenge_rating_id("0 (10 XP)"),Some(1));
+        assert_eq!(crate::dndbeyond::challenge_rating_id("1 (200 XP)"),Some(5));
+        assert_eq!(crate::dndbeyond::challenge_rating_id("30 (155,000 XP)"),Some(34));
+
+    }
+
+    #[test]
+    fn canonicalize_layout() {
+        fn headings(features: &Vec<StatBlockFeature>) -> Vec<&str> {
+            features.iter().filter_map(|a| match a.text.first() {
+                Some(TextBlock::Paragraph{ heading: Some(heading), ..}) => match heading.first() {
+                    Some(TextSpan::Normal(heading)) => Some(heading.as_str()),
+                    _ => None
+                },
+                _ => None
+            }).collect()
+        }
+        let commands = |canonicalize: bool| {
+            let mut commands = efreeti().0;
+            // setting the multiattack again moves it to the end of the actions
+            commands.push(CreatureCommand::Multiattack("${Subj} makes two scimitar attacks or uses its Hurl Flame twice.".to_owned(),Multiattack::Count(2,vec![Multiattack::Attack("Scimitar".to_owned())])));
+            commands.push(CreatureCommand::Feature(Feature::Feature("Fiery Aura".to_owned(),"${Subj} sheds bright light in a 10-foot radius.".to_owned()),None));
+            if canonicalize {
+                commands.push(CreatureCommand::CanonicalizeLayout);
+            }
+            CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.").try_into_stat_block().expect("Stat block should have been created")
+        };
+
+        let stat_block = commands(false);
+        assert_eq!(headings(&stat_block.special_abilities),vec!["Elemental Demise.","Innate Spellcasting.","Fiery Aura."]);
+        assert_eq!(headings(&stat_block.actions),vec!["Scimitar.","Hurl Flame.","Multiattack."]);
+
+        let stat_block = commands(true);
+        assert_eq!(headings(&stat_block.special_abilities),vec!["Elemental Demise.","Fiery Aura.","Innate Spellcasting."]);
+        assert_eq!(headings(&stat_block.actions),vec!["Multiattack.","Scimitar.","Hurl Flame."]);
+
+    }
+
+    #[test]
+    fn lair_actions() {
+        let stat_block = goblin_with(vec![
+            CreatureCommand::LairActions("On initiative count 20, ${subj} takes a lair action.".to_owned(),vec![
+                "Rocks fall, dealing ${2d6} bludgeoning damage.".to_owned(),
+                "${Subj} hides.".to_owned()
+            ],"".to_owned())
+        ]);
+        assert_eq!(stat_block.lair_actions,Some(StatBlockLairActions {
+            foreword: vec![TextBlock::Paragraph {
+                heading: None,
+                body: vec![TextSpan::Normal("On initiative count 20, the goblin takes a lair action.".to_owned())]
+            }],
+            actions: vec![StatBlockFeature {
+                text: vec![TextBlock::SubParagraph {
+                    heading: None,
+                    body: vec![TextSpan::Normal("Rocks fall, dealing 7 (2d6) bludgeoning damage.".to_owned())]
+                }],
+                trigger: None
+            },StatBlockFeature {
+                text: vec![TextBlock::SubParagraph {
+                    heading: None,
+                    body: vec![TextSpan::Normal("The goblin hides.".to_owned())]
+                }],
+                trigger: None
+            }],
+            afterword: vec![]
+        }));
+
+    }
+
+    #[test]
+    fn regional_effects() {
+        let stat_block = goblin_with(vec![
+            CreatureCommand::RegionalEffects("The region containing ${poss} lair is warped by ${posspro} presence.".to_owned(),vec![
+                "Rats swarm within 1 mile of the lair.".to_owned()
+            ],"If ${subj} dies, these effects fade over ${1d10} days.".to_owned())
+        ]);
+        assert_eq!(stat_block.regional_effects,Some(StatBlockRegionalEffects {
+            foreword: vec![TextBlock::Paragraph {
+                heading: None,
+                body: vec![TextSpan::Normal("The region containing the goblin's lair is warped by its presence.".to_owned())]
+            }],
+            effects: vec![StatBlockFeature {
+                text: vec![TextBlock::SubParagraph {
+                    heading: None,
+                    body: vec![TextSpan::Normal("Rats swarm within 1 mile of the lair.".to_owned())]
+                }],
+                trigger: None
+            }],
+            afterword: vec![TextBlock::Paragraph {
+                heading: None,
+                body: vec![TextSpan::Normal("If the goblin dies, these effects fade over 5 (1d10) days.".to_owned())]
+            }]
+        }));
+        let output = crate::template::process_template(&monstorr_data::templates::StoredTemplates::instance(None), monstorr_data::templates::PLAIN_TEMPLATE, &Vec::new(), &stat_block, &StatBlockLabels::default()).expect("Template should have been processed.");
+        assert!(output.contains("Regional Effects"));
+        assert!(output.contains("Rats swarm within 1 mile of the lair."));
+
+    }
+
+    #[test]
+    fn minion() {
+        let stat_block = goblin_with(vec![CreatureCommand::Minion]);
+        assert_eq!(stat_block.hit_points,"7 (minion)");
+        assert!(stat_block.minion);
+        assert_eq!(stat_block.special_abilities.first(),Some(&StatBlockFeature {
+            text: vec![TextBlock::Paragraph {
+                heading: Some(vec![TextSpan::Normal("Minion (Variant Rule).".to_owned())]),
+                body: vec![TextSpan::Normal("If the goblin takes any damage, it drops to 0 hit points.".to_owned())]
+            }],
+            trigger: None
+        }));
+        assert!(stat_block.write_to_string(true).expect("Stat block should have been written.").contains(r#""minion":true"#));
+        assert!(!goblin_stat_block().write_to_string(true).expect("Stat block should have been written.").contains("minion"));
+
+    }
+
+    #[test]
+    fn html_css_class() {
+        let html = |css_class: Option<String>| crate::template::process_template(&monstorr_data::templates::StoredTemplates::instance(monstorr_data::templates::TemplateOptions::html(monstorr_data::templates::HtmlOptions { css_class, ..Default::default() },None)), monstorr_data::templates::STAT_BLOCK_HTML_TEMPLATE, &Vec::new(), &goblin_stat_block(), &StatBlockLabels::default()).expect("Template should have been processed.");
+        assert!(html(None).contains(r#"<div class="stat-block">"#));
+        assert!(html(Some("fire red-tint".to_owned())).contains(r#"<div class="stat-block fire red-tint">"#));
+
+    }
+
+    #[test]
+    fn html_narrow() {
+        let html = |narrow: bool| crate::template::process_template(&monstorr_data::templates::StoredTemplates::instance(monstorr_data::templates::TemplateOptions::html(monstorr_data::templates::HtmlOptions { narrow, ..Default::default() },None)), monstorr_data::templates::FULL_HTML_TEMPLATE, &Vec::new(), &goblin_stat_block(), &StatBlockLabels::default()).expect("Template should have been processed.");
+        let narrow = html(true);
+        assert!(narrow.contains("max-width: 300px;"));
+        assert!(narrow.contains("grid-template-columns: repeat(3, 1fr);"));
+        // the default layout is unchanged.
+        assert!(!html(false).contains("max-width: 300px;"));
+        assert!(!html(false).contains("grid-template-columns"));
+
+    }
+
+    #[test]
+    fn calculate_challenge_rating() {
+        let commands = |expect: CreatureCommand, overridden: bool| {
+            let mut commands: Vec<CreatureCommand> = goblin().0.into_iter().filter(|command| overridden || (command != &CreatureCommand::OverrideQuarterChallenge)).collect();
+            commands.push(expect);
+            CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR")))
+        };
+        let creature = commands(CreatureCommand::ExpectQuarterChallenge,false).expect("Creature should have been created.");
+        assert_eq!(creature.calculate_challenge_rating(),ChallengeRating::Quarter);
+
+        match commands(CreatureCommand::ExpectChallenge(5),false) {
+            Err(err) => assert!(err.to_string().starts_with("Calculated challenge rating 1/4 did not match expected 5")),
+            Ok(_) => panic!("Creature should not have matched the expected challenge rating.")
+        }
+        // an overridden challenge rating is checked as it is
+        assert!(commands(CreatureCommand::ExpectQuarterChallenge,true).is_ok());
+        assert!(commands(CreatureCommand::ExpectChallenge(5),true).is_err());
+
+    }
+
+    #[test]
+    fn creature_builder() {
+        let mut builder = CreatureBuilder::new().with_working_dir(&PathBuf::from(env!("CARGO_MANIFEST_DIR")));
+        for command in goblin().0 {
+            builder.apply(command).expect("Command should have been applied.");
+        }
+        let creature = builder.build().expect("Creature should have been built.");
+        assert_eq!(creature.try_into_stat_block().expect("Stat block should have been created."),goblin_stat_block());
+
+        assert!(CreatureBuilder::new().build().is_err());
+
+    }
+
+    #[test]
+    fn spellcasting_properties() {
+        let lore = |spellcasting: bool, innate: bool| {
+            let mut commands = goblin().0;
+            if spellcasting {
+                commands.push(CreatureCommand::Spellcasting(vec![SpellcastingCommand::Level(3),SpellcastingCommand::Class("wizard".to_owned())],None));
+            }
+            if innate {
+                commands.push(CreatureCommand::InnateSpellcasting(vec![InnateSpellcastingCommand::SaveDC(13)]));
+            }
+            commands.push(CreatureCommand::Feature(Feature::Feature("Spell Lore".to_owned(),"${Subj} knows ${spell_class} spells of a level ${caster_level} caster, with a spell save DC of ${spell_dc}.".to_owned()),None));
+            let stat_block = CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.").try_into_stat_block().ok()?;
+            stat_block.special_abilities.iter().find_map(|a| a.text.iter().find_map(|b| match b {
+                TextBlock::Paragraph{ heading: Some(heading), body } if heading == &vec![TextSpan::Normal("Spell Lore.".to_owned())] => Some(body.clone()),
+                _ => None
+            }))
+        };
+
+        assert_eq!(lore(true,false),Some(vec![TextSpan::Normal("The goblin knows wizard spells of a level 3 caster, with a spell save DC of 10.".to_owned())]));
+        // innate spellcasting is preferred for the save DC
+        assert_eq!(lore(true,true),Some(vec![TextSpan::Normal("The goblin knows wizard spells of a level 3 caster, with a spell save DC of 13.".to_owned())]));
+        // without regular spellcasting, there is no caster level or class
+        assert_eq!(lore(false,true),None);
+        assert_eq!(lore(false,false),None);
+
+    }
+
+    #[test]
+    fn remove_all() {
+        let create = |extra: Vec<CreatureCommand>| {
+            let mut commands = goblin().0;
+            commands.extend(extra);
+            CreatureCreator(commands).create_creature_with_warnings(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.")
+        };
+
+        let (creature,warnings) = create(vec![CreatureCommand::RemoveActionsMatching("Short".to_owned())]);
+        assert_eq!(creature.actions.iter().map(|a| a.name.as_str()).collect::<Vec<&str>>(),vec!["Scimitar"]);
+        assert_eq!(warnings,Vec::<String>::new());
+
+        let (creature,warnings) = create(vec![
+            CreatureCommand::Reaction(Reaction::Parry(2),None),
+            CreatureCommand::RemoveAllActions,
+            CreatureCommand::RemoveAllReactions,
+            CreatureCommand::RemoveAllReactions,
+            CreatureCommand::RemoveActionsMatching("Scimitar".to_owned())
+        ]);
+        assert!(creature.actions.is_empty());
+        assert!(creature.reactions.is_empty());
+        assert_eq!(warnings,vec![
+            "RemoveAllReactions: there was nothing to remove.".to_owned(),
+            "RemoveActionsMatching: could not find 'Scimitar', nothing was removed.".to_owned()
+        ]);
+
+        // the multiattack goes with its action, so it isn't checked against the missing attacks
+        let mut commands = dragon().0;
+        commands.push(CreatureCommand::RemoveActionsMatching("Multi".to_owned()));
+        let creature = CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        assert!(creature.multiattack.is_none());
+        assert!(creature.actions.iter().any(|a| a.name == "Bite"));
+
+    }
+
+    #[test]
+    fn proficiency_bonus() {
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::ProficiencyBonus(3));
+        let creature = CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        assert!(creature.list_properties().contains(&("prof".to_owned(),Some("3".to_owned()))));
+        let stat_block = creature.try_into_stat_block().expect("Stat block should have been created.");
+        assert_eq!(stat_block.skills,Some("Stealth +8".to_owned()));
+        assert_eq!(stat_block.challenge_rating,goblin_stat_block().challenge_rating);
+        assert!(stat_block.actions.iter().all(|a| a.text.iter().any(|b| matches!(b,TextBlock::Paragraph{ body, .. } if body.iter().any(|c| matches!(c,TextSpan::Normal(text) if text.starts_with(" +5 to hit")))))));
+
+    }
+
+    #[test]
+    fn order_actions_and_features() {
+        let mut commands = goblin().0;
+        // the order can be given before the action is added, as in an include
+        commands.insert(1,CreatureCommand::OrderAction("Scimitar".to_owned(),1));
+        commands.push(CreatureCommand::Action(Action::Action("Taunt".to_owned(),"${Subj} jeers at one creature ${subjpro} can see.".to_owned(),None,None),None));
+        commands.push(CreatureCommand::Feature(Feature::Feature("Cowardly".to_owned(),"${Subj} flees at the first sign of trouble.".to_owned()),None));
+        commands.push(CreatureCommand::OrderFeature("Cowardly".to_owned(),-1));
+        let creature = CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        assert_eq!(creature.actions.iter().map(|a| a.name.as_str()).collect::<Vec<&str>>(),vec!["Shortbow","Taunt","Scimitar"]);
+        assert_eq!(creature.special_abilities.iter().map(|a| match a {
+            CreatureSpecialAbility::Feature(feature) => feature.name.as_str(),
+            _ => ""
+        }).collect::<Vec<&str>>(),vec!["Cowardly","Nimble Escape"]);
+
+    }
+
+    #[test]
+    fn completeness() {
+        let creature = goblin().create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        assert_eq!(creature.get_completeness(),100);
+
+        let stub = CreatureCreator(vec![CreatureCommand::Name("Stub".to_owned())]).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        assert_eq!(stub.get_completeness(),12);
+
+        let mut stat_block = goblin_stat_block();
+        assert!(!stat_block.write_to_string(true).expect("Stat block should have been written.").contains("diagnostics"));
+        stat_block.diagnostics = Some(StatBlockDiagnostics { completeness: 100, effective_hit_points: 7 });
+        assert!(stat_block.write_to_string(true).expect("Stat block should have been written.").contains(r#""diagnostics":{"completeness":100,"effective_hit_points":7}"#));
+
+    }
+
+    #[test]
+    fn custom_condition_immunities() {
+        let create = |extra: Vec<CreatureCommand>| {
+            let mut commands = goblin().0;
+            commands.extend(extra);
+            let (creature,warnings) = CreatureCreator(commands).create_creature_with_warnings(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+            (creature.try_into_stat_block().expect("Stat block should have been created.").condition_immunities,warnings)
+        };
+
+        assert_eq!(create(vec![
+            CreatureCommand::CustomConditionImmunity("slowed".to_owned()),
+            CreatureCommand::ConditionImmunity(Condition::Poisoned),
+            CreatureCommand::CustomConditionImmunity("cursed".to_owned())
+        ]),(Some("poisoned, slowed, cursed".to_owned()),vec![]));
+
+        assert_eq!(create(vec![
+            CreatureCommand::CustomConditionImmunity("cursed".to_owned()),
+            CreatureCommand::RemoveCustomConditionImmunity("cursed".to_owned()),
+            CreatureCommand::RemoveCustomConditionImmunity("slowed".to_owned())
+        ]),(None,vec!["RemoveCustomConditionImmunity: could not find 'slowed', nothing was removed.".to_owned()]));
+
+    }
+
+    #[test]
+    fn damage_types() {
+        let stat_block = goblin_with(vec![
+            CreatureCommand::Resistance(Damage::Force),
+            CreatureCommand::Resistance(Damage::Fire),
+            CreatureCommand::ResistanceType("sonic".to_owned()),
+            CreatureCommand::ImmunityType("sonic".to_owned()),
+            CreatureCommand::NonmagicalImmunity
+        ]);
+        assert_eq!(stat_block.damage_resistances,Some("fire, force, sonic".to_owned()));
+        assert_eq!(stat_block.damage_immunities,Some("sonic; bludgeoning, piercing, and slashing from nonmagical attacks".to_owned()));
+        assert_eq!(stat_block.damage_vulnerabilities,None);
+
+    }
+
+    #[test]
+    fn display_name() {
+        let stat_block = goblin_with(vec![CreatureCommand::DisplayName("Goblin (Skabgut)".to_owned())]);
+        assert_eq!((stat_block.name.as_str(),stat_block.display_name.as_deref()),("Goblin",Some("Goblin (Skabgut)")));
+        assert!(stat_block.special_abilities[0].text.iter().all(|block| !format!("{:?}",block).contains("Skabgut")));
+        let output = crate::template::process_template(&monstorr_data::templates::StoredTemplates::instance(None), monstorr_data::templates::PLAIN_TEMPLATE, &Vec::new(), &stat_block, &StatBlockLabels::default()).expect("Template should have been processed.");
+        assert!(output.contains("\nGoblin (Skabgut)\n"));
+        let output = crate::template::process_template(&monstorr_data::templates::StoredTemplates::instance(None), monstorr_data::templates::PLAIN_TEMPLATE, &Vec::new(), &goblin_stat_block(), &StatBlockLabels::default()).expect("Template should have been processed.");
+        assert!(output.contains("\nGoblin\n"));
+
+    }
+
+    #[test]
+    fn languages_knew_in_life_and_telepathy() {
+        let create = |commands: Vec<CreatureCommand>| goblin_with(commands).languages;
+
+        assert_eq!(create(vec![
+            CreatureCommand::Languages(vec![]),
+            CreatureCommand::LanguagesKnewInLife
+        ]),Some("the languages it knew in life".to_owned()));
+
+        assert_eq!(create(vec![
+            CreatureCommand::Telepathy(60,None),
+            CreatureCommand::UnspokenLanguages(vec![Language::KnewInLife]),
+            CreatureCommand::Telepathy(120,Some("works only with creatures that understand a language".to_owned()))
+        ]),Some("Common, Goblin, understand the languages it knew in life but does not speak, telepathy 120 ft. (works only with creatures that understand a language)".to_owned()));
+
+        assert_eq!(ron::from_str::<Language>("Telepathy(30)").expect("Language should have been parsed."),Language::Telepathy(30,None));
+        assert_eq!("any languages it knew in life".parse::<Language>().ok(),Some(Language::KnewInLife));
+
+    }
+
+    #[test]
+    fn lint_overrides() {
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::Str(16));
+        commands.push(CreatureCommand::Large);
+        commands.push(CreatureCommand::Str(18));
+        let (_,warnings) = CreatureCreator(commands).create_creature_with_warnings(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        assert_eq!(warnings,Vec::<String>::new());
+
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::Str(16));
+        commands.push(CreatureCommand::Large);
+        commands.push(CreatureCommand::Str(18));
+        commands.push(CreatureCommand::LintOverrides);
+        let (creature,warnings) = CreatureCreator(commands).create_creature_with_warnings(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        assert_eq!(creature.strength,18);
+        assert_eq!(warnings,vec![
+            "The size was set 2 times, only the last one is used.".to_owned(),
+            "The strength was set 3 times, only the last one is used.".to_owned()
+        ]);
+
+    }
+
+    #[test]
+    fn find_included_files() {
+        let working_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::Include("missing.creature".to_owned(),HashMap::new()));
+        commands.push(CreatureCommand::IncludeStored("Goblin".to_owned()));
+        commands.push(CreatureCommand::Include("missing.creature".to_owned(),HashMap::new()));
+        assert_eq!(CreatureCreator(commands).find_included_files(&working_dir),vec![working_dir.join("missing.creature")]);
+        assert_eq!(goblin().find_included_files(&working_dir),Vec::<PathBuf>::new());
+
+    }
+
+    #[test]
+    fn batch_file_extensions() {
+        assert_eq!(crate::OutputFormat::HTML(crate::HtmlOptions::default()).file_extension(),"html");
+        assert_eq!(crate::OutputFormat::LaTeX(false,None,None,None).file_extension(),"tex");
+        assert_eq!(crate::OutputFormat::Discord().file_extension(),"md");
+        assert_eq!(crate::OutputFormat::MiniJinjaTemplate("cards/card.svg".to_owned(),Vec::new()).file_extension(),"svg");
+        assert_eq!(crate::OutputFormat::MiniJinjaTemplate("card".to_owned(),Vec::new()).file_extension(),"txt");
+
+    }
+
+    #[test]
+    fn open5e_structured_attacks() {
+        let action = |name: &str, desc: &str| monstorr_open5e::Open5eMonsterAction {
+            name: name.to_owned(),
+            desc: desc.to_owned(),
+            attack_bonus: None,
+            damage_dice: None,
+            damage_bonus: None
+        };
+        let features = crate::open5e_convertor::actions_to_stat_block(vec![
+            action("Javelin","Melee or Ranged Weapon Attack: +4 to hit, reach 5 ft. or range 30/120 ft., one target. Hit: 5 (1d6 + 2) piercing damage plus 3 (1d6) poison damage."),
+            action("Bite","Melee Weapon Attack: +4 to hit, reach 5 ft., one target. Hit: 6 (1d6 + 2) piercing damage.")
+        ]).expect("Actions should have converted");
+
+        assert_eq!(features[0].text,vec![
+            TextBlock::Paragraph {
+                heading: Some(vec![TextSpan::Normal("Javelin.".to_owned())]),
+                body: vec![
+                    TextSpan::Italic("Melee or Ranged Weapon Attack:".to_owned()),
+                    TextSpan::Normal(" +4 to hit, reach 5 ft. or range 30/120 ft., one target. ".to_owned()),
+                    TextSpan::Italic("Hit:".to_owned()),
+                    TextSpan::Normal(" 5 (1d6 + 2) piercing damage plus 3 (1d6) poison damage.".to_owned())
+                ]
+            }
+        ]);
+
+        // the average doesn't match the dice, so the description is kept as written.
+        assert_eq!(features[1].text,crate::interpolation::interpolate_simple_markdown_naively("Bite","Melee Weapon Attack: +4 to hit, reach 5 ft., one target. Hit: 6 (1d6 + 2) piercing damage.","Bite",false,true).expect("Description should have converted"));
+
+    }
+
+    #[test]
+    fn innate_spellcasting_components() {
+        let mut spellcasting = crate::spellcasting::InnateSpellcasting::default();
+        InnateSpellcastingCommand::SaveDC(12).execute(&mut spellcasting).expect("Command should have been executed.");
+        InnateSpellcastingCommand::Attack(4).execute(&mut spellcasting).expect("Command should have been executed.");
+        assert!(spellcasting.get_description().contains("can innately cast the following spells, requiring no material components:"));
+        InnateSpellcastingCommand::Components("requiring only its pixie dust as a component".to_owned()).execute(&mut spellcasting).expect("Command should have been executed.");
+        assert!(spellcasting.get_description().contains("can innately cast the following spells, requiring only its pixie dust as a component:"));
+        InnateSpellcastingCommand::Components(String::new()).execute(&mut spellcasting).expect("Command should have been executed.");
+        assert!(spellcasting.get_description().contains("can innately cast the following spells:"));
+
+    }
+
+    #[test]
+    fn interpolate_str_with_own_data() {
+        let mut data = HashMap::new();
+        data.insert("hero".to_owned(),"Ilsa".to_owned());
+        match crate::interpolate_str("The dragon eats $<hero>.", "test", &data, crate::InterpolationMode::DeserializeCreatureCommands) {
+            Ok(crate::InterpolatedText::Plain(text)) => assert_eq!(text,"The dragon eats Ilsa."),
+            _ => panic!("String should have been interpolated to plain text.")
+        }
+        match crate::interpolate_str("${italic(}${hero}${)} wins.", "test", &data, crate::InterpolationMode::CalculateStatBlock) {
+            Ok(crate::InterpolatedText::Structured(text)) => assert_eq!(text,vec![
+                TextBlock::Paragraph {
+                    heading: None,
+                    body: vec![
+                        TextSpan::Italic("Ilsa".to_owned()),
+                        TextSpan::Normal(" wins.".to_owned())
+                    ]
+                }
+            ]),
+            _ => panic!("String should have been interpolated to structured text.")
+        }
+
+    }
+
+    #[test]
+    fn foundry_actor() {
+        let creature = goblin().create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        let attacks = crate::foundry::get_foundry_attacks(&creature);
+        let output = crate::foundry::write_foundry_actor(&goblin_stat_block(), &attacks, true).expect("Actor should have been written.");
+        let actor: serde_json::Value = serde_json::from_str(&output).expect("Actor should be JSON.");
+        assert_eq!(actor["name"],"Goblin");
+        assert_eq!(actor["system"]["abilities"]["dex"]["value"],14);
+        assert_eq!(actor["system"]["attributes"]["hp"],serde_json::json!({ "value": 7, "max": 7, "formula": "2d6" }));
+        assert_eq!(actor["system"]["attributes"]["ac"]["flat"],15);
+        assert_eq!(actor["system"]["details"]["cr"],0.25);
+        assert_eq!(actor["system"]["traits"]["size"],"sm");
+        assert_eq!(actor["items"][0]["name"],"Nimble Escape");
+        assert_eq!(actor["items"][0]["type"],"feat");
+        assert_eq!(actor["items"][1]["name"],"Scimitar");
+        assert_eq!(actor["items"][1]["type"],"weapon");
+        assert_eq!(actor["items"][1]["system"]["actionType"],"mwak");
+        assert_eq!(actor["items"][1]["system"]["damage"]["parts"],serde_json::json!([["1d6 + 2","slashing"]]));
+        assert_eq!(actor["items"][1]["flags"]["monstorr"]["rolls"],serde_json::json!({ "attack": "1d20 + 4", "damage": ["1d6 + 2"] }));
+        assert_eq!(actor["items"][2]["system"]["actionType"],"rwak");
+        assert_eq!(actor["items"][2]["system"]["range"]["long"],320);
+
+    }
+
+    #[test]
+    fn effect_descriptions() {
+        let dice: DiceExpression = Dice::new(2,&Die::D6).into();
+        assert_eq!(AttackEffect::Damage(dice.clone(),AttackBonus::Strength,Damage::Fire).get_description("str",&None),"${2d6 + str} fire damage.");
+        assert_eq!(AttackEffect::SaveHalf(13,Ability::Dexterity,dice.clone(),AttackBonus::Zero,Damage::Fire).get_description("str",&None),"the target must make a DC 13 Dexterity saving throw, taking ${2d6 + 0} fire damage on a failed save, or half as much damage on a successful one.");
+        assert_eq!(AttackEffect::AreaSaveAll(13,Ability::Dexterity,dice,AttackBonus::Zero,Damage::Fire).get_description("str",&None),"each target in the area must make a DC 13 Dexterity saving throw, taking ${2d6 + 0} fire damage on a failed save.");
+
+    }
+
+    #[test]
+    fn recharge_usage_limits() {
+        let heading = |limit: UsageLimit| {
+            let stat_block = goblin_with(vec![CreatureCommand::Action(Action::Action("Shriek".to_owned(),"The goblin shrieks.".to_owned(),None,None),Some(limit))]);
+            let action = stat_block.actions.iter().find(|action| blocks_to_text(&action.text).starts_with("Shriek")).expect("Action should have been found.");
+            match action.text.first() {
+                Some(TextBlock::Paragraph { heading: Some(heading), .. }) => spans_to_text(heading),
+                _ => panic!("Action should have had a heading.")
+            }
+        };
+        assert_eq!(heading(UsageLimit::Recharge(5)),"Shriek (Recharge 5–6).");
+        assert_eq!(heading(UsageLimit::Recharge(4)),"Shriek (Recharge 4–6).");
+        assert_eq!(heading(UsageLimit::Recharge(6)),"Shriek (Recharge 6).");
+
+    }
+
+    #[test]
+    fn flat_creature_format() {
+        let source = "# a goblin\nname Goblin\nsmall\nhumanoid\nsubtype goblinoid\nneutral evil\narmor leather\nshield\nhd 2d6\n\nwalk 30\nstr 8\nsaves dex, wis\nskills stealth\ncr 1/4\nfeature nimble escape\nweapon scimitar\naction Sneer: The goblin sneers.\nDarkvision(60)";
+        let creator = crate::flat_format::load_flat_creature_commands(source).expect("Flat creature should have loaded.");
+        assert_eq!(creator.0,vec![
+            CreatureCommand::Name("Goblin".to_owned()),
+            CreatureCommand::Small,
+            CreatureCommand::Humanoid,
+            CreatureCommand::Subtype("goblinoid".to_owned()),
+            CreatureCommand::NeutralEvil,
+            CreatureCommand::Armor(Armor::Leather),
+            CreatureCommand::Shield,
+            CreatureCommand::HitDie(Die::D6),
+            CreatureCommand::HitDiceCount(2),
+            CreatureCommand::Walk(30),
+            CreatureCommand::Str(8),
+            CreatureCommand::Saves(vec![Ability::Dexterity,Ability::Wisdom]),
+            CreatureCommand::Skills(vec![Skill::Stealth]),
+            CreatureCommand::OverrideQuarterChallenge,
+            CreatureCommand::Feature(Feature::NimbleEscape,None),
+            CreatureCommand::Weapon(Weapon::Scimitar(0),None),
+            CreatureCommand::Action(Action::Action("Sneer".to_owned(),"The goblin sneers.".to_owned(),None,None),None),
+            CreatureCommand::Darkvision(60)
+        ]);
+        assert!(creator.create_creature(&PathBuf::from(".")).is_ok());
+
+        assert_eq!(crate::flat_format::load_flat_creature_commands("name Goblin\nflibbertigibbet 3").map(|creator| creator.0),
+                   Err("Error loading creature commands: line 2 could not be understood: flibbertigibbet 3".to_owned()));
+    }
+
+    #[test]
+    fn multiple_spellcasting_features() {
+        let creature = |primary: bool| {
+            let mut commands = goblin().0;
+            commands.push(CreatureCommand::Spellcasting(vec![
+                SpellcastingCommand::Level(5),
+                SpellcastingCommand::Class("wizard".to_owned()),
+                SpellcastingCommand::Attack(5)
+            ],None));
+            let mut subclass = vec![
+                SpellcastingCommand::Level(3),
+                SpellcastingCommand::Class("cleric".to_owned()),
+                SpellcastingCommand::Attack(9)
+            ];
+            if primary {
+                subclass.push(SpellcastingCommand::Primary)
+            }
+            commands.push(CreatureCommand::Spellcasting(subclass,Some("Divine Spellcasting".to_owned())));
+            commands.push(CreatureCommand::Spellcasting(vec![SpellcastingCommand::Cantrips(vec!["light".to_owned()])],Some("Divine Spellcasting".to_owned())));
+            CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.")
+        };
+
+        let wizard = creature(false);
+        assert_eq!(wizard.get_spell_attack_bonus(),Some(5));
+        assert_eq!(wizard.get_spellcasting().map(|a| a.caster_level),Some(5));
+        let stat_block = wizard.try_into_stat_block().expect("Stat block should have been created.");
+        let headings: Vec<TextSpan> = stat_block.special_abilities.iter().filter_map(|a| match a.text.first() {
+            Some(TextBlock::Paragraph{ heading: Some(heading), .. }) => heading.first().cloned(),
+            _ => None
+        }).collect();
+        assert!(headings.contains(&TextSpan::Normal("Spellcasting.".to_owned())));
+        assert!(headings.contains(&TextSpan::Normal("Divine Spellcasting.".to_owned())));
+
+        let cleric = creature(true);
+        assert_eq!(cleric.get_spell_attack_bonus(),Some(9));
+        assert_eq!(cleric.get_spellcasting().map(|a| a.class.as_str()),Some("cleric"));
+        assert_eq!(cleric.get_spell_names(),vec!["light".to_owned()]);
+
+    }
+
+    #[test]
+    fn passive_perception_adjustments() {
+        let senses = |extra: Vec<CreatureCommand>| goblin_with(extra).senses;
+        assert_eq!(senses(vec![]),"darkvision 60 ft., passive Perception 9");
+        assert_eq!(senses(vec![CreatureCommand::PassivePerceptionBonus(5)]),"darkvision 60 ft., passive Perception 14");
+        assert_eq!(senses(vec![CreatureCommand::PassivePerceptionBonus(5),CreatureCommand::OverridePassivePerception(18)]),"darkvision 60 ft., passive Perception 18");
+
+    }
+
+    #[test]
+    fn lenient_open5e_list() {
+        let source = r#"{
+            "count": 2,
+            "next": null,
+            "previous": null,
+            "results": [
+                {
+                    "slug": "goblin", "name": "Goblin", "size": "Small", "type": "humanoid", "subtype": "goblinoid", "group": null,
+                    "alignment": "neutral evil", "armor_class": 15, "armor_desc": "leather armor, shield", "hit_points": 7, "hit_dice": "2d6",
+                    "speed": { "walk": 30, "walking": 30 },
+                    "strength": 8, "dexterity": 14, "constitution": 10, "intelligence": 10, "wisdom": 8, "charisma": 8,
+                    "strength_save": null, "dexterity_save": null, "constitution_save": null, "intelligence_save": null, "wisdom_save": null, "charisma_save": null,
+                    "perception": null, "skills": { "stealth": 6 },
+                    "damage_vulnerabilities": "", "damage_resistances": "", "damage_immunities": "", "condition_immunities": "",
+                    "senses": "darkvision 60 ft., passive Perception 9", "languages": "Common, Goblin",
+                    "actions": "", "reactions": "", "legendary_desc": "", "legendary_actions": "", "special_abilities": "", "spell_list": [],
+                    "img_main": null, "document__slug": "wotc-srd", "document__title": "Systems Reference Document", "document__license_url": "http://open5e.com/legal",
+                    "environments": ["Forest"]
+                },
+                "not a creature"
+            ]
+        }"#;
+
+        assert!(monstorr_open5e::Open5eMonsterList::load_from_str(source).is_err());
+
+        let (list,diagnostics) = match monstorr_open5e::Open5eMonsterList::load_from_str_lenient(source) {
+            Ok(result) => result,
+            Err(err) => panic!("List should have loaded leniently: {}",err)
+        };
+        assert_eq!(list.count,1);
+        assert_eq!(list.results[0].name,"Goblin");
+        assert_eq!(list.results[0].speed.walk,Some(30));
+        assert_eq!(list.results[0].skills.stealth,Some(6));
+        assert_eq!(list.results[0].challenge_rating,"0");
+        assert_eq!(diagnostics.len(),4);
+        assert!(diagnostics[0].starts_with("Open5e creature 'goblin': field 'speed.walking' was ignored: unknown field `walking`"));
+        assert_eq!(diagnostics[1],"Open5e creature 'goblin': field 'challenge_rating' is missing");
+        assert!(diagnostics[2].starts_with("Open5e creature 'goblin': field 'environments' was ignored: unknown field `environments`"));
+        assert!(diagnostics[3].starts_with("Open5e creature '#2' was skipped:"));
+
+    }
+
+    #[test]
+    fn lenient_open5e_extra_field() {
+        let source = r#"{
+            "slug": "bat", "name": "Bat", "size": "Tiny", "type": "beast", "subtype": "", "group": null,
+            "alignment": "unaligned", "armor_class": 12, "armor_desc": null, "hit_points": 1, "hit_dice": "1d4-1",
+            "speed": { "walk": 5, "fly": 30 },
+            "strength": 2, "dexterity": 15, "constitution": 8, "intelligence": 2, "wisdom": 12, "charisma": 4,
+            "strength_save": null, "dexterity_save": null, "constitution_save": null, "intelligence_save": null, "wisdom_save": null, "charisma_save": null,
+            "perception": null, "skills": {},
+            "damage_vulnerabilities": "", "damage_resistances": "", "damage_immunities": "", "condition_immunities": "",
+            "senses": "blindsight 60 ft., passive Perception 11", "languages": "", "challenge_rating": "0",
+            "actions": [{ "name": "Bite", "desc": "Melee Weapon Attack: +0 to hit, reach 5 ft., one creature. Hit: 1 piercing damage.", "attack_bonus": 0, "damage_dice": "1" }],
+            "reactions": "", "legendary_desc": "", "legendary_actions": "", "special_abilities": "", "spell_list": [],
+            "img_main": null, "document__slug": "wotc-srd", "document__title": "Systems Reference Document", "document__license_url": "http://open5e.com/legal",
+            "page_no": 367
+        }"#;
+
+        assert!(monstorr_open5e::Open5eMonster::load_from_str(source).is_err());
+
+        let (creature,diagnostics) = match monstorr_open5e::Open5eMonster::load_from_str_lenient(source) {
+            Ok(result) => result,
+            Err(err) => panic!("Creature should have loaded leniently: {}",err)
+        };
+        assert_eq!(creature.name,"Bat");
+        assert_eq!(creature.speed.fly,Some(30));
+        assert_eq!(creature.actions.len(),1);
+        assert_eq!(diagnostics.len(),1);
+        assert!(diagnostics[0].starts_with("field 'page_no' was ignored: unknown field `page_no`"));
+
+    }
+
+    #[test]
+    fn dice_properties() {
+        let dice = "2d4 - 1d4 + 3".parse::<DiceExpression>().expect("Dice expression should have parsed.");
+        assert_eq!(dice.minimum(),1);
+        assert_eq!(dice.maximum(),10);
+
+        let stat_block = goblin_with(vec![CreatureCommand::Feature(Feature::Feature("Tough".to_owned(),"${Subj} has between ${hit_dice.min} and ${hit_dice.max} hit points, usually ${hit_dice.avg}.".to_owned()),None)]);
+        assert!(stat_block.special_abilities.iter().any(|feature| feature.text == vec![
+            TextBlock::Paragraph {
+                heading: Some(vec![TextSpan::Normal("Tough.".to_owned())]),
+                body: vec![TextSpan::Normal("The goblin has between 2 and 12 hit points, usually 7.".to_owned())]
+            }
+        ]));
+
+    }
+
+    #[test]
+    fn conditional_interpolation() {
+        let mut data = HashMap::new();
+        data.insert("size".to_owned(),"Large".to_owned());
+        let interpolate = |source: &str, mode: crate::InterpolationMode| match crate::interpolate_str(source, "test", &data, mode) {
+            Ok(crate::InterpolatedText::Plain(text)) => Ok(text),
+            Ok(crate::InterpolatedText::Structured(text)) => Ok(text.into_iter().flat_map(|block| match block {
+                TextBlock::Paragraph{ body, .. } | TextBlock::SubParagraph{ body, .. } => body
+            }).map(|span| match span {
+                TextSpan::Normal(text) | TextSpan::Italic(text) | TextSpan::Bold(text) | TextSpan::BoldItalic(text) => text
+            }).collect::<String>()),
+            Err(err) => Err(format!("{}",err.error))
+        };
+
+        assert_eq!(interpolate(r#"It swings $<if(size == "Large", "its massive claws", "its claws")>."#, crate::InterpolationMode::DeserializeCreatureCommands),Ok("It swings its massive claws.".to_owned()));
+        assert_eq!(interpolate(r#"${if(size != "Large", "small", "large")} ${2 + 3 > 4} ${2 <= 1} ${1d6 == 1d6}"#, crate::InterpolationMode::CalculateStatBlock),Ok("large true false true".to_owned()));
+        // only the branch which is chosen is evaluated
+        assert_eq!(interpolate(r#"${if(3 >= 3, "yes", missing)}"#, crate::InterpolationMode::CalculateStatBlock),Ok("yes".to_owned()));
+        assert_eq!(interpolate(r#"${if(size, "yes", "no")}"#, crate::InterpolationMode::CalculateStatBlock),Err("The condition of 'if' must be a boolean".to_owned()));
+        assert_eq!(interpolate(r#"${size == 3}"#, crate::InterpolationMode::CalculateStatBlock),Err("Only two numbers, dice, strings or booleans can be checked for equality".to_owned()));
+        assert_eq!(interpolate(r#"${1d6 < 4}"#, crate::InterpolationMode::CalculateStatBlock),Err("Only numbers can be compared with '<' or '>', use avg(...) to compare dice".to_owned()));
+
+    }
+
+    #[test]
+    fn effective_hit_points() {
+        let creature = goblin().create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        assert_eq!(creature.get_effective_hit_points(),7);
+
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::Resistance(Damage::Fire));
+        let creature = CreatureCreator(commands.clone()).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        assert_eq!(creature.get_effective_hit_points(),7);
+
+        commands.push(CreatureCommand::NonmagicalResistance);
+        let creature = CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        assert_eq!(creature.get_effective_hit_points(),14);
+
+    }
+
+    #[test]
+    fn latex_theme() {
+        let latex = |template: &str, color: Option<&str>, font: Option<&str>| crate::template::process_template(&monstorr_data::templates::StoredTemplates::instance(monstorr_data::templates::TemplateOptions::latex(color.map(str::to_owned),font.map(str::to_owned),Some("20mm".to_owned()))), template, &Vec::new(), &goblin_stat_block(), &StatBlockLabels::default()).expect("Template should have been processed.");
+        let fragment = latex(monstorr_data::templates::LATEX_TEMPLATE,Some("7A200D"),Some("ppl"));
+        assert!(fragment.contains(r"\definecolor{monstorrThemeColor}{HTML}{7A200D}\color{monstorrThemeColor}"));
+        assert!(fragment.contains(r"\fontfamily{ppl}\selectfont"));
+        assert!(!fragment.contains(r"\documentclass"));
+        // the default fragment is unchanged.
+        assert!(!latex(monstorr_data::templates::LATEX_TEMPLATE,None,None).contains("monstorrThemeColor"));
+
+        let standalone = latex(monstorr_data::templates::LATEX_DOCUMENT_TEMPLATE,None,None);
+        assert!(standalone.starts_with(r"\documentclass{article}"));
+        assert!(standalone.contains(r"\usepackage[margin=20mm]{geometry}"));
+        assert!(standalone.contains(r"\providecommand{\rpgCreatureStat}[2]{\textbf{#1} #2\par}"));
+        assert!(standalone.contains(r"\begin{dnd5eStatBlock}"));
+
+        assert!(crate::validate_latex_theme(Some("#7a200d".to_owned()),None,None).is_ok());
+        assert!(crate::validate_latex_theme(Some("red}".to_owned()),None,None).is_err());
+        assert!(crate::validate_latex_theme(None,Some("{ppl}".to_owned()),None).is_err());
+        assert!(crate::validate_latex_theme(None,None,Some("2.5cm".to_owned())).is_ok());
+        assert!(crate::validate_latex_theme(None,None,Some("wide".to_owned())).is_err());
+
+    }
+
+    #[test]
+    fn skill_properties() {
+        let creature = goblin().create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        let properties = creature.list_properties();
+        // the goblin has expertise in stealth.
+        assert!(properties.contains(&("stealth".to_owned(),Some("6".to_owned()))));
+        assert!(properties.contains(&("perception".to_owned(),None)));
+
+    }
+
+    #[test]
+    fn escape_dc() {
+        let create = |strength: u8| goblin_with(vec![
+            CreatureCommand::CustomWeapon("Tentacle".to_owned(),WeaponReach::Reach(10),"1d6".parse::<DiceExpression>().expect("Dice expression should have parsed."),Damage::Bludgeoning,AttackBonus::Strength,Some(CompoundAttackEffect::And(AttackEffect::Escape("grappled".to_owned(),AttackBonus::Strength)))),
+            // the strength is changed after the weapon is added, and the DC should follow it.
+            CreatureCommand::Str(strength)
+        ]).write_to_string(true).expect("Stat block should have been written.");
+        assert!(create(8).contains("bludgeoning damage, and the target is grappled (escape DC 9)."));
+        assert!(create(16).contains("bludgeoning damage, and the target is grappled (escape DC 13)."));
+
+    }
+
+    #[test]
+    fn every_command_round_trip() {
+        let attack = Attack {
+            type_: Some(AttackType::Weapon),
+            bonus: AttackBonus::Fixed(2),
+            magic: Some(1),
+            reach: Some(5),
+            range: Some(20),
+            long_range: Some(60),
+            target: "one creature".to_owned(),
+            targeting: Some(AttackTargeting::Targets(2,Some(5)))
+        };
+        let dice: DiceExpression = "2d6 + 1".parse().expect("Dice expression should have parsed.");
+        let effect = AttackEffect::SaveHalf(13,Ability::Constitution,dice.clone(),AttackBonus::Zero,Damage::Poison);
+        let compound = Some(CompoundAttackEffect::PlusAnd(AttackEffect::Damage(dice.clone(),AttackBonus::Default,Damage::Fire),AttackEffect::Escape("grappled".to_owned(),AttackBonus::Strength)));
+        // every command, with optional arguments both given and left out, to catch serde attributes which don't survive being written and read again.
+        let creator = CreatureCreator(vec![
+            CreatureCommand::Monstorr(1.0,Some(1.0)),
+            CreatureCommand::Monstorr(1.0,None),
+            CreatureCommand::Include("other.creature".to_owned(),HashMap::from([("level".to_owned(),"5".to_owned())])),
+            CreatureCommand::IncludeStored("goblin".to_owned()),
+            CreatureCommand::Source("Test".to_owned()),
+            CreatureCommand::Companion("Wolf".to_owned()),
+            CreatureCommand::Environments(vec!["Forest".to_owned()]),
+            CreatureCommand::Habitat(vec!["Forest".to_owned(),"Hill".to_owned()]),
+            CreatureCommand::Treasure("Any".to_owned()),
+            CreatureCommand::Extra("key".to_owned(),"value".to_owned()),
+            CreatureCommand::Note("A note.".to_owned()),
+            CreatureCommand::DefineProperty("level".to_owned(),"$<level>".to_owned()),
+            CreatureCommand::CanonicalizeLayout,
+            CreatureCommand::StrictMultiattack,
+            CreatureCommand::LintOverrides,
+            CreatureCommand::Scale(1.5),
+            CreatureCommand::Name("Test".to_owned()),
+            CreatureCommand::DisplayName("Testy".to_owned()),
+            CreatureCommand::ProperName,
+            CreatureCommand::SubjectName("the test".to_owned()),
+            CreatureCommand::CapitalizeSubjectName("The test".to_owned()),
+            CreatureCommand::PossessiveName("the test's".to_owned()),
+            CreatureCommand::CapitalizePossessiveName("The test's".to_owned()),
+            CreatureCommand::Pronoun("they".to_owned()),
+            CreatureCommand::SubjectPronoun("they".to_owned()),
+            CreatureCommand::CapitalizeSubjectPronoun("They".to_owned()),
+            CreatureCommand::PossessivePronoun("their".to_owned()),
+            CreatureCommand::CapitalizePossessivePronoun("Their".to_owned()),
+            CreatureCommand::ObjectPronoun("them".to_owned()),
+            CreatureCommand::ReflexivePronoun("themself".to_owned()),
+            CreatureCommand::Tiny,
+            CreatureCommand::Small,
+            CreatureCommand::Medium,
+            CreatureCommand::Large,
+            CreatureCommand::Huge,
+            CreatureCommand::Gargantuan,
+            CreatureCommand::Aberration,
+            CreatureCommand::Beast,
+            CreatureCommand::Celestial,
+            CreatureCommand::Construct,
+            CreatureCommand::Dragon,
+            CreatureCommand::Elemental,
+            CreatureCommand::Fey,
+            CreatureCommand::Fiend,
+            CreatureCommand::Giant,
+            CreatureCommand::Humanoid,
+            CreatureCommand::Monstrosity,
+            CreatureCommand::Ooze,
+            CreatureCommand::Plant,
+            CreatureCommand::Undead,
+            CreatureCommand::CreatureType("swarm of Tiny beasts".to_owned()),
+            CreatureCommand::Subtype("goblinoid".to_owned()),
+            CreatureCommand::Group("Goblins".to_owned()),
+            CreatureCommand::AnyAlignment,
+            CreatureCommand::AnyNonGood,
+            CreatureCommand::AnyNonEvil,
+            CreatureCommand::AnyNonLawful,
+            CreatureCommand::AnyNonChaotic,
+            CreatureCommand::AnyGood,
+            CreatureCommand::AnyEvil,
+            CreatureCommand::AnyLawful,
+            CreatureCommand::AnyChaotic,
+            CreatureCommand::LawfulGood,
+            CreatureCommand::NeutralGood,
+            CreatureCommand::ChaoticGood,
+            CreatureCommand::LawfulNeutral,
+            CreatureCommand::Neutral,
+            CreatureCommand::ChaoticNeutral,
+            CreatureCommand::LawfulEvil,
+            CreatureCommand::NeutralEvil,
+            CreatureCommand::ChaoticEvil,
+            CreatureCommand::Unaligned,
+            CreatureCommand::Alignment("50% good".to_owned()),
+            CreatureCommand::AlignmentChance(vec![(75,Alignment::NeutralEvil),(25,Alignment::LawfulEvil)]),
+            CreatureCommand::HitDie(Die::D8),
+            CreatureCommand::HitDiceCount(3),
+            CreatureCommand::HitPoints(20),
+            CreatureCommand::Minion,
+            CreatureCommand::AverageRounding(AverageRounding::HalfEven),
+            CreatureCommand::Armor(Armor::Armor(12,"bark".to_owned())),
+            CreatureCommand::Armor(Armor::Natural(-1)),
+            CreatureCommand::Initiative(2),
+            CreatureCommand::OverrideInitiative(-1),
+            CreatureCommand::Shield,
+            CreatureCommand::NoShield,
+            CreatureCommand::Walk(30),
+            CreatureCommand::Swim(20),
+            CreatureCommand::Fly(60),
+            CreatureCommand::Hover,
+            CreatureCommand::Burrow(10),
+            CreatureCommand::Climb(15),
+            CreatureCommand::SpeedNotes("in bear form".to_owned()),
+            CreatureCommand::Speed("glide".to_owned(),40),
+            CreatureCommand::Str(12),
+            CreatureCommand::Dex(13),
+            CreatureCommand::Con(14),
+            CreatureCommand::Int(15),
+            CreatureCommand::Wis(16),
+            CreatureCommand::Cha(17),
+            CreatureCommand::Saves(vec![Ability::Strength,Ability::Charisma]),
+            CreatureCommand::Skills(vec![Skill::Stealth,Skill::SleightOfHand]),
+            CreatureCommand::Expertise(vec![Skill::Perception]),
+            CreatureCommand::RemoveSaves(vec![Ability::Charisma]),
+            CreatureCommand::RemoveSkills(vec![Skill::SleightOfHand]),
+            CreatureCommand::ClassProficiencies(CharacterClass::Fighter),
+            CreatureCommand::ConditionImmunity(Condition::Poisoned),
+            CreatureCommand::CustomConditionImmunity("sleep".to_owned()),
+            CreatureCommand::RemoveCustomConditionImmunity("sleep".to_owned()),
+            CreatureCommand::Vulnerability(Damage::Fire),
+            CreatureCommand::AllVulnerability,
+            CreatureCommand::RemoveVulnerability(Damage::Fire),
+            CreatureCommand::CustomVulnerability("fire from dragons".to_owned()),
+            CreatureCommand::VulnerabilityType("holy".to_owned()),
+            CreatureCommand::Resistance(Damage::Cold),
+            CreatureCommand::AllResistance,
+            CreatureCommand::NonmagicalResistance,
+            CreatureCommand::NonSilveredResistance,
+            CreatureCommand::NonAdamantineResistance,
+            CreatureCommand::RemoveResistance(Damage::Cold),
+            CreatureCommand::RemoveSpecialResistance,
+            CreatureCommand::CustomResistance("cold while submerged".to_owned()),
+            CreatureCommand::ResistanceType("sonic".to_owned()),
+            CreatureCommand::ResistanceNote("from stoneskin".to_owned()),
+            CreatureCommand::Immunity(Damage::Poison),
+            CreatureCommand::AllImmunity,
+            CreatureCommand::NonmagicalImmunity,
+            CreatureCommand::NonSilveredImmunity,
+            CreatureCommand::NonAdamantineImmunity,
+            CreatureCommand::RemoveImmunity(Damage::Poison),
+            CreatureCommand::RemoveSpecialImmunity,
+            CreatureCommand::CustomImmunity("poison from snakes".to_owned()),
+            CreatureCommand::ImmunityType("void".to_owned()),
+            CreatureCommand::ImmunityNote("while in the void".to_owned()),
+            CreatureCommand::Languages(vec![Language::Common,Language::Goblin]),
+            CreatureCommand::UnspokenLanguages(vec![Language::Draconic]),
+            CreatureCommand::LanguagesKnewInLife,
+            CreatureCommand::Telepathy(60,Some("with goblins".to_owned())),
+            CreatureCommand::Telepathy(120,None),
+            CreatureCommand::Darkvision(60),
+            CreatureCommand::Blindsight(10),
+            CreatureCommand::BlindsightBlindBeyond(30),
+            CreatureCommand::Truesight(120),
+            CreatureCommand::Tremorsense(30),
+            CreatureCommand::CustomSense("echolocation".to_owned(),60),
+            CreatureCommand::SensesSeparator("; ".to_owned()),
+            CreatureCommand::PassivePerceptionLabel("passive Wisdom (Perception)".to_owned()),
+            CreatureCommand::PassivePerceptionBonus(-2),
+            CreatureCommand::OverridePassivePerception(15),
+            CreatureCommand::ExpectNoChallenge,
+            CreatureCommand::ExpectChallenge(3),
+            CreatureCommand::ExpectHalfChallenge,
+            CreatureCommand::ExpectQuarterChallenge,
+            CreatureCommand::ExpectEighthChallenge,
+            CreatureCommand::OverrideNoChallenge,
+            CreatureCommand::OverrideChallenge(3),
+            CreatureCommand::OverrideHalfChallenge,
+            CreatureCommand::OverrideQuarterChallenge,
+            CreatureCommand::OverrideEighthChallenge,
+            CreatureCommand::ProficiencyBonus(3),
+            CreatureCommand::ShowProficiencyBonus,
+            CreatureCommand::AutoMultiattack(Multiattack::Count(2,vec![Multiattack::Melee])),
+            CreatureCommand::Multiattack("${Subj} attacks twice.".to_owned(),Multiattack::Or(vec![
+                Multiattack::Count(2,vec![Multiattack::Melee,Multiattack::Ranged]),
+                Multiattack::And(vec![Multiattack::Attack("Bite".to_owned()),Multiattack::Weapon(Weapon::Scimitar(0)),Multiattack::Spell]),
+                Multiattack::Except(vec![Multiattack::Any]),
+                Multiattack::Dice(dice.clone(),vec![Multiattack::Any])
+            ])),
+            CreatureCommand::OneHanded,
+            CreatureCommand::Weapon(Weapon::Scimitar(1),compound.clone()),
+            CreatureCommand::Weapon(Weapon::Shortbow(0),None),
+            CreatureCommand::CustomWeapon("Spit".to_owned(),WeaponReach::Thrown(5,20,60),dice.clone(),Damage::Acid,AttackBonus::Dexterity,compound.clone()),
+            CreatureCommand::CustomWeapon("Claw".to_owned(),WeaponReach::Reach(5),dice.clone(),Damage::Slashing,AttackBonus::Strength,None),
+            CreatureCommand::DefineWeapon("Chieftain's Axe".to_owned(),Weapon::Handaxe(1),compound.clone()),
+            CreatureCommand::DefineWeapon("Plain Axe".to_owned(),Weapon::Handaxe(0),None),
+            CreatureCommand::AddWeapon("Chieftain's Axe".to_owned()),
+            CreatureCommand::ExpectWeaponAttack(Weapon::Scimitar(1),attack.clone()),
+            CreatureCommand::ExpectWeaponEffect(Weapon::Scimitar(1),effect.clone()),
+            CreatureCommand::OverrideWeaponAttack(Weapon::Scimitar(1),attack.clone()),
+            CreatureCommand::OverrideWeaponEffect(Weapon::Scimitar(1),effect.clone()),
+            CreatureCommand::OverrideWeaponDescription(Weapon::Scimitar(1),"It slashes.".to_owned()),
+            CreatureCommand::Action(Action::Attack("Sting".to_owned(),attack.clone(),effect.clone(),compound.clone()),Some(UsageLimit::Recharge(5))),
+            CreatureCommand::Action(Action::Action("Shriek".to_owned(),"It shrieks.".to_owned(),Some(effect.clone()),None),Some(UsageLimit::PerDay(3))),
+            CreatureCommand::Action(Action::BreathWeapon("Poison Breath".to_owned(),"It breathes.".to_owned(),None,None),Some(UsageLimit::RechargeAfterRest)),
+            CreatureCommand::Action(Action::Teleport(30,Some("to a shadow".to_owned())),Some(UsageLimit::AlternateFormOnly("Bat Form Only".to_owned()))),
+            CreatureCommand::Action(Action::Swallow(dice.clone(),14,20,15),None),
+            CreatureCommand::OverrideActionDescription("Shriek".to_owned(),"It screams.".to_owned()),
+            CreatureCommand::RemoveWeapon(Weapon::Shortbow(0)),
+            CreatureCommand::MoveWeapon(Weapon::Scimitar(1),-1),
+            CreatureCommand::RemoveAction("Sting".to_owned()),
+            CreatureCommand::RemoveActionsMatching("Breath".to_owned()),
+            CreatureCommand::RemoveAllActions,
+            CreatureCommand::MoveAction("Shriek".to_owned(),2),
+            CreatureCommand::OrderAction("Shriek".to_owned(),-10),
+            CreatureCommand::Reaction(Reaction::Parry(2),Some(UsageLimit::PerTurn(1))),
+            CreatureCommand::Reaction(Reaction::Reaction("Dodge".to_owned(),"It dodges.".to_owned(),None),None),
+            CreatureCommand::RemoveReaction("Dodge".to_owned()),
+            CreatureCommand::RemoveAllReactions,
+            CreatureCommand::Feature(Feature::NimbleEscape,None),
+            CreatureCommand::Feature(Feature::LegendaryResistance,Some(UsageLimit::PerDay(3))),
+            CreatureCommand::RemoveFeature("Nimble Escape".to_owned()),
+            CreatureCommand::OrderFeature("Legendary Resistance".to_owned(),5),
+            CreatureCommand::Spellcasting(vec![
+                SpellcastingCommand::Level(5),
+                SpellcastingCommand::Class("wizard".to_owned()),
+                SpellcastingCommand::IsThirdCaster,
+                SpellcastingCommand::IsHalfCaster,
+                SpellcastingCommand::IsFullCaster,
+                SpellcastingCommand::IsWarlock,
+                SpellcastingCommand::Ability(Ability::Intelligence),
+                SpellcastingCommand::Slots(1,4),
+                SpellcastingCommand::SaveDC(13),
+                SpellcastingCommand::Attack(5),
+                SpellcastingCommand::Cantrips(vec!["light".to_owned()]),
+                SpellcastingCommand::Spells(1,vec!["shield".to_owned()]),
+                SpellcastingCommand::BeforeCombat(vec!["shield".to_owned()]),
+                SpellcastingCommand::RemoveSpells(vec!["light".to_owned()]),
+                SpellcastingCommand::Primary
+            ],Some("Ritual Spellcasting".to_owned())),
+            CreatureCommand::Spellcasting(vec![SpellcastingCommand::Level(1)],None),
+            CreatureCommand::InnateSpellcasting(vec![
+                InnateSpellcastingCommand::Ability(Ability::Charisma),
+                InnateSpellcastingCommand::SaveDC(12),
+                InnateSpellcastingCommand::Attack(4),
+                InnateSpellcastingCommand::AtWill(vec!["mage hand".to_owned()]),
+                InnateSpellcastingCommand::PerDay(1,vec!["sleep".to_owned()]),
+                InnateSpellcastingCommand::RemoveSpells(vec!["mage hand".to_owned()]),
+                InnateSpellcastingCommand::SpellRestriction("sleep".to_owned(),"self only".to_owned()),
+                InnateSpellcastingCommand::Components("requiring no material components".to_owned())
+            ]),
+            CreatureCommand::LegendaryActions(3,vec![
+                LegendaryAction::UseAction(1,"Shriek".to_owned(),"It shrieks.".to_owned(),"Shriek".to_owned()),
+                LegendaryAction::UseWeapon(2,"Slash".to_owned(),"It slashes.".to_owned(),Weapon::Scimitar(1)),
+                LegendaryAction::LegendaryAction(1,Action::Reel)
+            ]),
+            CreatureCommand::RemoveLegendaryAction("Slash".to_owned()),
+            CreatureCommand::LegendaryActionsStyle(LegendaryActionsStyle::Uses),
+            CreatureCommand::LairActions("On initiative count 20:".to_owned(),vec!["The ground shakes.".to_owned()],"".to_owned()),
+            CreatureCommand::RegionalEffects("The region is changed:".to_owned(),vec!["Birds go quiet.".to_owned()],"The effects fade.".to_owned())
+        ]);
+        let source = creator.save_to_string().expect("Creature should have been written.");
+        assert_eq!(CreatureCreator::load_from_str(&source),Ok(creator));
+
+    }
+
+    #[test]
+    fn natural_flat_armor() {
+        let armor = |armor: Armor| goblin_with(vec![CreatureCommand::Armor(armor),CreatureCommand::NoShield]).armor;
+        // the goblin's dexterity bonus is not added.
+        assert_eq!(armor(Armor::NaturalFlat(17,None)),"17 (natural armor)");
+        assert_eq!(armor(Armor::NaturalFlat(17,Some("stone hide".to_owned()))),"17 (natural armor, stone hide)");
+        assert_eq!(armor(Armor::Natural(5)),"17 (natural armor)");
+
+    }
+
+    #[test]
+    fn list_stored_creatures() {
+        let list = |type_: Option<&str>, max_cr: Option<&str>| crate::list_creatures(&[], crate::ListInputFormat::Stored, type_.map(str::to_owned), None, None, None, None, max_cr.map(str::to_owned), None).expect("Creatures should have been listed.").into_iter().map(|creature| creature.slug).collect::<Vec<String>>();
+        assert!(list(None,None).contains(&"goblin".to_owned()));
+        // the database is generated in the order of the file names, so it doesn't change when regenerated on another system.
+        let mut sorted = list(None,None);
+        sorted.sort();
+        assert_eq!(list(None,None),sorted);
+        assert!(list(Some("humanoid"),Some("1")).contains(&"goblin".to_owned()));
+        assert!(!list(Some("dragon"),None).contains(&"goblin".to_owned()));
+        assert!(!list(None,Some("1/8")).contains(&"goblin".to_owned()));
+
+    }
+
+    #[test]
+    fn compact_stat_block() {
+        let compact = goblin_stat_block().to_compact();
+        assert_eq!(compact.name,"Goblin");
+        assert_eq!(compact.armor,"15 (leather armor, shield)");
+        let text = compact.write_to_string(true).expect("Stat block should have been written.");
+        assert!(text.contains(r#""type":"humanoid""#));
+        assert!(text.contains(r#""challenge_rating":"1/4 (50 XP)""#));
+        assert!(!text.contains("actions"));
+
+    }
+
+
+    #[test]
+    fn dice_expression_variations() {
+        let parse = |expr: &str| expr.parse::<DiceExpression>().map_err(|e| e.to_string());
+        let expected = parse("2d6 + 3").expect("Dice expression should have parsed.");
+        assert_eq!(parse("2d6+3"),Ok(expected.clone()));
+        assert_eq!(parse(" +2d6 +3 "),Ok(expected.clone()));
+        assert_eq!(parse("\t2D6\t+\t3\n"),Ok(expected));
+        assert_eq!(parse("-1d4+2d6-1"),parse("- 1d4 + 2d6 - 1"));
+        assert_eq!(parse("+(1d6*2)+1"),parse("(1d6 * 2) + 1"));
+
+        assert_eq!(parse("  "),Err("Expected a dice expression, such as '2d6 + 3', but found nothing".to_owned()));
+        assert_eq!(parse("+ 3"),Err("Expected dice or open parenthesis after initial plus".to_owned()));
+        assert!(parse("2d6 +").is_err());
+        assert!(parse("2d6 + 3 3").is_err());
+        assert!(parse("2d6x").is_err());
+    }
+
+    #[test]
+    fn stat_block_schema() {
+        let schema: serde_json::Value = serde_json::from_str(CreatureStatBlock::json_schema()).expect("Schema should be JSON.");
+        let properties = schema["properties"].as_object().expect("Schema should have properties.");
+
+        let stat_block = goblin_stat_block();
+        let output: serde_json::Value = serde_json::from_str(&stat_block.write_to_string(true).expect("Stat block should have been written.")).expect("Stat block should be JSON.");
+        let output = output.as_object().expect("Stat block should be an object.");
+        for key in output.keys() {
+            assert!(properties.contains_key(key),"Schema is missing property '{}'",key);
+        }
+        for required in schema["required"].as_array().expect("Schema should have required properties.") {
+            assert!(output.contains_key(required.as_str().expect("Required property should be a string.")));
+        }
+
+        // the text blocks must use the names serde gives them.
+        let blocks = &schema["definitions"]["TextBlock"]["properties"]["block"]["enum"];
+        let styles = &schema["definitions"]["TextSpan"]["properties"]["style"]["enum"];
+        for feature in output["actions"].as_array().expect("Goblin should have actions.") {
+            for block in feature["text"].as_array().expect("Feature should have text.") {
+                assert!(blocks.as_array().expect("Blocks should be listed.").contains(&block["block"]));
+                for span in block["body"].as_array().expect("Block should have a body.") {
+                    assert!(styles.as_array().expect("Styles should be listed.").contains(&span["style"]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reaction_trigger() {
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::Reaction(Reaction::Reaction("Redirect Attack".to_owned(),"${Subj} chooses another goblin within 5 feet of ${objpro}. The two goblins swap places, and the chosen goblin becomes the target instead.".to_owned(),Some("A creature ${subj} can see targets ${objpro} with an attack.".to_owned())),None));
+        let creature = CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        let stat_block = creature.try_into_stat_block().expect("Stat block should have been created.");
+        assert_eq!(stat_block.reactions,vec![
+            StatBlockFeature {
+                text: vec![
+                    TextBlock::Paragraph {
+                        heading: Some(vec![TextSpan::Normal("Redirect Attack.".to_owned())]),
+                        body: vec![
+                            TextSpan::Italic("Trigger:".to_owned()),
+                            TextSpan::Normal(" A creature the goblin can see targets it with an attack. ".to_owned()),
+                            TextSpan::Italic("Response:".to_owned()),
+                            TextSpan::Normal(" The goblin chooses another goblin within 5 feet of it. The two goblins swap places, and the chosen goblin becomes the target instead.".to_owned())
+                        ]
+                    }
+                ],
+                trigger: Some("A creature the goblin can see targets it with an attack.".to_owned())
+            }
+        ]);
+    }
+
+    #[test]
+    fn hit_points_average() {
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::HitPoints(12));
+        commands.push(CreatureCommand::Feature(Feature::Feature("Sturdy".to_owned(),"${Subj} has ${hit_points} hit points, instead of ${hit_points_average} from ${hit_dice}.".to_owned()),None));
+        let (creature,_) = CreatureCreator(commands).create_creature_with_parameters(&PathBuf::from(env!("CARGO_MANIFEST_DIR")),&HashMap::new()).expect("Creature should have been created.");
+        let properties = creature.list_properties();
+        assert!(properties.contains(&("hit_points".to_owned(),Some("12".to_owned()))));
+        assert!(properties.contains(&("hit_points_average".to_owned(),Some("7".to_owned()))));
+
+        let stat_block = creature.try_into_stat_block().expect("Stat block should have been created.");
+        assert_eq!(stat_block.hit_points,"12 (2d6)");
+        assert!(stat_block.special_abilities.iter().any(|feature| feature.text == vec![
+            TextBlock::Paragraph {
+                heading: Some(vec![TextSpan::Normal("Sturdy.".to_owned())]),
+                body: vec![TextSpan::Normal("The goblin has 12 hit points, instead of 7 from 7 (2d6).".to_owned())]
+            }
+        ]));
+    }
+
+    #[test]
+    fn show_proficiency_bonus() {
+        let stat_block = goblin().create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.").try_into_stat_block().expect("Stat block should have been created.");
+        assert_eq!(stat_block.proficiency_bonus,None);
+
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::ShowProficiencyBonus);
+        let stat_block = CreatureCreator(commands.clone()).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.").try_into_stat_block().expect("Stat block should have been created.");
+        assert_eq!(stat_block.proficiency_bonus,Some("+2".to_owned()));
+
+        commands.push(CreatureCommand::ProficiencyBonus(3));
+        let stat_block = CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.").try_into_stat_block().expect("Stat block should have been created.");
+        assert_eq!(stat_block.proficiency_bonus,Some("+3".to_owned()));
+    }
+
+    #[test]
+    fn one_handed_weapons() {
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::Weapon(Weapon::Longsword(0),None));
+        let creature = CreatureCreator(commands.clone()).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        let longsword = creature.actions.iter().find(|action| action.name == "Longsword").expect("Longsword should have been added.");
+        assert!(matches!(longsword.effect,Some(AttackEffect::Or(..))));
+        assert!(longsword.description.contains("if used with two hands to make a melee attack"));
+
+        let mut commands = goblin().0;
+        commands.push(CreatureCommand::OneHanded);
+        commands.push(CreatureCommand::Weapon(Weapon::Longsword(0),None));
+        let creature = CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        let longsword = creature.actions.iter().find(|action| action.name == "Longsword").expect("Longsword should have been added.");
+        assert_eq!(longsword.effect,Some(AttackEffect::Damage("1d8".parse().expect("Dice expression should have parsed."),AttackBonus::Strength,Damage::Slashing)));
+        assert!(!longsword.description.contains("two hands"));
+        // weapons which aren't versatile are unchanged
+        assert_eq!(Weapon::Scimitar(0).get_one_handed_effect(&CreatureSize::Small),Weapon::Scimitar(0).get_effect(&CreatureSize::Small));
+    }
+
+    #[test]
+    fn auto_multiattack() {
+        let describe = |multiattack: Multiattack| multiattack.get_description();
+        assert_eq!(describe(Multiattack::Count(2,vec![Multiattack::Attack("Claw".to_owned())])),"${Subj} makes two claw attacks.");
+        assert_eq!(describe(Multiattack::And(vec![
+            Multiattack::Attack("Bite".to_owned()),
+            Multiattack::Count(2,vec![Multiattack::Attack("Claw".to_owned())])
+        ])),"${Subj} makes one bite attack and two claw attacks.");
+        assert_eq!(describe(Multiattack::Count(3,vec![Multiattack::Weapon(Weapon::Longsword(1)),Multiattack::Ranged])),"${Subj} makes three attacks, each one a longsword attack or a ranged attack.");
+        assert_eq!(describe(Multiattack::Except(vec![Multiattack::Attack("Bite".to_owned())])),"${Subj} makes one attack other than a bite attack.");
+        assert_eq!(describe(Multiattack::Dice("1d4".parse().expect("Dice expression should have parsed."),vec![Multiattack::Attack("Rotting Touch".to_owned())])),"${Subj} makes 1d4 rotting touch attacks.");
+
+        let stat_block = goblin_with(vec![CreatureCommand::AutoMultiattack(Multiattack::Count(2,vec![Multiattack::Weapon(Weapon::Scimitar(0))]))]);
+        assert!(stat_block.actions.iter().any(|feature| feature.text == vec![
+            TextBlock::Paragraph {
+                heading: Some(vec![TextSpan::Normal("Multiattack.".to_owned())]),
+                body: vec![TextSpan::Normal("The goblin makes two scimitar attacks.".to_owned())]
+            }
+        ]));
+    }
+
+    #[test]
+    fn class_proficiencies() {
+        let stat_block = goblin_with(vec![CreatureCommand::ClassProficiencies(CharacterClass::Rogue)]);
+        assert_eq!(stat_block.saving_throws,Some("Dex +4, Int +2".to_owned()));
+        let skills = stat_block.skills.expect("Skills should have been added.");
+        assert!(skills.contains("Acrobatics +4"));
+        assert!(skills.contains("Perception +1"));
+        // the goblin's expertise in stealth is kept
+        assert!(skills.contains("Stealth +6"));
+    }
+
+    #[test]
+    fn text_output() {
+        let text = crate::text_format::write_text(&goblin_stat_block(), &StatBlockLabels::default(), 60);
+        assert!(text.lines().all(|line| line.chars().count() <= 60),"Line too long in:\n{}",text);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(&lines[0..4],&[
+            "============================================================",
+            "Goblin",
+            "Small humanoid (goblinoid), neutral evil",
+            "============================================================"
+        ]);
+        assert!(lines.contains(&"Armor Class 15 (leather armor, shield)"));
+        assert!(lines.contains(&"STR      DEX      CON      INT      WIS      CHA"));
+        assert!(lines.contains(&"8 (-1)   14 (+2)  10 (+0)  10 (+0)  8 (-1)   8 (-1)"));
+        assert!(lines.contains(&"ACTIONS"));
+        // the styles are dropped, and the feature is wrapped without a hanging indent.
+        assert!(lines.contains(&"Nimble Escape. The goblin can take the Disengage or Hide"));
+    }
+
+    #[test]
+    fn interpolate_default_operator() {
+        let parameters = HashMap::from([("level".to_owned(),"5".to_owned()),("title".to_owned(),"".to_owned())]);
+        assert_eq!(crate::interpolate_source("[ HitDiceCount($<level ?? \"1\">) ]".to_owned(),"test",&parameters),Ok("[ HitDiceCount(5) ]".to_owned()));
+        assert_eq!(crate::interpolate_source("[ HitDiceCount($<rank ?? \"1\">) ]".to_owned(),"test",&parameters),Ok("[ HitDiceCount(1) ]".to_owned()));
+        assert_eq!(crate::interpolate_source("[ Name(\"$<title ?? name ?? \"Goblin\">\") ]".to_owned(),"test",&parameters),Ok("[ Name(\"Goblin\") ]".to_owned()));
+        assert_eq!(crate::interpolate_source("[ HitDiceCount($<(rank ?? \"2\") + \"0\">) ]".to_owned(),"test",&parameters),Ok("[ HitDiceCount(20) ]".to_owned()));
+        assert!(crate::interpolate_source("[ HitDiceCount($<\"1\" * 2 ?? \"1\">) ]".to_owned(),"test",&parameters).is_err());
+        assert!(crate::interpolate_source("[ HitDiceCount($<rank ? \"1\">) ]".to_owned(),"test",&parameters).is_err());
+        assert!(crate::interpolate_source("[ HitDiceCount($<rank ??>) ]".to_owned(),"test",&parameters).is_err());
+
+    }
+
+
+    #[test]
+    fn alignment_chance() {
+        let stat_block = goblin_with(vec![CreatureCommand::AlignmentChance(vec![(75,Alignment::NeutralEvil),(25,Alignment::LawfulEvil)])]);
+        assert_eq!(stat_block.alignment,"neutral evil (75%) or lawful evil (25%)");
+        assert_eq!(Alignment::Chance(vec![(50,Alignment::AnyGood),(30,Alignment::Neutral),(20,Alignment::Custom("hungry".to_owned()))]).to_string(),"any good (50%), neutral (30%) or hungry (20%)");
+        assert!(Alignment::text_matches(&stat_block.alignment,"Lawful Evil"));
+        assert!(Alignment::text_matches(&stat_block.alignment,"neutral evil (75%) or lawful evil (25%)"));
+        assert!(!Alignment::text_matches(&stat_block.alignment,"evil"));
+        assert!(!Alignment::text_matches("neutral evil","evil"));
+
+        let invalid = |chances: Vec<(u8,Alignment)>| {
+            let mut commands = goblin().0;
+            commands.push(CreatureCommand::AlignmentChance(chances));
+            matches!(CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))),Err(crate::errors::CreatureError::InvalidAlignmentChance(_)))
+        };
+        assert!(invalid(vec![]));
+        assert!(invalid(vec![(75,Alignment::NeutralEvil),(20,Alignment::LawfulEvil)]));
+        assert!(invalid(vec![(200,Alignment::NeutralEvil),(100,Alignment::LawfulEvil)]));
+        assert!(invalid(vec![(50,Alignment::NeutralEvil),(50,Alignment::Chance(vec![(100,Alignment::LawfulEvil)]))]));
+        assert!(!invalid(vec![(100,Alignment::NeutralEvil)]));
+    }
+
+
+    #[test]
+    fn damage_notes() {
+        let stat_block = goblin_with(vec![
+            CreatureCommand::Resistance(Damage::Cold),
+            CreatureCommand::Resistance(Damage::Fire),
+            CreatureCommand::ResistanceNote("from its ring of fire resistance".to_owned()),
+            CreatureCommand::NonmagicalResistance,
+            CreatureCommand::ResistanceNote("from stoneskin".to_owned()),
+            CreatureCommand::ImmunityType("sonic".to_owned()),
+            CreatureCommand::ImmunityNote("while deafened".to_owned())
+        ]);
+        assert_eq!(stat_block.damage_resistances,Some("cold; fire (from its ring of fire resistance); bludgeoning, piercing, and slashing from nonmagical attacks (from stoneskin)".to_owned()));
+        assert_eq!(stat_block.damage_immunities,Some("sonic (while deafened)".to_owned()));
+
+        assert_eq!(CreatureCreator(vec![
+            CreatureCommand::ResistanceNote("from stoneskin".to_owned())
+        ]).create_creature_with_warnings(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.").1,vec!["ResistanceNote: there is no resistance to add the note to.".to_owned()]);
+    }
+
+
+    #[test]
+    fn command_syntax_error_position() {
+        let source = "[\n    Name(\"Goblin\"), // a comment with a (\n    Small\n    Str(8)\n]";
+        let error = CreatureCreator::load_from_str(source).expect_err("Commands should not have been loaded.");
+        assert_eq!(error,crate::errors::CommandSyntaxError {
+            message: "Expected end of array".to_owned(),
+            line: 4,
+            column: 5,
+            command: Some("Small".to_owned())
+        });
+        assert_eq!(error.to_string(),"Expected end of array at line 4, column 5, near the 'Small' command");
+    }
+
+
+    #[test]
+    fn flat_creature_format_from_stdin() {
+        let source = crate::read_source_from_stdin("name Goblin\nsmall\nhumanoid\nstr 8\n".as_bytes()).expect("Source should have been read.");
+        let creator = crate::load_creature_commands_in_format(&source, &crate::CreatureFileFormat::Flat).expect("Flat creature should have loaded.");
+        assert_eq!(creator.0,vec![
+            CreatureCommand::Name("Goblin".to_owned()),
+            CreatureCommand::Small,
+            CreatureCommand::Humanoid,
+            CreatureCommand::Str(8)
+        ]);
+    }
+
+
+    #[test]
+    #[cfg(feature = "toml-format")]
+    fn toml_round_trip_from_stdin() {
+        let toml = crate::save_creature_commands(&goblin(), &crate::CreatureFileFormat::TOML).expect("Creature should have been written as TOML.");
+        assert!(toml.lines().count() > 1);
+        let source = crate::read_source_from_stdin(toml.as_bytes()).expect("Source should have been read.");
+        assert_eq!(crate::load_creature_commands_in_format(&source, &crate::CreatureFileFormat::TOML).expect("Creature should have been read from TOML."),goblin());
+    }
+
+
+    #[test]
+    fn command_syntax_error_position_from_stdin() {
+        let source = crate::read_source_from_stdin("[\n    Name(\"Goblin\"),\n    Small\n    Str(8)\n]\n".as_bytes()).expect("Source should have been read.");
+        assert_eq!(crate::load_creature_commands(&source).map(|creator| creator.0),
+                   Err("Error loading creature commands: Expected end of array at line 4, column 5, near the 'Small' command".to_owned()));
+    }
+
+
+    #[test]
+    fn scale_rejects_invalid_factors() {
+        for scale in [0.0,-1.0,f32::NAN] {
+            let mut commands = goblin().0;
+            commands.push(CreatureCommand::Scale(scale));
+            match CreatureCreator(commands).create_creature(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))) {
+                Err(crate::errors::CreatureError::InvalidScale(_)) => (),
+                Err(err) => panic!("Scale {} should have been rejected as invalid, found: {}",scale,err),
+                Ok(_) => panic!("Scale {} should have been rejected.",scale)
+            }
+        }
+    }
+
+    #[test]
+    fn remove_warnings() {
+        let mut commands = goblin().0;
+        commands.extend(vec![
+            CreatureCommand::RemoveAction("Scimitar".to_owned()),
+            CreatureCommand::RemoveAction("Claw".to_owned()),
+            CreatureCommand::RemoveFeature("Sunlight Sensitivity".to_owned()),
+            CreatureCommand::RemoveReaction("Parry".to_owned())
+        ]);
+        let (creature,warnings) = CreatureCreator(commands).create_creature_with_warnings(&PathBuf::from(env!("CARGO_MANIFEST_DIR"))).expect("Creature should have been created.");
+        assert!(!creature.actions.iter().any(|a| a.name == "Scimitar"));
+        assert_eq!(warnings,vec![
+            "RemoveAction: could not find 'Claw', nothing was removed.".to_owned(),
+            "RemoveFeature: could not find 'Sunlight Sensitivity', nothing was removed.".to_owned(),
+            "RemoveReaction: could not find 'Parry', nothing was removed.".to_owned()
+        ]);
+
+        assert!(crate::report_warnings(warnings.clone(),&crate::WarningLevel::Ignore).is_ok());
+        assert_eq!(crate::report_warnings(warnings,&crate::WarningLevel::Error),Err("RemoveAction: could not find 'Claw', nothing was removed.\nRemoveFeature: could not find 'Sunlight Sensitivity', nothing was removed.\nRemoveReaction: could not find 'Parry', nothing was removed.".to_owned()));
+
+    }
+
+    #[test]
+    fn discord_template() {
+        let output = crate::template::process_template(&monstorr_data::templates::StoredTemplates::instance(None), monstorr_data::templates::DISCORD_TEMPLATE, &Vec::new(), &goblin_stat_block(), &StatBlockLabels::default()).expect("Template should have been processed.");
+        assert_eq!(output,concat!(
+            "__**Goblin**__\n",
+            "*Small humanoid (goblinoid), neutral evil*\n",
+            "\n",
+            "**Armor Class** 15 (leather armor, shield)\n",
+            "**Hit Points** 7 (2d6)\n",
+            "**Speed** 30 ft.\n",
+            "```\n",
+            "STR      DEX      CON      INT      WIS      CHA\n",
+            "8 (-1)   14 (+2)  10 (+0)  10 (+0)  8 (-1)   8 (-1)\n",
+            "```\n",
+            "**Skills** Stealth +6\n",
+            "**Senses** darkvision 60 ft., passive Perception 9\n",
+            "**Languages** Common, Goblin\n",
+            "**Challenge** 1/4 (50 XP)\n",
+            "***Nimble Escape.*** The goblin can take the Disengage or Hide action as a bonus action on each of its turns.\n",
+            "\n",
+            "__**Actions**__\n",
+            "***Scimitar.*** *Melee Weapon Attack:* +4 to hit, reach 5 ft., one target. *Hit:* 5 (1d6 + 2) slashing damage.\n",
+            "***Shortbow.*** *Ranged Weapon Attack:* +4 to hit, range 80/320 ft., one target. *Hit:* 5 (1d6 + 2) piercing damage.\n",
+            "\n",
+            "*Source: D&D 5E System Reference Document*\n"
+        ));
+
+    }
+
+    #[test]
+    fn html_link_footer_escaping() {
+        assert_eq!(crate::escape_html_attribute("a&b \"c\" <d> {{ e }}"),"a&amp;b &quot;c&quot; &lt;d&gt; &#123;&#123; e &#125;&#125;");
+
+        let url = "https://example.com/goblin?a=1&b=\"2\"&c={{ name }}";
+        let footer = crate::html_link_footer(url,false).expect("Footer should have been created.");
+        let escaped = "https://example.com/goblin?a=1&amp;b=&quot;2&quot;&amp;c=&#123;&#123; name &#125;&#125;";
+        assert_eq!(footer,format!("<div class=\"stat-block-footer\"><a href=\"{}\">{}</a></div>",escaped,escaped));
+
+        // the footer is included as a template, so the escaped braces must come through as they are.
+        let html = crate::template::process_template(&monstorr_data::templates::StoredTemplates::instance(monstorr_data::templates::TemplateOptions::html(monstorr_data::templates::HtmlOptions::default(),Some(footer.clone()))), monstorr_data::templates::STAT_BLOCK_HTML_TEMPLATE, &Vec::new(), &goblin_stat_block(), &StatBlockLabels::default()).expect("Template should have been processed.");
+        assert!(html.contains(&footer));
+
+        #[cfg(feature = "qr")]
+        {
+            let footer = crate::html_link_footer(url,true).expect("Footer should have been created.");
+            assert!(footer.starts_with("<div class=\"stat-block-footer\"><svg"));
+            assert!(footer.ends_with(&format!("<a href=\"{}\">{}</a></div>",escaped,escaped)));
+        }
+        #[cfg(not(feature = "qr"))]
+        assert!(crate::html_link_footer(url,true).is_err());
+
+    }
+
+    #[test]
+    fn export_templates() {
+        let dir = std::env::temp_dir().join(format!("monstorr-export-templates-{}",std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let dir_name = dir.display().to_string();
+
+        crate::export_templates(Some("discord"), &dir_name, false).expect("Templates should have been exported.");
+        let mut files = std::fs::read_dir(&dir).expect("Directory should have been created.").map(|entry| entry.expect("Entry should have been read.").file_name().to_string_lossy().into_owned()).collect::<Vec<String>>();
+        files.sort();
+        assert_eq!(files,vec!["blocks-template.md","discord-stat-block-template.md","feature-template.md","spans-template.md"]);
+        let stored = monstorr_data::templates::StoredTemplates::instance(None);
+        for file in &files {
+            assert_eq!(std::fs::read_to_string(dir.join(file)).ok(),stored.get(file));
+        }
+
+        // the files written before aren't replaced unless asked.
+        let error = crate::export_templates(Some("discord"), &dir_name, false).expect_err("Export should have refused to overwrite.");
+        assert!(error.contains("already exists, use the overwrite option to replace it."));
+        assert!(crate::export_templates(Some("discord"), &dir_name, true).is_ok());
+
+        // a single existing file stops the export before any of the others are written.
+        std::fs::remove_dir_all(&dir).expect("Directory should have been removed.");
+        std::fs::create_dir_all(&dir).expect("Directory should have been created.");
+        std::fs::write(dir.join("spans-template.md"),"custom").expect("File should have been written.");
+        assert!(crate::export_templates(Some("discord"), &dir_name, false).is_err());
+        let files = std::fs::read_dir(&dir).expect("Directory should have been read.").map(|entry| entry.expect("Entry should have been read.").file_name().to_string_lossy().into_owned()).collect::<Vec<String>>();
+        assert_eq!(files,vec!["spans-template.md"]);
+        assert_eq!(std::fs::read_to_string(dir.join("spans-template.md")).ok(),Some("custom".to_owned()));
+
+        std::fs::remove_dir_all(&dir).expect("Directory should have been removed.");
+
+    }
+
+    #[test]
+    fn html_card_size() {
+        let html = |card_size: Option<crate::CardSize>| crate::template::process_template(&monstorr_data::templates::StoredTemplates::instance(monstorr_data::templates::TemplateOptions::html(monstorr_data::templates::HtmlOptions { card_size, ..Default::default() },None)), monstorr_data::templates::FULL_HTML_TEMPLATE, &Vec::new(), &goblin_stat_block(), &StatBlockLabels::default()).expect("Template should have been processed.");
+        let three_by_five = html(Some(crate::CardSize::ThreeByFive));
+        assert!(three_by_five.contains("@page {\n        size: 5in 3in;\n        margin: 0.15in;\n    }"));
+        assert!(three_by_five.contains("--card-font-size: 7.5px;"));
+        assert!(html(Some(crate::CardSize::FourBySix)).contains("size: 6in 4in;"));
+        assert!(!html(None).contains("@page"));
+        // a stat block which doesn't fit isn't cut off.
+        assert!(three_by_five.contains("overflow: visible;"));
+
+        let fits = |stat_block: &CreatureStatBlock, card_size: crate::CardSize| {
+            let (line_length,capacity) = card_size.text_capacity();
+            crate::text_format::write_text(stat_block, &StatBlockLabels::default(), line_length).lines().count() <= capacity
+        };
+        assert!(fits(&goblin_stat_block(),crate::CardSize::ThreeByFive));
+        assert!(!fits(&dragon_stat_block(),crate::CardSize::FourBySix));
+
+    }
+
+    #[test]
+    fn validate_creatures_in_directory() {
+        let dir = std::env::temp_dir().join(format!("monstorr-validate-creatures-{}",std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("Directory should have been created.");
+        let dir_name = dir.display().to_string();
+
+        let goblin = crate::save_creature_commands(&goblin(), &crate::CreatureFileFormat::RON).expect("Creature should have been written.");
+        std::fs::write(dir.join("goblin.creature"), &goblin).expect("Creature file should have been written.");
+        std::fs::write(dir.join("broken.creature"), "[\n    Name(\"Broken\"),\n    NotACommand\n]").expect("Creature file should have been written.");
+        assert_eq!(crate::validate_creatures_in_directory(&dir_name),Err("1 of 2 creatures failed validation.".to_owned()));
+
+        std::fs::remove_file(dir.join("broken.creature")).expect("Creature file should have been removed.");
+        assert_eq!(crate::validate_creatures_in_directory(&dir_name),Ok(()));
+
+        std::fs::remove_dir_all(&dir).expect("Directory should have been removed.");
+
+    }
+
+    #[test]
+    fn merge_open5e_lists() {
+        let bat = r#"{
+            "slug": "bat", "name": "Bat", "size": "Tiny", "type": "beast", "subtype": "", "group": null,
+            "alignment": "unaligned", "armor_class": 12, "armor_desc": null, "hit_points": 1, "hit_dice": "1d4-1",
+            "speed": { "walk": 5, "fly": 30 },
+            "strength": 2, "dexterity": 15, "constitution": 8, "intelligence": 2, "wisdom": 12, "charisma": 4,
+            "strength_save": null, "dexterity_save": null, "constitution_save": null, "intelligence_save": null, "wisdom_save": null, "charisma_save": null,
+            "perception": null, "skills": {},
+            "damage_vulnerabilities": "", "damage_resistances": "", "damage_immunities": "", "condition_immunities": "",
+            "senses": "blindsight 60 ft., passive Perception 11", "languages": "", "challenge_rating": "0",
+            "actions": "", "reactions": "", "legendary_desc": "", "legendary_actions": "", "special_abilities": "", "spell_list": [],
+            "img_main": null, "document__slug": "wotc-srd", "document__title": "Systems Reference Document", "document__license_url": "http://open5e.com/legal"
+        }"#;
+        let creature = |slug: &str, name: &str| bat.replace(r#""slug": "bat", "name": "Bat""#,&format!(r#""slug": "{}", "name": "{}""#,slug,name));
+        let list = |next: &str, creatures: Vec<String>| format!(r#"{{ "count": {}, "next": {}, "previous": null, "results": [{}] }}"#,creatures.len(),next,creatures.join(","));
+
+        let dir = std::env::temp_dir().join(format!("monstorr-merge-open5e-{}",std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("Directory should have been created.");
+        std::fs::write(dir.join("first.json"), list(r#""https://api.open5e.com/monsters/?page=2""#,vec![creature("bat","Bat"),creature("giant-bat","Giant Bat")])).expect("List should have been written.");
+        std::fs::write(dir.join("second.json"), list("null",vec![creature("vampire-bat","Vampire Bat")])).expect("List should have been written.");
+        std::fs::write(dir.join("empty.json"), list("null",vec![])).expect("List should have been written.");
+
+        let load = |files: &[&str]| {
+            let files = files.iter().map(|file| (*file).to_owned()).collect::<Vec<String>>();
+            let list = crate::load_open5e_list(&dir, &files, false).expect("Lists should have been loaded.");
+            (list.count,list.next.clone(),list.results.iter().map(|creature| creature.name.clone()).collect::<Vec<String>>())
+        };
+
+        // merged into an existing list, the creatures are added after the ones already there, and the page links are dropped.
+        assert_eq!(load(&["first.json","second.json"]),(3,None,vec!["Bat".to_owned(),"Giant Bat".to_owned(),"Vampire Bat".to_owned()]));
+        // merged into an empty list, the creatures are all from the other list.
+        assert_eq!(load(&["empty.json","second.json"]),(1,None,vec!["Vampire Bat".to_owned()]));
+        assert_eq!(load(&["first.json","empty.json"]),(2,None,vec!["Bat".to_owned(),"Giant Bat".to_owned()]));
+
+        let mut merged = monstorr_open5e::Open5eMonsterList { count: 0, next: None, previous: None, results: Vec::new() };
+        merged.append(monstorr_open5e::Open5eMonsterList::load_from_str(&list("null",vec![creature("bat","Bat")])).map_err(|e| e.to_string()).expect("List should have loaded."));
+        assert_eq!((merged.count,merged.results.len()),(1,1));
+
+        std::fs::remove_dir_all(&dir).expect("Directory should have been removed.");
+
+    }
+
+    #[test]
+    fn companion_rendering() {
+        let stat_block = goblin_with(vec![
+            CreatureCommand::Companion("wolf".to_owned()),
+            CreatureCommand::Companion("worg".to_owned())
+        ]);
+        assert_eq!(stat_block.companions,vec!["wolf".to_owned(),"worg".to_owned()]);
+
+        let render = |template: &str, options: Option<monstorr_data::templates::TemplateOptions>| crate::template::process_template(&monstorr_data::templates::StoredTemplates::instance(options), template, &Vec::new(), &stat_block, &StatBlockLabels::default()).expect("Template should have been processed.");
+        assert!(render(monstorr_data::templates::PLAIN_TEMPLATE,None).contains("\n-------------------------------------------\nSummons: wolf, worg\n"));
+        assert!(render(monstorr_data::templates::DISCORD_TEMPLATE,None).contains("\n**Summons** wolf, worg\n"));
+        assert!(render(monstorr_data::templates::STAT_BLOCK_HTML_TEMPLATE,monstorr_data::templates::TemplateOptions::html(Default::default(),None)).contains("<p class=\"property-line\"><b>Summons</b> wolf, worg</p>"));
+        assert!(crate::text_format::write_text(&stat_block, &StatBlockLabels::default(), 80).contains("\nSummons wolf, worg\n"));
+
+        // without companions, there's no summons line.
+        assert!(!crate::text_format::write_text(&goblin_stat_block(), &StatBlockLabels::default(), 80).contains("Summons"));
+
+    }
+
+    #[test]
+    fn senses_separator_and_passive_perception_label() {
+        assert_eq!(goblin_with(vec![]).senses,"darkvision 60 ft., passive Perception 9");
+        assert_eq!(goblin_with(vec![CreatureCommand::PassivePerceptionLabel("".to_owned())]).senses,"darkvision 60 ft., 9");
+
+        let stat_block = goblin_with(vec![
+            CreatureCommand::SensesSeparator("; ".to_owned()),
+            CreatureCommand::PassivePerceptionLabel("passive Wisdom (Perception)".to_owned())
+        ]);
+        assert_eq!(stat_block.senses,"darkvision 60 ft.; passive Wisdom (Perception) 9");
+        let output = crate::template::process_template(&monstorr_data::templates::StoredTemplates::instance(None), monstorr_data::templates::DISCORD_TEMPLATE, &Vec::new(), &stat_block, &StatBlockLabels::default()).expect("Template should have been processed.");
+        assert!(output.contains("\n**Senses** darkvision 60 ft.; passive Wisdom (Perception) 9\n"));
+
+    }
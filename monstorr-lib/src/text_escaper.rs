@@ -6,6 +6,7 @@ use crate::stat_block::StatBlockLairActions;
 use crate::stat_block::StatBlockLegendary;
 use crate::stat_block::StatBlockRegionalEffects;
 use crate::stat_block::CreatureStatBlock;
+use crate::stat_block::StatBlockLabels;
 
 pub trait TextEscaper {
 
@@ -118,7 +119,8 @@ impl Escapable for StatBlockFeature {
 
     fn escape<Escaper: TextEscaper>(&self, escaper: &Escaper) -> Self {
         Self {
-            text: self.text.escape(escaper)
+            text: self.text.escape(escaper),
+            trigger: self.trigger.escape(escaper)
         }
     }
 
@@ -164,11 +166,17 @@ impl Escapable for CreatureStatBlock {
 
         Self {
             name: self.name.escape(escaper),
+            display_name: self.display_name.escape(escaper),
             actions: self.actions.escape(escaper),
             alignment: self.alignment.escape(escaper),
             armor: self.armor.escape(escaper),
+            initiative: self.initiative.escape(escaper),
             challenge_rating: self.challenge_rating.escape(escaper),
+            proficiency_bonus: self.proficiency_bonus.escape(escaper),
             charisma: self.charisma.escape(escaper),
+            companions: self.companions.escape(escaper),
+            habitat: self.habitat.escape(escaper),
+            treasure: self.treasure.escape(escaper),
             condition_immunities: self.condition_immunities.escape(escaper),
             constitution: self.constitution.escape(escaper),
             damage_immunities: self.damage_immunities.escape(escaper),
@@ -194,12 +202,54 @@ impl Escapable for CreatureStatBlock {
             subtype: self.subtype.escape(escaper),
             type_: self.type_.escape(escaper),
             wisdom: self.wisdom.escape(escaper),
+            // these are not meant for templates, so they are passed on as-is
+            extra: self.extra.clone(),
+            minion: self.minion,
+            notes: self.notes.clone(),
+            diagnostics: self.diagnostics.clone(),
         }
     }
 
 
 }
 
+impl Escapable for StatBlockLabels {
+
+    fn escape<Escaper: TextEscaper>(&self, escaper: &Escaper) -> Self {
+        Self {
+            armor_class: self.armor_class.escape(escaper),
+            initiative: self.initiative.escape(escaper),
+            hit_points: self.hit_points.escape(escaper),
+            speed: self.speed.escape(escaper),
+            strength: self.strength.escape(escaper),
+            dexterity: self.dexterity.escape(escaper),
+            constitution: self.constitution.escape(escaper),
+            intelligence: self.intelligence.escape(escaper),
+            wisdom: self.wisdom.escape(escaper),
+            charisma: self.charisma.escape(escaper),
+            saving_throws: self.saving_throws.escape(escaper),
+            skills: self.skills.escape(escaper),
+            damage_vulnerabilities: self.damage_vulnerabilities.escape(escaper),
+            damage_resistances: self.damage_resistances.escape(escaper),
+            damage_immunities: self.damage_immunities.escape(escaper),
+            condition_immunities: self.condition_immunities.escape(escaper),
+            senses: self.senses.escape(escaper),
+            languages: self.languages.escape(escaper),
+            challenge: self.challenge.escape(escaper),
+            proficiency_bonus: self.proficiency_bonus.escape(escaper),
+            actions: self.actions.escape(escaper),
+            reactions: self.reactions.escape(escaper),
+            legendary_actions: self.legendary_actions.escape(escaper),
+            lair_actions: self.lair_actions.escape(escaper),
+            regional_effects: self.regional_effects.escape(escaper),
+            summons: self.summons.escape(escaper),
+            habitat: self.habitat.escape(escaper),
+            treasure: self.treasure.escape(escaper),
+            source: self.source.escape(escaper)
+        }
+    }
+}
+
 pub fn escape_latex(ch: char) -> Option<&'static str> {
     match ch {
         '&' => Some("\\&"),
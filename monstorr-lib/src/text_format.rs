@@ -0,0 +1,211 @@
+/*
+ * Copyright © 2022 Neil M. Sheldon
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+/*!
+Writes a stat block as plain text, wrapped to a fixed width, for pasting into forums and the chat of virtual tabletops. Unlike the plain-text templates, this walks the stat block directly, so the lines can be wrapped and the ability scores lined up in columns.
+
+The styles of the structured text, such as the bold and italic headings, are dropped, leaving only the words. Paragraphs are separated by blank lines, and sub-paragraphs, such as legendary actions, are indented with a hanging indent. The labels come from the [`crate::stat_block::StatBlockLabels`], the same as for the templates.
+*/
+
+use crate::stat_block::CreatureStatBlock;
+use crate::stat_block::StatBlockFeature;
+use crate::stat_block::StatBlockLabels;
+use crate::structured_text::TextBlock;
+use crate::structured_text::block_to_text;
+
+// the ability scores are lined up in columns of this width, which fits "30 (+10)" with a space after.
+const ABILITY_COLUMN_WIDTH: usize = 9;
+
+// sub-paragraphs and the continuation of property lines are indented by this much.
+const INDENT: usize = 2;
+
+/**
+Breaks the text into lines no longer than the width, at spaces. The first line is indented by `first_indent` and the rest by `indent`. A word longer than the width is put on a line of its own, rather than being broken.
+*/
+fn wrap(text: &str, width: usize, first_indent: usize, indent: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = " ".repeat(first_indent);
+    let mut line_is_empty = true;
+    for word in text.split_whitespace() {
+        let length = line.chars().count();
+        if !line_is_empty && (length + 1 + word.chars().count() > width) {
+            lines.push(line);
+            line = " ".repeat(indent);
+            line_is_empty = true;
+        }
+        if !line_is_empty {
+            line.push(' ');
+        }
+        line.push_str(word);
+        line_is_empty = false;
+    }
+    if !line_is_empty {
+        lines.push(line)
+    }
+    lines
+}
+
+struct TextWriter {
+    width: usize,
+    lines: Vec<String>
+}
+
+impl TextWriter {
+
+    fn blank(&mut self) {
+        if !matches!(self.lines.last(), Some(line) if line.is_empty()) {
+            self.lines.push(String::new())
+        }
+    }
+
+    fn rule(&mut self, ch: char) {
+        self.lines.push(ch.to_string().repeat(self.width))
+    }
+
+    fn heading(&mut self, label: &str) {
+        self.blank();
+        self.lines.push(label.to_uppercase());
+        self.lines.push("-".repeat(label.chars().count()));
+    }
+
+    fn paragraph(&mut self, text: &str) {
+        let mut lines = wrap(text, self.width, 0, 0);
+        self.lines.append(&mut lines)
+    }
+
+    fn property(&mut self, label: &str, value: &str) {
+        let mut lines = wrap(&format!("{} {}",label,value), self.width, 0, INDENT);
+        self.lines.append(&mut lines)
+    }
+
+    fn optional_property(&mut self, label: &str, value: &Option<String>) {
+        if let Some(value) = value {
+            self.property(label, value)
+        }
+    }
+
+    fn blocks(&mut self, blocks: &[TextBlock]) {
+        for block in blocks {
+            match block {
+                TextBlock::Paragraph { .. } => {
+                    self.blank();
+                    self.paragraph(&block_to_text(block))
+                },
+                TextBlock::SubParagraph { .. } => {
+                    let mut lines = wrap(&block_to_text(block), self.width, INDENT, INDENT * 2);
+                    self.lines.append(&mut lines)
+                }
+            }
+        }
+    }
+
+    fn features(&mut self, features: &[StatBlockFeature]) {
+        for feature in features {
+            self.blocks(&feature.text)
+        }
+    }
+
+    fn abilities(&mut self, labels: &StatBlockLabels, stat_block: &CreatureStatBlock) {
+        let columns = [
+            (&labels.strength,&stat_block.strength),
+            (&labels.dexterity,&stat_block.dexterity),
+            (&labels.constitution,&stat_block.constitution),
+            (&labels.intelligence,&stat_block.intelligence),
+            (&labels.wisdom,&stat_block.wisdom),
+            (&labels.charisma,&stat_block.charisma)
+        ];
+        let row = |cells: Vec<&String>| cells.iter().map(|cell| format!("{:<width$}",cell,width = ABILITY_COLUMN_WIDTH)).collect::<String>().trim_end().to_owned();
+        self.lines.push(row(columns.iter().map(|(label,_)| *label).collect()));
+        self.lines.push(row(columns.iter().map(|(_,score)| *score).collect()));
+    }
+
+}
+
+/**
+Writes the stat block as plain text, wrapped to the width given in characters. Eighty characters is a good width for most forums and terminals.
+*/
+pub fn write_text(stat_block: &CreatureStatBlock, labels: &StatBlockLabels, width: usize) -> String {
+    let mut writer = TextWriter {
+        width,
+        lines: Vec::new()
+    };
+
+    writer.rule('=');
+    writer.paragraph(stat_block.display_name.as_ref().unwrap_or(&stat_block.name));
+    let subtype = stat_block.subtype.as_ref().map(|subtype| format!(" ({})",subtype)).unwrap_or_default();
+    writer.paragraph(&format!("{} {}{}, {}",stat_block.size,stat_block.type_,subtype,stat_block.alignment));
+    writer.rule('=');
+
+    writer.blank();
+    writer.property(&labels.armor_class, &stat_block.armor);
+    writer.optional_property(&labels.initiative, &stat_block.initiative);
+    writer.property(&labels.hit_points, &stat_block.hit_points);
+    writer.property(&labels.speed, &stat_block.speed);
+
+    writer.blank();
+    writer.abilities(labels, stat_block);
+
+    writer.blank();
+    writer.optional_property(&labels.saving_throws, &stat_block.saving_throws);
+    writer.optional_property(&labels.skills, &stat_block.skills);
+    writer.optional_property(&labels.damage_vulnerabilities, &stat_block.damage_vulnerabilities);
+    writer.optional_property(&labels.damage_resistances, &stat_block.damage_resistances);
+    writer.optional_property(&labels.damage_immunities, &stat_block.damage_immunities);
+    writer.optional_property(&labels.condition_immunities, &stat_block.condition_immunities);
+    writer.property(&labels.senses, &stat_block.senses);
+    writer.property(&labels.languages, stat_block.languages.as_deref().unwrap_or("—"));
+    writer.property(&labels.challenge, &stat_block.challenge_rating);
+    writer.optional_property(&labels.proficiency_bonus, &stat_block.proficiency_bonus);
+
+    writer.features(&stat_block.special_abilities);
+
+    if !stat_block.actions.is_empty() {
+        writer.heading(&labels.actions);
+        writer.features(&stat_block.actions);
+    }
+
+    if !stat_block.reactions.is_empty() {
+        writer.heading(&labels.reactions);
+        writer.features(&stat_block.reactions);
+    }
+
+    if let Some(legendary_actions) = &stat_block.legendary_actions {
+        writer.heading(&labels.legendary_actions);
+        writer.blocks(&legendary_actions.description);
+        writer.features(&legendary_actions.actions);
+    }
+
+    if let Some(lair_actions) = &stat_block.lair_actions {
+        writer.heading(&labels.lair_actions);
+        writer.blocks(&lair_actions.foreword);
+        writer.features(&lair_actions.actions);
+        writer.blocks(&lair_actions.afterword);
+    }
+
+    if let Some(regional_effects) = &stat_block.regional_effects {
+        writer.heading(&labels.regional_effects);
+        writer.blocks(&regional_effects.foreword);
+        writer.features(&regional_effects.effects);
+        writer.blocks(&regional_effects.afterword);
+    }
+
+    if !stat_block.companions.is_empty() || stat_block.habitat.is_some() || stat_block.treasure.is_some() || stat_block.source.is_some() {
+        writer.blank();
+        writer.rule('-');
+        if !stat_block.companions.is_empty() {
+            writer.property(&labels.summons, &stat_block.companions.join(", "));
+        }
+        writer.optional_property(&labels.habitat, &stat_block.habitat);
+        writer.optional_property(&labels.treasure, &stat_block.treasure);
+        writer.optional_property(&labels.source, &stat_block.source);
+    }
+
+    let mut result = writer.lines.join("\n");
+    result.push('\n');
+    result
+}
@@ -64,10 +64,24 @@ pub enum Token<Keyword> {
     slash-greater-than = '/' '>'
     */
     SlashGreaterThan, // ceiling division
+    /*
+    comparison = '==' | '!=' | '<' | '<=' | '>' | '>='
+    */
+    EqualEqual,
+    NotEqual,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+    /*
+    question-question = '?' '?'
+    */
+    QuestionQuestion, // default
     OpenParenthesis,
     CloseParenthesis,
     Dot,
-    Dollar
+    Dollar,
+    Comma
 
 }
 
@@ -249,4 +249,36 @@ pub fn to_kebab_case(source: &str) -> String {
 
     source.split(' ').map(|a| a.to_lowercase()).collect::<Vec<String>>().join("-")
 
-}
\ No newline at end of file
+}
+
+pub fn to_title_case(source: &str) -> String {
+
+    source.split(' ').map(|a| a.to_owned().capitalize_first_letter()).collect::<Vec<String>>().join(" ")
+
+}
+
+// A small pseudo-random number generator (SplitMix64). This is used where results need to be reproducible
+// from a seed, so it's better to have the algorithm here than to depend on a crate which might change its
+// output in a future version. It is not suitable for anything requiring security.
+pub struct SeededRandom(u64);
+
+impl SeededRandom {
+
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    // returns a number from 0 up to, but not including, the bound. The bound must not be 0.
+    pub fn next_below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+}
@@ -17,7 +17,10 @@ use serde::de::Error as SerdeError;
 use serde::Deserialize;
 use serde::Deserializer;
 use serde::Serialize;
+use serde::de::DeserializeOwned;
 use serde_json;
+use serde_json::Map;
+use serde_json::Value;
 
 #[derive(Serialize,Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -272,6 +275,28 @@ pub struct Open5eMonster {
     pub document_license_url: String
 }
 
+// Reads an object one field at a time, starting from the defaults and replacing each field that can be read from the source. A field can be read if the defaults still load with just that field replaced, so a field with a typo in its name or a value of the wrong type is reported and left out, instead of failing the whole object.
+fn read_fields_leniently<Data: DeserializeOwned>(source: Map<String,Value>, defaults: &Map<String,Value>, prefix: &str, diagnostics: &mut Vec<String>) -> Map<String,Value> {
+    let mut result = defaults.clone();
+    for (key,default) in defaults {
+        // fields which default to null are optional anyway.
+        if !default.is_null() && !source.contains_key(key) {
+            diagnostics.push(format!("field '{}{}' is missing",prefix,key))
+        }
+    }
+    for (key,value) in source {
+        let mut candidate = defaults.clone();
+        candidate.insert(key.clone(),value.clone());
+        match Data::deserialize(&Value::Object(candidate)) {
+            Ok(_) => {
+                result.insert(key,value);
+            },
+            Err(err) => diagnostics.push(format!("field '{}{}' was ignored: {}",prefix,key,err))
+        }
+    }
+    result
+}
+
 impl Open5eMonster {
 
     pub fn load_from_str(source: &str) -> Result<Self,Open5eError> {
@@ -281,6 +306,89 @@ impl Open5eMonster {
         }
     }
 
+    /**
+    Loads a monster the same way as `load_from_str`, but if the data doesn't match the expected fields, reads as much as it can instead of failing. Unknown fields and fields that can't be read are left out, and missing fields are filled in with blank values. A message describing each problem is returned along with the monster.
+    */
+    pub fn load_from_str_lenient(source: &str) -> Result<(Self,Vec<String>),Open5eError> {
+        let source: Value = serde_json::from_str(source).map_err(Open5eError::DeserializationError)?;
+        Self::load_from_value_lenient(source).map_err(Open5eError::DeserializationError)
+    }
+
+    // the values used for fields which are missing or can't be read when loading leniently.
+    fn lenient_defaults() -> Map<String,Value> {
+        let text = || Value::from("");
+        let score = || Value::from(10);
+        let list = || Value::Array(Vec::new());
+        let object = || Value::Object(Map::new());
+        vec![
+            ("slug",text()),
+            ("name",text()),
+            ("size",text()),
+            ("type",text()),
+            ("subtype",text()),
+            ("group",Value::Null),
+            ("alignment",text()),
+            ("armor_class",score()),
+            ("armor_desc",Value::Null),
+            ("hit_points",Value::from(0)),
+            ("hit_dice",text()),
+            ("speed",object()),
+            ("strength",score()),
+            ("dexterity",score()),
+            ("constitution",score()),
+            ("intelligence",score()),
+            ("wisdom",score()),
+            ("charisma",score()),
+            ("strength_save",Value::Null),
+            ("dexterity_save",Value::Null),
+            ("constitution_save",Value::Null),
+            ("intelligence_save",Value::Null),
+            ("wisdom_save",Value::Null),
+            ("charisma_save",Value::Null),
+            ("perception",Value::Null),
+            ("skills",object()),
+            ("damage_vulnerabilities",text()),
+            ("damage_resistances",text()),
+            ("damage_immunities",text()),
+            ("condition_immunities",text()),
+            ("senses",text()),
+            ("languages",text()),
+            ("challenge_rating",Value::from("0")),
+            ("actions",list()),
+            ("reactions",list()),
+            ("legendary_desc",text()),
+            ("legendary_actions",list()),
+            ("special_abilities",list()),
+            ("spell_list",list()),
+            ("img_main",Value::Null),
+            ("document__slug",text()),
+            ("document__title",text()),
+            ("document__license_url",text())
+        ].into_iter().map(|(key,value)| (key.to_owned(),value)).collect()
+    }
+
+    fn load_from_value_lenient(source: Value) -> Result<(Self,Vec<String>),serde_json::Error> {
+        if let Ok(monster) = Self::deserialize(&source) {
+            return Ok((monster,Vec::new()))
+        }
+
+        let mut source = match source {
+            Value::Object(source) => source,
+            _ => Err(serde_json::Error::custom("expected a monster object"))?
+        };
+
+        let mut diagnostics = Vec::new();
+        // the speed and skills also don't allow unknown fields, so read those a field at a time as well, rather than losing all of them.
+        if let Some(Value::Object(speed)) = source.remove("speed") {
+            source.insert("speed".to_owned(),Value::Object(read_fields_leniently::<Open5eMonsterSpeed>(speed, &Map::new(), "speed.", &mut diagnostics)));
+        }
+        if let Some(Value::Object(skills)) = source.remove("skills") {
+            source.insert("skills".to_owned(),Value::Object(read_fields_leniently::<Open5eMonsterSkills>(skills, &Map::new(), "skills.", &mut diagnostics)));
+        }
+        let result = read_fields_leniently::<Self>(source, &Self::lenient_defaults(), "", &mut diagnostics);
+        Ok((Self::deserialize(&Value::Object(result))?,diagnostics))
+    }
+
 }
 
 #[derive(Serialize,Deserialize)]
@@ -333,6 +441,42 @@ impl Open5eMonsterList {
         }
     }
 
+    /**
+    Loads a list the same way as `load_from_str`, but reads each creature with `Open5eMonster::load_from_str_lenient`, so one creature with unexpected data doesn't prevent the rest of the list from loading. Creatures which can't be read at all are skipped. The returned messages name the creature each problem was found in.
+    */
+    pub fn load_from_str_lenient(source: &str) -> Result<(Self,Vec<String>),Open5eError> {
+        let source: Value = serde_json::from_str(source).map_err(Open5eError::DeserializationError)?;
+        if let Ok(list) = Self::deserialize(&source) {
+            return Ok((list,Vec::new()))
+        }
+
+        let results = match source.get("results") {
+            Some(Value::Array(results)) => results,
+            _ => Err(Open5eError::DeserializationError(serde_json::Error::custom("expected a list of results")))?
+        };
+
+        let mut diagnostics = Vec::new();
+        let mut list = Open5eMonsterList {
+            count: 0,
+            next: source.get("next").and_then(Value::as_str).map(str::to_owned),
+            previous: source.get("previous").and_then(Value::as_str).map(str::to_owned),
+            results: Vec::new()
+        };
+        for (index,creature) in results.iter().enumerate() {
+            // the slug is the most useful name for finding it in the data, but there might not be one.
+            let name = creature.get("slug").or_else(|| creature.get("name")).and_then(Value::as_str).map(str::to_owned).unwrap_or_else(|| format!("#{}",index + 1));
+            match Open5eMonster::load_from_value_lenient(creature.clone()) {
+                Ok((monster,messages)) => {
+                    diagnostics.extend(messages.into_iter().map(|message| format!("Open5e creature '{}': {}",name,message)));
+                    list.results.push(monster)
+                },
+                Err(err) => diagnostics.push(format!("Open5e creature '{}' was skipped: {}",name,err))
+            }
+        }
+        list.count = list.results.len();
+        Ok((list,diagnostics))
+    }
+
     pub fn load<Source: std::io::Read>(data: Source) -> Result<Self,Open5eError> {
         match serde_json::from_reader(data) {
             Ok(data) => Ok(data),
@@ -360,5 +504,15 @@ impl Open5eMonsterList {
 
     }
 
+    /**
+    Adds the creatures from another list to the end of this one, for data which has been split into several files. The merged list is no longer a single page of results, so the links to the next and previous pages are dropped.
+    */
+    pub fn append(&mut self, other: Open5eMonsterList) {
+        self.results.extend(other.results);
+        self.count = self.results.len();
+        self.next = None;
+        self.previous = None;
+    }
+
 }
 
@@ -16,6 +16,13 @@ For more detailed documentation, including information on how to build a creatur
 */
 
 use std::process;
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::collections::HashMap;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 use clap::Parser;
 use clap::ArgEnum;
@@ -24,13 +31,30 @@ use clap::Args;
 use monstorr_lib::create_stat_block;
 use monstorr_lib::list_template_names;
 use monstorr_lib::print_template;
+use monstorr_lib::export_templates;
 use monstorr_lib::list_creatures;
+use monstorr_lib::generate_random_encounter;
 use monstorr_lib::validate_creature;
+use monstorr_lib::list_creature_properties;
+use monstorr_lib::preprocess_creature;
+use monstorr_lib::get_input_files;
 use monstorr_lib::generate_creatures_as_rust_array;
+use monstorr_lib::validate_creatures_in_directory;
+use monstorr_lib::create_spell_index;
+use monstorr_lib::create_stat_blocks_in_directory;
+use monstorr_lib::write_stat_block_schema;
 use monstorr_lib::MONSTORR_VERSION;
 use monstorr_lib::InputFormat as MonstorrInputFormat;
 use monstorr_lib::OutputFormat as MonstorrOutputFormat;
 use monstorr_lib::ListInputFormat as MonstorrListInputFormat;
+use monstorr_lib::EncounterDifficulty as MonstorrEncounterDifficulty;
+use monstorr_lib::CardSize as MonstorrCardSize;
+use monstorr_lib::HtmlOptions as MonstorrHtmlOptions;
+use monstorr_lib::StatBlockStyle as MonstorrStatBlockStyle;
+use monstorr_lib::SpellIndexFormat as MonstorrSpellIndexFormat;
+use monstorr_lib::CreatureFileFormat as MonstorrCreatureFileFormat;
+use monstorr_lib::WarningLevel as MonstorrWarningLevel;
+use monstorr_lib::StatBlockOptions as MonstorrStatBlockOptions;
 
 
 // this is different from the one in monstorr-lib because its redefined to work as an ArgEnum
@@ -40,10 +64,18 @@ use monstorr_lib::ListInputFormat as MonstorrListInputFormat;
 enum InputFormat {
     /// A file containing creature commands for building a creature stat-block with automatic calculations
     Creature,
+    /// A creature file written in TOML instead of RON (requires monstorr to be built with the 'toml-format' feature)
+    TomlCreature,
+    /// A creature file written in the terse, line-based flat format
+    FlatCreature,
     /// A single-creature JSON file in the format used by Open5e
     Open5e,
+    /// A single-creature JSON file in the format used by Open5e, ignoring fields monstorr doesn't know about
+    LenientOpen5e,
     /// A JSON list in the format used by Open5e
     Open5eList,
+    /// A JSON list in the format used by Open5e, reading what it can from creatures with unexpected data
+    LenientOpen5eList,
     /// One of a few creatures stored in this program
     Stored
 }
@@ -53,11 +85,13 @@ enum InputFormat {
 enum ListInputFormat {
     /// A JSON list in the format used by Open5e
     Open5eList,
+    /// A JSON list in the format used by Open5e, reading what it can from creatures with unexpected data
+    LenientOpen5eList,
     /// The list of creatures stored in this program
     Stored
 }
 
-#[derive(Args)]
+#[derive(Args,Clone)]
 /// A central structure for input data, since the same by so many commands. Note that output format is not specified, as that's part of the command.
 struct InputOutputData {
     #[clap(short,long,arg_enum,default_value_t=InputFormat::Creature)]
@@ -73,18 +107,92 @@ struct InputOutputData {
     #[clap(value_name="FILENAME")]
     /// output file, if not specified will write to stdout.
     output: Option<String>,
+    #[clap(long)]
+    /// report warnings for commands which may be mistakes, such as a remove command like RemoveAction that does not find anything to remove. Warnings about the finished creature, such as a suspicious multiattack or two actions or features with the same name, are always reported.
+    warn_noop: bool,
+    #[clap(long)]
+    /// report the same warnings as --warn-noop, and the warnings about the finished creature, as errors, so the creature isn't built.
+    strict: bool,
+    #[clap(long)]
+    /// also warn when a statistic with a single value, such as the size, alignment, armor or an ability score, is set more than once, including in included files, as with the LintOverrides command. This implies --warn-noop.
+    lint: bool,
+    #[clap(long,arg_enum,default_value_t=StatBlockStyle::Classic)]
+    /// the edition of the core books whose style the stat-block should follow, such as in the capitalization of the creature type and alignment
+    style: StatBlockStyle,
+    #[clap(long)]
+    /// include notes added with the Note command in the stat-block, these are left out by default.
+    with_notes: bool,
+    #[clap(long,value_name="DIRECTORY")]
+    /// the directory that included files are found relative to, instead of the directory containing the input file. This is needed for includes when reading from stdin.
+    base_dir: Option<String>,
+    #[clap(long,value_name="FILENAME")]
+    /// a language file replacing the fixed labels in the stat-block, such as "Armor Class", for tables in other languages. The labels are in English if not specified.
+    lang: Option<String>,
+    #[clap(long="param",value_name="NAME=VALUE")]
+    /// a parameter made available as an interpolation variable, both to the creature file itself (as with the Include command) and to its descriptions (may be specified multiple times)
+    params: Vec<String>,
+    #[clap(long,value_name="FILENAME")]
+    /// another list file, such as open5e-list, whose creatures are merged with those in the input file before picking the creature (may be specified multiple times)
+    merge: Vec<String>,
 
 }
 
 impl InputOutputData {
 
+    /// Converts the 'style' argument to a [`monstorr_lib::StatBlockStyle`].
+    fn stat_block_style(&self) -> MonstorrStatBlockStyle {
+        match self.style {
+            StatBlockStyle::Classic => MonstorrStatBlockStyle::Classic,
+            StatBlockStyle::Modern => MonstorrStatBlockStyle::Modern
+        }
+    }
+
+    /// Converts the 'warn-noop', 'lint' and 'strict' arguments to a [`monstorr_lib::WarningLevel`].
+    fn warning_level(&self) -> MonstorrWarningLevel {
+        if self.strict {
+            MonstorrWarningLevel::Error
+        } else if self.warn_noop || self.lint {
+            MonstorrWarningLevel::Warn
+        } else {
+            MonstorrWarningLevel::Ignore
+        }
+    }
+
+    /// Parses the 'param' arguments into a map of names to values.
+    fn parameters(&self) -> Result<HashMap<String,String>,String> {
+        parse_parameters(&self.params)
+    }
+
+    /// Collects the arguments for building the creature and its stat block into a [`monstorr_lib::StatBlockOptions`].
+    fn stat_block_options(&self) -> Result<MonstorrStatBlockOptions,String> {
+        Ok(MonstorrStatBlockOptions {
+            style: self.stat_block_style(),
+            warnings: self.warning_level(),
+            lint: self.lint,
+            with_notes: self.with_notes,
+            parameters: self.parameters()?,
+            base_dir: self.base_dir.clone(),
+            lang_file: self.lang.clone()
+        })
+    }
+
     /// Converts to a [`monstorr_lib::InputFormat`] and Option<String> from the I/O arguments.
     fn into_monstorr_input_output(self) -> Result<(MonstorrInputFormat,Option<String>),String> {
         Ok(match self.format {
             InputFormat::Creature => (MonstorrInputFormat::Creature(self.input),self.output),
+            InputFormat::TomlCreature => (MonstorrInputFormat::TOMLCreature(self.input),self.output),
+            InputFormat::FlatCreature => (MonstorrInputFormat::FlatCreature(self.input),self.output),
             InputFormat::Open5e => (MonstorrInputFormat::Open5e(self.input),self.output),
+            InputFormat::LenientOpen5e => (MonstorrInputFormat::LenientOpen5e(self.input),self.output),
             InputFormat::Open5eList => if let Some(creature) = self.creature {
-                (MonstorrInputFormat::Open5eList(self.input,creature),self.output)
+                let input_files = self.input.into_iter().chain(self.merge).collect();
+                (MonstorrInputFormat::Open5eList(input_files,creature),self.output)
+            } else {
+                Err("Please specify a creature name to process.")?
+            },
+            InputFormat::LenientOpen5eList => if let Some(creature) = self.creature {
+                let input_files = self.input.into_iter().chain(self.merge).collect();
+                (MonstorrInputFormat::LenientOpen5eList(input_files,creature),self.output)
             } else {
                 Err("Please specify a creature name to process.")?
             },
@@ -101,6 +209,99 @@ impl InputOutputData {
 
 }
 
+/// Parses 'param' arguments into a map of names to values.
+fn parse_parameters(params: &[String]) -> Result<HashMap<String,String>,String> {
+    let mut result = HashMap::new();
+    for param in params {
+        if let Some((name,value)) = param.split_once('=') {
+            result.insert(name.trim().to_owned(), value.to_owned());
+        } else {
+            Err(format!("Parameter '{}' should be in the form NAME=VALUE.",param))?
+        }
+    }
+    Ok(result)
+}
+
+#[derive(ArgEnum,Clone)]
+/// Represents the target difficulty argument for the Encounter command
+enum EncounterDifficulty {
+    Easy,
+    Medium,
+    Hard,
+    Deadly
+}
+
+#[derive(ArgEnum,Clone)]
+/// Represents the format argument for the SpellIndex command
+enum SpellIndexFormat {
+    /// One line per spell, with the number of creatures which use it
+    Text,
+    /// A bulleted list for including in a document
+    Markdown,
+    /// An object mapping each spell to the creatures which use it
+    JSON
+}
+
+#[derive(ArgEnum,Clone)]
+/// Represents the format argument for the Batch command
+enum BatchFormat {
+    /// Stat-block data in JSON format, written to '.json' files
+    JSON,
+    /// Stat-blocks in HTML, written to '.html' files
+    HTML,
+    /// Stat-blocks in LaTeX, written to '.tex' files
+    #[clap(name="latex")]
+    LaTeX,
+    /// Stat-blocks in plain text, written to '.txt' files
+    Plain,
+    /// Stat-blocks in plain text wrapped at 80 columns, written to '.txt' files
+    Text,
+    /// Stat-blocks in Discord markdown, written to '.md' files
+    Discord
+}
+
+#[derive(ArgEnum,Clone)]
+/// Represents the format argument for commands which write creature files
+enum CreatureFileFormat {
+    /// The usual creature file format
+    RON,
+    /// TOML (requires monstorr to be built with the 'toml-format' feature)
+    TOML
+}
+
+impl CreatureFileFormat {
+
+    /// Converts to a [`monstorr_lib::CreatureFileFormat`].
+    fn into_monstorr(self) -> MonstorrCreatureFileFormat {
+        match self {
+            CreatureFileFormat::RON => MonstorrCreatureFileFormat::RON,
+            CreatureFileFormat::TOML => MonstorrCreatureFileFormat::TOML
+        }
+    }
+}
+
+#[derive(ArgEnum,Clone)]
+/// Represents the style argument for commands which produce stat blocks
+enum StatBlockStyle {
+    /// The style of the 2014 core books, with the creature type and alignment in lower case
+    #[clap(name="2014")]
+    Classic,
+    /// The style of the 2024 core books, with the creature type and alignment capitalized
+    #[clap(name="2024")]
+    Modern
+}
+
+#[derive(ArgEnum,Clone)]
+/// Represents the index card size argument for the HTML command
+enum CardSize {
+    /// A 3 by 5 inch index card
+    #[clap(name="3x5")]
+    ThreeByFive,
+    /// A 4 by 6 inch index card
+    #[clap(name="4x6")]
+    FourBySix
+}
+
 #[derive(ArgEnum,Clone)]
 /// Represents an argument requiring a class of templates for the ListTemplates command.
 enum TemplateClass {
@@ -109,7 +310,9 @@ enum TemplateClass {
     /// Templates (in MiniJinja syntax) used in producing LaTeX
     LATEX,
     /// Templates (in MiniJinja syntax) used in producing plain-text
-    Plain
+    Plain,
+    /// Templates (in MiniJinja syntax) used in producing Discord markdown
+    Discord
 }
 
 impl TemplateClass {
@@ -118,7 +321,8 @@ impl TemplateClass {
         match self {
             TemplateClass::HTML => "html",
             TemplateClass::LATEX => "latex",
-            TemplateClass::Plain => "plain"
+            TemplateClass::Plain => "plain",
+            TemplateClass::Discord => "discord"
         }
     }
 }
@@ -140,10 +344,18 @@ Monstorr recognizes a few formats for the input data for generating stat blocks.
 
 * `creature`: This is essentially a list of commands for designing the creature, assuming defaults for everything not added. The syntax for this file format is documented in this tool's code documentation. I hope to have a better link to this later.
 
-* `open5e-list`: This is the closest thing I could find to a standard format. This is a JSON format returned by queries to the monster database at [Open5e.com](https://open5e.com/monsters/monster-list). When generating stat-blocks from this format, a creature name is required. This format can also be queried using `list-creatures`. The stat-blocks generated from this list will not be formatted as nicely as with the `creature` format. Monstorr currently does not parse the Markdown text used in feature descriptions, calculations are not validated, and there are typos and errors in some of the creatures from that database.
+* `toml-creature`: The same as `creature`, but written in TOML for tools that prefer it. This requires monstorr to be built with the 'toml-format' feature. The `validate` command with `--to toml` will convert a creature file to this format.
+
+* `flat-creature`: A terse way of writing a creature file, with one command per line written as plain words, such as `str 16`, `neutral evil` or `weapon longsword +1`. Any line starting with a capital letter is read as a command in the `creature` syntax. Flat creature files can't be written back out, use the `creature` format for anything that needs to be saved.
+
+* `open5e-list`: This is the closest thing I could find to a standard format. This is a JSON format returned by queries to the monster database at [Open5e.com](https://open5e.com/monsters/monster-list). When generating stat-blocks from this format, a creature name is required. This format can also be queried using `list-creatures`. Data split into several files can be merged into one list, by giving several input files to `list-creatures` and `encounter`, or with `--merge` when generating stat-blocks. The stat-blocks generated from this list will not be formatted as nicely as with the `creature` format. Monstorr currently does not parse the Markdown text used in feature descriptions, calculations are not validated, and there are typos and errors in some of the creatures from that database.
+
+* `lenient-open5e-list`: The same as `open5e-list`, but creatures whose data doesn't match what monstorr expects, such as with misspelled or missing fields, are read as well as possible instead of stopping with an error. Each problem is printed as a warning naming the creature, and fields that can't be read are left blank. Creatures that can't be read at all are skipped.
 
 * `open5e`: This is simply a single creature block extracted from an `open5e-list` file, as a stand-alone JSON file.
 
+* `lenient-open5e`: The same as `open5e`, but read in the same way as `lenient-open5e-list`. This is useful when Open5e adds new fields to its data which monstorr doesn't know about yet, as they are ignored with a warning instead of stopping with an error.
+
 * `stored`: Monstorr contains a few pre-built creatures converted from D&D Fifth Edition System Reference Document* content. Currently, only four creatures are contained in the executable, as conversion of the creatures is a busy work task that would take more time than I want to spend. It is a dream that the entire SRD list of monsters be available in Monstorr, but it is likely that this will not happen before a hypothetical sixth edition happens.
 
 (I apologize for the unusual formatting of this text, the tools used to generate it do not yet support structured and formatted text in this part, so it is written in raw Markdown format.)
@@ -154,13 +366,25 @@ enum Command {
     /** 
     Generate a stat block in JSON format.
 
-    The structure for the stat-block JSON is documented in this tool's code documentation. I hope to have a better link to this later.
+    The structure for the stat-block JSON is documented in this tool's code documentation, and a JSON Schema for it can be written with the schema command. A compact version, with only the name, display name, size, type, subtype, alignment, armor, hit points, speed, ability scores and challenge rating, can be produced instead for tools which don't need the descriptions.
     */
     JSON {
     
         #[clap(long)]
         /// turns off pretty-printing of JSON text.
         ugly: bool,
+
+        #[clap(long)]
+        /// adds diagnostics for the people editing the creature, such as a 0 to 100 estimate of how completely it has been specified and the effective hit points after resistances, under the 'diagnostics' property. These are only available for creature files.
+        with_diagnostics: bool,
+
+        #[clap(long,conflicts_with="with_diagnostics")]
+        /// only the essential properties are written, leaving out the features, actions and other descriptions. The properties have the same names as in the full stat-block.
+        compact: bool,
+
+        #[clap(long)]
+        /// keeps running, and writes the output again whenever the input file, or a file it includes, changes. Stop it with Ctrl+C.
+        watch: bool,
     
         #[clap(flatten)]
         input_output: InputOutputData
@@ -183,6 +407,10 @@ enum Command {
         #[clap(short,long,value_name="FILENAME")]
         /// additional template file required by the main template (may be specified multiple times)
         include: Vec<String>,
+
+        #[clap(long)]
+        /// keeps running, and writes the output again whenever the input file, a file it includes, or one of the template files, changes. Stop it with Ctrl+C.
+        watch: bool,
     
         #[clap(flatten)]
         input_output: InputOutputData
@@ -193,9 +421,9 @@ enum Command {
     /**
     Generate a stat block in html using some nice styles that resembles the official books.
 
-    This command utilizes built-in MiniJinja templates (see the `mini-jinja` command) to generate the HTML. It supports one or two-column formats, although the two-column format requires a height for the output box in `px` units. It can also generate just the `div` tag and its contents instead of the full HTML document.
+    This command utilizes built-in MiniJinja templates (see the `mini-jinja` command) to generate the HTML. It supports one or two-column formats, although the two-column format requires a height for the output box in `px` units. It can also generate just the `div` tag and its contents instead of the full HTML document. For printing reference cards, the full document can be styled to fit a single creature onto a 3x5 or 4x6 index card. It can also be styled to fit a narrow sidebar or phone screen, with the ability scores wrapped onto two rows.
 
-    If you wish to modify the output, retrieve the styles for embedding multiple `div` fragments in a page, or just reference them for how to write a template, use the `list-templates` command to retrieve them. One simple template, for specifying the two-column mode, is generated at run-time, but a comment in the template explains how to add this yourself.
+    If you wish to modify the output, retrieve the styles for embedding multiple `div` fragments in a page, or just reference them for how to write a template, use the `list-templates` command to retrieve them. A few simple templates, for specifying the two-column mode, the footer link, the index card size and extra css classes, are generated at run-time, but comments in the templates explain how to add these yourself.
 
     The HTML templates were based on styles used in [statblock5e](https://valloric.github.io/statblock5e/). That code was converted from "web components" into plain HTML, so it can support older browsers and not require JavaScript.
     */
@@ -210,7 +438,31 @@ enum Command {
  
         #[clap(long)]
         /// only the stat-block div is produced, you would then import it into your own HTML page (see list-templates command to get the default styles)
-        fragment: bool
+        fragment: bool,
+
+        #[clap(long,value_name="URL")]
+        /// a link to an online version of the stat-block, which is placed at the bottom of the stat-block
+        link: Option<String>,
+
+        #[clap(long,requires="link")]
+        /// a QR code for the link is also placed at the bottom of the stat-block (requires monstorr to be built with the 'qr' feature)
+        qr: bool,
+
+        #[clap(long,arg_enum,conflicts_with_all=&["two_column","fragment"])]
+        /// the page is styled for printing on index cards of the specified size, in landscape orientation, one creature per card. Text which doesn't fit spills past the edge of the card, and a warning is printed when the stat-block probably won't fit.
+        card_size: Option<CardSize>,
+
+        #[clap(long,value_name="NAME")]
+        /// a css class added to the stat-block div, along with the usual 'stat-block' class, for styling particular creatures when several fragments are placed in one page. Separate several classes with spaces.
+        css_class: Option<String>,
+
+        #[clap(long,conflicts_with_all=&["two_column","card_size"])]
+        /// the page is styled for a narrow sidebar or a phone, about 300 pixels wide, with the ability scores in two rows. With --fragment, include the 'html-narrow-styles-fragment.html' template (see list-templates command) in your page after the default styles instead.
+        narrow: bool,
+
+        #[clap(long)]
+        /// keeps running, and writes the output again whenever the input file, or a file it includes, changes. Stop it with Ctrl+C.
+        watch: bool
     },
 
     #[clap(author="N. M. Sheldon", version, about, long_about = None)]
@@ -219,12 +471,30 @@ enum Command {
 
     This command utilizes built-in MiniJinja templates (see the `mini-jinja` command) to generate the LaTeX. It makes use of commands supplied by another LaTeX package which has not yet been published. It should be possible to create these commands yourself to customize the style.
 
+    By default, only the stat-block environment is produced, for including in your own document. A standalone document, with simple versions of the commands in its preamble, can be produced instead. The color and font of the stat-block can also be changed, without editing the templates.
+
     If you wish to modify the output, retrieve the command names, or just reference them for how to write a template, use the `list-templates` command to retrieve them.
     */
     LATEX {
  
         #[clap(flatten)]
-        input_output: InputOutputData
+        input_output: InputOutputData,
+
+        #[clap(long)]
+        /// a complete document is produced, which can be compiled on its own, instead of only the stat-block
+        standalone: bool,
+
+        #[clap(long,value_name="HEX")]
+        /// the color of the stat-block's text, as six hexadecimal digits such as '7A200D'. Your document must load the 'xcolor' package, unless --standalone is used.
+        color: Option<String>,
+
+        #[clap(long,value_name="FAMILY")]
+        /// the LaTeX name of the font family used for the stat-block, such as 'ppl' for Palatino
+        font: Option<String>,
+
+        #[clap(long,value_name="LENGTH",requires="standalone")]
+        /// the page margin of the standalone document, such as '20mm'. The default is '1in'.
+        margin: Option<String>
  
     },
 
@@ -243,6 +513,93 @@ enum Command {
  
     },
 
+    #[clap(author="N. M. Sheldon", version, about, long_about = None)]
+    /**
+    Generate a stat block in plain text, wrapped to a fixed width.
+
+    Unlike the plain command, this doesn't use templates. The lines are wrapped at spaces, the ability scores are lined up in columns, and the bold and italic styles are dropped, so the output can be pasted into forums and the chat of virtual tabletops without any markup.
+    */
+    Text {
+
+        #[clap(long,default_value_t=80)]
+        /// the number of characters to wrap the lines at
+        width: usize,
+
+        #[clap(flatten)]
+        input_output: InputOutputData
+
+    },
+
+    #[clap(author="N. M. Sheldon", version, about, long_about = None)]
+    /**
+    Generate a compact stat block for pasting into a Discord message.
+
+    This command utilizes built-in MiniJinja templates (see the `mini-jinja` command) to generate text in the limited markdown supported by Discord. Ability scores are aligned in a code block, since Discord doesn't support tables. Discord messages are limited to 2000 characters, so a warning will be printed if the stat block is longer than that, in which case you will have to split it up yourself.
+
+    If you wish to modify the output, use the `list-templates` command to retrieve the templates.
+    */
+    Discord {
+ 
+        #[clap(flatten)]
+        input_output: InputOutputData
+ 
+    },
+
+    #[clap(author="N. M. Sheldon", version, about, long_about = None)]
+    /**
+    Fill in the text fields of a fillable PDF monster sheet.
+
+    The form is not included with monstorr, you have to supply your own. By default, the form's fields are filled by the stat-block properties with the same names, as they appear in the output of the `json` command, such as `armor`, `hit_points` and `strength`. Features, actions, reactions, legendary actions, lair actions and regional effects are written as plain text into the fields named `special_abilities`, `actions`, `reactions`, `legendary_actions`, `lair_actions` and `regional_effects`, which should be the larger multi-line fields. If your form uses other names, supply a mapping file with `--fields`. This requires monstorr to be built with the 'pdf-form' feature.
+    */
+    PdfForm {
+
+        #[clap(long,value_name="FILENAME")]
+        /// the fillable PDF form to fill in
+        form: String,
+
+        #[clap(long,value_name="FILENAME")]
+        /// a RON map from the names of the form's fields to the stat-block properties that fill them, such as `{ "AC": "armor", "HP": "hit_points" }`
+        fields: Option<String>,
+
+        #[clap(flatten)]
+        input_output: InputOutputData
+
+    },
+
+    #[clap(author="N. M. Sheldon", version, about, long_about = None)]
+    /**
+    Generate an actor for the dnd5e system of the Foundry virtual tabletop.
+
+    The output is JSON which can be imported onto an NPC actor in Foundry with "Import Data". Abilities, hit points, armor class, speeds, traits and languages are filled in, and every feature and action becomes an item. Attack actions from creature files become weapon items with their attack bonus, range and damage, so they can be rolled. The finished roll formulas are also stored under the item's 'monstorr' flags, for use in roll macros. Actions from Open5e input only have their descriptions.
+    */
+    Foundry {
+
+        #[clap(long)]
+        /// turns off pretty-printing of JSON text.
+        ugly: bool,
+
+        #[clap(flatten)]
+        input_output: InputOutputData
+
+    },
+
+    #[clap(author="N. M. Sheldon", version, about, long_about = None)]
+    /**
+    Generate monster data in the shape used by D&D Beyond, for homebrew importers.
+
+    The output is JSON with the ability scores, armor class, hit points and hit dice, speeds, senses, languages and challenge rating, and the features and actions as HTML descriptions. Each action is also listed with its activation, and attack actions include their roll to hit, reach or range and damage dice. D&D Beyond doesn't document an import format, so check the output against the importer you use.
+    */
+    DndBeyond {
+
+        #[clap(long)]
+        /// turns off pretty-printing of JSON text.
+        ugly: bool,
+
+        #[clap(flatten)]
+        input_output: InputOutputData
+
+    },
+
     /**
     Produce creature files unprocessed.
 
@@ -253,6 +610,34 @@ enum Command {
     Validate {
         #[clap(flatten)]
         input_output: InputOutputData,
+        #[clap(long,arg_enum,default_value_t=CreatureFileFormat::RON)]
+        /// format to write the creature in, which can be used to convert a creature file to TOML
+        to: CreatureFileFormat
+    },
+
+    #[clap(author="N. M. Sheldon", version, about, long_about = None)]
+    /**
+    List the interpolation properties available to a creature's descriptions, with their values.
+
+    This is meant to help when writing descriptions and included files, so you can see what can be referenced with `${...}` without reading the documentation for the source code. Parameters passed with the `--param` option are included at the end. Only creature files and stored creatures are supported.
+    */
+    ListProperties {
+        #[clap(flatten)]
+        input_output: InputOutputData,
+    },
+
+    #[clap(author="N. M. Sheldon", version, about, long_about = None)]
+    /**
+    Print a creature file with its parameters interpolated and its included files inlined, without building it.
+
+    This is meant for debugging include chains. Every `Include` and `IncludeStored` command is replaced with the commands it loads, after interpolating the include's parameters, so you can see exactly which commands will be applied and in what order. Unlike `validate`, the creature isn't built, so this works even when the creature has errors. Only creature files and stored creatures are supported.
+    */
+    Preprocess {
+        #[clap(flatten)]
+        input_output: InputOutputData,
+        #[clap(long,arg_enum,default_value_t=CreatureFileFormat::RON)]
+        /// format to write the creature in
+        to: CreatureFileFormat
     },
 
     #[clap(author="N. M. Sheldon", version, about, long_about = None)]
@@ -270,11 +655,30 @@ enum Command {
         name: String
     },
 
+    #[clap(author="N. M. Sheldon", version, about, long_about = None)]
+    /**
+    Write built-in template files to a directory so you can customize the whole set.
+
+    The files are written with the names used to include them, so a customized main template can be used with the mini-jinja command, and will find the other templates in the same directory.
+    */
+    ExportTemplates {
+        /// The directory to write the templates into, which is created if it doesn't exist
+        dir: String,
+
+        /// The class of template to export, or none to export all.
+        #[clap(short,long,arg_enum)]
+        class: Option<TemplateClass>,
+
+        #[clap(long)]
+        /// replace template files which already exist in the directory
+        overwrite: bool
+    },
+
     #[clap(author="N. M. Sheldon", version, about, long_about = None)]
     /**
     List monsters in a file, filtering for specific data.
 
-    This is a simple filtering tool to help you find creatures in a list. It supports any list source (formats which would require a creature argument). See the information on input files in the main help for more information on this format.
+    This is a simple filtering tool to help you find creatures in a list. It supports any list source (formats which would require a creature argument), including the creatures stored in this program with `--format stored`. See the information on input files in the main help for more information on this format.
     */
     ListCreatures {
 
@@ -283,8 +687,8 @@ enum Command {
         format: ListInputFormat,
 
         #[clap(value_name="FILENAME")]
-        /// input file, if not specified will read from stdin
-        input: Option<String>,
+        /// input files, merged into one list if there are several. If not specified will read from stdin. These are ignored for the 'stored' format.
+        input: Vec<String>,
 
         #[clap(long)]
         /// type (humanoid, undead, monstrosity, etc.) of creatures to show 
@@ -302,6 +706,10 @@ enum Command {
         /// alignment of creatures to show
         alignment: Option<String>,
 
+        #[clap(long)]
+        /// environment (forest, urban, etc.) of creatures to show
+        environment: Option<String>,
+
         #[clap(long)]
         /// max challenge rating to show
         max_cr: Option<String>,
@@ -311,6 +719,143 @@ enum Command {
         min_cr: Option<String>
     },
 
+    #[clap(author="N. M. Sheldon", version, about, long_about = None)]
+    /**
+    Generate a random encounter from a list of creatures.
+
+    Creatures are chosen from the list, after filtering, so that the encounter fits the XP budget for the specified party and difficulty, using the encounter building rules from the Dungeon Master's Guide. The same list, filters, party and seed will always produce the same encounter. If no seed is specified, one is chosen and printed out so you can regenerate the encounter later.
+    */
+    Encounter {
+
+        #[clap(short,long,arg_enum)]
+        /// format of the input file
+        format: ListInputFormat,
+
+        #[clap(value_name="FILENAME")]
+        /// input files, merged into one list if there are several. If not specified will read from stdin. These are ignored for the 'stored' format.
+        input: Vec<String>,
+
+        #[clap(long,value_name="INTEGER")]
+        /// level of the characters in the party
+        party_level: u8,
+
+        #[clap(long,value_name="INTEGER",default_value_t=4)]
+        /// number of characters in the party
+        party_size: u8,
+
+        #[clap(long,arg_enum,default_value_t=EncounterDifficulty::Medium)]
+        /// the difficulty to aim for
+        difficulty: EncounterDifficulty,
+
+        #[clap(long,value_name="INTEGER")]
+        /// seed for the random number generator
+        seed: Option<u64>,
+
+        #[clap(long)]
+        /// type (humanoid, undead, monstrosity, etc.) of creatures to choose from
+        type_: Option<String>,
+
+        #[clap(long)]
+        /// subtype (goblinoid, shapechanger, etc.) of creatures to choose from
+        subtype: Option<String>,
+
+        #[clap(long)]
+        /// size of creatures to choose from
+        size: Option<String>,
+
+        #[clap(long)]
+        /// alignment of creatures to choose from
+        alignment: Option<String>,
+
+        #[clap(long)]
+        /// environment (forest, urban, etc.) of creatures to choose from
+        environment: Option<String>,
+
+        #[clap(long)]
+        /// max challenge rating to choose from
+        max_cr: Option<String>,
+
+        #[clap(long)]
+        /// minimum challenge rating to choose from
+        min_cr: Option<String>
+    },
+
+    #[clap(author="N. M. Sheldon", version, about, long_about = None)]
+    /**
+    List every spell cast by the creatures in a directory, with the creatures which cast them.
+
+    All of the creature files in the directory are built, and the spells from their spellcasting and innate spellcasting are collected into an index, for use in the spell appendix of a bestiary. Spell names are listed exactly as written, so a spell used by only one creature may be a misspelling. Creatures which fail to build are skipped with a warning.
+    */
+    SpellIndex {
+        #[clap(value_name="DIRECTORY")]
+        /// directory containing the creature files
+        dir: String,
+
+        #[clap(value_name="FILENAME")]
+        /// output file, if not specified will write to stdout.
+        output: Option<String>,
+
+        #[clap(short,long,arg_enum,default_value_t=SpellIndexFormat::Text)]
+        /// format of the index
+        format: SpellIndexFormat
+    },
+
+    #[clap(author="N. M. Sheldon", version, about, long_about = None)]
+    /**
+    Write a JSON Schema describing the stat-block JSON produced by the json command.
+
+    The schema documents every property, including the structure of the text blocks in features and actions, so it can be used to validate the output or to generate types for other languages.
+    */
+    Schema {
+        #[clap(value_name="FILENAME")]
+        /// output file, if not specified will write to stdout.
+        output: Option<String>
+    },
+
+    #[clap(author="N. M. Sheldon", version, about, long_about = None)]
+    /**
+    Build every creature file in a directory, writing each stat block next to its creature file.
+
+    Each output file has the same name as its creature file, with the extension for the format, so `goblin.creature` is written to `goblin.html` by default. A creature which fails to build is reported, and the rest are still written, followed by a summary. Exits with an error if any creature failed.
+    */
+    Batch {
+        #[clap(value_name="DIRECTORY")]
+        /// directory containing the creature files
+        dir: String,
+
+        #[clap(long,arg_enum,default_value_t=BatchFormat::HTML)]
+        /// format of the stat blocks
+        to: BatchFormat,
+
+        #[clap(long)]
+        /// report warnings for commands which may be mistakes, as with the other stat-block commands.
+        warn_noop: bool,
+
+        #[clap(long)]
+        /// report the same warnings as --warn-noop, and the warnings about the finished creature, as errors, so the creature isn't built.
+        strict: bool,
+
+        #[clap(long)]
+        /// also warn when a statistic with a single value is set more than once. This implies --warn-noop.
+        lint: bool,
+
+        #[clap(long,arg_enum,default_value_t=StatBlockStyle::Classic)]
+        /// the edition of the core books whose style the stat-blocks should follow
+        style: StatBlockStyle,
+
+        #[clap(long)]
+        /// include notes added with the Note command in the stat-blocks.
+        with_notes: bool,
+
+        #[clap(long,value_name="FILENAME")]
+        /// a language file replacing the fixed labels in the stat-blocks
+        lang: Option<String>,
+
+        #[clap(long="param",value_name="NAME=VALUE")]
+        /// a parameter made available as an interpolation variable to every creature (may be specified multiple times)
+        params: Vec<String>
+    },
+
     #[clap(author="N. M. Sheldon", version, about, long_about = None)]
     /** 
     Display the creature format version used by this tool.
@@ -323,11 +868,15 @@ enum Command {
     /**
     Internal command
 
-    This is used to generate the code for including creatures in the monstorr-data crate.
+    This is used to generate the code for including creatures in the monstorr-data crate. With the `--validate-only` flag, every creature in the directory is built instead, including its descriptions, and a summary of failures is printed. This is meant for checking the creature collection in continuous integration, and exits with an error if any creature fails.
     */
     GenCreaturesRustArray{
         /// Directory to search and place 'creature_database.rs.inc' file
-        dir: String 
+        dir: String,
+
+        #[clap(long)]
+        /// build all of the creatures without writing the file, failing if any of them fail
+        validate_only: bool
     }
 }
 
@@ -342,45 +891,114 @@ fn run(args: Command) -> Result<(),String> {
             println!("monstorr creature commands version: {}",MONSTORR_VERSION);
             process::exit(0);
         },
-        Command::JSON{ugly, input_output} => {
-            let output_format = MonstorrOutputFormat::JSON(ugly);
+        Command::JSON{ugly, with_diagnostics, compact, watch, input_output} => watch_or_run(watch, input_output, &[], |input_output| {
+            let output_format = if compact {
+                MonstorrOutputFormat::CompactJSON(ugly)
+            } else {
+                MonstorrOutputFormat::JSON(ugly,with_diagnostics)
+            };
+            let options = input_output.stat_block_options()?;
             let (input_format,output) = input_output.into_monstorr_input_output()?;
-            create_stat_block(input_format, output.as_deref(), output_format)
-        },
-        Command::HTML{input_output, two_column, fragment} => {
-            let output_format = MonstorrOutputFormat::HTML(two_column,fragment);
+            create_stat_block(input_format, output.as_deref(), output_format, &options)
+        }),
+        Command::HTML{input_output, two_column, fragment, link, qr, card_size, css_class, narrow, watch} => watch_or_run(watch, input_output, &[], |input_output| {
+            let card_size = card_size.clone().map(|card_size| match card_size {
+                CardSize::ThreeByFive => MonstorrCardSize::ThreeByFive,
+                CardSize::FourBySix => MonstorrCardSize::FourBySix
+            });
+            let output_format = MonstorrOutputFormat::HTML(MonstorrHtmlOptions {
+                two_column_height: two_column,
+                fragment,
+                link: link.clone(),
+                qr,
+                card_size,
+                css_class: css_class.clone(),
+                narrow
+            });
+            let options = input_output.stat_block_options()?;
             let (input_format,output) = input_output.into_monstorr_input_output()?;
-            create_stat_block(input_format, output.as_deref(), output_format)
-        },
-        Command::LATEX{input_output} => {
-            let output_format = MonstorrOutputFormat::LaTeX();
+            create_stat_block(input_format, output.as_deref(), output_format, &options)
+        }),
+        Command::LATEX{input_output, standalone, color, font, margin} => {
+            let output_format = MonstorrOutputFormat::LaTeX(standalone,color,font,margin);
+            let options = input_output.stat_block_options()?;
             let (input_format,output) = input_output.into_monstorr_input_output()?;
-            create_stat_block(input_format, output.as_deref(), output_format)
+            create_stat_block(input_format, output.as_deref(), output_format, &options)
         },
         Command::Plain{input_output} => {
             let output_format = MonstorrOutputFormat::Plain();
+            let options = input_output.stat_block_options()?;
             let (input_format,output) = input_output.into_monstorr_input_output()?;
-            create_stat_block(input_format, output.as_deref(), output_format)
+            create_stat_block(input_format, output.as_deref(), output_format, &options)
         }
-        Command::MiniJinja{template,include,input_output} => {
-            let output_format = MonstorrOutputFormat::MiniJinjaTemplate(template,include);
+        Command::Text{width,input_output} => {
+            let output_format = MonstorrOutputFormat::Text(width);
+            let options = input_output.stat_block_options()?;
+            let (input_format,output) = input_output.into_monstorr_input_output()?;
+            create_stat_block(input_format, output.as_deref(), output_format, &options)
+        },
+        Command::Discord{input_output} => {
+            let output_format = MonstorrOutputFormat::Discord();
+            let options = input_output.stat_block_options()?;
+            let (input_format,output) = input_output.into_monstorr_input_output()?;
+            create_stat_block(input_format, output.as_deref(), output_format, &options)
+        },
+        Command::PdfForm{form,fields,input_output} => {
+            let output_format = MonstorrOutputFormat::PDFForm(form,fields);
+            let options = input_output.stat_block_options()?;
+            let (input_format,output) = input_output.into_monstorr_input_output()?;
+            create_stat_block(input_format, output.as_deref(), output_format, &options)
+        },
+        Command::Foundry{ugly,input_output} => {
+            let output_format = MonstorrOutputFormat::Foundry(ugly);
+            let options = input_output.stat_block_options()?;
             let (input_format,output) = input_output.into_monstorr_input_output()?;
-            create_stat_block(input_format, output.as_deref(), output_format)
+            create_stat_block(input_format, output.as_deref(), output_format, &options)
         },
-        Command::Validate{input_output} => {
+        Command::DndBeyond{ugly,input_output} => {
+            let output_format = MonstorrOutputFormat::DndBeyond(ugly);
+            let options = input_output.stat_block_options()?;
             let (input_format,output) = input_output.into_monstorr_input_output()?;
-            validate_creature(input_format, output.as_deref())
+            create_stat_block(input_format, output.as_deref(), output_format, &options)
+        },
+        Command::MiniJinja{template,include,watch,input_output} => {
+            let template_files: Vec<String> = std::iter::once(template.clone()).chain(include.clone()).collect();
+            watch_or_run(watch, input_output, &template_files, |input_output| {
+                let output_format = MonstorrOutputFormat::MiniJinjaTemplate(template.clone(),include.clone());
+                let options = input_output.stat_block_options()?;
+                let (input_format,output) = input_output.into_monstorr_input_output()?;
+                create_stat_block(input_format, output.as_deref(), output_format, &options)
+            })
+        },
+        Command::Validate{input_output,to} => {
+            let (input_format,output) = input_output.into_monstorr_input_output()?;
+            validate_creature(input_format, output.as_deref(), to.into_monstorr())
+        },
+        Command::ListProperties{input_output} => {
+            let warnings = input_output.warning_level();
+            let lint = input_output.lint;
+            let parameters = input_output.parameters()?;
+            let base_dir = input_output.base_dir.clone();
+            let (input_format,output) = input_output.into_monstorr_input_output()?;
+            list_creature_properties(input_format, output.as_deref(), warnings, lint, &parameters, base_dir.as_deref())
+        },
+        Command::Preprocess{input_output,to} => {
+            let parameters = input_output.parameters()?;
+            let base_dir = input_output.base_dir.clone();
+            let (input_format,output) = input_output.into_monstorr_input_output()?;
+            preprocess_creature(input_format, output.as_deref(), to.into_monstorr(), &parameters, base_dir.as_deref())
         },
         Command::ListTemplates{ class } => {
             println!("{}",list_template_names(class.map(|c| c.to_string())).join("\n"));
             Ok(())
         },
-        Command::ListCreatures{format,input,type_,subtype,size,alignment,max_cr,min_cr} => {
+        Command::ListCreatures{format,input,type_,subtype,size,alignment,environment,max_cr,min_cr} => {
             let format = match format {
                 ListInputFormat::Open5eList => MonstorrListInputFormat::Open5eList,
+                ListInputFormat::LenientOpen5eList => MonstorrListInputFormat::LenientOpen5eList,
                 ListInputFormat::Stored => MonstorrListInputFormat::Stored
             };
-            for creature in list_creatures(input.as_deref(),format,type_,subtype,size,alignment,max_cr,min_cr)? {
+            for creature in list_creatures(&input,format,type_,subtype,size,alignment,environment,max_cr,min_cr)? {
                 println!("{} [{}] Challenge {}: {} {}{}, {} ",
                           creature.name,
                           creature.slug,
@@ -397,18 +1015,137 @@ fn run(args: Command) -> Result<(),String> {
             Ok(())
         },
 
+        Command::Encounter{format,input,party_level,party_size,difficulty,seed,type_,subtype,size,alignment,environment,max_cr,min_cr} => {
+            let format = match format {
+                ListInputFormat::Open5eList => MonstorrListInputFormat::Open5eList,
+                ListInputFormat::LenientOpen5eList => MonstorrListInputFormat::LenientOpen5eList,
+                ListInputFormat::Stored => MonstorrListInputFormat::Stored
+            };
+            let difficulty = match difficulty {
+                EncounterDifficulty::Easy => MonstorrEncounterDifficulty::Easy,
+                EncounterDifficulty::Medium => MonstorrEncounterDifficulty::Medium,
+                EncounterDifficulty::Hard => MonstorrEncounterDifficulty::Hard,
+                EncounterDifficulty::Deadly => MonstorrEncounterDifficulty::Deadly
+            };
+            let seed = if let Some(seed) = seed {
+                seed
+            } else {
+                SystemTime::now().duration_since(UNIX_EPOCH).map_err(|e| format!("Could not pick a seed: {}",e))?.as_secs()
+            };
+            let encounter = generate_random_encounter(&input,format,type_,subtype,size,alignment,environment,max_cr,min_cr,party_size,party_level,difficulty,seed)?;
+            println!("Encounter (seed {}): {}",seed,encounter.difficulty.map(|d| d.to_string()).unwrap_or_else(|| "trivial".to_owned()));
+            for (creature,count) in &encounter.creatures {
+                println!("  {} x {} [{}] Challenge {}",count,creature.name,creature.slug,creature.challenge_rating);
+            }
+            println!("Total XP: {}, adjusted XP: {}",encounter.total_xp,encounter.adjusted_xp);
+            println!("Party thresholds: {}",encounter.describe_thresholds());
+            Ok(())
+        },
+
         Command::GetTemplate{name} => {
             print_template(&name)
         },
 
-        Command::GenCreaturesRustArray{dir} => {
-            generate_creatures_as_rust_array(&dir)
+        Command::ExportTemplates{dir,class,overwrite} => {
+            export_templates(class.map(|c| c.to_string()),&dir,overwrite)
+        },
+
+        Command::SpellIndex{dir,output,format} => {
+            let format = match format {
+                SpellIndexFormat::Text => MonstorrSpellIndexFormat::Text,
+                SpellIndexFormat::Markdown => MonstorrSpellIndexFormat::Markdown,
+                SpellIndexFormat::JSON => MonstorrSpellIndexFormat::JSON
+            };
+            create_spell_index(&dir, output.as_deref(), format)
+        },
+
+        Command::Schema{output} => {
+            write_stat_block_schema(output.as_deref())
+        },
+
+        Command::Batch{dir,to,warn_noop,strict,lint,style,with_notes,lang,params} => {
+            let output_format = match to {
+                BatchFormat::JSON => MonstorrOutputFormat::JSON(false,false),
+                BatchFormat::HTML => MonstorrOutputFormat::HTML(MonstorrHtmlOptions::default()),
+                BatchFormat::LaTeX => MonstorrOutputFormat::LaTeX(false,None,None,None),
+                BatchFormat::Plain => MonstorrOutputFormat::Plain(),
+                BatchFormat::Text => MonstorrOutputFormat::Text(80),
+                BatchFormat::Discord => MonstorrOutputFormat::Discord()
+            };
+            let warnings = if strict {
+                MonstorrWarningLevel::Error
+            } else if warn_noop || lint {
+                MonstorrWarningLevel::Warn
+            } else {
+                MonstorrWarningLevel::Ignore
+            };
+            let style = match style {
+                StatBlockStyle::Classic => MonstorrStatBlockStyle::Classic,
+                StatBlockStyle::Modern => MonstorrStatBlockStyle::Modern
+            };
+            let options = MonstorrStatBlockOptions {
+                style,
+                warnings,
+                lint,
+                with_notes,
+                parameters: parse_parameters(&params)?,
+                base_dir: None,
+                lang_file: lang
+            };
+            create_stat_blocks_in_directory(&dir, output_format, &options)
+        },
+
+        Command::GenCreaturesRustArray{dir,validate_only} => {
+            if validate_only {
+                validate_creatures_in_directory(&dir)
+            } else {
+                generate_creatures_as_rust_array(&dir)
+            }
         },
     }
 
 
 }
 
+/**
+Runs a command which writes a stat block. If `watch` is true, the command keeps running, checking the input files, and any other files given, for changes twice a second, and runs again when they change. Errors while watching are printed, instead of stopping, since the file is probably in the middle of being edited.
+*/
+fn watch_or_run<Run: Fn(InputOutputData) -> Result<(),String>>(watch: bool, input_output: InputOutputData, other_files: &[String], run: Run) -> Result<(),String> {
+    if !watch {
+        return run(input_output)
+    }
+
+    // the included files are found again after every run, since the includes might have changed.
+    let find_files = |input_output: &InputOutputData| -> Result<Vec<PathBuf>,String> {
+        let parameters = input_output.parameters()?;
+        let (input_format,_) = input_output.clone().into_monstorr_input_output()?;
+        let mut files = get_input_files(&input_format, &parameters, input_output.base_dir.as_deref())?;
+        files.extend(other_files.iter().map(PathBuf::from));
+        Ok(files)
+    };
+    let modified_times = |files: &Vec<PathBuf>| -> Vec<Option<SystemTime>> {
+        files.iter().map(|file| fs::metadata(file).and_then(|metadata| metadata.modified()).ok()).collect()
+    };
+
+    let mut files = find_files(&input_output)?;
+    let mut last_modified = None;
+    loop {
+        let modified = modified_times(&files);
+        if last_modified.as_ref() != Some(&modified) {
+            match run(input_output.clone()) {
+                Ok(()) => eprintln!("Output written, watching {} file(s) for changes.",files.len()),
+                Err(e) => eprintln!("{}",e)
+            }
+            match find_files(&input_output) {
+                Ok(found) => files = found,
+                Err(e) => eprintln!("{}",e)
+            }
+            last_modified = Some(modified_times(&files));
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+}
+
 /**
 Parses the command line arguments, runs the tool, and prints out any error messages.
 */